@@ -76,6 +76,22 @@ export interface ReceiveEnterRoom {
   type: 'EnterRoom';
   file_id: string;
   sequence_num: number;
+  resume_token: string;
+}
+
+export interface SendResumeSession {
+  type: 'ResumeSession';
+  session_id: string;
+  file_id: string;
+  resume_token: string;
+  last_sequence_num: number;
+  unacknowledged?: string[];
+}
+
+export interface ReceiveResumeSession {
+  type: 'ResumeSession';
+  file_id: string;
+  sequence_num: number;
 }
 
 export interface Transaction {
@@ -122,6 +138,7 @@ export interface SendGetBinaryTransactions {
   session_id: string;
   file_id: string;
   min_sequence_num: number;
+  fast_forward?: boolean;
 }
 
 export interface ReceiveTransactions {
@@ -144,6 +161,71 @@ export interface ReceiveCurrentTransaction {
   sequence_num: number;
 }
 
+export interface ReceiveFastForward {
+  type: 'FastForward';
+  file_id: string;
+  checkpoint_sequence_num: number;
+  checkpoint_version: string;
+  checkpoint_key: string;
+  sequence_num: number;
+}
+
+export interface CellAnchor {
+  sheet_id: string;
+  selection: string;
+}
+
+export interface ChatMessage {
+  id: string;
+  session_id: string;
+  user_id: string;
+  first_name: string;
+  last_name: string;
+  text: string;
+  mentions: string[];
+  anchor?: CellAnchor;
+  created_at: string;
+}
+
+export interface SendChatMessage {
+  type: 'ChatMessage';
+  id: string;
+  session_id: string;
+  file_id: string;
+  text: string;
+  mentions?: string[];
+  anchor?: CellAnchor;
+}
+
+export interface SendPingCell {
+  type: 'PingCell';
+  session_id: string;
+  file_id: string;
+  sheet_id: string;
+  selection: string;
+  session_ids?: string[];
+}
+
+export interface ReceiveChatMessage {
+  type: 'ChatMessage' | 'Mention';
+  file_id: string;
+  message: ChatMessage;
+}
+
+export interface ReceiveChatMessages {
+  type: 'ChatMessages';
+  file_id: string;
+  messages: ChatMessage[];
+}
+
+export interface ReceivePingCell {
+  type: 'PingCell';
+  file_id: string;
+  session_id: string;
+  sheet_id: string;
+  selection: string;
+}
+
 export interface ReceiveError {
   type: 'Error';
   error: string | Record<string, string[]>;
@@ -158,13 +240,21 @@ export type ReceiveMessages =
   | ReceiveEmpty
   | ReceiveTransactions
   | ReceiveEnterRoom
+  | ReceiveResumeSession
   | ReceiveError
-  | ReceiveCurrentTransaction;
+  | ReceiveCurrentTransaction
+  | ReceiveFastForward
+  | ReceiveChatMessage
+  | ReceiveChatMessages
+  | ReceivePingCell;
 
 export type MultiplayerServerMessage =
   | SendTransaction
   | SendEnterRoom
+  | SendResumeSession
   | SendGetTransactions
-  | SendGetBinaryTransactions;
+  | SendGetBinaryTransactions
+  | SendChatMessage
+  | SendPingCell;
 
 export type MultiplayerServerBinaryMessage = SendBinaryTransaction;
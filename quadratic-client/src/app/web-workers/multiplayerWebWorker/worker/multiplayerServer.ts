@@ -77,6 +77,10 @@ export class MultiplayerServer {
   private lastHeartbeat = 0;
   private updateId?: number;
 
+  // set once the server points us at a newer checkpoint; the transactions
+  // that follow it can't be applied until the file is reloaded
+  private fastForwarding = false;
+
   private sendAnalyticsError = (from: string, error: Error | unknown) => {
     sendAnalyticsError('multiplayerServer', from, error);
   };
@@ -292,9 +296,21 @@ export class MultiplayerServer {
         break;
 
       case 'BinaryTransactions':
+        if (this.fastForwarding) break;
         multiplayerCore.receiveTransactions(data);
         break;
 
+      case 'FastForward':
+        // reloading loads the latest checkpoint and then replays only the
+        // transactions after it
+        if (data.file_id !== this.fileId) throw new Error('Expected file_id to match in FastForward');
+        console.warn(
+          `[Multiplayer] Fast-forwarding to checkpoint ${data.checkpoint_sequence_num} (${data.checkpoint_key}), current sequence_num is ${data.sequence_num}`
+        );
+        this.fastForwarding = true;
+        multiplayerClient.reload();
+        break;
+
       case 'EnterRoom':
         if (data.file_id !== this.fileId) throw new Error('Expected file_id to match in EnterRoom');
         multiplayerCore.receiveCurrentTransaction(data.sequence_num);
@@ -374,6 +390,7 @@ export class MultiplayerServer {
       session_id: this.sessionId,
       file_id: this.fileId,
       min_sequence_num: sequenceNum,
+      fast_forward: true,
     };
 
     this.send(message);
@@ -13,6 +13,8 @@ use quadratic_rust_shared::{SharedError, clean_errors};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::metrics::METRICS;
+
 pub(crate) type Result<T> = std::result::Result<T, ConnectionError>;
 
 #[derive(Error, Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -115,6 +117,7 @@ impl IntoResponse for ConnectionError {
         };
 
         tracing::warn!("{} {}: {:?}", status, error, self);
+        METRICS.error(&self);
 
         (status, error).into_response()
     }
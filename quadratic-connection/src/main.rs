@@ -9,6 +9,7 @@ mod connection;
 mod error;
 mod header;
 mod health;
+mod metrics;
 mod proxy;
 mod server;
 mod sql;
@@ -0,0 +1,77 @@
+//! Metrics
+//!
+//! Prometheus metrics for the connection service, served at `/metrics`.
+
+use axum::{http::header::CONTENT_TYPE, response::IntoResponse};
+use quadratic_rust_shared::metrics::{self, Registry};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+pub(crate) static METRICS: LazyLock<Registry> =
+    LazyLock::new(|| Registry::new("quadratic_connection"));
+
+/// The kind of connection, e.g. `postgres` for `PostgresConnection`.
+pub(crate) fn connection_kind<T>() -> String {
+    let name = std::any::type_name::<T>();
+    let name = name.rsplit("::").next().unwrap_or(name);
+
+    name.trim_end_matches("Connection").to_lowercase()
+}
+
+/// Record a query against a database.
+pub(crate) fn record_query(kind: &str, duration: Duration, bytes: usize) {
+    let labels = [("kind", kind)];
+
+    METRICS.inc("queries_total", "Queries by connection kind", &labels);
+    METRICS.observe(
+        "query_duration_seconds",
+        "Query latency by connection kind",
+        &labels,
+        metrics::DURATION_BUCKETS,
+        duration.as_secs_f64(),
+    );
+    METRICS.inc_by(
+        "query_bytes_total",
+        "Bytes returned by connection kind",
+        &labels,
+        bytes as f64,
+    );
+    METRICS.observe(
+        "query_size_bytes",
+        "Size of query results by connection kind",
+        &labels,
+        metrics::SIZE_BUCKETS,
+        bytes as f64,
+    );
+}
+
+/// Render all metrics.
+pub(crate) async fn metrics() -> impl IntoResponse {
+    ([(CONTENT_TYPE, metrics::CONTENT_TYPE)], METRICS.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use quadratic_rust_shared::sql::postgres_connection::PostgresConnection;
+
+    use super::*;
+
+    #[test]
+    fn gets_the_connection_kind() {
+        assert_eq!(connection_kind::<PostgresConnection>(), "postgres");
+    }
+
+    #[tokio::test]
+    async fn renders_metrics() {
+        record_query("postgres", Duration::from_millis(30), 2_000);
+
+        let response = metrics().await.into_response();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.contains("quadratic_connection_queries_total{kind=\"postgres\"}"));
+        assert!(body.contains("quadratic_connection_query_bytes_total{kind=\"postgres\"}"));
+        assert!(body.contains("# TYPE quadratic_connection_query_duration_seconds histogram\n"));
+    }
+}
@@ -32,6 +32,7 @@ use crate::{
     config::config,
     error::{ConnectionError, Result},
     health::{full_healthcheck, healthcheck},
+    metrics::metrics,
     proxy::proxy,
     sql::{
         bigquery::{query as query_bigquery, schema as schema_bigquery, test as test_bigquery},
@@ -160,6 +161,9 @@ pub(crate) fn app(state: State) -> Result<Router> {
         // full healthcheck of dependencies
         .route("/health/full", get(full_healthcheck))
         //
+        // prometheus metrics
+        .route("/metrics", get(metrics))
+        //
         // state, required
         .with_state(state.clone())
         //
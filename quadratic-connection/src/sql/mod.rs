@@ -10,6 +10,7 @@ use uuid::Uuid;
 use crate::{
     error::{ConnectionError, Result},
     header::{number_header, time_header},
+    metrics::{connection_kind, record_query},
     server::SqlQuery,
     ssh::{UsesSsh, open_ssh_tunnel_for_connection},
     state::State,
@@ -68,6 +69,11 @@ pub(crate) async fn query_generic<'a, T: Connection<'a>>(
     headers.insert("RECORD-COUNT", number_header(num_records));
     headers.insert("ELAPSED-DATABASE-QUERY-MS", time_header(start_query));
     headers.insert("OVER-THE-LIMIT", number_header(over_the_limit));
+    record_query(
+        &connection_kind::<T>(),
+        start_query.elapsed(),
+        parquet.len(),
+    );
 
     state.stats.lock().await.last_query_time = Some(Instant::now());
     headers.insert("ELAPSED-TOTAL-MS", time_header(start));
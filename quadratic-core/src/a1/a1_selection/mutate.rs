@@ -1,7 +1,7 @@
 //! Mutation methods that insert or delete columns and rows from a selection.
 
 use crate::{
-    RefError,
+    Pos, RefError,
     a1::A1Context,
     grid::{RefAdjust, SheetId},
 };
@@ -152,6 +152,27 @@ impl A1Selection {
         self.saturating_adjust(adjust)
     }
 
+    /// Transposes the selection around `origin` (i.e., rows become columns).
+    /// Ranges that cannot be transposed are removed. Returns `None` if the
+    /// whole selection becomes empty.
+    #[must_use = "this method returns a new value instead of modifying its input"]
+    pub fn transpose(self, origin: Pos) -> Option<Self> {
+        let cursor = Pos {
+            x: (origin.x + self.cursor.y - origin.y).max(1),
+            y: (origin.y + self.cursor.x - origin.x).max(1),
+        };
+        Some(Self {
+            sheet_id: self.sheet_id,
+            cursor,
+            ranges: self
+                .ranges
+                .into_iter()
+                .filter_map(|r| r.transpose(origin, origin).ok())
+                .collect(),
+        })
+        .filter(|sel| !sel.ranges.is_empty())
+    }
+
     /// Replaces a table name in the selection.
     pub fn replace_table_name(&mut self, old_name: &str, new_name: &str) {
         self.ranges.iter_mut().for_each(|range| {
@@ -558,4 +579,12 @@ mod tests {
         selection.change_to_table_refs(sheet_id, &context);
         assert_eq!(selection.to_string(Some(sheet_id), &context), "A1:C2");
     }
+
+    #[test]
+    fn test_transpose() {
+        let selection = A1Selection::test_a1("B2:D3,E2");
+        let transposed = selection.transpose(pos![B2]).unwrap();
+        assert_eq!(transposed.test_to_string(), "B2:C4,B5");
+        assert_eq!(transposed.cursor, pos![B5]);
+    }
 }
@@ -102,6 +102,27 @@ impl CellRefRangeEnd {
         }
     }
 
+    /// Transposes a reference made from a cell at `source` that is moved to
+    /// `target` by a transposed paste. The offset from the cell to a fully
+    /// relative reference is swapped; references with any absolute coordinate
+    /// keep their absolute coordinate and are translated by the cell's move.
+    /// Returns an error if the result is out of bounds.
+    #[must_use = "this method returns a new value instead of modifying its input"]
+    pub fn transpose(self, source: Pos, target: Pos) -> Result<Self, RefError> {
+        if self.col.is_absolute || self.row.is_absolute {
+            return Ok(Self {
+                col: self.col.adjust(true, target.x - source.x)?,
+                row: self.row.adjust(true, target.y - source.y)?,
+            });
+        }
+        let x = target.x.saturating_add(self.row.coord - source.y);
+        let y = target.y.saturating_add(self.col.coord - source.x);
+        if x <= 0 || y <= 0 {
+            return Err(RefError);
+        }
+        Ok(Self::new_relative_xy(x, y))
+    }
+
     /// Returns whether an adjustment should affect this position.
     fn affected_by_adjustment(self, adjust: RefAdjust) -> bool {
         self.col.coord >= adjust.x_start && self.row.coord >= adjust.y_start
@@ -600,6 +621,26 @@ mod tests {
         assert_eq!(Ok(expected_abs), init_abs.adjust(all));
     }
 
+    #[test]
+    fn test_transpose() {
+        // C1 referenced from A1, with A1 pasted transposed to B2
+        let source = Pos::new(1, 1);
+        let target = Pos::new(2, 2);
+        let rel = CellRefRangeEnd::new_relative_xy(3, 1);
+        assert_eq!(rel.transpose(source, target).unwrap().to_string(), "B4");
+
+        // absolute references keep their absolute coordinates
+        let abs = CellRefRangeEnd {
+            col: CellRefCoord::new_abs(3),
+            row: CellRefCoord::new_rel(1),
+        };
+        assert_eq!(abs.transpose(source, target).unwrap().to_string(), "$C2");
+
+        // references that move out of bounds are errors
+        let rel = CellRefRangeEnd::new_relative_xy(1, 1);
+        assert_eq!(rel.transpose(Pos::new(5, 5), Pos::new(1, 1)), Err(RefError));
+    }
+
     #[test]
     fn test_unbounded() {
         assert_eq!(CellRefRangeEnd::UNBOUNDED.col.coord, UNBOUNDED);
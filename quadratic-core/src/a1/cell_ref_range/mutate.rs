@@ -16,6 +16,18 @@ impl CellRefRange {
         }
     }
 
+    /// Transposes the range as referenced from a cell at `source` that is
+    /// moved to `target`. Returns an error if the result is out of bounds.
+    #[must_use = "this method returns a new value instead of modifying its input"]
+    pub fn transpose(self, source: Pos, target: Pos) -> Result<Self, RefError> {
+        match self {
+            Self::Sheet { range } => Ok(Self::Sheet {
+                range: range.transpose(source, target)?,
+            }),
+            other => Ok(other),
+        }
+    }
+
     /// Adjusts coordinates by `adjust`, clamping the result within the sheet
     /// bounds. Returns `None` if the result is empty.
     ///
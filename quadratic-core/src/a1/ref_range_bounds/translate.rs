@@ -48,6 +48,20 @@ impl RefRangeBounds {
         Some(Self { start, end })
     }
 
+    /// Transposes the range as referenced from a cell at `source` that is
+    /// moved to `target`. See [`CellRefRangeEnd::transpose()`]. Unbounded
+    /// ranges are not changed.
+    #[must_use = "this method returns a new value instead of modifying its input"]
+    pub fn transpose(self, source: Pos, target: Pos) -> Result<Self, RefError> {
+        if self.is_any_unbounded() {
+            return Ok(self);
+        }
+        Ok(Self {
+            start: self.start.transpose(source, target)?,
+            end: self.end.transpose(source, target)?,
+        })
+    }
+
     // TODO: remove this function when switching to u64
     #[must_use = "this method returns a new value instead of modifying its input"]
     pub fn translate_unchecked(self, x: i64, y: i64) -> Self {
@@ -118,6 +132,22 @@ mod tests {
         assert_eq!(range.saturating_adjust(adj).unwrap().to_string(), "A2:P2");
     }
 
+    #[test]
+    fn test_transpose() {
+        // a row referenced from B2 becomes a column when B2 is pasted to B2
+        let range = RefRangeBounds::test_a1("B1:D1");
+        let pos = Pos::new(2, 2);
+        assert_eq!(range.transpose(pos, pos).unwrap().to_string(), "A2:A4");
+
+        // absolute references are unchanged
+        let range = RefRangeBounds::test_a1("$A$1:$C$1");
+        assert_eq!(range.transpose(pos, pos).unwrap().to_string(), "$A$1:$C$1");
+
+        // unbounded ranges are unchanged
+        let range = RefRangeBounds::test_a1("A:C");
+        assert_eq!(range.transpose(pos, pos).unwrap().to_string(), "A:C");
+    }
+
     #[test]
     fn test_adjust_column_row() {
         let sheet_id = SheetId::TEST;
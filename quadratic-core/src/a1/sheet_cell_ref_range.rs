@@ -120,6 +120,17 @@ impl SheetCellRefRange {
             Ok(self)
         }
     }
+    /// Transposes the range as referenced from a cell at `source` that is
+    /// moved to `target`. Returns an error if the result is out of bounds.
+    #[must_use = "this method returns a new value instead of modifying its input"]
+    pub fn transpose(self, source: Pos, target: Pos) -> Result<Self, RefError> {
+        Ok(Self {
+            sheet_id: self.sheet_id,
+            cells: self.cells.transpose(source, target)?,
+            explicit_sheet_name: self.explicit_sheet_name,
+        })
+    }
+
     /// Adjusts coordinates by `adjust`, clamping the result within the sheet
     /// bounds. Returns `None` if the whole range goes out of bounds.
     #[must_use = "this method returns a new value instead of modifying its input"]
@@ -15,7 +15,15 @@ use quadratic_core::controller::execution::run_code::get_cells::JsCellsA1Error;
 use quadratic_core::controller::execution::run_code::get_cells::JsCellsA1Response;
 use quadratic_core::controller::execution::run_code::get_cells::JsCellsA1Value;
 use quadratic_core::controller::execution::run_code::get_cells::JsCellsA1Values;
+use quadratic_core::controller::export::{
+    CsvExportOptions, CsvLineEnding, CsvQuoteStyle, ExcelExportOptions,
+};
+use quadratic_core::controller::operations::clipboard::PasteOperation;
 use quadratic_core::controller::operations::clipboard::PasteSpecial;
+use quadratic_core::controller::operations::clipboard::PasteSpecialOptions;
+use quadratic_core::controller::operations::csv::CsvColumnType;
+use quadratic_core::controller::operations::csv::CsvImportOptions;
+use quadratic_core::controller::operations::csv::CsvPreview;
 use quadratic_core::controller::operations::tracked_operation::TrackedOperation;
 use quadratic_core::controller::tracked_transaction::TrackedTransaction;
 use quadratic_core::controller::transaction_types::JsCellValueResult;
@@ -59,6 +67,9 @@ use quadratic_core::grid::sheet::borders::CellBorderLine;
 use quadratic_core::grid::sheet::borders::JsBorderHorizontal;
 use quadratic_core::grid::sheet::borders::JsBorderVertical;
 use quadratic_core::grid::sheet::borders::JsBordersSheet;
+use quadratic_core::grid::series::fill_series::{
+    FillDateUnit, FillDirection, FillSeriesOptions, FillSeriesType,
+};
 use quadratic_core::grid::sheet::search::SearchOptions;
 use quadratic_core::grid::sheet::validations::rules::ValidationRule;
 use quadratic_core::grid::sheet::validations::rules::validation_date_time::{
@@ -128,8 +139,19 @@ fn main() {
         CodeCellLanguage,
         ColumnRow,
         ConnectionKind,
+        CsvColumnType,
+        CsvExportOptions,
+        CsvImportOptions,
+        CsvLineEnding,
+        CsvPreview,
+        CsvQuoteStyle,
         DataTableSort,
         DateTimeRange,
+        ExcelExportOptions,
+        FillDateUnit,
+        FillDirection,
+        FillSeriesOptions,
+        FillSeriesType,
         Format,
         FormatUpdate,
         GridBounds,
@@ -187,7 +209,9 @@ fn main() {
         NumberRange,
         NumericFormat,
         NumericFormatKind,
+        PasteOperation,
         PasteSpecial,
+        PasteSpecialOptions,
         Pos,
         Rect,
         RefRangeBounds,
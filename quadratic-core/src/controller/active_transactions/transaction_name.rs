@@ -34,4 +34,5 @@ pub enum TransactionName {
     MoveCells,
     Validation,
     ManipulateColumnRow,
+    SetCustomLists,
 }
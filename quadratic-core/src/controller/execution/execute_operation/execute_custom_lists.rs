@@ -0,0 +1,50 @@
+use crate::controller::{
+    GridController, active_transactions::pending_transaction::PendingTransaction,
+    operations::operation::Operation,
+};
+
+impl GridController {
+    pub(crate) fn execute_set_custom_lists(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetCustomLists { custom_lists } = op);
+
+        let old_custom_lists = std::mem::replace(&mut self.grid.custom_lists, custom_lists);
+
+        if transaction.is_user_ai_undo_redo() {
+            transaction
+                .forward_operations
+                .push(Operation::SetCustomLists {
+                    custom_lists: self.grid.custom_lists.clone(),
+                });
+            transaction
+                .reverse_operations
+                .push(Operation::SetCustomLists {
+                    custom_lists: old_custom_lists,
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controller::GridController;
+
+    #[test]
+    fn test_execute_set_custom_lists() {
+        let mut gc = GridController::test();
+        assert!(gc.custom_lists().is_empty());
+
+        let custom_lists = vec![vec!["Low".to_string(), "High".to_string()]];
+        gc.set_custom_lists(custom_lists.clone(), None, false);
+        assert_eq!(gc.custom_lists(), custom_lists.as_slice());
+
+        gc.undo(1, None, false);
+        assert!(gc.custom_lists().is_empty());
+
+        gc.redo(1, None, false);
+        assert_eq!(gc.custom_lists(), custom_lists.as_slice());
+    }
+}
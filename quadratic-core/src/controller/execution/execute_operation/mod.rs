@@ -29,6 +29,7 @@ mod execute_borders_old;
 mod execute_code;
 mod execute_col_rows;
 mod execute_cursor;
+mod execute_custom_lists;
 mod execute_data_table;
 mod execute_formats;
 mod execute_formats_old;
@@ -209,6 +210,10 @@ impl GridController {
 
                 Operation::MoveColumns { .. } => self.execute_move_columns(transaction, op),
                 Operation::MoveRows { .. } => self.execute_move_rows(transaction, op),
+
+                Operation::SetCustomLists { .. } => {
+                    self.execute_set_custom_lists(transaction, op);
+                }
             }
         }
 
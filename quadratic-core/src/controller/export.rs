@@ -1,26 +1,59 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    io::{Cursor, Write},
+    sync::Arc,
+};
 
 use anyhow::{Context, Result, anyhow, bail};
-use csv::Writer;
-use itertools::PeekingNext;
+use arrow_array::RecordBatch;
+use arrow_schema::{Field, Schema};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use csv::{QuoteStyle, Terminator, Writer, WriterBuilder};
+use itertools::{Itertools, PeekingNext};
 use lazy_static::lazy_static;
+use quick_xml::escape::escape;
 use rust_decimal::{Decimal, prelude::ToPrimitive};
 use rust_xlsxwriter::{
-    Format, FormatAlign, FormatBorder, FormatPattern, FormatUnderline, Workbook, XlsxError,
-    worksheet::Worksheet,
+    DataValidation, DataValidationErrorStyle, DataValidationRule, Format, FormatAlign,
+    FormatBorder, FormatPattern, FormatUnderline, Formula, Image, IntoDataValidationValue, Table,
+    TableColumn, Url, Workbook, XlsxError, utility::worksheet_range_absolute, worksheet::Worksheet,
 };
+use serde::{Deserialize, Serialize};
+use zip::{ZipWriter, write::SimpleFileOptions};
 
 use super::GridController;
 use crate::{
-    CellValue, Pos, Value,
-    a1::{A1Selection, CellRefRange},
+    CellValue, Hyperlink, HyperlinkTarget, Pos, Rect, TableRef, Value,
+    a1::{A1Context, A1Selection, CellRefRange, ColRange, column_name},
+    arrow::{cell_values_to_arrow_col, record_batch_to_arrow_ipc},
     color::Rgba,
-    controller::operations::import::{COLUMN_WIDTH_MULTIPLIER, ROW_HEIGHT_MULTIPLIER},
-    date_time::{DEFAULT_DATE_FORMAT, DEFAULT_DATE_TIME_FORMAT, DEFAULT_TIME_FORMAT},
+    controller::operations::{
+        import::{COLUMN_WIDTH_MULTIPLIER, ROW_HEIGHT_MULTIPLIER},
+        xlsx::{CODE_CELLS_SHEET_NAME, XlsxCodeCell},
+    },
+    date_time::{
+        DEFAULT_DATE_FORMAT, DEFAULT_DATE_TIME_FORMAT, DEFAULT_TIME_FORMAT, i32_to_naive_time,
+        i64_to_naive_date,
+    },
+    formulas::convert_table_refs_to_a1,
     grid::{
-        CellAlign, CellVerticalAlign, CellWrap, CodeCellLanguage, GridBounds, NumericFormatKind,
-        Sheet, sheet::borders::CellBorderLine,
+        CellAlign, CellVerticalAlign, CellWrap, CodeCellLanguage, DataTable, GridBounds,
+        NumericFormatKind, Sheet,
+        sheet::{
+            borders::CellBorderLine,
+            validations::{
+                rules::{
+                    ValidationRule, validation_date_time::DateTimeRange,
+                    validation_list::ValidationListSource, validation_number::NumberRange,
+                    validation_text::TextMatch,
+                },
+                validation::{Validation, ValidationStyle},
+            },
+        },
     },
+    json::rows_to_json,
+    parquet::record_batch_to_parquet,
 };
 
 lazy_static! {
@@ -51,16 +84,120 @@ lazy_static! {
 const MAX_EXCEL_ROW: i64 = 1048576;
 const MAX_EXCEL_COL: i64 = 16384;
 
+/// When values are quoted in an exported CSV file.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum CsvQuoteStyle {
+    /// Only values with delimiters, quotes, or line breaks are quoted.
+    #[default]
+    Necessary,
+    Always,
+    NonNumeric,
+    Never,
+}
+
+/// Line ending of an exported CSV file.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum CsvLineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// Options for exporting CSV files.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct CsvExportOptions {
+    /// Delimiter between values, e.g., `\t` for TSV files.
+    pub delimiter: u8,
+
+    pub quote_style: CsvQuoteStyle,
+    pub line_ending: CsvLineEnding,
+
+    /// Exports values as displayed, using their numeric and date formats,
+    /// instead of their raw values.
+    pub formatted_values: bool,
+
+    /// Exports hidden columns when exporting a data table.
+    pub include_hidden_columns: bool,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote_style: CsvQuoteStyle::default(),
+            line_ending: CsvLineEnding::default(),
+            formatted_values: false,
+            include_hidden_columns: false,
+        }
+    }
+}
+
+impl CsvExportOptions {
+    fn writer(&self) -> Writer<Vec<u8>> {
+        let quote_style = match self.quote_style {
+            CsvQuoteStyle::Necessary => QuoteStyle::Necessary,
+            CsvQuoteStyle::Always => QuoteStyle::Always,
+            CsvQuoteStyle::NonNumeric => QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => QuoteStyle::Never,
+        };
+        let terminator = match self.line_ending {
+            CsvLineEnding::Lf => Terminator::Any(b'\n'),
+            CsvLineEnding::Crlf => Terminator::CRLF,
+        };
+
+        WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote_style(quote_style)
+            .terminator(terminator)
+            .from_writer(vec![])
+    }
+}
+
+/// Options for exporting excel files.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct ExcelExportOptions {
+    /// Fallback images (data URLs) for HTML charts, keyed by the chart's name.
+    pub chart_images: HashMap<String, String>,
+
+    /// Stores the code of non-formula code cells (e.g., Python) in a hidden
+    /// sheet, so they are recreated when the file is imported again.
+    pub include_code_cells: bool,
+}
+
 impl GridController {
     /// exports a CSV string from a selection on the grid.
     ///
     /// Returns a [`String`].
     pub fn export_csv_selection(&self, selection: &mut A1Selection) -> Result<String> {
+        self.export_csv_selection_with_options(selection, &CsvExportOptions::default())
+    }
+
+    /// Exports a CSV string from a selection on the grid using the export
+    /// options.
+    ///
+    /// Returns a [`String`].
+    pub fn export_csv_selection_with_options(
+        &self,
+        selection: &mut A1Selection,
+        options: &CsvExportOptions,
+    ) -> Result<String> {
         let sheet = self
             .grid
             .try_sheet(selection.sheet_id)
             .context("Sheet not found")?;
 
+        if let [CellRefRange::Table { range }] = selection.ranges.as_slice()
+            && options.include_hidden_columns
+        {
+            return self.export_csv_table(range, options);
+        }
+
         if let Some(CellRefRange::Table { range }) = selection.ranges.first_mut() {
             range.headers = true;
         }
@@ -70,7 +207,7 @@ impl GridController {
             .context("No values")?;
 
         let values = sheet.selection_sorted_vec(selection, false, true, &self.a1_context);
-        let mut writer = Writer::from_writer(vec![]);
+        let mut writer = options.writer();
         let mut iter = values.iter();
         let context = self.a1_context();
         for y in bounds.min.y..=bounds.max.y {
@@ -78,9 +215,14 @@ impl GridController {
             for x in bounds.min.x..=bounds.max.x {
                 // we need to ignore unselected columns or rows
                 if selection.might_contain_pos(Pos { x, y }, context) {
-                    if let Some((_, value)) = iter.peeking_next(|(pos, _)| pos.x == x && pos.y == y)
+                    if let Some((pos, value)) =
+                        iter.peeking_next(|(pos, _)| pos.x == x && pos.y == y)
                     {
-                        line.push(value.to_string());
+                        if options.formatted_values {
+                            line.push(formatted_value(value, sheet.cell_format(*pos)));
+                        } else {
+                            line.push(value.to_string());
+                        }
                     } else {
                         line.push("".to_string());
                     }
@@ -96,21 +238,453 @@ impl GridController {
         Ok(output)
     }
 
+    /// Exports the data table's columns in the table reference, including
+    /// hidden columns, as a CSV string. Rows are in the table's sort order.
+    fn export_csv_table(&self, range: &TableRef, options: &CsvExportOptions) -> Result<String> {
+        let table = self
+            .a1_context
+            .try_table(&range.table_name)
+            .context("Table not found")?;
+        let data_table = self.grid.data_table_at(table.sheet_id, &table.bounds.min)?;
+        let values = data_table.value.to_owned().into_array()?;
+        let column_headers = data_table
+            .column_headers
+            .to_owned()
+            .unwrap_or_else(|| data_table.default_header(None));
+        let column_names = data_table.columns_map(true);
+        let columns = table_columns_in_range(&range.col_range, &column_names);
+        if columns.is_empty() {
+            bail!("Column not found");
+        }
+
+        let mut writer = options.writer();
+        writer.write_record(columns.iter().map(|&i| &column_names[i]))?;
+
+        // formats are stored by the unsorted row of the table's value
+        let rows = match &data_table.display_buffer {
+            Some(display_buffer) => display_buffer.to_owned(),
+            None => (0..values.height() as u64).collect(),
+        };
+        for y in rows {
+            if data_table.header_is_first_row && y == 0 {
+                continue;
+            }
+            let line = columns
+                .iter()
+                .map(|&i| {
+                    let x = column_headers[i].value_index;
+                    let value = values.get(x, y as u32)?;
+                    if options.formatted_values {
+                        let format = data_table
+                            .formats
+                            .as_ref()
+                            .and_then(|formats| {
+                                formats.try_format(Pos {
+                                    x: x as i64 + 1,
+                                    y: y as i64 + 1,
+                                })
+                            })
+                            .unwrap_or_default();
+                        Ok(formatted_value(value, format))
+                    } else {
+                        Ok(value.to_string())
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            writer.write_record(line)?;
+        }
+        let output = String::from_utf8(writer.into_inner()?)?;
+
+        Ok(output)
+    }
+
+    /// Exports every sheet as a CSV file in a zip file. Files are named after
+    /// their sheets, and empty sheets are exported as empty files.
+    ///
+    /// Returns a [`Vec<u8>`].
+    pub fn export_csv_sheets_zip(&self, options: &CsvExportOptions) -> Result<Vec<u8>> {
+        let extension = if options.delimiter == b'\t' {
+            "tsv"
+        } else {
+            "csv"
+        };
+        let error = |e| anyhow!("Error writing zip file: {}", e);
+
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        let mut file_names = HashSet::new();
+        for sheet in self.sheets() {
+            let mut selection = A1Selection::all(sheet.id);
+            let csv = match sheet.all_bounds() {
+                GridBounds::Empty => String::new(),
+                GridBounds::NonEmpty(_) => {
+                    self.export_csv_selection_with_options(&mut selection, options)?
+                }
+            };
+
+            // sheet names may have characters that aren't valid in file names
+            let name = sheet
+                .name
+                .replace(['/', '\\', ':', '*', '?', '"', '<', '>', '|'], "_");
+            let mut file_name = format!("{name}.{extension}");
+            let mut i = 1;
+            while !file_names.insert(file_name.to_lowercase()) {
+                i += 1;
+                file_name = format!("{name} ({i}).{extension}");
+            }
+
+            zip.start_file(file_name, SimpleFileOptions::default())
+                .map_err(error)?;
+            zip.write_all(csv.as_bytes())?;
+        }
+
+        Ok(zip.finish().map_err(error)?.into_inner())
+    }
+
+    /// Exports a selection on the grid as a Markdown table of formatted
+    /// display values. Markdown tables require a header row, so the first
+    /// row is used (i.e., a data table's column headers when selected).
+    ///
+    /// Returns a [`String`].
+    pub fn export_markdown_selection(&self, selection: &A1Selection) -> Result<String> {
+        let (sheet, rows) = self.selection_rows(selection)?;
+
+        let mut markdown = String::new();
+        for (index, row) in rows.iter().enumerate() {
+            let cells = row
+                .iter()
+                .map(|(pos, value)| {
+                    value
+                        .map(|value| formatted_value(value, sheet.cell_format(*pos)))
+                        .unwrap_or_default()
+                        .replace('|', "\\|")
+                        .replace('\n', "<br>")
+                })
+                .collect::<Vec<_>>();
+            writeln!(markdown, "| {} |", cells.join(" | "))?;
+            if index == 0 {
+                writeln!(markdown, "|{}", " --- |".repeat(cells.len()))?;
+            }
+        }
+
+        Ok(markdown)
+    }
+
+    /// Exports a selection on the grid as a standalone HTML table of
+    /// formatted display values, with the cells' styles inlined. Data table
+    /// column headers are exported as header cells.
+    ///
+    /// Returns a [`String`].
+    pub fn export_html_selection(&self, selection: &A1Selection) -> Result<String> {
+        let (sheet, rows) = self.selection_rows(selection)?;
+
+        // leading rows of column headers are the table's head
+        let head_rows = rows
+            .iter()
+            .take_while(|row| row.iter().all(|(pos, _)| is_column_header(sheet, *pos)))
+            .count();
+
+        let mut html = String::from("<table>");
+        for (index, row) in rows.iter().enumerate() {
+            if index == 0 && head_rows > 0 {
+                html.push_str("<thead>");
+            }
+            if index == head_rows {
+                html.push_str("<tbody>");
+            }
+
+            html.push_str("<tr>");
+            for (pos, value) in row {
+                let tag = if is_column_header(sheet, *pos) {
+                    "th"
+                } else {
+                    "td"
+                };
+                let format = sheet.cell_format(*pos);
+                let style = html_style(&format);
+                let text = value
+                    .map(|value| formatted_value(value, format))
+                    .unwrap_or_default();
+                let text = escape(&text).replace('\n', "<br>");
+                match value {
+                    Some(CellValue::Hyperlink(Hyperlink {
+                        target: HyperlinkTarget::Url(url),
+                        ..
+                    })) => write!(
+                        html,
+                        r#"<{tag}{style}><a href="{}">{text}</a></{tag}>"#,
+                        escape(url)
+                    )?,
+                    _ => write!(html, "<{tag}{style}>{text}</{tag}>")?,
+                }
+            }
+            html.push_str("</tr>");
+
+            if index + 1 == head_rows {
+                html.push_str("</thead>");
+            }
+        }
+        if rows.len() > head_rows {
+            html.push_str("</tbody>");
+        }
+        html.push_str("</table>");
+
+        Ok(html)
+    }
+
+    /// Returns the cells of a selection by row, skipping unselected columns
+    /// and data table name rows. Cells without a value are None.
+    #[allow(clippy::type_complexity)]
+    fn selection_rows(
+        &self,
+        selection: &A1Selection,
+    ) -> Result<(&Sheet, Vec<Vec<(Pos, Option<&CellValue>)>>)> {
+        let sheet = self
+            .grid
+            .try_sheet(selection.sheet_id)
+            .context("Sheet not found")?;
+
+        let mut selection = selection.to_owned();
+        if let Some(CellRefRange::Table { range }) = selection.ranges.first_mut() {
+            range.headers = true;
+        }
+
+        let bounds = sheet
+            .selection_bounds(&selection, false, false, true, &self.a1_context)
+            .context("No values")?;
+
+        let values = sheet.selection_sorted_vec(&selection, false, true, &self.a1_context);
+        let mut iter = values.into_iter();
+        let mut rows = vec![];
+        for y in bounds.min.y..=bounds.max.y {
+            let mut row = vec![];
+            for x in bounds.min.x..=bounds.max.x {
+                let pos = Pos { x, y };
+                // we need to ignore unselected columns or rows
+                if selection.might_contain_pos(pos, &self.a1_context) {
+                    let value = iter
+                        .peeking_next(|(value_pos, _)| *value_pos == pos)
+                        .map(|(_, value)| value);
+                    row.push((pos, value));
+                }
+            }
+
+            let is_name_row = row
+                .iter()
+                .all(|(pos, _)| sheet.table_header_at(*pos).is_some());
+            if !row.is_empty() && !is_name_row {
+                rows.push(row);
+            }
+        }
+
+        Ok((sheet, rows))
+    }
+
+    /// Exports a data table as a JSON array of objects, one per displayed
+    /// row, keyed by the table's visible column names. Dotted column names
+    /// are written as nested objects.
+    ///
+    /// Returns a [`String`].
+    pub fn export_table_json(&self, table_name: &str) -> Result<String> {
+        let table = self
+            .a1_context
+            .try_table(table_name)
+            .context("Table not found")?;
+        let data_table = self.grid.data_table_at(table.sheet_id, &table.bounds.min)?;
+
+        let columns = data_table.columns_map(false);
+        let values = data_table.display_value(false)?.into_array()?;
+        let rows = values
+            .rows()
+            .skip(usize::from(data_table.header_is_first_row))
+            .map(|row| row.to_vec())
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::to_string(&rows_to_json(&columns, &rows))?)
+    }
+
+    /// Exports a Parquet file from a selection on the grid. Column types are
+    /// preserved, and a selected data table uses its column names as the
+    /// schema.
+    ///
+    /// Returns a [`Vec<u8>`].
+    pub fn export_parquet_selection(&self, selection: &A1Selection) -> Result<Vec<u8>> {
+        let batch = self.selection_record_batch(selection)?;
+        record_batch_to_parquet(&batch)
+    }
+
+    /// Exports an Arrow IPC file from a selection on the grid. Column types
+    /// are preserved, and a selected data table uses its column names as the
+    /// schema.
+    ///
+    /// Returns a [`Vec<u8>`].
+    pub fn export_arrow_selection(&self, selection: &A1Selection) -> Result<Vec<u8>> {
+        let batch = self.selection_record_batch(selection)?;
+        record_batch_to_arrow_ipc(&batch)
+    }
+
+    /// Converts a selection to an arrow record batch, one column per selected
+    /// sheet column.
+    ///
+    /// A selected table is exported without its header row and named by its
+    /// columns. Otherwise, the first row is used for the column names when
+    /// it's all unique text; if not, columns are named by their letter.
+    fn selection_record_batch(&self, selection: &A1Selection) -> Result<RecordBatch> {
+        let sheet = self
+            .grid
+            .try_sheet(selection.sheet_id)
+            .context("Sheet not found")?;
+        let context = self.a1_context();
+
+        let mut selection = selection.clone();
+        let mut table = None;
+        if let [CellRefRange::Table { range }] = selection.ranges.as_mut_slice() {
+            range.headers = false;
+            range.data = true;
+            table = context.try_table(&range.table_name);
+        }
+
+        let bounds = sheet
+            .selection_bounds(&selection, false, false, true, context)
+            .context("No values")?;
+
+        // values by column, with unselected cells left blank
+        let columns = bounds
+            .x_range()
+            .filter(|&x| {
+                bounds
+                    .y_range()
+                    .any(|y| selection.might_contain_pos(Pos { x, y }, context))
+            })
+            .collect::<Vec<_>>();
+        let mut values = vec![vec![CellValue::Blank; bounds.height() as usize]; columns.len()];
+        for (pos, value) in sheet.selection_sorted_vec(&selection, false, true, context) {
+            if let Ok(index) = columns.binary_search(&pos.x)
+                && selection.might_contain_pos(pos, context)
+            {
+                values[index][(pos.y - bounds.min.y) as usize] = value.clone();
+            }
+        }
+
+        let names = match table {
+            Some(table) => columns
+                .iter()
+                .map(|&x| {
+                    table
+                        .visible_columns
+                        .get((x - table.bounds.min.x) as usize)
+                        .cloned()
+                        .unwrap_or_else(|| column_name(x))
+                })
+                .collect::<Vec<_>>(),
+            None => {
+                let first_row = values
+                    .iter()
+                    .filter_map(|column| match column.first() {
+                        Some(CellValue::Text(text)) if !text.is_empty() => Some(text.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                if bounds.height() > 1
+                    && first_row.len() == columns.len()
+                    && first_row.iter().all_unique()
+                {
+                    values.iter_mut().for_each(|column| {
+                        column.remove(0);
+                    });
+                    first_row
+                } else {
+                    columns.iter().map(|&x| column_name(x)).collect()
+                }
+            }
+        };
+
+        let arrays = values
+            .iter()
+            .map(Vec::as_slice)
+            .map(cell_values_to_arrow_col)
+            .collect::<Result<Vec<_>>>()?;
+        let fields = names
+            .into_iter()
+            .zip(&arrays)
+            .map(|(name, array)| Field::new(name, array.data_type().clone(), true))
+            .collect::<Vec<_>>();
+
+        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+    }
+
     /// Exports an excel file from the grid.
-    /// Only preserves formulas, everything else is flattened.
+    /// Preserves formulas and data validations, and exports imported data
+    /// tables as excel tables; everything else is flattened.
     ///
     /// Returns a [`Vec<u8>`].
     pub fn export_excel(&self) -> Result<Vec<u8>> {
+        self.export_excel_with_chart_images(&HashMap::new())
+    }
+
+    /// Exports an excel file from the grid, with charts embedded as pictures.
+    /// Image charts are embedded directly. HTML charts can't be rendered
+    /// here, so they use the fallback images in `chart_images` (data URLs
+    /// keyed by the chart's name); charts without a fallback are skipped.
+    ///
+    /// Returns a [`Vec<u8>`].
+    pub fn export_excel_with_chart_images(
+        &self,
+        chart_images: &HashMap<String, String>,
+    ) -> Result<Vec<u8>> {
+        self.export_excel_with_options(&ExcelExportOptions {
+            chart_images: chart_images.to_owned(),
+            ..Default::default()
+        })
+    }
+
+    /// Exports an excel file from the grid using the ExcelExportOptions.
+    ///
+    /// Returns a [`Vec<u8>`].
+    pub fn export_excel_with_options(&self, options: &ExcelExportOptions) -> Result<Vec<u8>> {
         let mut workbook = Workbook::new();
         let error = |e: XlsxError| anyhow!("Error exporting excel file: {}", e);
 
+        // data tables are added as excel tables before any formulas are
+        // written, so formulas know which table references excel can resolve
+        let mut worksheets = vec![];
+        let mut excel_table_names = vec![];
+        let mut code_cells = vec![];
         for sheet in self.sheets() {
-            // add the sheet to the workbook and set the name
-            let worksheet = workbook.add_worksheet();
+            let mut worksheet = Worksheet::new();
             worksheet
                 .set_name(sheet.name.to_string())
                 .map_err(|e| anyhow!("Error creating excel sheet: {}", e))?;
 
+            let mut name_rects = vec![];
+            for (pos, data_table) in sheet.data_tables.expensive_iter() {
+                let Some(excel_table) = excel_table(*pos, data_table) else {
+                    continue;
+                };
+                let (min, max) = (excel_table.rect.min, excel_table.rect.max);
+
+                // tables that excel rejects (e.g., a name excel doesn't
+                // allow) are exported as cells
+                if worksheet
+                    .add_table(
+                        min.y as u32 - 1,
+                        min.x as u16 - 1,
+                        max.y as u32 - 1,
+                        max.x as u16 - 1,
+                        &excel_table.table,
+                    )
+                    .is_ok()
+                {
+                    excel_table_names.push(data_table.name().to_string());
+                    name_rects.extend(excel_table.name_rect);
+                }
+            }
+            worksheets.push((worksheet, name_rects));
+        }
+
+        for (sheet, (mut sheet_worksheet, name_rects)) in self.sheets().into_iter().zip(worksheets)
+        {
+            let worksheet = &mut sheet_worksheet;
+
             // column widths
             let custom_column_widths: Vec<(i64, f64)> =
                 sheet.offsets.iter_column_widths().collect();
@@ -140,11 +714,17 @@ impl GridController {
 
             // add grid values to the worksheet
             match sheet.all_bounds() {
-                GridBounds::Empty => continue,
+                GridBounds::Empty => (),
                 GridBounds::NonEmpty(mut rect) => {
                     rect.max.x = rect.max.x.min(MAX_EXCEL_COL);
                     rect.max.y = rect.max.y.min(MAX_EXCEL_ROW);
                     for pos in rect.iter() {
+                        // excel tables store the name of the table, so the
+                        // name row is left blank
+                        if name_rects.iter().any(|rect| rect.contains(pos)) {
+                            continue;
+                        }
+
                         let (col, row) = (pos.x as u16 - 1, pos.y as u32 - 1);
                         let mut is_formula_output = false;
 
@@ -160,7 +740,15 @@ impl GridController {
                             // we currently only care about formulas
                             // skip spill and error formulas
                             if is_formula && !data_table.has_spill() && !data_table.has_error() {
-                                let code = code_cell_value.code.as_str();
+                                // references to tables that aren't excel tables
+                                // are replaced with cell references
+                                let code = convert_table_refs_to_a1(
+                                    &code_cell_value.code,
+                                    &self.a1_context,
+                                    pos.to_sheet_pos(sheet.id),
+                                    |table_ref| is_excel_table_ref(table_ref, &excel_table_names),
+                                );
+                                let code = code.as_str();
                                 let display_value = data_table.display_value(false)?;
 
                                 match display_value {
@@ -192,6 +780,64 @@ impl GridController {
                     }
                 }
             }
+
+            for (pos, data_table) in sheet.data_tables.expensive_iter() {
+                if data_table.is_html_or_image() {
+                    insert_excel_chart(worksheet, sheet, *pos, data_table, &options.chart_images)?;
+                }
+            }
+
+            if options.include_code_cells {
+                code_cells.extend(excel_code_cells(sheet));
+            }
+
+            // data validations that excel rejects are skipped
+            for validation in sheet.validations.validations().into_iter().flatten() {
+                let Some(data_validation) = excel_data_validation(validation, &self.a1_context)
+                else {
+                    continue;
+                };
+                for rect in validation.selection.rects_unbounded(&self.a1_context) {
+                    if rect.min.x > MAX_EXCEL_COL || rect.min.y > MAX_EXCEL_ROW {
+                        continue;
+                    }
+                    let _ = worksheet.add_data_validation(
+                        rect.min.y as u32 - 1,
+                        rect.min.x as u16 - 1,
+                        rect.max.y.min(MAX_EXCEL_ROW) as u32 - 1,
+                        rect.max.x.min(MAX_EXCEL_COL) as u16 - 1,
+                        &data_validation,
+                    );
+                }
+            }
+
+            workbook.push_worksheet(sheet_worksheet);
+        }
+
+        // the code cells sheet is skipped if a sheet already uses its name
+        if !code_cells.is_empty()
+            && self
+                .grid
+                .try_sheet_from_name(CODE_CELLS_SHEET_NAME)
+                .is_none()
+        {
+            let worksheet = workbook
+                .add_worksheet()
+                .set_name(CODE_CELLS_SHEET_NAME)
+                .map_err(error)?
+                .set_hidden(true);
+            for (col, header) in XlsxCodeCell::HEADERS.iter().enumerate() {
+                worksheet
+                    .write_string(0, col as u16, *header)
+                    .map_err(error)?;
+            }
+            for (row, code_cell) in code_cells.iter().enumerate() {
+                for (col, text) in code_cell.to_row().iter().enumerate() {
+                    worksheet
+                        .write_string(row as u32 + 1, col as u16, text)
+                        .map_err(error)?;
+                }
+            }
         }
 
         let buffer = workbook
@@ -202,6 +848,475 @@ impl GridController {
     }
 }
 
+/// Adds a chart to the worksheet as a picture below the chart's name row (if
+/// shown), scaled to the cells the chart covers.
+fn insert_excel_chart(
+    worksheet: &mut Worksheet,
+    sheet: &Sheet,
+    pos: Pos,
+    data_table: &DataTable,
+    chart_images: &HashMap<String, String>,
+) -> Result<()> {
+    let data_url = match &data_table.value {
+        Value::Single(CellValue::Image(image)) => Some(image),
+        _ => chart_images.get(data_table.name()),
+    };
+    let Some(buffer) = data_url.and_then(|data_url| decode_data_url(data_url.as_str())) else {
+        return Ok(());
+    };
+
+    // images that excel can't read (e.g., webp) are skipped
+    let Ok(image) = Image::new_from_buffer(&buffer) else {
+        return Ok(());
+    };
+
+    let top = pos.y + data_table.get_show_name() as i64;
+    if pos.x > MAX_EXCEL_COL || top > MAX_EXCEL_ROW {
+        return Ok(());
+    }
+    let (width, height) = match (data_table.chart_output, data_table.chart_pixel_output) {
+        (Some((w, h)), _) => (
+            (pos.x..pos.x + w as i64)
+                .map(|x| sheet.offsets.column_width(x))
+                .sum::<f64>(),
+            (top..top + h as i64)
+                .map(|y| sheet.offsets.row_height(y))
+                .sum::<f64>(),
+        ),
+        (None, Some((width, height))) => (width as f64, height as f64),
+        (None, None) => (image.width(), image.height()),
+    };
+
+    let image = image
+        .set_scale_to_size(width, height, false)
+        .set_alt_text(data_table.name());
+    worksheet
+        .insert_image(top as u32 - 1, pos.x as u16 - 1, &image)
+        .map_err(|e| anyhow!("Error adding chart to excel sheet: {}", e))?;
+
+    Ok(())
+}
+
+/// Returns the non-formula code cells of a sheet, which excel can't run.
+fn excel_code_cells(sheet: &Sheet) -> Vec<XlsxCodeCell> {
+    sheet
+        .data_tables
+        .expensive_iter()
+        .filter_map(|(pos, data_table)| {
+            let code_run = data_table.code_run()?;
+            if code_run.language == CodeCellLanguage::Formula {
+                return None;
+            }
+            let is_chart = data_table.is_html_or_image();
+
+            // outputs with errors aren't exported, so there is nothing to
+            // read back on import
+            let output_size = (!data_table.has_spill() && !data_table.has_error())
+                .then(|| data_table.output_size());
+
+            Some(XlsxCodeCell {
+                sheet_name: sheet.name.to_owned(),
+                pos: *pos,
+                language: code_run.language.to_owned(),
+                name: data_table.name().to_string(),
+                show_name: data_table.get_show_name(),
+                show_columns: data_table.get_show_columns(),
+                is_chart,
+                output_size,
+                code: code_run.code.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Decodes a base64 data URL (e.g., `data:image/png;base64,...`). Plain base64
+/// without the data URL prefix is also accepted.
+fn decode_data_url(data_url: &str) -> Option<Vec<u8>> {
+    let data = match data_url.strip_prefix("data:") {
+        Some(data_url) => {
+            let (media_type, data) = data_url.split_once(',')?;
+            if !media_type.ends_with(";base64") {
+                return None;
+            }
+            data
+        }
+        None => data_url,
+    };
+
+    STANDARD.decode(data.trim()).ok()
+}
+
+/// Returns the indices of the table's columns (including hidden columns) that
+/// are in the column range.
+fn table_columns_in_range(col_range: &ColRange, column_names: &[String]) -> Vec<usize> {
+    let index = |name: &str| {
+        column_names
+            .iter()
+            .position(|column_name| column_name.eq_ignore_ascii_case(name))
+    };
+    let columns = match col_range {
+        ColRange::All => Some(0..column_names.len()),
+        ColRange::Col(name) => index(name).map(|i| i..i + 1),
+        ColRange::ColRange(start, end) => index(start)
+            .zip(index(end))
+            .map(|(start, end)| start.min(end)..start.max(end) + 1),
+        ColRange::ColToEnd(name) => index(name).map(|i| i..column_names.len()),
+    };
+
+    columns.map(Iterator::collect).unwrap_or_default()
+}
+
+/// Returns a value as displayed, using its numeric or date format.
+fn formatted_value(value: &CellValue, format: crate::grid::formats::Format) -> String {
+    match value {
+        CellValue::Number(_) => value.to_number_display(
+            format.numeric_format,
+            format.numeric_decimals,
+            format.numeric_commas,
+        ),
+        CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Time(_) => {
+            Sheet::value_date_time(value, format.date_time)
+        }
+        _ => value.to_display(),
+    }
+}
+
+/// Returns true if the position is a column header of a data table.
+fn is_column_header(sheet: &Sheet, pos: Pos) -> bool {
+    sheet
+        .data_table_that_contains(pos)
+        .is_some_and(|(data_table_pos, data_table)| {
+            data_table.get_show_columns()
+                && pos.y == data_table_pos.y + data_table.get_show_name() as i64
+        })
+}
+
+/// Returns the style attribute of an html cell with the cell's format.
+fn html_style(format: &crate::grid::formats::Format) -> String {
+    let mut style = String::new();
+    if let Some(align) = format.align {
+        style.push_str(align.as_css_string());
+    }
+    if let Some(vertical_align) = format.vertical_align {
+        style.push_str(vertical_align.as_css_string());
+    }
+    if let Some(wrap) = format.wrap {
+        style.push_str(wrap.as_css_string());
+    }
+    if format.bold == Some(true) {
+        style.push_str("font-weight:bold;");
+    }
+    if format.italic == Some(true) {
+        style.push_str("font-style:italic;");
+    }
+    if let Some(text_color) = &format.text_color
+        && let Ok(text_color) = Rgba::try_from(text_color.as_str())
+    {
+        write!(style, "color:{};", text_color.as_rgb_hex()).ok();
+    }
+    if let Some(fill_color) = &format.fill_color
+        && let Ok(fill_color) = Rgba::try_from(fill_color.as_str())
+    {
+        write!(style, "background-color:{};", fill_color.as_rgb_hex()).ok();
+    }
+    match (
+        format.underline == Some(true),
+        format.strike_through == Some(true),
+    ) {
+        (true, true) => style.push_str("text-decoration:underline line-through;"),
+        (true, false) => style.push_str("text-decoration:underline;"),
+        (false, true) => style.push_str("text-decoration:line-through;"),
+        (false, false) => (),
+    }
+
+    if style.is_empty() {
+        style
+    } else {
+        format!(r#" style="{style}""#)
+    }
+}
+
+/// An excel table to export for a data table.
+struct ExcelTable {
+    /// The range of the table, without the name row.
+    rect: Rect,
+
+    /// The name row of the data table, if shown.
+    name_rect: Option<Rect>,
+
+    table: Table,
+}
+
+/// Returns the excel table for a data table. Only imported data tables are
+/// exported as excel tables; code output is flattened.
+fn excel_table(pos: Pos, data_table: &DataTable) -> Option<ExcelTable> {
+    if data_table.is_code()
+        || data_table.is_html_or_image()
+        || data_table.has_spill()
+        || data_table.has_error()
+    {
+        return None;
+    }
+
+    let mut rect = data_table.output_rect(pos, false);
+    if rect.max.x > MAX_EXCEL_COL || rect.max.y > MAX_EXCEL_ROW {
+        return None;
+    }
+    let name_rect = data_table.get_show_name().then(|| {
+        let name_rect = Rect::new(rect.min.x, rect.min.y, rect.max.x, rect.min.y);
+        rect.min.y += 1;
+        name_rect
+    });
+
+    // excel tables need at least one data row
+    let header_row = data_table.get_show_columns();
+    if rect.height() < if header_row { 2 } else { 1 } {
+        return None;
+    }
+
+    let columns = data_table
+        .column_headers
+        .as_ref()?
+        .iter()
+        .filter(|header| header.display)
+        .map(|header| TableColumn::new().set_header(header.name.to_string()))
+        .collect::<Vec<_>>();
+    if columns.len() != rect.width() as usize {
+        return None;
+    }
+
+    let table = Table::new()
+        .set_name(data_table.name())
+        .set_columns(&columns)
+        .set_header_row(header_row)
+        .set_banded_rows(data_table.alternating_colors);
+
+    Some(ExcelTable {
+        rect,
+        name_rect,
+        table,
+    })
+}
+
+/// Returns true if excel can resolve a table reference: the table is an excel
+/// table and the reference doesn't use syntax that only Quadratic supports.
+fn is_excel_table_ref(table_ref: &TableRef, excel_table_names: &[String]) -> bool {
+    excel_table_names
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(&table_ref.table_name))
+        && !table_ref.totals
+        && !(table_ref.headers && table_ref.data)
+        && !matches!(table_ref.col_range, ColRange::ColToEnd(_))
+}
+
+/// Inclusive bounds of a validation rule, for conversion to an excel rule.
+enum ExcelBounds<T> {
+    Range(Option<T>, Option<T>),
+    Equal(T),
+    NotEqual(T),
+}
+
+/// Converts the bounds of a validation rule to an excel rule. Excel has one
+/// comparison per validation, so only a single range or value, or two ranges
+/// that exclude the values between them, can be converted.
+fn excel_rule<T: IntoDataValidationValue + PartialOrd + Copy>(
+    bounds: &[ExcelBounds<T>],
+) -> Option<DataValidationRule<T>> {
+    match bounds {
+        [ExcelBounds::Range(Some(min), Some(max))] => Some(DataValidationRule::Between(*min, *max)),
+        [ExcelBounds::Range(Some(min), None)] => {
+            Some(DataValidationRule::GreaterThanOrEqualTo(*min))
+        }
+        [ExcelBounds::Range(None, Some(max))] => Some(DataValidationRule::LessThanOrEqualTo(*max)),
+        [ExcelBounds::Equal(value)] => Some(DataValidationRule::EqualTo(*value)),
+        [ExcelBounds::NotEqual(value)] => Some(DataValidationRule::NotEqualTo(*value)),
+        [
+            ExcelBounds::Range(None, Some(below)),
+            ExcelBounds::Range(Some(above), None),
+        ] if below < above => Some(DataValidationRule::NotBetween(*below, *above)),
+        _ => None,
+    }
+}
+
+/// Returns the single value of a list of values.
+fn single<T: Copy>(values: &[T]) -> Option<T> {
+    match values {
+        [value] => Some(*value),
+        _ => None,
+    }
+}
+
+/// Returns the excel formula for a list validation's source. Excel lists
+/// must be a range of cells, so table references are converted to their
+/// range.
+fn excel_list_formula(selection: &A1Selection, a1_context: &A1Context) -> Option<Formula> {
+    let [range] = selection.ranges.as_slice() else {
+        return None;
+    };
+    let rect = range.to_rect_unbounded(a1_context)?;
+    let sheet_name = a1_context.try_sheet_id(selection.sheet_id)?;
+    Some(Formula::new(format!(
+        "={}",
+        worksheet_range_absolute(
+            sheet_name,
+            rect.min.y as u32 - 1,
+            rect.min.x as u16 - 1,
+            rect.max.y.min(MAX_EXCEL_ROW) as u32 - 1,
+            rect.max.x.min(MAX_EXCEL_COL) as u16 - 1,
+        )
+    )))
+}
+
+/// Converts the rule of a validation to an excel data validation. Returns
+/// None for rules that excel can't express (e.g., text that must contain a
+/// value).
+fn excel_data_validation_rule(
+    rule: &ValidationRule,
+    a1_context: &A1Context,
+) -> Option<DataValidation> {
+    let data_validation = DataValidation::new();
+    match rule {
+        ValidationRule::None => Some(data_validation.allow_any_value()),
+        ValidationRule::List(list) => {
+            let data_validation = match &list.source {
+                ValidationListSource::List(items) => {
+                    data_validation.allow_list_strings(items).ok()?
+                }
+                ValidationListSource::Selection(selection) => {
+                    data_validation.allow_list_formula(excel_list_formula(selection, a1_context)?)
+                }
+            };
+            Some(
+                data_validation
+                    .ignore_blank(list.ignore_blank)
+                    .show_dropdown(list.drop_down),
+            )
+        }
+        // checkboxes are exported as a TRUE/FALSE list
+        ValidationRule::Logical(logical) => Some(
+            data_validation
+                .allow_list_strings(&["TRUE", "FALSE"])
+                .ok()?
+                .ignore_blank(logical.ignore_blank),
+        ),
+        ValidationRule::Text(text) => {
+            let [TextMatch::TextLength { min, max }] = text.text_match.as_slice() else {
+                return None;
+            };
+            let length = |length: &Option<i16>| length.map(|length| length.max(0) as u32);
+            let rule = excel_rule(&[ExcelBounds::Range(length(min), length(max))])?;
+            Some(
+                data_validation
+                    .allow_text_length(rule)
+                    .ignore_blank(text.ignore_blank),
+            )
+        }
+        ValidationRule::Number(number) => {
+            let bounds = number
+                .ranges
+                .iter()
+                .map(|range| match range {
+                    NumberRange::Range(min, max) => Some(ExcelBounds::Range(*min, *max)),
+                    NumberRange::Equal(values) => single(values).map(ExcelBounds::Equal),
+                    NumberRange::NotEqual(values) => single(values).map(ExcelBounds::NotEqual),
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(
+                data_validation
+                    .allow_decimal_number(excel_rule(&bounds)?)
+                    .ignore_blank(number.ignore_blank),
+            )
+        }
+        ValidationRule::DateTime(date_time) => {
+            let date = |date: &Option<i64>| match date {
+                Some(date) => i64_to_naive_date(*date).map(Some),
+                None => Some(None),
+            };
+            let time = |time: &Option<i32>| match time {
+                Some(time) => i32_to_naive_time(*time).map(Some),
+                None => Some(None),
+            };
+            let date_bounds = date_time
+                .ranges
+                .iter()
+                .map(|range| match range {
+                    DateTimeRange::DateRange(min, max) => {
+                        Some(ExcelBounds::Range(date(min)?, date(max)?))
+                    }
+                    DateTimeRange::DateEqual(values) => {
+                        Some(ExcelBounds::Equal(i64_to_naive_date(single(values)?)?))
+                    }
+                    DateTimeRange::DateNotEqual(values) => {
+                        Some(ExcelBounds::NotEqual(i64_to_naive_date(single(values)?)?))
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let time_bounds = date_time
+                .ranges
+                .iter()
+                .map(|range| match range {
+                    DateTimeRange::TimeRange(min, max) => {
+                        Some(ExcelBounds::Range(time(min)?, time(max)?))
+                    }
+                    DateTimeRange::TimeEqual(values) => {
+                        Some(ExcelBounds::Equal(i32_to_naive_time(single(values)?)?))
+                    }
+                    DateTimeRange::TimeNotEqual(values) => {
+                        Some(ExcelBounds::NotEqual(i32_to_naive_time(single(values)?)?))
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let data_validation = if let Some(rule) = date_bounds.and_then(|b| excel_rule(&b)) {
+                data_validation.allow_date(rule)
+            } else if let Some(rule) = time_bounds.and_then(|b| excel_rule(&b)) {
+                data_validation.allow_time(rule)
+            } else {
+                return None;
+            };
+            Some(data_validation.ignore_blank(date_time.ignore_blank))
+        }
+    }
+}
+
+/// Converts a validation to an excel data validation. Rules that excel can't
+/// express are exported as allowing any value, so their messages are kept.
+fn excel_data_validation(
+    validation: &Validation,
+    a1_context: &A1Context,
+) -> Option<DataValidation> {
+    // excel limits titles to 32 characters and messages to 255
+    let truncate = |text: &String, max: usize| text.chars().take(max).collect::<String>();
+
+    let mut data_validation = excel_data_validation_rule(&validation.rule, a1_context)
+        .unwrap_or_else(|| DataValidation::new().allow_any_value())
+        .show_input_message(validation.message.show)
+        .show_error_message(validation.error.show)
+        .set_error_style(match validation.error.style {
+            ValidationStyle::Stop => DataValidationErrorStyle::Stop,
+            ValidationStyle::Warning => DataValidationErrorStyle::Warning,
+            ValidationStyle::Information => DataValidationErrorStyle::Information,
+        });
+    if let Some(title) = &validation.message.title {
+        data_validation = data_validation.set_input_title(truncate(title, 32)).ok()?;
+    }
+    if let Some(message) = &validation.message.message {
+        data_validation = data_validation
+            .set_input_message(truncate(message, 255))
+            .ok()?;
+    }
+    if let Some(title) = &validation.error.title {
+        data_validation = data_validation.set_error_title(truncate(title, 32)).ok()?;
+    }
+    if let Some(message) = &validation.error.message {
+        data_validation = data_validation
+            .set_error_message(truncate(message, 255))
+            .ok()?;
+    }
+    Some(data_validation)
+}
+
 /// Writes a value to an excel worksheet and sets the format.
 fn write_excel_value(
     worksheet: &mut Worksheet,
@@ -223,6 +1338,14 @@ fn write_excel_value(
                 CellValue::Time(t) => worksheet.write_datetime(row, col, t),
                 CellValue::DateTime(dt) => worksheet.write_datetime(row, col, dt),
                 CellValue::Logical(b) => worksheet.write_boolean(row, col, *b),
+                CellValue::Hyperlink(hyperlink) => {
+                    // urls that excel can't store (e.g., too long) are written as text
+                    if worksheet.write_url(row, col, excel_url(hyperlink)).is_err() {
+                        worksheet.write_string(row, col, hyperlink.display_text())
+                    } else {
+                        Ok(&mut *worksheet)
+                    }
+                }
                 _ => worksheet.write_string(row, col, cell_value.to_string()),
             }
             .map(|_| ())
@@ -239,9 +1362,27 @@ fn write_excel_value(
     Ok(())
 }
 
+/// Converts a hyperlink to an excel url. Internal links use excel's
+/// `internal:` prefix.
+fn excel_url(hyperlink: &Hyperlink) -> Url {
+    let url = match &hyperlink.target {
+        HyperlinkTarget::Url(url) => Url::new(url),
+        HyperlinkTarget::Internal(reference) => Url::new(format!("internal:{reference}")),
+    };
+    match &hyperlink.text {
+        Some(text) => url.set_text(text),
+        None => url,
+    }
+}
+
 /// Gets the excel formats for a cell value.
 fn get_excel_formats(v: Option<&CellValue>, pos: Pos, sheet: &Sheet) -> Format {
     let mut format = Format::new();
+
+    if let Some(CellValue::Hyperlink(_)) = v {
+        format = format.set_hyperlink();
+    }
+
     let cell_format = sheet.cell_format(pos);
     let bold = cell_format.bold.unwrap_or(false);
     let italic = cell_format.italic.unwrap_or(false);
@@ -453,10 +1594,34 @@ mod tests {
 
     use super::*;
 
+    use std::io::Cursor;
+
+    use arrow_ipc::reader::FileReader;
+    use arrow_schema::DataType;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
     use crate::{
         Array,
-        controller::user_actions::import::tests::{assert_flattened_simple_csv, simple_csv},
-        grid::sheet::borders::{BorderSelection, BorderStyle, Borders},
+        controller::user_actions::import::tests::simple_csv,
+        date_time::naive_date_to_i64,
+        grid::{
+            CodeCellValue,
+            column_header::DataTableColumnHeader,
+            sheet::{
+                borders::{BorderSelection, BorderStyle, Borders},
+                validations::{
+                    rules::{
+                        validation_date_time::ValidationDateTime, validation_list::ValidationList,
+                        validation_logical::ValidationLogical, validation_number::ValidationNumber,
+                        validation_text::ValidationText,
+                    },
+                    validation::{ValidationError, ValidationMessage},
+                },
+            },
+        },
+        parquet::parquet_to_array,
+        test_util::*,
     };
 
     #[test]
@@ -496,9 +1661,335 @@ mod tests {
         println!("{result}");
     }
 
+    #[test]
+    fn exports_a_csv_with_options() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_value(pos![sheet_id!A1], "1234.5".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!B1], "2024-01-02".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!C1], "a,b".into(), None, false);
+        gc.set_currency(&A1Selection::test_a1("A1"), "$".into(), None, false)
+            .unwrap();
+        gc.set_date_time_format(
+            &A1Selection::test_a1("B1"),
+            Some("%d/%m/%Y".into()),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let options = CsvExportOptions {
+            delimiter: b'\t',
+            quote_style: CsvQuoteStyle::Always,
+            line_ending: CsvLineEnding::Crlf,
+            formatted_values: true,
+            ..Default::default()
+        };
+        let result = gc
+            .export_csv_selection_with_options(&mut A1Selection::test_a1("A1:C1"), &options)
+            .unwrap();
+        assert_eq!(result, "\"$1,234.50\"\t\"02/01/2024\"\t\"a,b\"\r\n");
+
+        let result = gc
+            .export_csv_selection_with_options(
+                &mut A1Selection::test_a1("A1"),
+                &CsvExportOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(result, "1234.5\n");
+    }
+
+    #[test]
+    fn exports_a_csv_table_with_hidden_columns() {
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+        let pos = pos![A1];
+        test_create_data_table(&mut gc, sheet_id, pos, 3, 2);
+
+        let data_table = gc.sheet(sheet_id).data_table_at(&pos).unwrap();
+        let table_name = data_table.name().to_string();
+        let mut column_headers = data_table
+            .column_headers
+            .to_owned()
+            .unwrap_or_else(|| data_table.default_header(None));
+        column_headers[1].display = false;
+        gc.test_data_table_update_meta(
+            pos.to_sheet_pos(sheet_id),
+            Some(column_headers),
+            None,
+            None,
+        );
+
+        let data_table = gc.sheet(sheet_id).data_table_at(&pos).unwrap();
+        let header = data_table.columns_map(true).join(",");
+        let options = CsvExportOptions {
+            include_hidden_columns: true,
+            ..Default::default()
+        };
+        let mut selection = A1Selection::table(pos.to_sheet_pos(sheet_id), &table_name);
+        let result = gc
+            .export_csv_selection_with_options(&mut selection, &options)
+            .unwrap();
+        assert_eq!(result, format!("{header}\n0,1,2\n3,4,5\n"));
+
+        let result = gc.export_csv_selection(&mut selection).unwrap();
+        assert!(!result.contains("0,1,2"));
+        assert!(result.contains("0,2"));
+    }
+
+    #[test]
+    fn exports_selected_csv_table_columns_with_hidden_columns() {
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+        let pos = pos![A1];
+        test_create_data_table(&mut gc, sheet_id, pos, 4, 2);
+
+        let data_table = gc.sheet(sheet_id).data_table_at(&pos).unwrap();
+        let table_name = data_table.name().to_string();
+        let mut column_headers = data_table
+            .column_headers
+            .to_owned()
+            .unwrap_or_else(|| data_table.default_header(None));
+        column_headers[1].display = false;
+        gc.test_data_table_update_meta(
+            pos.to_sheet_pos(sheet_id),
+            Some(column_headers),
+            None,
+            None,
+        );
+
+        let column_names = gc
+            .sheet(sheet_id)
+            .data_table_at(&pos)
+            .unwrap()
+            .columns_map(true);
+        let options = CsvExportOptions {
+            include_hidden_columns: true,
+            ..Default::default()
+        };
+        let export = |a1: String| {
+            let mut selection = A1Selection::parse(&a1, sheet_id, gc.a1_context(), None).unwrap();
+            gc.export_csv_selection_with_options(&mut selection, &options)
+                .unwrap()
+        };
+
+        // a single column
+        let result = export(format!("{table_name}[{}]", column_names[2]));
+        assert_eq!(result, format!("{}\n2\n6\n", column_names[2]));
+
+        // a range of columns includes the hidden columns within it
+        let result = export(format!(
+            "{table_name}[[{}]:[{}]]",
+            column_names[0], column_names[2]
+        ));
+        assert_eq!(
+            result,
+            format!("{}\n0,1,2\n4,5,6\n", column_names[..3].join(","))
+        );
+    }
+
+    #[test]
+    fn exports_all_sheets_as_a_csv_zip() {
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+        gc.set_cell_value(pos![sheet_id!A1], "a".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!B2], "b".into(), None, false);
+        gc.add_sheet_with_name("Q1/Q2".into(), None, false);
+
+        let options = CsvExportOptions {
+            delimiter: b'\t',
+            ..Default::default()
+        };
+        let file = gc.export_csv_sheets_zip(&options).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(file)).unwrap();
+
+        let mut files = vec![];
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i).unwrap();
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut file, &mut content).unwrap();
+            files.push((file.name().to_string(), content));
+        }
+        assert_eq!(
+            files,
+            vec![
+                ("Sheet1.tsv".to_string(), "a\t\n\tb\n".to_string()),
+                ("Q1_Q2.tsv".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn exports_parquet_and_arrow_selections() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        for (a1, value) in [
+            ("A1", "name"),
+            ("B1", "amount"),
+            ("C1", "date"),
+            ("A2", "a"),
+            ("B2", "1.5"),
+            ("C2", "2024-01-02"),
+            ("A3", "b"),
+            ("B3", "2"),
+        ] {
+            gc.set_cell_value(
+                Pos::try_a1_string(a1).unwrap().to_sheet_pos(sheet_id),
+                value.to_string(),
+                None,
+                false,
+            );
+        }
+        let selection = A1Selection::test_a1("A1:C3");
+
+        let parquet = gc.export_parquet_selection(&selection).unwrap();
+        let array = parquet_to_array(parquet, "test.parquet", None::<fn(&str, u32, u32)>).unwrap();
+        assert_eq!(array.get(1, 0).unwrap(), &CellValue::Text("amount".into()));
+        assert_eq!(array.get(0, 2).unwrap(), &CellValue::Text("b".into()));
+        assert_eq!(
+            array.get(1, 1).unwrap(),
+            &CellValue::Number(Decimal::new(15, 1))
+        );
+        assert_eq!(
+            array.get(2, 1).unwrap(),
+            &CellValue::Date(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap())
+        );
+        assert_eq!(array.get(2, 2).unwrap(), &CellValue::Blank);
+
+        let arrow = gc.export_arrow_selection(&selection).unwrap();
+        let reader = FileReader::try_new(Cursor::new(arrow), None).unwrap();
+        let schema = reader.schema();
+        let types = schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().as_str(), field.data_type().clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                ("name", DataType::Utf8),
+                ("amount", DataType::Decimal128(38, 1)),
+                ("date", DataType::Date32),
+            ]
+        );
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+    }
+
+    #[test]
+    fn exports_decimals_that_dont_fit_the_column_scale_as_floats() {
+        let values = [
+            CellValue::Number(Decimal::new(15, 1)),
+            CellValue::Blank,
+            CellValue::Number(Decimal::new(225, 2)),
+        ];
+        let array = cell_values_to_arrow_col(&values).unwrap();
+        assert_eq!(array.data_type(), &DataType::Decimal128(38, 2));
+
+        // the largest decimal can't be rescaled to hold the other's digits
+        let values = [
+            CellValue::Number(Decimal::MAX),
+            CellValue::Number(Decimal::new(1, 10)),
+        ];
+        let array = cell_values_to_arrow_col(&values).unwrap();
+        assert_eq!(array.data_type(), &DataType::Float64);
+    }
+
+    #[test]
+    fn exports_a_table_to_arrow_with_its_column_names() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        test_create_data_table(&mut gc, sheet_id, pos![B2], 3, 2);
+
+        let selection = A1Selection::test_a1_context("test_table", gc.a1_context());
+        let arrow = gc.export_arrow_selection(&selection).unwrap();
+        let reader = FileReader::try_new(Cursor::new(arrow), None).unwrap();
+
+        let names = reader
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        let table = gc.a1_context().try_table("test_table").unwrap();
+        assert_eq!(names, table.visible_columns);
+
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+    }
+
+    #[test]
+    fn exports_a_selection_as_markdown_and_html() {
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+
+        test_create_data_table(&mut gc, sheet_id, pos![A1], 2, 2);
+        gc.set_cell_value(pos![sheet_id!D1], "a|b".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!D2], "x<y".into(), None, false);
+        gc.set_bold(&A1Selection::test_a1("D2"), Some(true), None, false)
+            .unwrap();
+
+        // the table's name row is skipped and its column headers are the
+        // header row
+        let selection = A1Selection::test_a1("A1:B4");
+        assert_eq!(
+            gc.export_markdown_selection(&selection).unwrap(),
+            "| Column 1 | Column 2 |\n| --- | --- |\n| 0 | 1 |\n| 2 | 3 |\n"
+        );
+        assert_eq!(
+            gc.export_html_selection(&A1Selection::test_a1("A2:B3"))
+                .unwrap(),
+            "<table><thead><tr><th>Column 1</th><th>Column 2</th></tr></thead><tbody><tr><td>0</td><td>1</td></tr></tbody></table>"
+        );
+
+        let selection = A1Selection::test_a1("D1:D2");
+        assert_eq!(
+            gc.export_markdown_selection(&selection).unwrap(),
+            "| a\\|b |\n| --- |\n| x<y |\n"
+        );
+        assert_eq!(
+            gc.export_html_selection(&selection).unwrap(),
+            r#"<table><tbody><tr><td>a|b</td></tr><tr><td style="font-weight:bold;">x&lt;y</td></tr></tbody></table>"#
+        );
+    }
+
+    #[test]
+    fn exports_a_table_as_json() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let file = br#"[{"name": "a", "address": {"city": "Paris"}, "joined": "2024-01-02"}, {"name": "b", "count": 2}]"#;
+        gc.import_json(sheet_id, file, "people.json", pos![A1], None, false, false)
+            .unwrap();
+
+        let table_name = gc
+            .sheet(sheet_id)
+            .data_table_at(&pos![A1])
+            .unwrap()
+            .name()
+            .to_string();
+        let json = gc.export_table_json(&table_name).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!([
+                {"name": "a", "address": {"city": "Paris"}, "joined": "2024-01-02", "count": null},
+                {"name": "b", "address": {"city": null}, "joined": null, "count": 2},
+            ])
+        );
+
+        assert!(gc.export_table_json("missing").is_err());
+    }
+
     #[test]
     fn exports_excel() {
-        let (gc, ..) = simple_csv();
+        let (gc, csv_sheet_id, csv_pos, _) = simple_csv();
+        let table_name = gc
+            .sheet(csv_sheet_id)
+            .data_table_at(&csv_pos)
+            .unwrap()
+            .name()
+            .to_string();
         let file_name = "test.xlsx";
         let excel = gc.export_excel();
 
@@ -512,13 +2003,232 @@ mod tests {
         gc.import_excel(&excel.unwrap(), file_name, None, false)
             .unwrap();
         let sheet_id = gc.sheet_ids()[1];
-        let pos = Pos { x: 1, y: 1 };
 
-        assert_flattened_simple_csv(&gc, sheet_id, pos, file_name);
+        // the data table is exported as an excel table without its name row
+        let data_table = gc.sheet(sheet_id).data_table_at(&pos![A2]).unwrap();
+        assert_eq!(data_table.name(), table_name);
+        assert!(!data_table.get_show_name());
+
+        let first_row = vec!["city", "region", "country", "population"];
+        assert_cell_value_row(&gc, sheet_id, 1, 4, 2, first_row);
+
+        let last_row = vec!["Concord", "NH", "United States", "42605"];
+        assert_cell_value_row(&gc, sheet_id, 1, 4, 12, last_row);
 
         // TODO(ddimaria): test excel file formatting once import formatting is implemented
     }
 
+    #[test]
+    fn test_import_export_import_excel_with_tables() {
+        let mut gc_1 = GridController::test();
+        let sheet_id_1 = gc_1.sheet_ids()[0];
+        let values = vec![
+            vec!["Region".to_string(), "Amount".to_string()],
+            vec!["East".to_string(), "10".to_string()],
+            vec!["West".to_string(), "20".to_string()],
+        ];
+        gc_1.add_data_table(
+            pos![sheet_id_1!A1],
+            "Sales".to_string(),
+            values,
+            true,
+            None,
+            false,
+        );
+        gc_1.sheet_mut(sheet_id_1)
+            .modify_data_table_at(&pos![A1], |dt| {
+                dt.alternating_colors = false;
+                Ok(())
+            })
+            .unwrap();
+
+        // code output is flattened, so references to it become cell references
+        let code_table = test_create_code_table(&mut gc_1, sheet_id_1, pos![D1], 1, 2);
+        gc_1.set_code_cell(
+            pos![sheet_id_1!F1],
+            CodeCellLanguage::Formula,
+            "SUM(Sales[Amount])".to_string(),
+            None,
+            None,
+            false,
+        );
+        gc_1.set_code_cell(
+            pos![sheet_id_1!F2],
+            CodeCellLanguage::Formula,
+            format!("COUNTA({})", code_table.name()),
+            None,
+            None,
+            false,
+        );
+
+        let excel = gc_1.export_excel().unwrap();
+
+        let mut gc_2 = GridController::new_blank();
+        gc_2.import_excel(&excel, "test.xlsx", None, false).unwrap();
+        let sheet_id_2 = gc_2.sheet_ids()[0];
+        let sheet_2 = gc_2.sheet(sheet_id_2);
+
+        let data_table = sheet_2.data_table_at(&pos![A2]).unwrap();
+        assert_eq!(data_table.name(), "Sales");
+        assert!(data_table.get_show_columns());
+        assert!(!data_table.alternating_colors);
+        assert_eq!(
+            data_table.column_headers_to_cell_values(),
+            Some(vec![
+                CellValue::Text("Region".to_string()),
+                CellValue::Text("Amount".to_string())
+            ])
+        );
+        assert_display_cell_value(&gc_2, sheet_id_2, 2, 4, "20");
+
+        assert_eq!(
+            sheet_2.cell_value(pos![F1]),
+            Some(CellValue::Code(CodeCellValue {
+                language: CodeCellLanguage::Formula,
+                code: "SUM(Sales[Amount])".to_string(),
+            }))
+        );
+        assert_display_cell_value(&gc_2, sheet_id_2, 6, 1, "30");
+        assert!(sheet_2.data_table_at(&pos![D1]).is_none());
+        assert_display_cell_value(&gc_2, sheet_id_2, 6, 2, "2");
+    }
+
+    #[test]
+    fn test_exports_excel_with_charts_as_pictures() {
+        const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGP4z8DwHwAFAAH/iZk9HQAAAABJRU5ErkJggg==";
+
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+
+        test_create_js_chart(&mut gc, sheet_id, pos![A1], 3, 4);
+        gc.sheet_mut(sheet_id)
+            .modify_data_table_at(&pos![A1], |dt| {
+                dt.value = Value::Single(CellValue::Image(format!("data:image/png;base64,{PNG}")));
+                Ok(())
+            })
+            .unwrap();
+        let html_chart = test_create_html_chart(&mut gc, sheet_id, pos![F1], 3, 4);
+
+        let pictures = |file: Vec<u8>| {
+            let mut archive = zip::ZipArchive::new(Cursor::new(file)).unwrap();
+            let Ok(mut drawing) = archive.by_name("xl/drawings/drawing1.xml") else {
+                return 0;
+            };
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut drawing, &mut content).unwrap();
+            content.matches("<xdr:pic>").count()
+        };
+
+        // the html chart is skipped without a fallback image
+        assert_eq!(pictures(gc.export_excel().unwrap()), 1);
+
+        let chart_images = HashMap::from([(html_chart.name().to_string(), PNG.to_string())]);
+        assert_eq!(
+            pictures(gc.export_excel_with_chart_images(&chart_images).unwrap()),
+            2
+        );
+
+        assert_eq!(decode_data_url("data:text/plain,hello"), None);
+        assert_eq!(decode_data_url("aGk="), Some(b"hi".to_vec()));
+    }
+
+    #[test]
+    fn test_excel_chart_is_placed_below_the_name_row() {
+        const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR4nGP4z8DwHwAFAAH/iZk9HQAAAABJRU5ErkJggg==";
+
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+        test_create_code_table(&mut gc, sheet_id, pos![B3], 2, 2);
+
+        let sheet = gc.sheet(sheet_id);
+        let mut data_table = sheet.data_table_at(&pos![B3]).unwrap().to_owned();
+        let chart_images = HashMap::from([(data_table.name().to_string(), PNG.to_string())]);
+
+        // the (0-indexed) row the picture is anchored to
+        let anchor_row = |data_table: &DataTable| {
+            let mut workbook = Workbook::new();
+            let worksheet = workbook.add_worksheet();
+            insert_excel_chart(worksheet, sheet, pos![B3], data_table, &chart_images).unwrap();
+
+            let file = workbook.save_to_buffer().unwrap();
+            let mut archive = zip::ZipArchive::new(Cursor::new(file)).unwrap();
+            let mut drawing = archive.by_name("xl/drawings/drawing1.xml").unwrap();
+            let mut content = String::new();
+            std::io::Read::read_to_string(&mut drawing, &mut content).unwrap();
+
+            let from = content.split("<xdr:from>").nth(1).unwrap();
+            let row = from.split("<xdr:row>").nth(1).unwrap();
+            row.split('<').next().unwrap().parse::<u32>().unwrap()
+        };
+
+        data_table.show_name = Some(true);
+        assert_eq!(anchor_row(&data_table), 3);
+
+        // without a name row, the picture starts at the table's first row
+        data_table.show_name = Some(false);
+        assert_eq!(anchor_row(&data_table), 2);
+    }
+
+    #[test]
+    fn test_import_export_import_excel_with_code_cells() {
+        let mut gc_1 = test_create_gc();
+        let sheet_id_1 = first_sheet_id(&gc_1);
+
+        test_create_code_table(&mut gc_1, sheet_id_1, pos![A1], 1, 1);
+        test_create_code_table(&mut gc_1, sheet_id_1, pos![C1], 2, 2);
+        gc_1.test_data_table_update_meta(
+            pos![sheet_id_1!C1],
+            Some(vec![
+                DataTableColumnHeader::new("a".to_string(), true, 0),
+                DataTableColumnHeader::new("b".to_string(), true, 1),
+            ]),
+            Some(true),
+            Some(true),
+        );
+
+        let import = |excel: Vec<u8>| {
+            let mut gc = GridController::new_blank();
+            gc.import_excel(&excel, "test.xlsx", None, false).unwrap();
+            gc
+        };
+
+        // code cells are flattened by default
+        let gc_2 = import(gc_1.export_excel().unwrap());
+        let sheet_id_2 = gc_2.sheet_ids()[0];
+        assert!(gc_2.sheet(sheet_id_2).data_table_at(&pos![C1]).is_none());
+        assert_display_cell_value(&gc_2, sheet_id_2, 4, 4, "3");
+
+        let options = ExcelExportOptions {
+            include_code_cells: true,
+            ..Default::default()
+        };
+        let gc_2 = import(gc_1.export_excel_with_options(&options).unwrap());
+        assert_eq!(gc_2.sheet_ids().len(), 1);
+        let sheet_id_2 = gc_2.sheet_ids()[0];
+        let sheet_2 = gc_2.sheet(sheet_id_2);
+
+        let code = Some(CellValue::Code(CodeCellValue {
+            language: CodeCellLanguage::Python,
+            code: "code".to_string(),
+        }));
+        assert_eq!(sheet_2.cell_value(pos![A1]), code);
+        assert_display_cell_value(&gc_2, sheet_id_2, 1, 1, "0");
+
+        assert_eq!(sheet_2.cell_value(pos![C1]), code);
+        let data_table = sheet_2.data_table_at(&pos![C1]).unwrap();
+        assert!(data_table.get_show_name());
+        assert!(data_table.get_show_columns());
+        assert_eq!(
+            data_table.column_headers_to_cell_values(),
+            Some(vec![
+                CellValue::Text("a".to_string()),
+                CellValue::Text("b".to_string())
+            ])
+        );
+        assert_display_cell_value(&gc_2, sheet_id_2, 3, 3, "0");
+        assert_display_cell_value(&gc_2, sheet_id_2, 4, 4, "3");
+    }
+
     #[test]
     fn test_write_excel_value() {
         let mut gc = GridController::test();
@@ -533,6 +2243,167 @@ mod tests {
         assert!(buffer.is_ok());
     }
 
+    #[test]
+    fn test_import_export_import_excel_with_hyperlinks() {
+        let mut gc_1 = GridController::test();
+        let sheet_id_1 = gc_1.sheet_ids()[0];
+        let url = CellValue::Hyperlink(Hyperlink::new_url(
+            "https://quadratichq.com",
+            Some("Quadratic".to_string()),
+        ));
+        let url_without_text =
+            CellValue::Hyperlink(Hyperlink::new_url("https://quadratichq.com", None));
+        let internal = CellValue::Hyperlink(Hyperlink::new_internal(
+            "'Sheet 1'!B2",
+            Some("Go to B2".to_string()),
+        ));
+        let sheet = gc_1.sheet_mut(sheet_id_1);
+        sheet.set_cell_value(pos![A1], url.clone());
+        sheet.set_cell_value(pos![A2], url_without_text.clone());
+        sheet.set_cell_value(pos![A3], internal.clone());
+
+        let excel = gc_1.export_excel().unwrap();
+
+        let mut gc_2 = GridController::new_blank();
+        gc_2.import_excel(&excel, "test.xlsx", None, false).unwrap();
+        let sheet_2 = gc_2.sheet(gc_2.sheet_ids()[0]);
+
+        assert_eq!(sheet_2.cell_value(pos![A1]), Some(url));
+        assert_eq!(sheet_2.cell_value(pos![A2]), Some(url_without_text));
+        assert_eq!(sheet_2.cell_value(pos![A3]), Some(internal));
+    }
+
+    #[test]
+    fn test_import_export_import_excel_with_validations() {
+        let mut gc_1 = GridController::test();
+        let sheet_id_1 = gc_1.sheet_ids()[0];
+        let validation = |a1: &str, rule: ValidationRule| Validation {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id(a1, sheet_id_1),
+            rule,
+            message: ValidationMessage::default(),
+            error: ValidationError::default(),
+        };
+        let validations = vec![
+            Validation {
+                message: ValidationMessage {
+                    show: true,
+                    title: Some("Status".to_string()),
+                    message: Some("Pick a status".to_string()),
+                },
+                error: ValidationError {
+                    show: true,
+                    style: ValidationStyle::Warning,
+                    title: Some("Unknown status".to_string()),
+                    message: Some("Use one of the statuses".to_string()),
+                },
+                ..validation(
+                    "A1:A5",
+                    ValidationRule::List(ValidationList {
+                        source: ValidationListSource::List(vec![
+                            "Open".to_string(),
+                            "Closed".to_string(),
+                        ]),
+                        ignore_blank: true,
+                        drop_down: true,
+                    }),
+                )
+            },
+            validation(
+                "B1",
+                ValidationRule::List(ValidationList {
+                    source: ValidationListSource::Selection(A1Selection::test_a1_sheet_id(
+                        "A10:A12", sheet_id_1,
+                    )),
+                    ignore_blank: false,
+                    drop_down: false,
+                }),
+            ),
+            validation(
+                "C1:C5",
+                ValidationRule::Number(ValidationNumber {
+                    ignore_blank: true,
+                    ranges: vec![NumberRange::Range(Some(1.0), Some(10.0))],
+                }),
+            ),
+            validation(
+                "D1",
+                ValidationRule::DateTime(ValidationDateTime {
+                    ignore_blank: true,
+                    ranges: vec![DateTimeRange::DateRange(
+                        naive_date_to_i64(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+                        None,
+                    )],
+                    ..Default::default()
+                }),
+            ),
+            validation(
+                "E1",
+                ValidationRule::Logical(ValidationLogical {
+                    show_checkbox: true,
+                    ignore_blank: true,
+                }),
+            ),
+            validation(
+                "F1",
+                ValidationRule::Text(ValidationText {
+                    ignore_blank: true,
+                    text_match: vec![TextMatch::TextLength {
+                        min: Some(2),
+                        max: Some(5),
+                    }],
+                }),
+            ),
+        ];
+        let sheet = gc_1.sheet_mut(sheet_id_1);
+        for validation in validations.iter() {
+            sheet.validations.set(validation.clone());
+        }
+        sheet.set_cell_value(pos![C1], CellValue::Number(20.into()));
+
+        let excel = gc_1.export_excel().unwrap();
+
+        let mut gc_2 = GridController::new_blank();
+        gc_2.import_excel(&excel, "test.xlsx", None, false).unwrap();
+        let sheet_id_2 = gc_2.sheet_ids()[0];
+        let sheet_2 = gc_2.sheet(sheet_id_2);
+        let imported = sheet_2.validations.validations().unwrap();
+        assert_eq!(imported.len(), validations.len());
+
+        for validation in validations.iter() {
+            let rects = validation.selection.rects(gc_1.a1_context());
+            let imported = imported
+                .iter()
+                .find(|imported| imported.selection.rects(gc_2.a1_context()) == rects)
+                .unwrap();
+            assert_eq!(imported.selection.sheet_id, sheet_id_2);
+            assert_eq!(imported.message, validation.message);
+            assert_eq!(imported.error, validation.error);
+            match (&imported.rule, &validation.rule) {
+                (
+                    ValidationRule::List(ValidationList {
+                        source: ValidationListSource::Selection(imported_source),
+                        ..
+                    }),
+                    ValidationRule::List(ValidationList {
+                        source: ValidationListSource::Selection(source),
+                        ..
+                    }),
+                ) => {
+                    assert_eq!(imported_source.sheet_id, sheet_id_2);
+                    assert_eq!(
+                        imported_source.rects(gc_2.a1_context()),
+                        source.rects(gc_1.a1_context())
+                    );
+                }
+                (imported_rule, rule) => assert_eq!(imported_rule, rule),
+            }
+        }
+
+        // warnings are computed for the imported values
+        assert!(sheet_2.validations.warnings.contains_key(&pos![C1]));
+    }
+
     #[test]
     fn test_get_excel_formats() {
         let cell_value = CellValue::Number(100.into());
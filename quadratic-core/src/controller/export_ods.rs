@@ -0,0 +1,801 @@
+//! Exports the grid as an OpenDocument spreadsheet (ODS).
+//!
+//! Mirrors the excel export: values, formulas (translated to OpenFormula),
+//! number formats, fonts, fills, borders, column widths, and row heights.
+//! Data tables are flattened to their displayed values.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{Cursor, Write},
+};
+
+use anyhow::{Result, anyhow};
+use quick_xml::escape::escape;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use super::GridController;
+use crate::{
+    CellValue, HyperlinkTarget, Pos, Value,
+    color::Rgba,
+    date_time::{DEFAULT_DATE_FORMAT, DEFAULT_DATE_TIME_FORMAT, DEFAULT_TIME_FORMAT},
+    formulas::convert_to_open_formula,
+    grid::{
+        CellAlign, CellVerticalAlign, CellWrap, CodeCellLanguage, GridBounds, NumericFormatKind,
+        Sheet, formats::Format, sheet::borders::CellBorderLine,
+    },
+};
+
+const ODS_MIMETYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+const ODS_NAMESPACES: &str = concat!(
+    r#"xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" "#,
+    r#"xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" "#,
+    r#"xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" "#,
+    r#"xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" "#,
+    r#"xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" "#,
+    r#"xmlns:number="urn:oasis:names:tc:opendocument:xmlns:datastyle:1.0" "#,
+    r#"xmlns:xlink="http://www.w3.org/1999/xlink" "#,
+    r#"xmlns:of="urn:oasis:names:tc:opendocument:xmlns:of:1.2" "#,
+    r#"office:version="1.3""#,
+);
+
+const ODS_MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3">
+<manifest:file-entry manifest:full-path="/" manifest:version="1.3" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+<manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>"#;
+
+const MAX_ODS_ROW: i64 = 1048576;
+const MAX_ODS_COL: i64 = 16384;
+
+/// Screen pixels per inch, for converting column widths and row heights.
+const PIXELS_PER_INCH: f64 = 96.0;
+
+impl GridController {
+    /// Exports an OpenDocument spreadsheet from the grid.
+    ///
+    /// Returns a [`Vec<u8>`].
+    pub fn export_ods(&self) -> Result<Vec<u8>> {
+        let mut styles = OdsStyles::default();
+        let mut tables = String::new();
+
+        for sheet in self.sheets() {
+            self.write_ods_table(&mut tables, &mut styles, sheet)?;
+        }
+
+        let content = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><office:document-content {ODS_NAMESPACES}><office:automatic-styles>{}</office:automatic-styles><office:body><office:spreadsheet>{tables}</office:spreadsheet></office:body></office:document-content>"#,
+            styles.xml
+        );
+        let document_styles = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><office:document-styles {ODS_NAMESPACES}><office:styles><style:default-style style:family="table-cell"><style:text-properties fo:font-size="10pt"/></style:default-style><style:style style:name="Default" style:family="table-cell"/></office:styles></office:document-styles>"#
+        );
+
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        let error = |e| anyhow!("Error writing ods file: {}", e);
+
+        // the mimetype must be the first file and can't be compressed
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for (name, data, options) in [
+            ("mimetype", ODS_MIMETYPE, stored),
+            ("META-INF/manifest.xml", ODS_MANIFEST, deflated),
+            ("styles.xml", document_styles.as_str(), deflated),
+            ("content.xml", content.as_str(), deflated),
+        ] {
+            zip.start_file(name, options).map_err(error)?;
+            zip.write_all(data.as_bytes())?;
+        }
+
+        Ok(zip.finish().map_err(error)?.into_inner())
+    }
+
+    /// Writes a sheet as a table of the ODS content.
+    fn write_ods_table(
+        &self,
+        xml: &mut String,
+        styles: &mut OdsStyles,
+        sheet: &Sheet,
+    ) -> Result<()> {
+        let (max_x, max_y) = match sheet.all_bounds() {
+            GridBounds::Empty => (1, 1),
+            GridBounds::NonEmpty(rect) => {
+                (rect.max.x.min(MAX_ODS_COL), rect.max.y.min(MAX_ODS_ROW))
+            }
+        };
+
+        write!(xml, r#"<table:table table:name="{}">"#, escape(&sheet.name))?;
+
+        // columns, with runs of the same width combined
+        let columns = (1..=max_x).map(|x| {
+            styles.style(
+                "co",
+                "table-column",
+                &format!(
+                    r#"<style:table-column-properties style:column-width="{}"/>"#,
+                    ods_length(sheet.offsets.column_width(x))
+                ),
+            )
+        });
+        for (style_name, repeated) in runs(columns, |_| true) {
+            write!(
+                xml,
+                r#"<table:table-column table:style-name="{style_name}"{}/>"#,
+                repeated_attribute("table:number-columns-repeated", repeated)
+            )?;
+        }
+
+        // rows, with runs of identical rows combined; formulas are relative
+        // to their cell, so rows and cells with formulas aren't repeated
+        let rows = (1..=max_y)
+            .map(|y| self.ods_row(styles, sheet, y, max_x))
+            .collect::<Result<Vec<_>>>()?;
+        for ((style_name, cells), repeated) in runs(rows, |(_, cells)| !has_formula(cells)) {
+            write!(
+                xml,
+                r#"<table:table-row table:style-name="{style_name}"{}>{cells}</table:table-row>"#,
+                repeated_attribute("table:number-rows-repeated", repeated)
+            )?;
+        }
+
+        xml.push_str("</table:table>");
+
+        Ok(())
+    }
+
+    /// Returns the style name and cells of a row.
+    fn ods_row(
+        &self,
+        styles: &mut OdsStyles,
+        sheet: &Sheet,
+        y: i64,
+        max_x: i64,
+    ) -> Result<(String, String)> {
+        let style_name = styles.style(
+            "ro",
+            "table-row",
+            &format!(
+                r#"<style:table-row-properties style:row-height="{}" style:use-optimal-row-height="false"/>"#,
+                ods_length(sheet.offsets.row_height(y))
+            ),
+        );
+
+        let cells = (1..=max_x)
+            .map(|x| self.ods_cell(styles, sheet, Pos { x, y }))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut xml = String::new();
+        for ((attributes, content), repeated) in
+            runs(cells, |(attributes, _)| !has_formula(attributes))
+        {
+            let repeated = repeated_attribute("table:number-columns-repeated", repeated);
+            if content.is_empty() {
+                write!(xml, "<table:table-cell{attributes}{repeated}/>")?;
+            } else {
+                write!(
+                    xml,
+                    "<table:table-cell{attributes}{repeated}>{content}</table:table-cell>"
+                )?;
+            }
+        }
+
+        Ok((style_name, xml))
+    }
+
+    /// Returns the attributes and content of a cell.
+    fn ods_cell(
+        &self,
+        styles: &mut OdsStyles,
+        sheet: &Sheet,
+        pos: Pos,
+    ) -> Result<(String, String)> {
+        let value = sheet
+            .display_value(pos)
+            .filter(|value| !value.is_html() && !value.is_image());
+        let mut attributes = String::new();
+
+        if let Some(style_name) = ods_cell_style(styles, sheet, pos, value.as_ref()) {
+            write!(attributes, r#" table:style-name="{style_name}""#)?;
+        }
+
+        // formulas are written at their anchor cell; spills and errors are
+        // flattened to their values
+        if let Some((_, data_table)) = sheet.data_tables.get_contains(pos)
+            && let Some(CellValue::Code(code_cell_value)) = sheet.cell_value(pos)
+            && code_cell_value.language == CodeCellLanguage::Formula
+            && !data_table.has_spill()
+            && !data_table.has_error()
+        {
+            let code = convert_to_open_formula(
+                &code_cell_value.code,
+                &self.a1_context,
+                pos.to_sheet_pos(sheet.id),
+            );
+            write!(attributes, r#" table:formula="of:={}""#, escape(&code))?;
+
+            if let Value::Array(array) = data_table.display_value(false)? {
+                let size = array.size();
+                write!(
+                    attributes,
+                    r#" table:number-matrix-columns-spanned="{}" table:number-matrix-rows-spanned="{}""#,
+                    size.w, size.h
+                )?;
+            }
+        }
+
+        let Some(value) = value else {
+            return Ok((attributes, String::new()));
+        };
+
+        let text = |text: &str| {
+            text.split('\n')
+                .map(|line| format!("<text:p>{}</text:p>", escape(line)))
+                .collect::<String>()
+        };
+        let numeric_kind = sheet
+            .cell_format(pos)
+            .numeric_format
+            .map(|numeric_format| numeric_format.kind);
+
+        let content = match &value {
+            CellValue::Blank => String::new(),
+            CellValue::Number(number) => {
+                let value_type = match numeric_kind {
+                    Some(NumericFormatKind::Percentage) => "percentage",
+                    Some(NumericFormatKind::Currency) => "currency",
+                    _ => "float",
+                };
+                write!(
+                    attributes,
+                    r#" office:value-type="{value_type}" office:value="{number}""#
+                )?;
+                text(&value.to_display())
+            }
+            CellValue::Logical(logical) => {
+                write!(
+                    attributes,
+                    r#" office:value-type="boolean" office:boolean-value="{logical}""#
+                )?;
+                text(&value.to_display())
+            }
+            CellValue::Date(date) => {
+                write!(
+                    attributes,
+                    r#" office:value-type="date" office:date-value="{}""#,
+                    date.format("%Y-%m-%d")
+                )?;
+                text(&value.to_display())
+            }
+            CellValue::DateTime(date_time) => {
+                write!(
+                    attributes,
+                    r#" office:value-type="date" office:date-value="{}""#,
+                    date_time.format("%Y-%m-%dT%H:%M:%S")
+                )?;
+                text(&value.to_display())
+            }
+            CellValue::Time(time) => {
+                write!(
+                    attributes,
+                    r#" office:value-type="time" office:time-value="{}""#,
+                    time.format("PT%HH%MM%SS")
+                )?;
+                text(&value.to_display())
+            }
+            CellValue::Hyperlink(hyperlink) => {
+                let href = match &hyperlink.target {
+                    HyperlinkTarget::Url(url) => url.to_owned(),
+                    // internal links are to a sheet and cell, e.g., `#Sheet1.A1`
+                    HyperlinkTarget::Internal(reference) => {
+                        format!("#{}", reference.replacen('!', ".", 1))
+                    }
+                };
+                attributes.push_str(r#" office:value-type="string""#);
+                format!(
+                    r#"<text:p><text:a xlink:type="simple" xlink:href="{}">{}</text:a></text:p>"#,
+                    escape(&href),
+                    escape(&hyperlink.display_text())
+                )
+            }
+            _ => {
+                attributes.push_str(r#" office:value-type="string""#);
+                text(&value.to_display())
+            }
+        };
+
+        Ok((attributes, content))
+    }
+}
+
+/// Automatic styles of the ODS content, deduplicated by their properties.
+#[derive(Default)]
+struct OdsStyles {
+    xml: String,
+    names: HashMap<String, String>,
+}
+
+impl OdsStyles {
+    /// Returns the name of a style with the given family and properties,
+    /// adding the style if it doesn't exist yet.
+    fn style(&mut self, prefix: &str, family: &str, properties: &str) -> String {
+        self.add(
+            prefix,
+            "style:style",
+            &format!(r#"style:family="{family}""#),
+            properties,
+        )
+    }
+
+    /// Returns the name of an element with the given attributes and
+    /// children, adding the element if it doesn't exist yet.
+    fn add(&mut self, prefix: &str, element: &str, attributes: &str, children: &str) -> String {
+        let key = format!("{element} {attributes}>{children}");
+        if let Some(name) = self.names.get(&key) {
+            return name.to_owned();
+        }
+
+        let name = format!("{prefix}{}", self.names.len() + 1);
+        let _ = write!(
+            self.xml,
+            r#"<{element} style:name="{name}" {attributes}>{children}</{element}>"#
+        );
+        self.names.insert(key, name.to_owned());
+        name
+    }
+}
+
+/// Returns the name of the cell style for a cell, or None if the cell has no
+/// formatting.
+fn ods_cell_style(
+    styles: &mut OdsStyles,
+    sheet: &Sheet,
+    pos: Pos,
+    value: Option<&CellValue>,
+) -> Option<String> {
+    let format = sheet.cell_format(pos);
+
+    let mut cell_properties = String::new();
+    if let Some(color) = format.fill_color.as_deref().and_then(ods_color) {
+        let _ = write!(cell_properties, r#" fo:background-color="{color}""#);
+    }
+    let borders = sheet.borders.get_style_cell(pos);
+    for (side, border) in [
+        ("top", borders.top),
+        ("bottom", borders.bottom),
+        ("left", borders.left),
+        ("right", borders.right),
+    ] {
+        if let Some(border) = border
+            && let Some(line) = ods_border_line(border.line)
+        {
+            let _ = write!(
+                cell_properties,
+                r#" fo:border-{side}="{line} {}""#,
+                border.color.as_rgb_hex()
+            );
+        }
+    }
+    if format.wrap == Some(CellWrap::Wrap) {
+        cell_properties.push_str(r#" fo:wrap-option="wrap""#);
+    }
+    if let Some(vertical_align) = format.vertical_align {
+        let vertical_align = match vertical_align {
+            CellVerticalAlign::Top => "top",
+            CellVerticalAlign::Middle => "middle",
+            CellVerticalAlign::Bottom => "bottom",
+        };
+        let _ = write!(
+            cell_properties,
+            r#" style:vertical-align="{vertical_align}""#
+        );
+    }
+
+    let mut paragraph_properties = String::new();
+    if let Some(align) = format.align {
+        let align = match align {
+            CellAlign::Left => "start",
+            CellAlign::Center => "center",
+            CellAlign::Right => "end",
+        };
+        let _ = write!(paragraph_properties, r#" fo:text-align="{align}""#);
+    }
+
+    let mut text_properties = String::new();
+    if format.bold == Some(true) {
+        text_properties.push_str(r#" fo:font-weight="bold""#);
+    }
+    if format.italic == Some(true) {
+        text_properties.push_str(r#" fo:font-style="italic""#);
+    }
+    if format.underline == Some(true) || matches!(value, Some(CellValue::Hyperlink(_))) {
+        text_properties.push_str(r#" style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color""#);
+    }
+    if format.strike_through == Some(true) {
+        text_properties.push_str(r#" style:text-line-through-style="solid""#);
+    }
+    if let Some(color) = format.text_color.as_deref().and_then(ods_color) {
+        let _ = write!(text_properties, r#" fo:color="{color}""#);
+    }
+
+    let mut children = String::new();
+    for (element, properties) in [
+        ("table-cell", cell_properties),
+        ("paragraph", paragraph_properties),
+        ("text", text_properties),
+    ] {
+        if !properties.is_empty() {
+            let _ = write!(children, "<style:{element}-properties{properties}/>");
+        }
+    }
+
+    let mut attributes =
+        r#"style:family="table-cell" style:parent-style-name="Default""#.to_string();
+    if let Some(data_style_name) = ods_data_style(styles, &format, value) {
+        let _ = write!(attributes, r#" style:data-style-name="{data_style_name}""#);
+    } else if children.is_empty() {
+        return None;
+    }
+
+    Some(styles.add("ce", "style:style", &attributes, &children))
+}
+
+/// Returns the name of the number style for a cell's format, or None if it
+/// uses the default format.
+fn ods_data_style(
+    styles: &mut OdsStyles,
+    format: &Format,
+    value: Option<&CellValue>,
+) -> Option<String> {
+    let grouping = format.numeric_commas == Some(true);
+    let number = |decimals: i16| {
+        format!(
+            r#"<number:number number:decimal-places="{decimals}" number:min-decimal-places="{decimals}" number:min-integer-digits="1"{}/>"#,
+            if grouping {
+                r#" number:grouping="true""#
+            } else {
+                ""
+            }
+        )
+    };
+
+    match format
+        .numeric_format
+        .as_ref()
+        .map(|numeric_format| numeric_format.kind)
+    {
+        Some(NumericFormatKind::Percentage) => {
+            let decimals = format.numeric_decimals.unwrap_or(1);
+            return Some(styles.add(
+                "N",
+                "number:percentage-style",
+                "",
+                &format!("{}<number:text>%</number:text>", number(decimals)),
+            ));
+        }
+        Some(NumericFormatKind::Currency) => {
+            let symbol = format
+                .numeric_format
+                .as_ref()
+                .and_then(|numeric_format| numeric_format.symbol.to_owned())
+                .unwrap_or_else(|| "$".to_string());
+            let decimals = format.numeric_decimals.unwrap_or(2);
+            return Some(styles.add(
+                "N",
+                "number:currency-style",
+                "",
+                &format!(
+                    "<number:currency-symbol>{}</number:currency-symbol>{}",
+                    escape(&symbol),
+                    number(decimals)
+                ),
+            ));
+        }
+        Some(NumericFormatKind::Exponential) => {
+            let decimals = format.numeric_decimals.unwrap_or(2);
+            return Some(styles.add(
+                "N",
+                "number:number-style",
+                "",
+                &format!(
+                    r#"<number:scientific-number number:decimal-places="{decimals}" number:min-integer-digits="1" number:min-exponent-digits="2"/>"#
+                ),
+            ));
+        }
+        Some(NumericFormatKind::Number) | None => {
+            if grouping || format.numeric_decimals.is_some() {
+                let decimals = format.numeric_decimals.unwrap_or(0);
+                return Some(styles.add("N", "number:number-style", "", &number(decimals)));
+            }
+        }
+    }
+
+    // dates and times use their format, or the default format for their type
+    let date_time_format = match (&format.date_time, value) {
+        (
+            Some(date_time_format),
+            Some(CellValue::Date(_) | CellValue::Time(_) | CellValue::DateTime(_)),
+        ) => date_time_format.as_str(),
+        (_, Some(CellValue::Date(_))) => DEFAULT_DATE_FORMAT,
+        (_, Some(CellValue::Time(_))) => DEFAULT_TIME_FORMAT,
+        (_, Some(CellValue::DateTime(_))) => DEFAULT_DATE_TIME_FORMAT,
+        _ => return None,
+    };
+    let (is_date, children) = chrono_to_ods_date_style(date_time_format);
+    let element = if is_date {
+        "number:date-style"
+    } else {
+        "number:time-style"
+    };
+
+    Some(styles.add("N", element, "", &children))
+}
+
+/// Converts a chrono format to the children of an ODS date or time style.
+/// Returns whether the format includes a date, and the children.
+fn chrono_to_ods_date_style(chrono_format: &str) -> (bool, String) {
+    let mut is_date = false;
+    let mut children = String::new();
+    let mut literal = String::new();
+
+    let mut chars = chrono_format.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            literal.push(ch);
+            continue;
+        }
+
+        // `%-d` etc. are unpadded
+        let mut specifier = chars.next();
+        let padded = specifier != Some('-');
+        if !padded {
+            specifier = chars.next();
+        }
+        let style = if padded {
+            r#" number:style="long""#
+        } else {
+            ""
+        };
+
+        let elements = match specifier {
+            Some('Y') => vec![r#"<number:year number:style="long"/>"#.to_string()],
+            Some('y') => vec!["<number:year/>".to_string()],
+            Some('m') => vec![format!("<number:month{style}/>")],
+            Some('b' | 'h') => vec![r#"<number:month number:textual="true"/>"#.to_string()],
+            Some('B') => {
+                vec![r#"<number:month number:style="long" number:textual="true"/>"#.to_string()]
+            }
+            Some('d') => vec![format!("<number:day{style}/>")],
+            Some('e') => vec!["<number:day/>".to_string()],
+            Some('a') => vec!["<number:day-of-week/>".to_string()],
+            Some('A') => vec![r#"<number:day-of-week number:style="long"/>"#.to_string()],
+            Some('H' | 'I') => vec![format!("<number:hours{style}/>")],
+            Some('k' | 'l') => vec!["<number:hours/>".to_string()],
+            Some('M') => vec![format!("<number:minutes{style}/>")],
+            Some('S') => vec![format!("<number:seconds{style}/>")],
+            Some('p' | 'P') => vec!["<number:am-pm/>".to_string()],
+            Some('F') => {
+                let (_, children) = chrono_to_ods_date_style("%Y-%m-%d");
+                vec![children]
+            }
+            Some('T') => {
+                let (_, children) = chrono_to_ods_date_style("%H:%M:%S");
+                vec![children]
+            }
+            Some('D') => {
+                let (_, children) = chrono_to_ods_date_style("%m/%d/%y");
+                vec![children]
+            }
+            Some(other) => {
+                literal.push(other);
+                continue;
+            }
+            None => {
+                literal.push('%');
+                continue;
+            }
+        };
+
+        if matches!(
+            specifier,
+            Some('Y' | 'y' | 'm' | 'b' | 'h' | 'B' | 'd' | 'e' | 'a' | 'A' | 'F' | 'D')
+        ) {
+            is_date = true;
+        }
+        if !literal.is_empty() {
+            let _ = write!(children, "<number:text>{}</number:text>", escape(&literal));
+            literal.clear();
+        }
+        children.extend(elements);
+    }
+    if !literal.is_empty() {
+        let _ = write!(children, "<number:text>{}</number:text>", escape(&literal));
+    }
+
+    (is_date, children)
+}
+
+/// Converts a color to an ODS color (e.g., `#ff0000`).
+fn ods_color(color: &str) -> Option<String> {
+    Rgba::try_from(color).ok().map(|color| color.as_rgb_hex())
+}
+
+/// Converts a border line to the width and style of an ODS border.
+fn ods_border_line(line: CellBorderLine) -> Option<&'static str> {
+    match line {
+        CellBorderLine::Line1 => Some("0.74pt solid"),
+        CellBorderLine::Line2 => Some("1.76pt solid"),
+        CellBorderLine::Line3 => Some("2.49pt solid"),
+        CellBorderLine::Dotted => Some("0.74pt dotted"),
+        CellBorderLine::Dashed => Some("0.74pt dashed"),
+        CellBorderLine::Double => Some("2.01pt double"),
+        CellBorderLine::Clear => None,
+    }
+}
+
+/// Converts a size in pixels to an ODS length.
+fn ods_length(pixels: f64) -> String {
+    format!("{:.4}in", pixels / PIXELS_PER_INCH)
+}
+
+/// Returns the attribute for a repeated column, row, or cell.
+fn repeated_attribute(name: &str, repeated: usize) -> String {
+    if repeated > 1 {
+        format!(r#" {name}="{repeated}""#)
+    } else {
+        String::new()
+    }
+}
+
+/// Returns true if the xml of a row or cell has a formula.
+fn has_formula(xml: &str) -> bool {
+    xml.contains("table:formula=")
+}
+
+/// Combines runs of equal items into (item, count) pairs. Only items where
+/// `can_repeat` is true are combined.
+fn runs<T: PartialEq>(
+    items: impl IntoIterator<Item = T>,
+    can_repeat: impl Fn(&T) -> bool,
+) -> Vec<(T, usize)> {
+    let mut runs: Vec<(T, usize)> = vec![];
+    for item in items {
+        match runs.last_mut() {
+            Some((last, count)) if *last == item && can_repeat(&item) => *count += 1,
+            _ => runs.push((item, 1)),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::{
+        a1::A1Selection,
+        grid::sheet::borders::{BorderSelection, BorderStyle},
+        test_util::*,
+    };
+
+    fn ods_file(file: &[u8], name: &str) -> String {
+        let mut archive = ZipArchive::new(Cursor::new(file)).unwrap();
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    #[test]
+    fn test_export_ods() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_value(pos![sheet_id!A1], "Name".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!A2], "1.5".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!A3], "2024-01-02".into(), None, false);
+        gc.set_code_cell(
+            pos![sheet_id!B2],
+            CodeCellLanguage::Formula,
+            "SUM(A2, 2)".into(),
+            None,
+            None,
+            false,
+        );
+        gc.set_bold(&A1Selection::test_a1("A1"), Some(true), None, false)
+            .unwrap();
+        gc.set_fill_color(
+            &A1Selection::test_a1("A1"),
+            Some("#ff0000".into()),
+            None,
+            false,
+        )
+        .unwrap();
+        gc.set_borders(
+            A1Selection::test_a1("A2"),
+            BorderSelection::Bottom,
+            Some(BorderStyle {
+                color: Rgba::new(0, 0, 0, 255),
+                line: CellBorderLine::Line1,
+            }),
+            None,
+            false,
+        );
+
+        let file = gc.export_ods().unwrap();
+        assert_eq!(ods_file(&file, "mimetype"), ODS_MIMETYPE);
+
+        let content = ods_file(&file, "content.xml");
+        assert!(content.contains(r#"<table:table table:name="Sheet1">"#));
+        assert!(content.contains(r#"office:value-type="float" office:value="1.5""#));
+        assert!(content.contains(r#"office:value-type="date" office:date-value="2024-01-02""#));
+        assert!(content.contains(r#"table:formula="of:=SUM([.A2]; 2)""#));
+        assert!(content.contains(r#"fo:font-weight="bold""#));
+        assert!(content.contains(r#"fo:background-color="#ff0000""#));
+        assert!(content.contains(r#"fo:border-bottom="0.74pt solid #000000""#));
+        assert!(content.contains("<text:p>Name</text:p>"));
+    }
+
+    #[test]
+    fn test_export_ods_reimports() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![sheet_id!A1], "hello".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!C4], "42".into(), None, false);
+
+        let file = gc.export_ods().unwrap();
+
+        let mut imported = GridController::new_blank();
+        imported
+            .import_excel(&file, "export.ods", None, false)
+            .unwrap();
+        let sheet_id = imported.sheet_ids()[0];
+        assert_display_cell_value(&imported, sheet_id, 1, 1, "hello");
+        assert_display_cell_value(&imported, sheet_id, 3, 4, "42");
+    }
+
+    #[test]
+    fn test_chrono_to_ods_date_style() {
+        assert_eq!(
+            chrono_to_ods_date_style("%m/%d/%Y"),
+            (
+                true,
+                concat!(
+                    r#"<number:month number:style="long"/><number:text>/</number:text>"#,
+                    r#"<number:day number:style="long"/><number:text>/</number:text>"#,
+                    r#"<number:year number:style="long"/>"#,
+                )
+                .to_string()
+            )
+        );
+        assert_eq!(
+            chrono_to_ods_date_style("%-I:%M %p"),
+            (
+                false,
+                concat!(
+                    r#"<number:hours/><number:text>:</number:text>"#,
+                    r#"<number:minutes number:style="long"/><number:text> </number:text>"#,
+                    r#"<number:am-pm/>"#,
+                )
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(runs([1, 1, 2, 1], |_| true), vec![(1, 2), (2, 1), (1, 1)]);
+        assert_eq!(
+            runs([2, 2, 1], |item| *item != 2),
+            vec![(2, 1), (2, 1), (1, 1)]
+        );
+        assert_eq!(runs(Vec::<i32>::new(), |_| true), vec![]);
+    }
+}
@@ -14,6 +14,7 @@ pub mod active_transactions;
 pub mod dependencies;
 pub mod execution;
 pub mod export;
+pub mod export_ods;
 pub mod formula;
 pub mod operations;
 pub mod send_render;
@@ -6,7 +6,7 @@ use crate::{
     grid::{
         SheetId,
         formats::SheetFormatUpdates,
-        series::{SeriesOptions, find_auto_complete},
+        series::{SeriesOptions, find_auto_complete_with_custom_lists, flash_fill::flash_fill},
         sheet::borders::BordersUpdates,
         unique_data_table_name,
     },
@@ -614,6 +614,90 @@ impl GridController {
         Ok(ops)
     }
 
+    /// Flash fills the blank cells of the `target_x` column in the rows of
+    /// `source`, using the column's non-blank cells as examples of how the
+    /// `source` columns in the same row are transformed. Returns no
+    /// operations if no transformation fits the examples or if they are
+    /// ambiguous.
+    pub fn flash_fill_operations(
+        &self,
+        sheet_id: SheetId,
+        source: Rect,
+        target_x: i64,
+    ) -> Result<Vec<Operation>> {
+        let Some(sheet) = self.try_sheet(sheet_id) else {
+            return Err(Error::msg("Sheet not found"));
+        };
+        if source.x_range().contains(&target_x) {
+            return Err(Error::msg(
+                "Flash fill column cannot be one of the source columns",
+            ));
+        }
+
+        let mut examples = vec![];
+        let mut fill_rows = vec![];
+        let mut inputs = vec![];
+        for y in source.y_range() {
+            let row = source
+                .x_range()
+                .map(|x| {
+                    sheet
+                        .display_value(Pos::new(x, y))
+                        .map(|value| value.to_display())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+
+            match sheet.display_value(Pos::new(target_x, y)) {
+                Some(value) if !value.is_blank_or_empty_string() => {
+                    examples.push((row, value.to_display()));
+                }
+                _ if row.iter().any(|input| !input.is_empty()) => {
+                    fill_rows.push(y);
+                    inputs.push(row);
+                }
+                _ => (),
+            }
+        }
+
+        let (Some(min_y), Some(max_y)) = (fill_rows.first(), fill_rows.last()) else {
+            return Ok(vec![]);
+        };
+        let Some(outputs) = flash_fill(&examples, &inputs) else {
+            return Ok(vec![]);
+        };
+
+        let fill_rect = Rect::new(target_x, *min_y, target_x, *max_y);
+        let mut values = CellValues::new(1, fill_rect.height());
+        for (y, output) in fill_rows.iter().zip(outputs) {
+            if let Some(output) = output {
+                values.set(0, (y - min_y) as u32, CellValue::Text(output));
+            }
+        }
+
+        let sheet_pos = fill_rect.min.to_sheet_pos(sheet_id);
+        let selection = A1Selection::from_rect(SheetRect::new_from_rect(fill_rect, sheet_id));
+        let mut cells = CellValues::default();
+        let cell_values_ops = self.cell_values_operations(
+            Some(&selection),
+            sheet_pos,
+            Pos::new(0, 0),
+            &mut cells,
+            values,
+            false,
+        )?;
+
+        let mut ops = vec![];
+        if !cells.is_empty() {
+            ops.push(Operation::SetCellValues {
+                sheet_pos,
+                values: cells,
+            });
+        }
+        ops.extend(cell_values_ops);
+        Ok(ops)
+    }
+
     /// Given an array of values, determine if a series exists and if so, apply it.
     fn apply_auto_complete(
         &mut self,
@@ -633,11 +717,14 @@ impl GridController {
             sheet.cell_values_pos_in_rect(initial_range, true)
         };
 
-        let series = find_auto_complete(SeriesOptions {
-            series: values,
-            spaces: (final_range.width() * final_range.height()) as i32,
-            negative,
-        });
+        let series = find_auto_complete_with_custom_lists(
+            SeriesOptions {
+                series: values,
+                spaces: (final_range.width() * final_range.height()) as i32,
+                negative,
+            },
+            &self.grid.custom_lists,
+        );
         let mut new_series = series.clone();
 
         // we don't need to apply any operations to the cells set in
@@ -23,11 +23,15 @@ use crate::grid::SheetId;
 use crate::grid::formats::Format;
 use crate::grid::formats::SheetFormatUpdates;
 use crate::grid::js_types::JsClipboard;
+use crate::grid::js_types::JsColumnWidth;
 use crate::grid::sheet::borders::Borders;
 use crate::grid::sheet::borders::BordersUpdates;
 use crate::grid::sheet::validations::validation::Validation;
 use crate::grid::unique_data_table_name;
-use crate::{CellValue, Pos, Rect, RefAdjust, RefError, SheetPos, SheetRect, a1::A1Selection};
+use crate::{
+    CellValue, Pos, Rect, RefAdjust, RefError, RunErrorMsg, SheetPos, SheetRect, Span, Spanned,
+    a1::{A1Context, A1Selection},
+};
 
 lazy_static! {
     static ref CLIPBOARD_REGEX: Regex = Regex::new(r#"data-quadratic="(.*?)".*><tbody"#)
@@ -43,6 +47,12 @@ pub enum PasteSpecial {
     Values,
     // paste only formatting/borders
     Formats,
+    // paste only code cells and values (no formatting/borders/validations)
+    Formulas,
+    // paste only validations
+    Validations,
+    // paste only column widths
+    ColumnWidths,
 }
 impl From<&str> for PasteSpecial {
     fn from(s: &str) -> Self {
@@ -50,11 +60,65 @@ impl From<&str> for PasteSpecial {
             "None" => PasteSpecial::None,
             "Values" => PasteSpecial::Values,
             "Formats" => PasteSpecial::Formats,
+            "Formulas" => PasteSpecial::Formulas,
+            "Validations" => PasteSpecial::Validations,
+            "ColumnWidths" => PasteSpecial::ColumnWidths,
             _ => panic!("Invalid PasteSpecial: {s}"),
         }
     }
 }
 
+/// Arithmetic used to combine pasted numbers with the existing numbers.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum PasteOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+impl PasteOperation {
+    /// Combines an existing value with a pasted value. Returns `None` if the
+    /// values cannot be combined, in which case the existing value is kept.
+    fn apply(self, existing: &CellValue, pasted: &CellValue) -> Option<CellValue> {
+        let span = Span::empty(0);
+        let lhs = Spanned {
+            span,
+            inner: existing,
+        };
+        let rhs = Spanned {
+            span,
+            inner: pasted,
+        };
+        let result = match self {
+            PasteOperation::Add => CellValue::add(span, lhs, rhs),
+            PasteOperation::Subtract => CellValue::sub(span, lhs, rhs),
+            PasteOperation::Multiply => CellValue::mul(span, lhs, rhs),
+            PasteOperation::Divide => CellValue::checked_div(span, lhs, rhs),
+        };
+        match result {
+            Ok(value) => Some(value.inner),
+            Err(e) if e.msg == RunErrorMsg::DivideByZero => Some(CellValue::Error(Box::new(e))),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Options that may be combined with any [`PasteSpecial`].
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct PasteSpecialOptions {
+    /// Swaps the rows and columns of the pasted cells.
+    pub transpose: bool,
+
+    /// Blank cells in the clipboard do not replace existing cells.
+    pub skip_blanks: bool,
+
+    /// Combines pasted numbers with the existing numbers.
+    pub operation: Option<PasteOperation>,
+}
+
 /// This is used to track the origin of copies from column, row, or all
 /// selection. In order to paste a column, row, or all, we need to know the
 /// origin of the copy.
@@ -167,6 +231,78 @@ impl Clipboard {
     pub fn to_rect(&self, insert_at: Pos) -> Rect {
         Rect::from_numbers(insert_at.x, insert_at.y, self.w as i64, self.h as i64)
     }
+
+    /// Transposes the clipboard around its origin so rows become columns.
+    /// For copies, relative references in code cells are transposed as well.
+    pub fn transpose(&mut self, a1_context: &A1Context) {
+        let origin = Pos::new(self.origin.x, self.origin.y);
+        let rect = self.to_rect(origin);
+        let transpose_pos = |pos: Pos| Pos {
+            x: origin.x + pos.y - origin.y,
+            y: origin.y + pos.x - origin.x,
+        };
+
+        if matches!(self.operation, ClipboardOperation::Copy) {
+            for (x, col) in self.cells.columns.iter_mut().enumerate() {
+                for (&y, cell) in col.iter_mut() {
+                    if let CellValue::Code(code_cell) = cell {
+                        let pos = Pos::new(origin.x + x as i64, origin.y + y as i64);
+                        code_cell.transpose_references(
+                            a1_context,
+                            pos.to_sheet_pos(self.origin.sheet_id),
+                            transpose_pos(pos),
+                        );
+                    }
+                }
+            }
+        }
+
+        self.cells = std::mem::take(&mut self.cells).transpose();
+        self.values = std::mem::take(&mut self.values).transpose();
+        std::mem::swap(&mut self.w, &mut self.h);
+
+        if let Some(formats) = self.formats.as_mut() {
+            formats.transpose_in_place(rect);
+        }
+        if let Some(borders) = self.borders.as_mut() {
+            borders.transpose_in_place(rect);
+        }
+
+        // validation selections are relative to A1
+        if let Some(clipboard_validations) = self.validations.as_mut() {
+            clipboard_validations.validations =
+                std::mem::take(&mut clipboard_validations.validations)
+                    .into_iter()
+                    .filter_map(|mut validation| {
+                        validation.selection = validation.selection.transpose(Pos::new(1, 1))?;
+                        Some(validation)
+                    })
+                    .collect();
+        }
+
+        self.data_tables = std::mem::take(&mut self.data_tables)
+            .into_iter()
+            .map(|(pos, data_table)| (transpose_pos(pos), data_table))
+            .collect();
+
+        if let Some(selection) = self.selection.clone().transpose(origin) {
+            self.selection = selection;
+        }
+
+        // a transposed column or row is no longer a column or row
+        self.origin.column = None;
+        self.origin.row = None;
+        self.origin.all = None;
+    }
+
+    /// Removes blank cells so they do not replace existing cells.
+    fn remove_blanks(&mut self) {
+        for values in [&mut self.cells, &mut self.values] {
+            for col in values.columns.iter_mut() {
+                col.retain(|_, value| !matches!(value, CellValue::Blank));
+            }
+        }
+    }
 }
 
 impl From<Clipboard> for JsClipboard {
@@ -311,9 +447,17 @@ impl From<Clipboard> for JsClipboard {
 
                 let display_value = clipboard.values.get(x, y);
                 if let Some(value) = display_value {
-                    let value = value.to_string();
-                    html_body.push_str(&value);
-                    plain_text.push_str(&value.replace("\n", " ").replace("\t", " "));
+                    let text = value.to_string();
+                    if let CellValue::Hyperlink(hyperlink) = value {
+                        let href = hyperlink
+                            .href()
+                            .replace('&', "&amp;")
+                            .replace('"', "&quot;");
+                        html_body.push_str(format!("<a href=\"{href}\">{text}</a>").as_str());
+                    } else {
+                        html_body.push_str(&text);
+                    }
+                    plain_text.push_str(&text.replace("\n", " ").replace("\t", " "));
                 }
             }
         }
@@ -380,7 +524,7 @@ impl GridController {
     ) -> (Option<CellValues>, Vec<(u32, u32)>) {
         match special {
             PasteSpecial::Values => (Some(values.to_owned()), vec![]),
-            PasteSpecial::None => {
+            PasteSpecial::None | PasteSpecial::Formulas => {
                 let code = cells
                     .columns
                     .iter()
@@ -606,6 +750,79 @@ impl GridController {
         }
     }
 
+    /// Gets the operation to resize the pasted columns to the widths of the
+    /// copied columns. This is only possible when the copied sheet is in this
+    /// file.
+    fn clipboard_column_widths_operations(
+        &self,
+        clipboard: &Clipboard,
+        sheet_id: SheetId,
+        insert_x: i64,
+        max_x: i64,
+    ) -> Vec<Operation> {
+        if clipboard.w == 0 {
+            return vec![];
+        }
+
+        let Some(source_sheet) = self.try_sheet(clipboard.origin.sheet_id) else {
+            return vec![];
+        };
+
+        let column_widths = (insert_x..=max_x)
+            .step_by(clipboard.w as usize)
+            .flat_map(|tile_start_x| {
+                (0..clipboard.w as i64).map(move |x| JsColumnWidth {
+                    column: tile_start_x + x,
+                    width: source_sheet.offsets.column_width(clipboard.origin.x + x),
+                })
+            })
+            .collect();
+
+        vec![Operation::ResizeColumns {
+            sheet_id,
+            column_widths,
+        }]
+    }
+
+    /// Combines pasted numbers with the values already in the sheet at
+    /// `start_pos`. Pasted values that are not numbers are left unchanged,
+    /// and code cells (including their output) keep their code.
+    fn combine_paste_values(
+        &self,
+        start_pos: SheetPos,
+        values: &mut CellValues,
+        operation: PasteOperation,
+    ) {
+        let Some(sheet) = self.try_sheet(start_pos.sheet_id) else {
+            return;
+        };
+
+        for (x, col) in values.columns.iter_mut().enumerate() {
+            col.retain(|&y, value| {
+                if !matches!(value, CellValue::Number(_)) {
+                    return true;
+                }
+                let pos = Pos::new(start_pos.x + x as i64, start_pos.y + y as i64);
+                let is_code = sheet
+                    .data_table_pos_that_contains(pos)
+                    .and_then(|data_table_pos| sheet.data_table_at(&data_table_pos))
+                    .is_some_and(|data_table| data_table.is_code());
+                if is_code {
+                    return false;
+                }
+                let existing = sheet.display_value(pos).unwrap_or(CellValue::Blank);
+                match operation.apply(&existing, value) {
+                    Some(combined) => {
+                        *value = combined;
+                        true
+                    }
+                    // keep the existing value
+                    None => false,
+                }
+            });
+        }
+    }
+
     /// Collect the operations to paste the clipboard cells
     /// For cell values, formats and borders, we just add to the data structure to avoid extra operations
     #[allow(clippy::too_many_arguments)]
@@ -653,7 +870,7 @@ impl GridController {
         let delete_value = matches!(clipboard.operation, ClipboardOperation::Cut);
 
         match special {
-            PasteSpecial::None => {
+            PasteSpecial::None | PasteSpecial::Formulas => {
                 let (values, tables) = GridController::cell_values_from_clipboard_cells(
                     &clipboard.cells,
                     &clipboard.values,
@@ -679,11 +896,13 @@ impl GridController {
                     &mut cursor,
                 )?);
 
-                let validations_ops = self.clipboard_validations_operations(
-                    &clipboard.validations,
-                    start_pos.to_sheet_pos(selection.sheet_id),
-                );
-                ops.extend(validations_ops);
+                if matches!(special, PasteSpecial::None) {
+                    let validations_ops = self.clipboard_validations_operations(
+                        &clipboard.validations,
+                        start_pos.to_sheet_pos(selection.sheet_id),
+                    );
+                    ops.extend(validations_ops);
+                }
             }
             PasteSpecial::Values => {
                 let (values, _) = GridController::cell_values_from_clipboard_cells(
@@ -704,7 +923,14 @@ impl GridController {
                     ops.extend(cell_value_ops);
                 }
             }
-            PasteSpecial::Formats => (),
+            PasteSpecial::Validations => {
+                let validations_ops = self.clipboard_validations_operations(
+                    &clipboard.validations,
+                    start_pos.to_sheet_pos(selection.sheet_id),
+                );
+                ops.extend(validations_ops);
+            }
+            PasteSpecial::Formats | PasteSpecial::ColumnWidths => (),
         }
 
         if matches!(special, PasteSpecial::None | PasteSpecial::Formats) {
@@ -749,21 +975,37 @@ impl GridController {
         selection: &A1Selection,
         plain_text: String,
         special: PasteSpecial,
+        options: PasteSpecialOptions,
     ) -> Result<Vec<Operation>> {
-        // nothing to paste from plain text for formats
-        if matches!(special, PasteSpecial::Formats) {
+        // nothing to paste from plain text for formats, validations or column widths
+        if matches!(
+            special,
+            PasteSpecial::Formats | PasteSpecial::Validations | PasteSpecial::ColumnWidths
+        ) {
             return Ok(vec![]);
         }
 
-        let lines: Vec<&str> = plain_text.split('\n').collect();
+        let mut lines: Vec<Vec<&str>> = plain_text
+            .split('\n')
+            .map(|line| line.split('\t').collect())
+            .collect();
         let mut ops = vec![];
         let mut compute_code_ops = vec![];
 
+        if options.transpose {
+            let transposed_h = lines.iter().map(Vec::len).max().unwrap_or(0);
+            lines = (0..transposed_h)
+                .map(|x| {
+                    lines
+                        .iter()
+                        .map(|line| line.get(x).copied().unwrap_or_default())
+                        .collect()
+                })
+                .collect();
+        }
+
         // calculate the width by checking the first line (with the assumption that all lines should have the same width)
-        let w = lines
-            .first()
-            .map(|line| line.split('\t').count())
-            .unwrap_or(0);
+        let w = lines.first().map(Vec::len).unwrap_or(0);
         let h = lines.len();
 
         // If the clipboard is larger than the selection, we need to paste multiple times.
@@ -778,10 +1020,12 @@ impl GridController {
 
         // collect the plain text clipboard cells
         lines.iter().enumerate().for_each(|(y, line)| {
-            line.split('\t').enumerate().for_each(|(x, value)| {
+            line.iter().enumerate().for_each(|(x, value)| {
                 let (cell_value, format_update) = self.string_to_cell_value(value, true);
                 let is_code = matches!(cell_value, CellValue::Code(_));
 
+                // empty fields are left out of the paste, so they never
+                // replace existing cells (with or without skip blanks)
                 if cell_value != CellValue::Blank {
                     values.set(x as u32, y as u32, cell_value);
                 }
@@ -809,13 +1053,19 @@ impl GridController {
                 let cell_value_pos = Pos::from((start_x, start_y));
                 let sheet_pos = SheetPos::new(start_pos.sheet_id, x, y);
 
+                // we need to copy the values for each paste block
+                let mut tile_values = values.to_owned();
+                if let Some(operation) = options.operation {
+                    self.combine_paste_values(sheet_pos, &mut tile_values, operation);
+                }
+
                 ops.extend(self.cell_values_operations(
                     None,
                     sheet_pos,
                     cell_value_pos,
                     &mut cell_values,
-                    values.to_owned(), // we need to copy the values for each paste block
-                    false,             // we don't delete values for plain text
+                    tile_values,
+                    false, // we don't delete values for plain text
                 )?);
             }
         }
@@ -826,8 +1076,7 @@ impl GridController {
         });
 
         if !sheet_format_updates.is_default() {
-            let formats_rect =
-                Rect::from_numbers(start_pos.x, start_pos.y, w as i64, lines.len() as i64);
+            let formats_rect = Rect::from_numbers(start_pos.x, start_pos.y, w as i64, h as i64);
 
             ops.extend(self.clipboard_formats_operations(
                 start_pos.sheet_id,
@@ -854,13 +1103,41 @@ impl GridController {
 
     // todo: parse table structure to provide better pasting experience from other spreadsheets
     pub fn paste_html_operations(
+        &mut self,
+        insert_at: Pos,
+        end_pos: Pos,
+        selection: &A1Selection,
+        clipboard: Clipboard,
+        special: PasteSpecial,
+    ) -> Result<(Vec<Operation>, Vec<Operation>)> {
+        self.paste_special_html_operations(
+            insert_at,
+            end_pos,
+            selection,
+            clipboard,
+            special,
+            PasteSpecialOptions::default(),
+        )
+    }
+
+    /// Collect the operations to paste the clipboard with paste special
+    /// options (transpose, skip blanks and arithmetic).
+    pub fn paste_special_html_operations(
         &mut self,
         insert_at: Pos,
         end_pos: Pos,
         selection: &A1Selection,
         mut clipboard: Clipboard,
         special: PasteSpecial,
+        options: PasteSpecialOptions,
     ) -> Result<(Vec<Operation>, Vec<Operation>)> {
+        if options.transpose {
+            clipboard.transpose(self.a1_context());
+        }
+        if options.skip_blanks || options.operation.is_some() {
+            clipboard.remove_blanks();
+        }
+
         let mut ops = vec![];
         let mut clipboard_ops = vec![];
         let mut compute_code_ops = vec![];
@@ -879,12 +1156,33 @@ impl GridController {
         let (max_x, max_y, cell_value_width, cell_value_height) =
             Self::get_max_paste_area(insert_at, end_pos, clipboard.w, clipboard.h);
 
+        if matches!(special, PasteSpecial::ColumnWidths) {
+            // a transposed clipboard's columns were rows, which have no widths
+            if !options.transpose {
+                ops.extend(self.clipboard_column_widths_operations(
+                    &clipboard,
+                    selection.sheet_id,
+                    insert_at.x,
+                    max_x,
+                ));
+            }
+            ops.push(Operation::SetCursorA1 {
+                selection: selection.to_owned(),
+            });
+            return Ok((ops, vec![]));
+        }
+
         // collect all cell values, values and sheet format updates for a a single operation
         let mut cell_values = CellValues::new(cell_value_width as u32, cell_value_height as u32);
         let mut formats = clipboard.formats.to_owned().unwrap_or_default();
         let mut borders = clipboard.borders.to_owned().unwrap_or_default();
         let source_columns = clipboard.cells.columns;
 
+        // values are combined with the existing values for each pass
+        let source_values = options
+            .operation
+            .map(|_| clipboard.values.columns.to_owned());
+
         // collect information for growing data tables
         let mut data_table_columns: HashMap<SheetPos, Vec<u32>> = HashMap::new();
         let mut data_table_rows: HashMap<SheetPos, Vec<u32>> = HashMap::new();
@@ -984,6 +1282,15 @@ impl GridController {
                     }
                 }
 
+                if let (Some(operation), Some(source_values)) = (options.operation, &source_values)
+                {
+                    clipboard.values.columns = source_values.to_owned();
+
+                    let tile_start = SheetPos::new(selection.sheet_id, tile_start_x, tile_start_y);
+                    self.combine_paste_values(tile_start, &mut clipboard.cells, operation);
+                    self.combine_paste_values(tile_start, &mut clipboard.values, operation);
+                }
+
                 let (clipboard_op, code_ops) = self.get_clipboard_ops(
                     Pos::new(tile_start_x, tile_start_y),
                     Pos::new(tile_start_x - insert_at.x, tile_start_y - insert_at.y),
@@ -999,23 +1306,27 @@ impl GridController {
             }
         }
 
-        // cell values need to be set before the compute_code_ops
-        if matches!(special, PasteSpecial::None | PasteSpecial::Values) {
-            ops.extend(clipboard_ops);
-
-            if !cell_values.is_empty() {
-                ops.push(Operation::SetCellValues {
-                    sheet_pos: insert_at.to_sheet_pos(selection.sheet_id),
-                    values: cell_values,
-                });
-            }
+        match special {
+            // cell values need to be set before the compute_code_ops
+            PasteSpecial::None | PasteSpecial::Values | PasteSpecial::Formulas => {
+                ops.extend(clipboard_ops);
+
+                if !cell_values.is_empty() {
+                    ops.push(Operation::SetCellValues {
+                        sheet_pos: insert_at.to_sheet_pos(selection.sheet_id),
+                        values: cell_values,
+                    });
+                }
 
-            ops.extend(compute_code_ops);
+                ops.extend(compute_code_ops);
 
-            data_table_ops.extend(GridController::grow_data_table_operations(
-                data_table_columns,
-                data_table_rows,
-            ));
+                data_table_ops.extend(GridController::grow_data_table_operations(
+                    data_table_columns,
+                    data_table_rows,
+                ));
+            }
+            PasteSpecial::Validations => ops.extend(clipboard_ops),
+            PasteSpecial::Formats | PasteSpecial::ColumnWidths => (),
         }
 
         if matches!(special, PasteSpecial::None | PasteSpecial::Formats) {
@@ -1096,6 +1407,7 @@ mod test {
     use crate::grid::js_types::{JsClipboard, JsSnackbarSeverity};
     use crate::grid::sheet::validations::rules::ValidationRule;
     use crate::grid::{CellWrap, CodeCellLanguage, SheetId};
+    use crate::number::decimal_from_str;
     use crate::test_util::*;
     use crate::wasm_bindings::js::{clear_js_calls, expect_js_call};
 
@@ -2299,6 +2611,383 @@ mod test {
         assert_cell_format_bold_row(&gc, sheet_id, 2, 5, 2, vec![false, true, false, false]);
     }
 
+    fn copy_rect(gc: &GridController, sheet_id: SheetId, rect: Rect) -> JsClipboard {
+        gc.sheet(sheet_id)
+            .copy_to_clipboard(
+                &A1Selection::from_rect(rect.to_sheet_rect(sheet_id)),
+                gc.a1_context(),
+                ClipboardOperation::Copy,
+                true,
+            )
+            .into()
+    }
+
+    fn paste_special(
+        gc: &mut GridController,
+        a1: &str,
+        js_clipboard: JsClipboard,
+        special: PasteSpecial,
+        options: PasteSpecialOptions,
+    ) {
+        gc.paste_special_from_clipboard(
+            &A1Selection::test_a1(a1),
+            js_clipboard,
+            special,
+            options,
+            None,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_paste_special_transpose() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_values(
+            pos![sheet_id!B1],
+            vec![vec!["1".into(), "2".into()]],
+            None,
+            false,
+        );
+        gc.set_code_cell(
+            pos![sheet_id!B2],
+            CodeCellLanguage::Formula,
+            "B1 + C1".to_string(),
+            None,
+            None,
+            false,
+        );
+        gc.set_bold(
+            &A1Selection::test_a1_sheet_id("C1", sheet_id),
+            Some(true),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![B1:C2]);
+        let options = PasteSpecialOptions {
+            transpose: true,
+            ..Default::default()
+        };
+        paste_special(&mut gc, "E5", js_clipboard, PasteSpecial::None, options);
+
+        assert_display_cell_value(&gc, sheet_id, 5, 5, "1");
+        assert_display_cell_value(&gc, sheet_id, 5, 6, "2");
+        assert_display_cell_value(&gc, sheet_id, 6, 6, "");
+
+        // the formula's relative references are transposed
+        let sheet = gc.sheet(sheet_id);
+        let Some(CellValue::Code(code_cell)) = sheet.cell_value(pos![F5]) else {
+            panic!("Expected a code cell at F5");
+        };
+        assert_eq!(code_cell.code, "E5 + E6");
+        assert_eq!(
+            sheet.get_code_cell_value(pos![F5]),
+            Some(CellValue::Number(3.into()))
+        );
+
+        // formats are transposed
+        assert_eq!(sheet.formats.bold.get(pos![E6]), Some(true));
+        assert_eq!(sheet.formats.bold.get(pos![F5]), None);
+
+        // the paste is a single transaction
+        gc.undo(1, None, false);
+        assert_display_cell_value(&gc, sheet_id, 5, 5, "");
+        assert_display_cell_value(&gc, sheet_id, 6, 5, "");
+    }
+
+    #[test]
+    fn test_paste_special_transpose_plain_text() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let js_clipboard = JsClipboard {
+            plain_text: "1\t2\t3\n4\t5\t6".to_string(),
+            html: String::new(),
+        };
+        let options = PasteSpecialOptions {
+            transpose: true,
+            ..Default::default()
+        };
+        paste_special(&mut gc, "A1", js_clipboard, PasteSpecial::None, options);
+
+        assert_cell_value_row(&gc, sheet_id, 1, 2, 1, vec!["1", "4"]);
+        assert_cell_value_row(&gc, sheet_id, 1, 2, 2, vec!["2", "5"]);
+        assert_cell_value_row(&gc, sheet_id, 1, 2, 3, vec!["3", "6"]);
+    }
+
+    #[test]
+    fn test_paste_special_operation() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_values(
+            pos![sheet_id!A1],
+            vec![
+                vec!["1".into(), "2".into(), "3".into(), "".into()],
+                vec!["10".into(), "text".into(), "".into(), "5".into()],
+            ],
+            None,
+            false,
+        );
+
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![A1:D1]);
+        let options = PasteSpecialOptions {
+            operation: Some(PasteOperation::Add),
+            ..Default::default()
+        };
+        paste_special(
+            &mut gc,
+            "A2",
+            js_clipboard.clone(),
+            PasteSpecial::Values,
+            options,
+        );
+
+        // numbers are added, text is kept, blanks are treated as 0, and blank
+        // clipboard cells do not replace existing values
+        assert_cell_value_row(&gc, sheet_id, 1, 4, 2, vec!["11", "text", "3", "5"]);
+
+        let options = PasteSpecialOptions {
+            operation: Some(PasteOperation::Multiply),
+            ..Default::default()
+        };
+        paste_special(&mut gc, "A2", js_clipboard, PasteSpecial::Values, options);
+        assert_cell_value_row(&gc, sheet_id, 1, 4, 2, vec!["11", "text", "9", "5"]);
+
+        // undo restores the values of the previous paste
+        gc.undo(1, None, false);
+        assert_cell_value_row(&gc, sheet_id, 1, 4, 2, vec!["11", "text", "3", "5"]);
+
+        // formulas are kept rather than replaced with a number
+        gc.set_cell_value(pos![sheet_id!A3], "=1+1".into(), None, false);
+        let options = PasteSpecialOptions {
+            operation: Some(PasteOperation::Add),
+            ..Default::default()
+        };
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![A1:A1]);
+        paste_special(&mut gc, "A3", js_clipboard, PasteSpecial::Values, options);
+
+        let sheet = gc.sheet(sheet_id);
+        assert!(matches!(
+            sheet.cell_value(pos![A3]),
+            Some(CellValue::Code(_))
+        ));
+        assert_eq!(
+            sheet.display_value(pos![A3]),
+            Some(CellValue::Number(2.into()))
+        );
+    }
+
+    #[test]
+    fn test_paste_operation_apply() {
+        let one = CellValue::Number(1.into());
+        let two = CellValue::Number(2.into());
+        assert_eq!(
+            PasteOperation::Subtract.apply(&one, &two),
+            Some(CellValue::Number((-1).into()))
+        );
+        assert_eq!(
+            PasteOperation::Divide.apply(&one, &two),
+            Some(CellValue::Number(decimal_from_str("0.5").unwrap()))
+        );
+        assert_eq!(
+            PasteOperation::Add.apply(&CellValue::Blank, &two),
+            Some(two.clone())
+        );
+        assert!(matches!(
+            PasteOperation::Divide.apply(&one, &CellValue::Blank),
+            Some(CellValue::Error(_))
+        ));
+        assert_eq!(
+            PasteOperation::Add.apply(&CellValue::Text("text".into()), &two),
+            None
+        );
+    }
+
+    #[test]
+    fn test_paste_special_skip_blanks() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_values(
+            pos![sheet_id!A1],
+            vec![
+                vec!["1".into(), "".into(), "3".into()],
+                vec!["a".into(), "b".into(), "c".into()],
+            ],
+            None,
+            false,
+        );
+
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![A1:C1]);
+        let options = PasteSpecialOptions {
+            skip_blanks: true,
+            ..Default::default()
+        };
+        paste_special(&mut gc, "A2", js_clipboard, PasteSpecial::None, options);
+        assert_cell_value_row(&gc, sheet_id, 1, 3, 2, vec!["1", "b", "3"]);
+    }
+
+    #[test]
+    fn test_paste_special_skip_blanks_plain_text() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_values(
+            pos![sheet_id!A1],
+            vec![vec!["1".into(), "2".into(), "3".into()]],
+            None,
+            false,
+        );
+
+        let js_clipboard = JsClipboard {
+            plain_text: "a\t\tb".into(),
+            html: String::new(),
+        };
+        let options = PasteSpecialOptions {
+            skip_blanks: true,
+            ..Default::default()
+        };
+        paste_special(&mut gc, "A1", js_clipboard, PasteSpecial::None, options);
+        assert_cell_value_row(&gc, sheet_id, 1, 3, 1, vec!["a", "2", "b"]);
+    }
+
+    #[test]
+    fn test_paste_special_formulas() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_value(pos![sheet_id!A1], "1".into(), None, false);
+        gc.set_code_cell(
+            pos![sheet_id!B1],
+            CodeCellLanguage::Formula,
+            "A1 + 1".to_string(),
+            None,
+            None,
+            false,
+        );
+        gc.set_bold(
+            &A1Selection::test_a1_sheet_id("A1:B1", sheet_id),
+            Some(true),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![A1:B1]);
+        paste_special(
+            &mut gc,
+            "A3",
+            js_clipboard,
+            PasteSpecial::Formulas,
+            PasteSpecialOptions::default(),
+        );
+
+        let sheet = gc.sheet(sheet_id);
+        assert_display_cell_value(&gc, sheet_id, 1, 3, "1");
+        assert_eq!(
+            sheet.get_code_cell_value(pos![B3]),
+            Some(CellValue::Number(2.into()))
+        );
+        assert_eq!(sheet.formats.bold.get(pos![A3]), None);
+        assert_eq!(sheet.formats.bold.get(pos![B3]), None);
+    }
+
+    #[test]
+    fn test_paste_special_validations() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_value(pos![sheet_id!A1], "1".into(), None, false);
+        gc.sheet_mut(sheet_id).validations.set(Validation {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id("A1", sheet_id),
+            rule: ValidationRule::Logical(Default::default()),
+            message: Default::default(),
+            error: Default::default(),
+        });
+
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![A1:A1]);
+        paste_special(
+            &mut gc,
+            "C3",
+            js_clipboard,
+            PasteSpecial::Validations,
+            PasteSpecialOptions::default(),
+        );
+
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.display_value(pos![C3]), None);
+        assert!(
+            sheet
+                .validations
+                .get_validation_from_pos(pos![C3], gc.a1_context())
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_paste_special_column_widths() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_value(pos![sheet_id!B1], "1".into(), None, false);
+        gc.sheet_mut(sheet_id).offsets.set_column_width(2, 200.0);
+        gc.sheet_mut(sheet_id).offsets.set_column_width(3, 50.0);
+
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![B1:C1]);
+        paste_special(
+            &mut gc,
+            "E1:H1",
+            js_clipboard,
+            PasteSpecial::ColumnWidths,
+            PasteSpecialOptions::default(),
+        );
+
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.display_value(pos![E1]), None);
+        assert_eq!(sheet.offsets.column_width(5), 200.0);
+        assert_eq!(sheet.offsets.column_width(6), 50.0);
+        assert_eq!(sheet.offsets.column_width(7), 200.0);
+        assert_eq!(sheet.offsets.column_width(8), 50.0);
+
+        gc.undo(1, None, false);
+        assert_eq!(
+            gc.sheet(sheet_id).offsets.column_width(5),
+            crate::DEFAULT_COLUMN_WIDTH
+        );
+
+        // transposed columns were rows, so there are no widths to paste
+        let js_clipboard = copy_rect(&gc, sheet_id, rect![B1:C1]);
+        let options = PasteSpecialOptions {
+            transpose: true,
+            ..Default::default()
+        };
+        paste_special(
+            &mut gc,
+            "E1:H1",
+            js_clipboard,
+            PasteSpecial::ColumnWidths,
+            options,
+        );
+        assert_eq!(
+            gc.sheet(sheet_id).offsets.column_width(5),
+            crate::DEFAULT_COLUMN_WIDTH
+        );
+
+        // an empty clipboard has no widths to paste
+        let selection = A1Selection::test_a1("B1:C1");
+        let (mut clipboard, _) = gc.cut_to_clipboard_operations(&selection, false).unwrap();
+        clipboard.w = 0;
+        assert!(
+            gc.clipboard_column_widths_operations(&clipboard, sheet_id, 5, 8)
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_clipboard_code_operations_should_rerun() {
         let mut gc = GridController::test();
@@ -2448,4 +3137,36 @@ mod test {
             CellValue::Text("ab cd ef".to_string())
         );
     }
+
+    #[test]
+    fn copy_paste_hyperlink() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let hyperlink = CellValue::Hyperlink(crate::Hyperlink::new_url(
+            "https://quadratichq.com?a=1&b=2",
+            Some("Quadratic".to_string()),
+        ));
+        gc.sheet_mut(sheet_id)
+            .set_cell_value(pos![B2], hyperlink.clone());
+
+        let js_clipboard: JsClipboard = gc
+            .sheet(sheet_id)
+            .copy_to_clipboard(
+                &A1Selection::test_a1_sheet_id("B2", sheet_id),
+                gc.a1_context(),
+                ClipboardOperation::Copy,
+                true,
+            )
+            .into();
+        assert_eq!(js_clipboard.plain_text, "Quadratic");
+        assert!(
+            js_clipboard
+                .html
+                .contains("<a href=\"https://quadratichq.com?a=1&amp;b=2\">Quadratic</a>")
+        );
+
+        paste(&mut gc, sheet_id, 4, 2, js_clipboard);
+        assert_eq!(gc.sheet(sheet_id).cell_value(pos![D2]), Some(hyperlink));
+    }
 }
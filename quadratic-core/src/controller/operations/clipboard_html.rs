@@ -0,0 +1,1112 @@
+//! Decodes generic HTML tables from the clipboard. This is used when pasting
+//! from Excel, Google Sheets or a web page, where the clipboard does not
+//! contain Quadratic's own `data-quadratic` payload.
+//!
+//! The parser is intentionally forgiving: it only understands the subset of
+//! HTML and CSS that spreadsheets write to the clipboard (inline styles, class
+//! rules in `<style>` blocks, `colspan`/`rowspan`, Excel's
+//! `mso-number-format` and `x:num` attributes, and Google Sheets'
+//! `data-sheets-*` attributes).
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Result, bail};
+use indexmap::IndexMap;
+
+use super::clipboard::{Clipboard, ClipboardOperation, ClipboardOrigin};
+use super::import::excel_number_format_update;
+use crate::color::Rgba;
+use crate::grid::formats::{FormatUpdate, SheetFormatUpdates};
+use crate::grid::{CellAlign, CellVerticalAlign, CellWrap, SheetId};
+use crate::number::decimal_from_str;
+use crate::{CellValue, Hyperlink, Pos, Rect, SheetRect, a1::A1Selection, cell_values::CellValues};
+
+/// Maximum number of columns or rows a single cell may span.
+const MAX_SPAN: u32 = 1000;
+
+/// Maximum number of grid cells that the spanning cells of a table may cover
+/// together, so that a few large spans can't take up unbounded memory.
+const MAX_SPAN_AREA: u32 = 100_000;
+
+/// CSS declarations in the order they were written.
+type Declarations = Vec<(String, String)>;
+
+#[derive(Debug)]
+enum Token<'a> {
+    Start {
+        name: String,
+        attrs: HashMap<String, String>,
+    },
+    End(String),
+    Text(&'a str),
+    Style(&'a str),
+}
+
+/// A table cell that is being parsed.
+#[derive(Default)]
+struct HtmlCell {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    text: String,
+    href: Option<String>,
+    format: FormatUpdate,
+    number_format: Option<String>,
+    value: Option<CellValue>,
+}
+
+impl Clipboard {
+    /// Decodes the first `<table>` in clipboard html that was not written by
+    /// Quadratic. The result is pasted like a copy from `sheet_id`.
+    pub fn decode_html_table(html: &str, sheet_id: SheetId) -> Result<Self> {
+        let cells = parse_html_table(html);
+        if cells.is_empty() {
+            bail!("Clipboard html does not contain a table");
+        }
+
+        let w = cells.iter().map(|cell| cell.x + cell.w).max().unwrap_or(0);
+        let h = cells.iter().map(|cell| cell.y + cell.h).max().unwrap_or(0);
+
+        let mut values = CellValues::new_blank(w, h);
+        let mut formats = SheetFormatUpdates::default();
+
+        for cell in cells {
+            let (value, format) = cell.to_value_and_format();
+            let pos = cell_pos(cell.x, cell.y);
+            let rect = Rect::from_numbers(pos.x, pos.y, cell.w as i64, cell.h as i64);
+            if !format.is_default() {
+                formats.set_format_rect(rect, format);
+            }
+            values.set(cell.x, cell.y, value);
+        }
+
+        Ok(Clipboard {
+            origin: ClipboardOrigin {
+                x: 1,
+                y: 1,
+                ..ClipboardOrigin::default(sheet_id)
+            },
+            selection: A1Selection::from_rect(SheetRect::new(1, 1, w as i64, h as i64, sheet_id)),
+            w,
+            h,
+            cells: values.clone(),
+            values,
+            formats: (!formats.is_default()).then_some(formats),
+            borders: None,
+            validations: None,
+            data_tables: IndexMap::new(),
+            operation: ClipboardOperation::Copy,
+        })
+    }
+}
+
+/// Converts a 0-based table position to its position in the clipboard.
+fn cell_pos(x: u32, y: u32) -> Pos {
+    Pos::new(x as i64 + 1, y as i64 + 1)
+}
+
+impl HtmlCell {
+    /// Returns the cell's value and formatting. Values provided by the source
+    /// spreadsheet (`x:num`, `data-sheets-value`) take precedence over the
+    /// displayed text, except for dates and times, which are better
+    /// represented by the displayed text.
+    fn to_value_and_format(&self) -> (CellValue, FormatUpdate) {
+        let text = normalize_text(&self.text);
+
+        let is_text_format = self.number_format.as_deref() == Some("@");
+        let (parsed, parsed_format) = if is_text_format {
+            (CellValue::Text(text.clone()), FormatUpdate::default())
+        } else {
+            CellValue::string_to_cell_value(&text, false, false)
+        };
+        let is_temporal = matches!(
+            parsed,
+            CellValue::Date(_)
+                | CellValue::Time(_)
+                | CellValue::DateTime(_)
+                | CellValue::Duration(_)
+        );
+
+        let value = match &self.value {
+            Some(value) if !is_temporal && !is_text_format => value.clone(),
+            _ if text.is_empty() => CellValue::Blank,
+            _ => parsed,
+        };
+
+        let number_format = self
+            .number_format
+            .as_deref()
+            .filter(|_| !is_temporal && !is_text_format)
+            .and_then(excel_named_number_format)
+            .map(excel_number_format_update)
+            .unwrap_or_default();
+        let format = self.format.combine(&number_format).combine(&parsed_format);
+
+        let value = match &self.href {
+            Some(href) if !matches!(value, CellValue::Blank) => {
+                CellValue::Hyperlink(Hyperlink::parse(href, Some(text)))
+            }
+            _ => value,
+        };
+
+        (value, format)
+    }
+}
+
+/// Parses the first table in the html and returns its cells.
+fn parse_html_table(html: &str) -> Vec<HtmlCell> {
+    let mut classes: HashMap<String, Declarations> = HashMap::new();
+    let mut cells = vec![];
+    let mut occupied = HashSet::new();
+    let mut span_area = 0;
+
+    let mut table_depth = 0;
+    let mut y: Option<u32> = None;
+    let mut row_declarations = Declarations::new();
+    let mut cell: Option<HtmlCell> = None;
+
+    for token in tokenize(html) {
+        match token {
+            Token::Style(css) => parse_style_sheet(css, &mut classes),
+            Token::Start { name, attrs } => {
+                if name == "table" {
+                    table_depth += 1;
+                    continue;
+                }
+                if table_depth != 1 {
+                    continue;
+                }
+                match name.as_str() {
+                    "tr" => {
+                        y = Some(y.map_or(0, |y| y + 1));
+                        row_declarations = element_declarations(&attrs, &classes);
+                        if let Some(cell) = cell.take() {
+                            cells.push(cell);
+                        }
+                    }
+                    "td" | "th" => {
+                        if let Some(cell) = cell.take() {
+                            cells.push(cell);
+                        }
+                        let y = *y.get_or_insert(0);
+                        let mut x = 0;
+                        while occupied.contains(&(x, y)) {
+                            x += 1;
+                        }
+                        let span = |name: &str| {
+                            attrs
+                                .get(name)
+                                .and_then(|s| s.trim().parse::<u32>().ok())
+                                .unwrap_or(1)
+                                .clamp(1, MAX_SPAN)
+                        };
+                        let (mut w, mut h) = (span("colspan"), span("rowspan"));
+                        if w * h > 1 {
+                            // spans past the table's budget collapse to a single cell
+                            if span_area + w * h > MAX_SPAN_AREA {
+                                (w, h) = (1, 1);
+                            } else {
+                                span_area += w * h;
+                            }
+                        }
+                        for dx in 0..w {
+                            for dy in 0..h {
+                                occupied.insert((x + dx, y + dy));
+                            }
+                        }
+
+                        let mut declarations = row_declarations.clone();
+                        declarations.extend(element_declarations(&attrs, &classes));
+
+                        let mut new_cell = HtmlCell {
+                            x,
+                            y,
+                            w,
+                            h,
+                            ..Default::default()
+                        };
+                        if name == "th" {
+                            new_cell.format.bold = Some(Some(true));
+                        }
+                        apply_declarations(&mut new_cell, &declarations);
+                        apply_sheets_attributes(&mut new_cell, &attrs);
+                        apply_excel_attributes(&mut new_cell, &attrs);
+                        cell = Some(new_cell);
+                    }
+                    _ => {
+                        if let Some(cell) = cell.as_mut() {
+                            apply_inline_element(cell, &name, &attrs, &classes);
+                        }
+                    }
+                }
+            }
+            Token::End(name) => {
+                if name == "table" {
+                    table_depth -= 1;
+                    if table_depth == 0 {
+                        break;
+                    }
+                    continue;
+                }
+                if table_depth != 1 {
+                    continue;
+                }
+                match name.as_str() {
+                    "td" | "th" | "tr" => {
+                        if let Some(cell) = cell.take() {
+                            cells.push(cell);
+                        }
+                    }
+                    "p" | "div" => {
+                        if let Some(cell) = cell.as_mut() {
+                            cell.text.push('\n');
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            Token::Text(text) => {
+                if table_depth == 1
+                    && let Some(cell) = cell.as_mut()
+                {
+                    // line breaks in the source are whitespace; only <br> breaks lines
+                    let text = text.replace(['\n', '\r', '\t'], " ");
+                    cell.text.push_str(&decode_entities(&text));
+                }
+            }
+        }
+    }
+
+    if let Some(cell) = cell.take() {
+        cells.push(cell);
+    }
+    cells
+}
+
+/// Applies formatting from elements nested inside a cell (`<b>`, `<a>`,
+/// `<span style>`, etc.).
+fn apply_inline_element(
+    cell: &mut HtmlCell,
+    name: &str,
+    attrs: &HashMap<String, String>,
+    classes: &HashMap<String, Declarations>,
+) {
+    match name {
+        "br" => cell.text.push('\n'),
+        "b" | "strong" => cell.format.bold = Some(Some(true)),
+        "i" | "em" => cell.format.italic = Some(Some(true)),
+        "u" => cell.format.underline = Some(Some(true)),
+        "s" | "strike" | "del" => cell.format.strike_through = Some(Some(true)),
+        "a" => {
+            if let Some(href) = attrs.get("href").filter(|href| !href.trim().is_empty()) {
+                cell.href = Some(href.to_owned());
+            }
+        }
+        "font" => {
+            if let Some(color) = attrs.get("color").and_then(|color| css_color(color)) {
+                cell.format.text_color = Some(Some(color));
+            }
+        }
+        _ => (),
+    }
+    apply_declarations(cell, &element_declarations(attrs, classes));
+}
+
+/// Returns the declarations for an element from its class and style
+/// attributes. Inline styles override class rules.
+fn element_declarations(
+    attrs: &HashMap<String, String>,
+    classes: &HashMap<String, Declarations>,
+) -> Declarations {
+    let mut declarations = Declarations::new();
+    if let Some(class) = attrs.get("class") {
+        for class in class.split_whitespace() {
+            if let Some(class_declarations) = classes.get(class) {
+                declarations.extend(class_declarations.iter().cloned());
+            }
+        }
+    }
+    if let Some(style) = attrs.get("style") {
+        declarations.extend(parse_declarations(style));
+    }
+    declarations
+}
+
+/// Applies CSS declarations to a cell's formatting. Later declarations
+/// override earlier ones, eg, `font-weight:normal` removes bold from a class.
+fn apply_declarations(cell: &mut HtmlCell, declarations: &Declarations) {
+    for (name, value) in declarations {
+        let value_lower = value.to_ascii_lowercase();
+        let format = &mut cell.format;
+        match name.as_str() {
+            "font-weight" => {
+                let bold = match value_lower.as_str() {
+                    "bold" | "bolder" => true,
+                    weight => weight.parse::<u32>().is_ok_and(|weight| weight >= 600),
+                };
+                format.bold = bold.then_some(Some(true));
+            }
+            "font-style" => {
+                let italic = matches!(value_lower.as_str(), "italic" | "oblique");
+                format.italic = italic.then_some(Some(true));
+            }
+            "text-decoration" | "text-decoration-line" => {
+                format.underline = value_lower.contains("underline").then_some(Some(true));
+                format.strike_through = value_lower.contains("line-through").then_some(Some(true));
+            }
+            "color" => {
+                if let Some(color) = css_color(&value_lower) {
+                    format.text_color = Some(Some(color));
+                }
+            }
+            "background" | "background-color" => {
+                if let Some(color) = css_color(&value_lower) {
+                    format.fill_color = Some(Some(color));
+                }
+            }
+            "text-align" => {
+                format.align = match value_lower.as_str() {
+                    "left" | "start" => Some(Some(CellAlign::Left)),
+                    "center" => Some(Some(CellAlign::Center)),
+                    "right" | "end" => Some(Some(CellAlign::Right)),
+                    _ => format.align,
+                };
+            }
+            "vertical-align" => {
+                format.vertical_align = match value_lower.as_str() {
+                    "top" => Some(Some(CellVerticalAlign::Top)),
+                    "middle" => Some(Some(CellVerticalAlign::Middle)),
+                    "bottom" => Some(Some(CellVerticalAlign::Bottom)),
+                    _ => format.vertical_align,
+                };
+            }
+            "white-space" => {
+                format.wrap = match value_lower.as_str() {
+                    "normal" | "pre-wrap" | "break-spaces" => Some(Some(CellWrap::Wrap)),
+                    "clip" => Some(Some(CellWrap::Clip)),
+                    _ => format.wrap,
+                };
+            }
+            "mso-number-format" => cell.number_format = Some(unescape_css(value)),
+            _ => (),
+        }
+    }
+}
+
+/// Applies Google Sheets' `data-sheets-value` and `data-sheets-numberformat`
+/// attributes, which hold the typed value and number format as JSON.
+fn apply_sheets_attributes(cell: &mut HtmlCell, attrs: &HashMap<String, String>) {
+    let json = |name: &str| {
+        attrs
+            .get(name)
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
+    };
+
+    if let Some(value) = json("data-sheets-value") {
+        // "1" is the type: 2 = text, 3 = number, 4 = boolean
+        cell.value = match value.get("1").and_then(|t| t.as_u64()) {
+            Some(2) => value
+                .get("2")
+                .and_then(|s| s.as_str())
+                .map(|s| CellValue::Text(s.to_string())),
+            Some(3) => value
+                .get("3")
+                .and_then(|n| decimal_from_str(&n.to_string()).ok())
+                .map(CellValue::Number),
+            Some(4) => value
+                .get("4")
+                .and_then(|b| b.as_bool().or_else(|| b.as_u64().map(|b| b != 0)))
+                .map(CellValue::Logical),
+            _ => None,
+        };
+    }
+
+    if let Some(number_format) = json("data-sheets-numberformat")
+        && let Some(pattern) = number_format.get("2").and_then(|s| s.as_str())
+    {
+        cell.number_format = Some(pattern.to_string());
+    }
+}
+
+/// Applies Excel's `x:num` and `x:bool` attributes, which hold the
+/// underlying value when it differs from the displayed text.
+fn apply_excel_attributes(cell: &mut HtmlCell, attrs: &HashMap<String, String>) {
+    if let Some(number) = attrs.get("x:num")
+        && let Ok(number) = decimal_from_str(number.trim())
+    {
+        cell.value = Some(CellValue::Number(number));
+    } else if let Some(logical) = attrs.get("x:bool") {
+        cell.value = Some(CellValue::Logical(
+            logical.trim().eq_ignore_ascii_case("true"),
+        ));
+    }
+}
+
+/// Returns the number format string to use for an Excel number format,
+/// including the named formats Excel writes to the clipboard. Returns `None`
+/// for formats that do not change how numbers are displayed.
+fn excel_named_number_format(format: &str) -> Option<&str> {
+    // only the format for positive numbers is used
+    let format = format.split(';').next().unwrap_or_default().trim();
+    match format.to_ascii_lowercase().as_str() {
+        "" | "general" | "@" => None,
+        "fixed" => Some("0.00"),
+        "standard" => Some("#,##0.00"),
+        "percent" => Some("0.00%"),
+        "scientific" => Some("0.00E+00"),
+        "currency" => Some("$#,##0.00"),
+        _ => Some(format),
+    }
+}
+
+/// Collapses whitespace the way a browser renders it, keeping explicit line
+/// breaks.
+fn normalize_text(text: &str) -> String {
+    text.replace('\u{a0}', " ")
+        .split('\n')
+        .map(|line| {
+            line.split(' ')
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+/// Converts a CSS color to a hex string. Returns `None` for transparent or
+/// unsupported colors.
+fn css_color(color: &str) -> Option<String> {
+    let color = color.trim().to_ascii_lowercase();
+    let color = match color.as_str() {
+        "black" | "windowtext" => "#000000",
+        "white" => "#ffffff",
+        "red" => "#ff0000",
+        "green" => "#008000",
+        "lime" => "#00ff00",
+        "blue" => "#0000ff",
+        "yellow" => "#ffff00",
+        "orange" => "#ffa500",
+        "purple" => "#800080",
+        "gray" | "grey" => "#808080",
+        "silver" => "#c0c0c0",
+        "maroon" => "#800000",
+        "navy" => "#000080",
+        "teal" => "#008080",
+        "olive" => "#808000",
+        "aqua" | "cyan" => "#00ffff",
+        "fuchsia" | "magenta" => "#ff00ff",
+        color => color,
+    };
+
+    // #rgb shorthand
+    let expanded;
+    let color = match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 3 => {
+            expanded = format!("#{}", hex.chars().flat_map(|c| [c, c]).collect::<String>());
+            expanded.as_str()
+        }
+        _ => color,
+    };
+
+    // rgba() is only supported when the color is not transparent
+    let rgb;
+    let color = match color.strip_prefix("rgba(") {
+        Some(rgba) => {
+            let parts: Vec<&str> = rgba.trim_end_matches(')').split(',').collect();
+            let alpha = parts.get(3)?.trim().parse::<f64>().ok()?;
+            if parts.len() != 4 || alpha == 0.0 {
+                return None;
+            }
+            rgb = format!("rgb({})", parts[..3].join(","));
+            rgb.as_str()
+        }
+        None => color,
+    };
+
+    let is_valid_hex = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if !is_valid_hex && !color.starts_with("rgb(") {
+        return None;
+    }
+    Rgba::try_from(color).ok().map(|rgba| rgba.as_rgb_hex())
+}
+
+/// Splits the html into tokens. Comments, doctypes and scripts are skipped.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let is_tag = rest.starts_with('<')
+            && rest[1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'));
+        if !is_tag {
+            let end = match rest.strip_prefix('<') {
+                Some(text) => text.find('<').map_or(rest.len(), |i| i + 1),
+                None => rest.find('<').unwrap_or(rest.len()),
+            };
+            tokens.push(Token::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+
+        let Some(end) = find_tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::End(tag_name(name)));
+        } else if !tag.starts_with(['!', '?']) {
+            let name = tag_name(tag);
+            let attrs = parse_attributes(&tag[name.len()..]);
+
+            // the contents of style and script elements are not html
+            if matches!(name.as_str(), "style" | "script") {
+                let close = format!("</{name}");
+                let end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+                if name == "style" {
+                    tokens.push(Token::Style(&rest[..end]));
+                }
+                rest = &rest[end..];
+                rest = rest.find('>').map_or("", |i| &rest[i + 1..]);
+            } else {
+                tokens.push(Token::Start { name, attrs });
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Finds the closing `>` of a tag that starts at the beginning of `s`,
+/// ignoring any `>` within quoted attribute values.
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Parses attributes from the inside of a tag (after the tag name).
+/// Attribute names are lowercased and values have entities decoded.
+fn parse_attributes(s: &str) -> HashMap<String, String> {
+    let mut attrs = HashMap::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == '/' {
+            chars.next();
+            continue;
+        }
+
+        // attribute name
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() || c == '=' || c == '/' {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let name = s[start..end].to_ascii_lowercase();
+
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+        // attribute value
+        let mut value = String::new();
+        if chars.next_if(|&(_, c)| c == '=').is_some() {
+            while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+            let quote = chars
+                .next_if(|&(_, c)| c == '"' || c == '\'')
+                .map(|(_, c)| c);
+            while let Some(&(_, c)) = chars.peek() {
+                if quote.map_or(c.is_whitespace(), |q| c == q) {
+                    chars.next();
+                    break;
+                }
+                value.push(c);
+                chars.next();
+            }
+        }
+
+        if !name.is_empty() {
+            attrs.insert(name, decode_entities(&value));
+        }
+    }
+
+    attrs
+}
+
+/// Decodes html character references.
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+
+    let mut decoded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let c = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (c, entity) {
+            (Some(c), Some(entity)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Parses the class rules of a `<style>` block. Only simple class selectors
+/// (`.xl65` or `td.xl65`) are used, since those are what spreadsheets write.
+fn parse_style_sheet(css: &str, classes: &mut HashMap<String, Declarations>) {
+    let mut css = css.replace("<!--", "").replace("-->", "");
+    while let Some(start) = css.find("/*") {
+        let end = css[start..]
+            .find("*/")
+            .map_or(css.len(), |end| start + end + 2);
+        css.replace_range(start..end, "");
+    }
+
+    let mut rest = css.as_str();
+    while let Some(open) = rest.find('{') {
+        let selectors = &rest[..open];
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let declarations = parse_declarations(&rest[open + 1..open + close]);
+        rest = &rest[open + close + 1..];
+
+        for selector in selectors.split(',') {
+            let selector = selector.trim();
+            if selector.starts_with('@') || selector.contains(char::is_whitespace) {
+                continue;
+            }
+            if let Some((_, class)) = selector.split_once('.') {
+                classes
+                    .entry(class.to_string())
+                    .or_default()
+                    .extend(declarations.iter().cloned());
+            }
+        }
+    }
+}
+
+/// Parses CSS declarations (`name: value; ...`). Semicolons within quotes
+/// or escaped with a backslash do not end a declaration.
+fn parse_declarations(s: &str) -> Declarations {
+    let mut declarations = Declarations::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = s.chars();
+
+    let mut push = |declaration: &str| {
+        if let Some((name, value)) = declaration.split_once(':') {
+            let name = name.trim().to_ascii_lowercase();
+            let value = value.trim().trim_end_matches("!important").trim();
+            if !name.is_empty() && !value.is_empty() {
+                declarations.push((name, value.to_string()));
+            }
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (Some(q), c) if q == c => {
+                quote = None;
+                current.push(c);
+            }
+            (None, ';') => {
+                push(&current);
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    push(&current);
+
+    declarations
+}
+
+/// Removes the quotes and CSS escapes from a value, eg, Excel writes
+/// `"\#\,\#\#0\.00"` for `#,##0.00` and `\0022` for `"`.
+fn unescape_css(value: &str) -> String {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value);
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        let hex: String = chars.clone().take(4).collect();
+        if hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                unescaped.push(c);
+            }
+            chars.nth(3);
+            chars.next_if_eq(&' ');
+        } else if let Some(next) = chars.next() {
+            unescaped.push(next);
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{NumericFormat, NumericFormatKind};
+
+    fn decode(html: &str) -> Clipboard {
+        Clipboard::decode_html_table(html, SheetId::TEST).unwrap()
+    }
+
+    fn format(clipboard: &Clipboard, x: i64, y: i64) -> FormatUpdate {
+        clipboard
+            .formats
+            .as_ref()
+            .unwrap()
+            .format_update(Pos::new(x, y))
+    }
+
+    #[test]
+    fn test_decode_html_table() {
+        let html = r#"<meta charset="utf-8"><table><tbody>
+            <tr><th>Name</th><th>Amount</th></tr>
+            <tr><td>Tom &amp; Jerry</td><td>1,234.5</td></tr>
+            <tr><td>line<br>break</td><td>TRUE</td></tr>
+        </tbody></table>"#;
+        let clipboard = decode(html);
+        assert_eq!((clipboard.w, clipboard.h), (2, 3));
+        assert_eq!(
+            clipboard.values.get(0, 0),
+            Some(&CellValue::Text("Name".into()))
+        );
+        assert_eq!(
+            clipboard.values.get(0, 1),
+            Some(&CellValue::Text("Tom & Jerry".into()))
+        );
+        assert_eq!(
+            clipboard.values.get(1, 1),
+            Some(&CellValue::Number(decimal_from_str("1234.5").unwrap()))
+        );
+        assert_eq!(
+            clipboard.values.get(0, 2),
+            Some(&CellValue::Text("line\nbreak".into()))
+        );
+        assert_eq!(clipboard.values.get(1, 2), Some(&CellValue::Logical(true)));
+        assert_eq!(format(&clipboard, 1, 1).bold, Some(Some(true)));
+        assert_eq!(format(&clipboard, 2, 2).numeric_commas, Some(Some(true)));
+        assert_eq!(clipboard.selection, A1Selection::test_a1("A1:B3"));
+    }
+
+    #[test]
+    fn test_decode_html_table_not_a_table() {
+        assert!(Clipboard::decode_html_table("<p>hello</p>", SheetId::TEST).is_err());
+        assert!(Clipboard::decode_html_table("hello", SheetId::TEST).is_err());
+    }
+
+    #[test]
+    fn test_decode_html_table_spans() {
+        let html = r#"<table>
+            <tr><td colspan="2" style="background-color:#ff0">wide</td><td rowspan=2>tall</td></tr>
+            <tr><td>a</td><td>b</td></tr>
+        </table>"#;
+        let clipboard = decode(html);
+        assert_eq!((clipboard.w, clipboard.h), (3, 2));
+        assert_eq!(
+            clipboard.values.get(0, 0),
+            Some(&CellValue::Text("wide".into()))
+        );
+        assert_eq!(clipboard.values.get(1, 0), Some(&CellValue::Blank));
+        assert_eq!(
+            clipboard.values.get(2, 0),
+            Some(&CellValue::Text("tall".into()))
+        );
+        assert_eq!(
+            clipboard.values.get(0, 1),
+            Some(&CellValue::Text("a".into()))
+        );
+        assert_eq!(
+            clipboard.values.get(1, 1),
+            Some(&CellValue::Text("b".into()))
+        );
+        assert_eq!(clipboard.values.get(2, 1), Some(&CellValue::Blank));
+
+        // the fill covers the whole merged area
+        assert_eq!(
+            format(&clipboard, 2, 1).fill_color,
+            Some(Some("#ffff00".to_string()))
+        );
+        assert_eq!(format(&clipboard, 1, 2).fill_color, None);
+    }
+
+    #[test]
+    fn test_decode_html_table_span_area() {
+        // the first span fits within the budget, the rest collapse
+        let html = r#"<table><tr>
+            <td colspan=300 rowspan=300>a</td>
+            <td colspan=1000 rowspan=1000>b</td>
+            <td colspan=1000 rowspan=1000>c</td>
+        </tr></table>"#;
+        let cells = parse_html_table(html);
+        let spans = cells
+            .iter()
+            .map(|cell| (cell.x, cell.w, cell.h))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![(0, 300, 300), (300, 1, 1), (301, 1, 1)]);
+    }
+
+    #[test]
+    fn test_decode_html_table_inline_styles() {
+        let html = r#"<table><tr>
+            <td style="font-weight:700;font-style:italic;color:rgb(255, 0, 0);text-align:center;vertical-align:middle;white-space:normal">a</td>
+            <td style="text-decoration:underline line-through"><a href="https://example.com">link</a></td>
+            <td><span style="font-weight:bold">b</span></td>
+        </tr></table>"#;
+        let clipboard = decode(html);
+
+        let a = format(&clipboard, 1, 1);
+        assert_eq!(a.bold, Some(Some(true)));
+        assert_eq!(a.italic, Some(Some(true)));
+        assert_eq!(a.text_color, Some(Some("#ff0000".to_string())));
+        assert_eq!(a.align, Some(Some(CellAlign::Center)));
+        assert_eq!(a.vertical_align, Some(Some(CellVerticalAlign::Middle)));
+        assert_eq!(a.wrap, Some(Some(CellWrap::Wrap)));
+
+        let link = format(&clipboard, 2, 1);
+        assert_eq!(link.underline, Some(Some(true)));
+        assert_eq!(link.strike_through, Some(Some(true)));
+        assert_eq!(
+            clipboard.values.get(1, 0),
+            Some(&CellValue::Hyperlink(Hyperlink::new_url(
+                "https://example.com",
+                Some("link".into())
+            )))
+        );
+
+        assert_eq!(format(&clipboard, 3, 1).bold, Some(Some(true)));
+    }
+
+    #[test]
+    fn test_decode_html_table_excel() {
+        let html = r#"<html xmlns:x="urn:schemas-microsoft-com:office:excel">
+<head><style>
+<!--table
+	{mso-displayed-decimal-separator:"\.";}
+.xl65
+	{mso-number-format:"\#\,\#\#0\.00";}
+.xl66
+	{mso-number-format:Percent;
+	background:yellow;}
+.xl67
+	{mso-number-format:"\@";}
+.xl68
+	{mso-number-format:"Short Date";}
+-->
+</style></head>
+<body>
+<!--StartFragment-->
+<table border=0 cellpadding=0 cellspacing=0 width=128>
+ <tr height=20>
+  <td height=20 class=xl65 align=right x:num="1234.5">1,234.50</td>
+  <td class=xl66 align=right x:num="0.125">12.50%</td>
+ </tr>
+ <tr height=20>
+  <td class=xl67>00123</td>
+  <td class=xl68 align=right x:num="45292">1/1/2024</td>
+ </tr>
+<!--EndFragment-->
+</table>
+</body>
+</html>"#;
+        let clipboard = decode(html);
+
+        assert_eq!(
+            clipboard.values.get(0, 0),
+            Some(&CellValue::Number(decimal_from_str("1234.5").unwrap()))
+        );
+        let number = format(&clipboard, 1, 1);
+        assert_eq!(number.numeric_decimals, Some(Some(2)));
+        assert_eq!(number.numeric_commas, Some(Some(true)));
+
+        assert_eq!(
+            clipboard.values.get(1, 0),
+            Some(&CellValue::Number(decimal_from_str("0.125").unwrap()))
+        );
+        let percent = format(&clipboard, 2, 1);
+        assert_eq!(
+            percent.numeric_format,
+            Some(Some(NumericFormat {
+                kind: NumericFormatKind::Percentage,
+                symbol: None,
+            }))
+        );
+        assert_eq!(percent.fill_color, Some(Some("#ffff00".to_string())));
+
+        // text format keeps leading zeros
+        assert_eq!(
+            clipboard.values.get(0, 1),
+            Some(&CellValue::Text("00123".into()))
+        );
+
+        // dates use the displayed text instead of the serial number
+        assert_eq!(
+            clipboard.values.get(1, 1),
+            Some(&CellValue::Date(
+                chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_decode_html_table_google_sheets() {
+        let html = r#"<google-sheets-html-origin><style type="text/css"><!--td {border: 1px solid #cccccc;}br {mso-data-placement:same-cell;}--></style><table xmlns="http://www.w3.org/1999/xhtml" cellspacing="0" cellpadding="0" dir="ltr" border="1" style="table-layout:fixed;font-size:10pt;font-family:Arial;width:0px;border-collapse:collapse;border:none" data-sheets-root="1"><colgroup><col width="100"/><col width="100"/></colgroup><tbody><tr style="height:21px;"><td style="overflow:hidden;padding:2px 3px 2px 3px;vertical-align:bottom;text-align:right;" data-sheets-value="{&quot;1&quot;:3,&quot;3&quot;:1234.567}" data-sheets-numberformat="{&quot;1&quot;:2,&quot;2&quot;:&quot;#,##0.0&quot;,&quot;3&quot;:1}">1,234.6</td><td style="overflow:hidden;padding:2px 3px 2px 3px;vertical-align:bottom;font-weight:bold;" data-sheets-value="{&quot;1&quot;:2,&quot;2&quot;:&quot;0042&quot;}">0042</td><td style="overflow:hidden;padding:2px 3px 2px 3px;vertical-align:bottom;text-align:center;" data-sheets-value="{&quot;1&quot;:4,&quot;4&quot;:1}">TRUE</td></tr></tbody></table></google-sheets-html-origin>"#;
+        let clipboard = decode(html);
+        assert_eq!((clipboard.w, clipboard.h), (3, 1));
+
+        assert_eq!(
+            clipboard.values.get(0, 0),
+            Some(&CellValue::Number(decimal_from_str("1234.567").unwrap()))
+        );
+        let number = format(&clipboard, 1, 1);
+        assert_eq!(number.numeric_decimals, Some(Some(1)));
+        assert_eq!(number.numeric_commas, Some(Some(true)));
+        assert_eq!(number.align, Some(Some(CellAlign::Right)));
+        assert_eq!(number.vertical_align, Some(Some(CellVerticalAlign::Bottom)));
+
+        assert_eq!(
+            clipboard.values.get(1, 0),
+            Some(&CellValue::Text("0042".into()))
+        );
+        assert_eq!(format(&clipboard, 2, 1).bold, Some(Some(true)));
+
+        assert_eq!(clipboard.values.get(2, 0), Some(&CellValue::Logical(true)));
+    }
+
+    #[test]
+    fn test_css_color() {
+        assert_eq!(css_color("#F00"), Some("#ff0000".to_string()));
+        assert_eq!(css_color("#00ff00"), Some("#00ff00".to_string()));
+        assert_eq!(css_color("rgb(0, 0, 255)"), Some("#0000ff".to_string()));
+        assert_eq!(
+            css_color("rgba(0, 0, 255, 0.5)"),
+            Some("#0000ff".to_string())
+        );
+        assert_eq!(css_color("rgba(0, 0, 0, 0)"), None);
+        assert_eq!(css_color("yellow"), Some("#ffff00".to_string()));
+        assert_eq!(css_color("transparent"), None);
+        assert_eq!(css_color("#zzz"), None);
+    }
+
+    #[test]
+    fn test_unescape_css() {
+        assert_eq!(unescape_css(r#""\#\,\#\#0\.00""#), "#,##0.00");
+        assert_eq!(unescape_css(r#""\0022$\0022\#\,\#\#0""#), "\"$\"#,##0");
+        assert_eq!(unescape_css("Percent"), "Percent");
+    }
+
+    #[test]
+    fn test_parse_declarations() {
+        assert_eq!(
+            parse_declarations(
+                r#"color: red; mso-number-format:"0\;\[Red\]0"; font-weight:bold !important"#
+            ),
+            vec![
+                ("color".to_string(), "red".to_string()),
+                (
+                    "mso-number-format".to_string(),
+                    r#""0\;\[Red\]0""#.to_string()
+                ),
+                ("font-weight".to_string(), "bold".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_tables_are_ignored() {
+        let html = "<table><tr><td>a<table><tr><td>x</td><td>y</td></tr></table></td><td>b</td></tr></table>";
+        let clipboard = decode(html);
+        assert_eq!((clipboard.w, clipboard.h), (2, 1));
+        assert_eq!(
+            clipboard.values.get(0, 0),
+            Some(&CellValue::Text("a".into()))
+        );
+        assert_eq!(
+            clipboard.values.get(1, 0),
+            Some(&CellValue::Text("b".into()))
+        );
+    }
+}
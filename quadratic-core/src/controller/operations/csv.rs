@@ -4,15 +4,311 @@
 use std::{collections::HashMap, io::Read};
 
 use anyhow::{Result, anyhow};
-use encoding_rs_io::DecodeReaderBytes;
+use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CellValue, Pos,
+    cell_values::CellValues,
+    grid::formats::{FormatUpdate, SheetFormatUpdates},
+};
+
+use super::import::IMPORT_LINES_PER_OPERATION;
 
 // possible CSV delimiters
 const CSV_POSSIBLE_DELIMITERS: [u8; 5] = [b',', b';', b'\t', b'|', b' '];
 const CSV_SAMPLE_LINES: usize = 10;
 
-/// Converts a CSV file to utf8 using encoding_rs_io.
-pub(crate) fn clean_csv_file(file: &[u8]) -> Result<Vec<u8>> {
-    let mut decoder = DecodeReaderBytes::new(file);
+/// Type of the values in a column of an imported CSV file.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum CsvColumnType {
+    /// Detects the type of each value.
+    #[default]
+    Auto,
+
+    /// Keeps values as text (e.g., to keep leading zeros).
+    Text,
+
+    /// Parses values as numbers. Other values are kept as text.
+    Number,
+
+    /// Parses values as dates, times, or date times. Other values are kept as
+    /// text.
+    Date,
+
+    /// Parses values as booleans. Other values are kept as text.
+    Logical,
+}
+
+/// Options for importing a CSV file. Options that aren't set are detected from
+/// the file.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+#[serde(default)]
+pub struct CsvImportOptions {
+    /// Label of the file's encoding (e.g., `windows-1252` or `shift_jis`).
+    /// Detected from the byte order mark if not set, falling back to UTF-8.
+    pub encoding: Option<String>,
+
+    pub delimiter: Option<u8>,
+
+    /// Quote character, defaults to `"`.
+    pub quote: Option<u8>,
+
+    /// Escape character for quotes inside quoted values. Quotes are escaped by
+    /// doubling them if not set.
+    pub escape: Option<u8>,
+
+    /// Lines starting with this character are ignored.
+    pub comment: Option<u8>,
+
+    /// Number of lines to skip at the start of the file.
+    pub skip_rows: u32,
+
+    /// Whether the first row is the header of a data table.
+    pub header_is_first_row: Option<bool>,
+
+    /// Decimal separator of numbers, defaults to `.` (or `,` if the
+    /// thousands separator is `.`).
+    pub decimal_separator: Option<char>,
+
+    /// Thousands separator of numbers, defaults to `,` (or `.` if the decimal
+    /// separator is `,`).
+    pub thousands_separator: Option<char>,
+
+    /// Types of the columns by their index. Missing columns are `Auto`.
+    pub column_types: Vec<CsvColumnType>,
+}
+
+impl CsvImportOptions {
+    /// Returns a CSV reader for the text with the quoting and comment options.
+    fn reader<'a>(&self, text: &'a [u8], delimiter: u8) -> csv::Reader<&'a [u8]> {
+        csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .quote(self.quote.unwrap_or(b'"'))
+            .escape(self.escape)
+            .double_quote(self.escape.is_none())
+            .comment(self.comment)
+            .from_reader(text)
+    }
+
+    /// Converts a CSV value to a cell value using the type of its column.
+    fn cell_value(&self, value: &str, x: usize) -> (CellValue, FormatUpdate) {
+        let column_type = self.column_types.get(x).copied().unwrap_or_default();
+        if column_type == CsvColumnType::Text {
+            let cell_value = if value.is_empty() {
+                CellValue::Blank
+            } else {
+                CellValue::Text(value.to_owned())
+            };
+            return (cell_value, FormatUpdate::default());
+        }
+
+        let number = self.normalize_number(value);
+        let (cell_value, format_update) =
+            CellValue::string_to_cell_value(number.as_deref().unwrap_or(value), false, false);
+
+        let matches_type = match column_type {
+            CsvColumnType::Auto | CsvColumnType::Text => true,
+            CsvColumnType::Number => matches!(cell_value, CellValue::Number(_)),
+            CsvColumnType::Date => matches!(
+                cell_value,
+                CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Time(_)
+            ),
+            CsvColumnType::Logical => matches!(cell_value, CellValue::Logical(_)),
+        };
+        if matches_type || cell_value == CellValue::Blank {
+            (cell_value, format_update)
+        } else {
+            (CellValue::Text(value.to_owned()), FormatUpdate::default())
+        }
+    }
+
+    /// Returns the decimal and thousands separators, defaulting each so that
+    /// it doesn't collide with the other.
+    fn separators(&self) -> (char, char) {
+        match (self.decimal_separator, self.thousands_separator) {
+            (Some(decimal), Some(thousands)) => (decimal, thousands),
+            (Some(','), None) => (',', '.'),
+            (Some(decimal), None) => (decimal, ','),
+            (None, Some('.')) => (',', '.'),
+            (None, Some(thousands)) => ('.', thousands),
+            (None, None) => ('.', ','),
+        }
+    }
+
+    /// Rewrites a number that uses custom decimal or thousands separators
+    /// (e.g., `1.234,5`) with the default separators (e.g., `1,234.5`).
+    /// Returns None if the separators aren't set or the value isn't a number.
+    fn normalize_number(&self, value: &str) -> Option<String> {
+        if self.decimal_separator.is_none() && self.thousands_separator.is_none() {
+            return None;
+        }
+        let (decimal, thousands) = self.separators();
+
+        let value = value.trim();
+        let (sign, value) = match value.strip_prefix('-') {
+            Some(value) => ("-", value),
+            None => ("", value),
+        };
+        let (value, percent) = match value.strip_suffix('%') {
+            Some(value) => (value, "%"),
+            None => (value, ""),
+        };
+        let (integer, fraction) = match value.split_once(decimal) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (value, None),
+        };
+
+        let is_digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+        let groups = integer.split(thousands).collect::<Vec<_>>();
+        let valid_groups = groups.iter().enumerate().all(|(i, &group)| {
+            is_digits(group)
+                && (groups.len() == 1 || (i == 0 && group.len() <= 3) || group.len() == 3)
+        });
+        if !valid_groups || !fraction.is_none_or(is_digits) {
+            return None;
+        }
+
+        let fraction = fraction
+            .map(|fraction| format!(".{fraction}"))
+            .unwrap_or_default();
+        Some(format!("{sign}{}{fraction}{percent}", groups.join(",")))
+    }
+}
+
+/// Preview of a CSV import: the first rows of the file as displayed text and
+/// the detected options.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub struct CsvPreview {
+    pub delimiter: u8,
+    pub header_is_first_row: bool,
+    pub width: u32,
+
+    /// Number of rows in the file, which may be more than the previewed rows.
+    pub height: u32,
+
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A CSV file parsed into cell values.
+pub(crate) struct ParsedCsv {
+    pub(crate) delimiter: u8,
+    pub(crate) is_table: bool,
+
+    /// Number of rows in the file, which may be more than the parsed rows.
+    pub(crate) height: u32,
+
+    pub(crate) cell_values: CellValues,
+    pub(crate) format_updates: SheetFormatUpdates,
+}
+
+/// Parses a CSV file into cell values, stopping after `max_rows` rows if set.
+pub(crate) fn parse_csv(
+    file: &[u8],
+    file_name: &str,
+    options: &CsvImportOptions,
+    max_rows: Option<u32>,
+) -> Result<ParsedCsv> {
+    let error = |message: String| anyhow!("Error parsing CSV file {}: {}", file_name, message);
+
+    let (decimal, thousands) = options.separators();
+    if decimal == thousands {
+        return Err(error(format!(
+            "the decimal and thousands separators are both '{decimal}'"
+        )));
+    }
+
+    let converted_file = clean_csv_file(file, options.encoding.as_deref())?;
+    let text = skip_lines(&converted_file, options.skip_rows);
+
+    let (d, width, height, is_table) = find_csv_info(text, options);
+    let delimiter = options.delimiter.unwrap_or(d);
+
+    let mut cell_values = CellValues::new(width, max_rows.unwrap_or(height).min(height));
+    let mut format_updates = SheetFormatUpdates::default();
+
+    let mut y: u32 = 0;
+
+    for entry in options.reader(text, delimiter).records() {
+        if max_rows.is_some_and(|max_rows| y >= max_rows) {
+            break;
+        }
+
+        match entry {
+            Err(e) => {
+                // lines are counted from the start of the file, including
+                // the skipped rows
+                let line = e
+                    .position()
+                    .map_or(u64::from(y) + 1, |position| position.line());
+                let line = line + u64::from(options.skip_rows);
+                return Err(error(format!("line {line}: {e}")));
+            }
+            Ok(record) => {
+                for (x, value) in record.iter().enumerate() {
+                    let (cell_value, format_update) = options.cell_value(value, x);
+
+                    cell_values.set(x as u32, y, cell_value);
+
+                    if !format_update.is_default() {
+                        let pos = Pos {
+                            x: x as i64 + 1,
+                            y: y as i64 + 1,
+                        };
+                        format_updates.set_format_cell(pos, format_update);
+                    }
+                }
+            }
+        }
+        y += 1;
+
+        // update the progress bar every time there's a new batch
+        let should_update = y % IMPORT_LINES_PER_OPERATION == 0;
+
+        if should_update && (cfg!(target_family = "wasm") || cfg!(test)) {
+            crate::wasm_bindings::js::jsImportProgress(file_name, y, height);
+        }
+    }
+
+    Ok(ParsedCsv {
+        delimiter,
+        is_table,
+        height,
+        cell_values,
+        format_updates,
+    })
+}
+
+/// Returns the text after the first `lines` lines.
+fn skip_lines(text: &[u8], lines: u32) -> &[u8] {
+    let mut rest = text;
+    for _ in 0..lines {
+        match rest.iter().position(|&byte| byte == b'\n') {
+            Some(index) => rest = &rest[index + 1..],
+            None => return &[],
+        }
+    }
+    rest
+}
+
+/// Converts a CSV file to utf8 using encoding_rs_io. The encoding is detected
+/// from the byte order mark if it's not given.
+pub(crate) fn clean_csv_file(file: &[u8], encoding: Option<&str>) -> Result<Vec<u8>> {
+    let encoding = encoding
+        .map(|label| {
+            Encoding::for_label(label.trim().as_bytes())
+                .ok_or_else(|| anyhow!("unknown encoding {label}"))
+        })
+        .transpose()?;
+    let mut decoder = DecodeReaderBytesBuilder::new()
+        .encoding(encoding)
+        .build(file);
     let mut converted_file = vec![];
     if decoder.read_to_end(&mut converted_file).is_err() {
         return Err(anyhow!("error converting file to utf8"));
@@ -75,7 +371,7 @@ fn compute_score(column_counts: &HashMap<usize, usize>, sample_size: usize) -> (
 /// potential delimiters, along with how many columns they split the line into.
 ///
 /// Returns (delimiter, width, height, is_table)
-pub(crate) fn find_csv_info(text: &[u8]) -> (u8, u32, u32, bool) {
+pub(crate) fn find_csv_info(text: &[u8], options: &CsvImportOptions) -> (u8, u32, u32, bool) {
     let mut is_table = true;
 
     let mut delimiter_stats: Vec<DelimiterStats> = CSV_POSSIBLE_DELIMITERS
@@ -87,18 +383,10 @@ pub(crate) fn find_csv_info(text: &[u8]) -> (u8, u32, u32, bool) {
         })
         .collect();
 
-    let create_reader = |delim: u8| {
-        csv::ReaderBuilder::new()
-            .delimiter(delim)
-            .has_headers(false)
-            .flexible(true)
-            .from_reader(text)
-    };
-
     let mut sample_size = 0;
     for stats in delimiter_stats.iter_mut() {
         sample_size = 0;
-        let mut reader = create_reader(stats.delimiter as u8);
+        let mut reader = options.reader(text, stats.delimiter as u8);
         for (i, result) in reader.records().enumerate() {
             if i >= CSV_SAMPLE_LINES {
                 break;
@@ -144,15 +432,13 @@ pub(crate) fn find_csv_info(text: &[u8]) -> (u8, u32, u32, bool) {
         best_width = comma_width;
     }
 
-    // calculate height of the CSV file
-    let reader = |flexible| {
-        csv::ReaderBuilder::new()
-            .delimiter(b',') // delimiter doesn't matter here, we just need to count the lines
-            .has_headers(false)
-            .flexible(flexible)
-            .from_reader(text)
-    };
-    let height = reader(true).records().filter(|r| r.is_ok()).count();
+    // calculate height of the CSV file; the delimiter doesn't matter here, we
+    // just need to count the lines
+    let height = options
+        .reader(text, b',')
+        .records()
+        .filter(|r| r.is_ok())
+        .count();
 
     (
         best_delimiter,
@@ -165,6 +451,7 @@ pub(crate) fn find_csv_info(text: &[u8]) -> (u8, u32, u32, bool) {
 #[cfg(test)]
 mod tests {
     use crate::test_util::*;
+    use rust_decimal::Decimal;
     use std::path::Path;
 
     use super::*;
@@ -177,16 +464,16 @@ mod tests {
     #[test]
     fn test_simple_csv() {
         let file = read_test_csv_file("simple.csv");
-        let converted_file = clean_csv_file(&file).unwrap();
-        let info = find_csv_info(&converted_file);
+        let converted_file = clean_csv_file(&file, None).unwrap();
+        let info = find_csv_info(&converted_file, &CsvImportOptions::default());
         assert_eq!(info, (b',', 4, 11, true));
     }
 
     #[test]
     fn test_kaggle_csv() {
         let file = read_test_csv_file("kaggle_top_100_dataset.csv");
-        let converted_file = clean_csv_file(&file).unwrap();
-        let info = find_csv_info(&converted_file);
+        let converted_file = clean_csv_file(&file, None).unwrap();
+        let info = find_csv_info(&converted_file, &CsvImportOptions::default());
         assert_eq!(info, (b';', 9, 100, true));
     }
 
@@ -194,8 +481,8 @@ mod tests {
     fn test_find_delimiter() {
         let info = |filename: &str| -> (u8, u32, u32, bool) {
             let file = read_test_csv_file(filename);
-            let converted_file = clean_csv_file(&file).unwrap();
-            find_csv_info(&converted_file)
+            let converted_file = clean_csv_file(&file, None).unwrap();
+            find_csv_info(&converted_file, &CsvImportOptions::default())
         };
 
         assert_eq!(info("encoding_issue.csv"), (b',', 3, 4, true));
@@ -209,23 +496,23 @@ mod tests {
     #[test]
     fn test_bad_line() {
         let csv = "980E92207901934";
-        let info = find_csv_info(csv.as_bytes());
+        let info = find_csv_info(csv.as_bytes(), &CsvImportOptions::default());
         assert_eq!(info, (b',', 1, 1, false));
     }
 
     #[test]
     fn test_csv_error_1() {
         let file = read_test_csv_file("csv-error-1.csv");
-        let converted_file = clean_csv_file(&file).unwrap();
-        let info = find_csv_info(&converted_file);
+        let converted_file = clean_csv_file(&file, None).unwrap();
+        let info = find_csv_info(&converted_file, &CsvImportOptions::default());
         assert_eq!(info, (b',', 18, 5, true));
     }
 
     #[test]
     fn test_csv_error_2() {
         let file = read_test_csv_file("csv-error-2.csv");
-        let converted_file = clean_csv_file(&file).unwrap();
-        let info = find_csv_info(&converted_file);
+        let converted_file = clean_csv_file(&file, None).unwrap();
+        let info = find_csv_info(&converted_file, &CsvImportOptions::default());
         assert_eq!(info, (b',', 18, 7, true));
     }
 
@@ -249,4 +536,106 @@ mod tests {
 
         assert_display_cell_value(&gc, sheet_id, 1, 2, "Database");
     }
+
+    #[test]
+    fn test_parse_csv_with_options() {
+        // windows-1252 file with a title line, a comment, and european numbers
+        let mut file = b"Sales report\n# exported today\nname;zip;amount\n".to_vec();
+        file.extend(b"caf\xe9;02134;1.234,5\n");
+        file.extend(b"bar;00501;-2,25\n");
+
+        let options = CsvImportOptions {
+            encoding: Some("windows-1252".into()),
+            skip_rows: 1,
+            comment: Some(b'#'),
+            decimal_separator: Some(','),
+            thousands_separator: Some('.'),
+            column_types: vec![CsvColumnType::Auto, CsvColumnType::Text],
+            ..Default::default()
+        };
+        let parsed = parse_csv(&file, "test.csv", &options, None).unwrap();
+        let value = |x, y| parsed.cell_values.get(x, y).cloned();
+
+        assert_eq!(parsed.delimiter, b';');
+        assert_eq!(parsed.height, 3);
+        assert!(parsed.is_table);
+        assert_eq!(value(0, 1), Some(CellValue::Text("café".into())));
+        assert_eq!(value(1, 1), Some(CellValue::Text("02134".into())));
+        assert_eq!(value(1, 2), Some(CellValue::Text("00501".into())));
+        assert_eq!(value(2, 1), Some(CellValue::Number(Decimal::new(12345, 1))));
+        assert_eq!(value(2, 2), Some(CellValue::Number(Decimal::new(-225, 2))));
+
+        let preview = parse_csv(&file, "test.csv", &options, Some(1)).unwrap();
+        assert_eq!(preview.cell_values.h, 1);
+        assert_eq!(preview.height, 3);
+
+        let options = CsvImportOptions {
+            encoding: Some("not-an-encoding".into()),
+            ..Default::default()
+        };
+        assert!(parse_csv(&file, "test.csv", &options, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_quote_and_escape() {
+        let file = b"a,'b\\'c, d'\n1,'2'\n";
+        let options = CsvImportOptions {
+            delimiter: Some(b','),
+            quote: Some(b'\''),
+            escape: Some(b'\\'),
+            ..Default::default()
+        };
+        let parsed = parse_csv(file, "test.csv", &options, None).unwrap();
+
+        assert_eq!(
+            parsed.cell_values.get(1, 0),
+            Some(&CellValue::Text("b'c, d".into()))
+        );
+        assert_eq!(
+            parsed.cell_values.get(1, 1),
+            Some(&CellValue::Number(2.into()))
+        );
+    }
+
+    #[test]
+    fn test_normalize_number() {
+        let options = CsvImportOptions {
+            decimal_separator: Some(','),
+            thousands_separator: Some(' '),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.normalize_number("1 234 567,89"),
+            Some("1,234,567.89".into())
+        );
+        assert_eq!(options.normalize_number("-12,5%"), Some("-12.5%".into()));
+        assert_eq!(options.normalize_number("12 34"), None);
+        assert_eq!(options.normalize_number("01.02.2024"), None);
+        assert_eq!(options.normalize_number("abc"), None);
+        assert_eq!(CsvImportOptions::default().normalize_number("1,5"), None);
+
+        // a missing separator defaults to one that doesn't collide
+        let options = CsvImportOptions {
+            thousands_separator: Some('.'),
+            ..Default::default()
+        };
+        assert_eq!(options.normalize_number("1.234"), Some("1,234".into()));
+        assert_eq!(options.normalize_number("1.234,5"), Some("1,234.5".into()));
+
+        let options = CsvImportOptions {
+            decimal_separator: Some(','),
+            ..Default::default()
+        };
+        assert_eq!(options.normalize_number("1.234,5"), Some("1,234.5".into()));
+    }
+
+    #[test]
+    fn test_parse_csv_same_separators() {
+        let options = CsvImportOptions {
+            decimal_separator: Some('.'),
+            thousands_separator: Some('.'),
+            ..Default::default()
+        };
+        assert!(parse_csv(b"1.234\n", "test.csv", &options, None).is_err());
+    }
 }
@@ -0,0 +1,217 @@
+use anyhow::{Error, Result};
+
+use crate::{
+    Pos, Rect, SheetRect,
+    a1::A1Selection,
+    cell_values::CellValues,
+    controller::GridController,
+    grid::{
+        SheetId,
+        formats::SheetFormatUpdates,
+        series::fill_series::{FillDirection, FillSeriesOptions},
+    },
+};
+
+use super::operation::Operation;
+
+impl GridController {
+    /// Fills each column (or row) of `rect` from its first cell in the fill
+    /// direction. Lanes whose first cell can't start the series are left
+    /// untouched, as are any cells past the stop value.
+    pub fn fill_series_operations(
+        &self,
+        sheet_id: SheetId,
+        rect: Rect,
+        options: &FillSeriesOptions,
+    ) -> Result<Vec<Operation>> {
+        let Some(sheet) = self.try_sheet(sheet_id) else {
+            return Err(Error::msg("Sheet not found"));
+        };
+
+        // the first cell of each lane is the start of its series
+        let (starts, count): (Vec<Pos>, i64) = match options.direction {
+            FillDirection::Down => (
+                rect.x_range().map(|x| Pos::new(x, rect.min.y)).collect(),
+                rect.height() as i64 - 1,
+            ),
+            FillDirection::Up => (
+                rect.x_range().map(|x| Pos::new(x, rect.max.y)).collect(),
+                rect.height() as i64 - 1,
+            ),
+            FillDirection::Right => (
+                rect.y_range().map(|y| Pos::new(rect.min.x, y)).collect(),
+                rect.width() as i64 - 1,
+            ),
+            FillDirection::Left => (
+                rect.y_range().map(|y| Pos::new(rect.max.x, y)).collect(),
+                rect.width() as i64 - 1,
+            ),
+        };
+
+        let mut ops = vec![];
+        if count < 1 {
+            return Ok(ops);
+        }
+
+        let mut formats = SheetFormatUpdates::default();
+
+        for start in starts {
+            let Some(start_value) = sheet.cell_value(start) else {
+                continue;
+            };
+            let Some(mut values) = options.series(&start_value, count as usize) else {
+                continue;
+            };
+            if values.is_empty() {
+                continue;
+            }
+
+            // values are in fill order; CellValues are in sheet order
+            let filled = values.len() as i64;
+            let filled_rect = match options.direction {
+                FillDirection::Down => Rect::new(start.x, start.y + 1, start.x, start.y + filled),
+                FillDirection::Up => Rect::new(start.x, start.y - filled, start.x, start.y - 1),
+                FillDirection::Right => Rect::new(start.x + 1, start.y, start.x + filled, start.y),
+                FillDirection::Left => Rect::new(start.x - filled, start.y, start.x - 1, start.y),
+            };
+            if matches!(options.direction, FillDirection::Up | FillDirection::Left) {
+                values.reverse();
+            }
+
+            let (w, h) = (filled_rect.width(), filled_rect.height());
+            let sheet_pos = filled_rect.min.to_sheet_pos(sheet_id);
+            let selection = A1Selection::from_rect(SheetRect::new_from_rect(filled_rect, sheet_id));
+            let mut cells = CellValues::default();
+            let cell_values_ops = self.cell_values_operations(
+                Some(&selection),
+                sheet_pos,
+                Pos::new(0, 0),
+                &mut cells,
+                CellValues::from_flat_array(w, h, values),
+                false,
+            )?;
+            if !cells.is_empty() {
+                ops.push(Operation::SetCellValues {
+                    sheet_pos,
+                    values: cells,
+                });
+            }
+            ops.extend(cell_values_ops);
+
+            formats.set_format_rect(filled_rect, sheet.formats.format(start).into());
+        }
+
+        if !ops.is_empty() {
+            ops.push(Operation::SetCellFormatsA1 { sheet_id, formats });
+        }
+
+        Ok(ops)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::{
+        CellValue, Rect,
+        controller::GridController,
+        grid::series::fill_series::{
+            FillDateUnit, FillDirection, FillSeriesOptions, FillSeriesType,
+        },
+        test_util::*,
+    };
+
+    fn linear(direction: FillDirection, step: f64) -> FillSeriesOptions {
+        FillSeriesOptions {
+            direction,
+            series_type: FillSeriesType::Linear,
+            date_unit: FillDateUnit::Day,
+            step,
+            stop_value: None,
+        }
+    }
+
+    #[test]
+    fn test_fill_series_operations_down() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![sheet_id!A1], "1".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!B1], "10".into(), None, false);
+
+        gc.fill_series(
+            sheet_id,
+            Rect::test_a1("A1:B4"),
+            linear(FillDirection::Down, 5.0),
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert_display_cell_value(&gc, sheet_id, 1, 2, "6");
+        assert_display_cell_value(&gc, sheet_id, 1, 4, "16");
+        assert_display_cell_value(&gc, sheet_id, 2, 4, "25");
+    }
+
+    #[test]
+    fn test_fill_series_operations_up_and_left() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![sheet_id!A3], "1".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!C5], "1".into(), None, false);
+
+        gc.fill_series(
+            sheet_id,
+            Rect::test_a1("A1:A3"),
+            linear(FillDirection::Up, 1.0),
+            None,
+            false,
+        )
+        .unwrap();
+        assert_display_cell_value(&gc, sheet_id, 1, 2, "2");
+        assert_display_cell_value(&gc, sheet_id, 1, 1, "3");
+
+        gc.fill_series(
+            sheet_id,
+            Rect::test_a1("A5:C5"),
+            linear(FillDirection::Left, -1.0),
+            None,
+            false,
+        )
+        .unwrap();
+        assert_display_cell_value(&gc, sheet_id, 2, 5, "0");
+        assert_display_cell_value(&gc, sheet_id, 1, 5, "-1");
+    }
+
+    #[test]
+    fn test_fill_series_operations_stop_value() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![sheet_id!A1], "2024-01-31".into(), None, false);
+        gc.set_cell_value(pos![sheet_id!A4], "keep".into(), None, false);
+
+        let options = FillSeriesOptions {
+            series_type: FillSeriesType::Date,
+            date_unit: FillDateUnit::Month,
+            stop_value: Some("2024-03-31".to_string()),
+            ..linear(FillDirection::Down, 1.0)
+        };
+        gc.fill_series(sheet_id, Rect::test_a1("A1:A4"), options, None, false)
+            .unwrap();
+
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.cell_value(pos![A2]),
+            Some(CellValue::Date(
+                NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+            ))
+        );
+        assert_eq!(
+            sheet.cell_value(pos![A3]),
+            Some(CellValue::Date(
+                NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()
+            ))
+        );
+        assert_display_cell_value(&gc, sheet_id, 1, 4, "keep");
+    }
+}
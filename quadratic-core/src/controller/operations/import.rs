@@ -1,25 +1,43 @@
-use std::{io::Cursor, path::Path};
+use std::{collections::HashSet, io::Cursor, path::Path};
 
 use anyhow::{Result, anyhow, bail};
 use chrono::{NaiveDate, NaiveTime};
 use rust_decimal::prelude::ToPrimitive;
+use uuid::Uuid;
 
 use crate::color::Rgba;
 use crate::grid::sheet::borders::{BorderStyleCell, BorderStyleTimestamp, CellBorderLine};
 use crate::{
-    Array, CellValue, Pos, SheetPos,
+    Array, CellValue, Hyperlink, Pos, Rect, SheetPos, Value,
+    a1::{A1Context, A1Selection},
     cell_values::CellValues,
     cellvalue::Import,
     controller::{
         GridController, active_transactions::pending_transaction::PendingTransaction,
         execution::TransactionSource,
     },
-    date_time::{DEFAULT_DATE_FORMAT, DEFAULT_TIME_FORMAT},
+    date_time::{DEFAULT_DATE_FORMAT, DEFAULT_TIME_FORMAT, naive_date_to_i64},
     grid::{
-        CellAlign, CellVerticalAlign, CellWrap, CodeCellLanguage, CodeCellValue, DataTable,
-        NumericFormat, NumericFormatKind, Sheet, SheetId, fix_names::sanitize_table_name,
-        formats::SheetFormatUpdates, unique_data_table_name,
+        CellAlign, CellVerticalAlign, CellWrap, CodeCellLanguage, CodeCellValue, CodeRun,
+        DataTable, DataTableKind, NumericFormat, NumericFormatKind, Sheet, SheetId,
+        column_header::DataTableColumnHeader,
+        fix_names::sanitize_table_name,
+        formats::{FormatUpdate, SheetFormatUpdates},
+        sheet::validations::{
+            rules::{
+                ValidationRule,
+                validation_date_time::{DateTimeRange, ValidationDateTime},
+                validation_list::{ValidationList, ValidationListSource},
+                validation_logical::ValidationLogical,
+                validation_number::{NumberRange, ValidationNumber},
+                validation_text::{TextMatch, ValidationText},
+            },
+            validation::{Validation, ValidationError, ValidationMessage, ValidationStyle},
+        },
+        sort::{DataTableSort, SortDirection},
+        unique_data_table_name,
     },
+    json::json_to_array,
     parquet::parquet_to_array,
     small_timestamp::SmallTimestamp,
 };
@@ -29,11 +47,15 @@ use calamine::{
 };
 
 use super::{
-    csv::{clean_csv_file, find_csv_info},
+    csv::{CsvImportOptions, CsvPreview, ParsedCsv, parse_csv},
     operation::Operation,
+    xlsx::{
+        CODE_CELLS_SHEET_NAME, XlsxCodeCell, XlsxDataValidation, XlsxPackage,
+        replace_structured_references,
+    },
 };
 
-const IMPORT_LINES_PER_OPERATION: u32 = 10000;
+pub(crate) const IMPORT_LINES_PER_OPERATION: u32 = 10000;
 pub const COLUMN_WIDTH_MULTIPLIER: f64 = 7.0;
 pub const ROW_HEIGHT_MULTIPLIER: f64 = 1.5;
 
@@ -110,59 +132,16 @@ impl GridController {
         file: &[u8],
         file_name: &str,
         insert_at: Pos,
-        delimiter: Option<u8>,
-        create_table: Option<bool>,
+        options: &CsvImportOptions,
     ) -> Result<Vec<Operation>> {
-        let error = |message: String| anyhow!("Error parsing CSV file {}: {}", file_name, message);
         let sheet_pos = SheetPos::from((insert_at, sheet_id));
 
-        let converted_file = clean_csv_file(file)?;
-
-        let (d, width, height, is_table) = find_csv_info(&converted_file);
-        let delimiter = delimiter.unwrap_or(d);
-
-        let reader = |flexible| {
-            csv::ReaderBuilder::new()
-                .delimiter(delimiter)
-                .has_headers(false)
-                .flexible(flexible)
-                .from_reader(converted_file.as_slice())
-        };
-
-        let mut cell_values = CellValues::new(width, height);
-
-        let mut sheet_format_updates = SheetFormatUpdates::default();
-
-        let mut y: u32 = 0;
-
-        for entry in reader(true).records() {
-            match entry {
-                Err(e) => return Err(error(format!("line {}: {}", y + 1, e))),
-                Ok(record) => {
-                    for (x, value) in record.iter().enumerate() {
-                        let (cell_value, format_update) = self.string_to_cell_value(value, false);
-
-                        cell_values.set(x as u32, y, cell_value);
-
-                        if !format_update.is_default() {
-                            let pos = Pos {
-                                x: x as i64 + 1,
-                                y: y as i64 + 1,
-                            };
-                            sheet_format_updates.set_format_cell(pos, format_update);
-                        }
-                    }
-                }
-            }
-            y += 1;
-
-            // update the progress bar every time there's a new batch
-            let should_update = y % IMPORT_LINES_PER_OPERATION == 0;
-
-            if should_update && (cfg!(target_family = "wasm") || cfg!(test)) {
-                crate::wasm_bindings::js::jsImportProgress(file_name, y, height);
-            }
-        }
+        let ParsedCsv {
+            is_table,
+            cell_values,
+            format_updates: mut sheet_format_updates,
+            ..
+        } = parse_csv(file, file_name, options, None)?;
 
         if cell_values.w == 0 || cell_values.h == 0 {
             bail!("CSV file is empty");
@@ -170,11 +149,9 @@ impl GridController {
 
         let mut ops = vec![];
 
-        let apply_first_row_as_header = match create_table {
-            Some(true) => true,
-            Some(false) => false,
-            None => GridController::guess_csv_first_row_is_header(&cell_values),
-        };
+        let apply_first_row_as_header = options
+            .header_is_first_row
+            .unwrap_or_else(|| GridController::guess_csv_first_row_is_header(&cell_values));
 
         if is_table && apply_first_row_as_header {
             let cell_values: Array = cell_values.into();
@@ -217,6 +194,48 @@ impl GridController {
         Ok(ops)
     }
 
+    /// Parses the first `max_rows` rows of a CSV file without changing the
+    /// grid, so the import options can be checked before importing.
+    pub fn preview_csv(
+        file: &[u8],
+        file_name: &str,
+        options: &CsvImportOptions,
+        max_rows: u32,
+    ) -> Result<CsvPreview> {
+        let ParsedCsv {
+            delimiter,
+            is_table,
+            height,
+            cell_values,
+            ..
+        } = parse_csv(file, file_name, options, Some(max_rows))?;
+
+        let header_is_first_row = is_table
+            && options
+                .header_is_first_row
+                .unwrap_or_else(|| GridController::guess_csv_first_row_is_header(&cell_values));
+        let rows = (0..cell_values.h)
+            .map(|y| {
+                (0..cell_values.w)
+                    .map(|x| {
+                        cell_values
+                            .get(x, y)
+                            .map(|value| value.to_display())
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(CsvPreview {
+            delimiter,
+            header_is_first_row,
+            width: cell_values.w,
+            height,
+            rows,
+        })
+    }
+
     /// Imports an Excel file into the grid.
     pub fn import_excel_operations(
         &mut self,
@@ -243,7 +262,27 @@ impl GridController {
             _ => return Err(anyhow!("Cannot detect file format")),
         };
 
-        let sheets = workbook.sheet_names().to_owned();
+        let mut sheets = workbook.sheet_names().to_owned();
+
+        // non-formula code cells (e.g., Python) are stored in a hidden sheet
+        let mut code_cells = vec![];
+        if sheets
+            .iter()
+            .any(|sheet_name| sheet_name == CODE_CELLS_SHEET_NAME)
+        {
+            let range = workbook
+                .worksheet_range(CODE_CELLS_SHEET_NAME)
+                .map_err(error)?;
+            code_cells = range
+                .rows()
+                .skip(1)
+                .filter_map(|row| {
+                    let row = row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>();
+                    XlsxCodeCell::from_row(&row)
+                })
+                .collect();
+            sheets.retain(|sheet_name| sheet_name != CODE_CELLS_SHEET_NAME);
+        }
 
         for new_sheet_name in sheets.iter() {
             if self.try_sheet_from_name(new_sheet_name).is_some() {
@@ -277,7 +316,46 @@ impl GridController {
 
         let formula_start_name = unique_data_table_name("Formula1", false, None, self.a1_context());
 
+        // calamine does not read hyperlinks, so we read them from the xlsx package
+        let mut xlsx_package = match workbook {
+            Sheets::Xlsx(_) => Some(XlsxPackage::new(file)?),
+            _ => None,
+        };
+
+        // excel tables are imported as data tables, except for tables with
+        // formulas (e.g., calculated columns), which stay as cells
+        let mut xlsx_tables = vec![];
+        let mut flattened_tables = HashSet::new();
+        if let Some(xlsx_package) = xlsx_package.as_mut() {
+            for sheet_name in sheets.iter() {
+                let tables = xlsx_package.tables(sheet_name)?;
+                if tables.is_empty() {
+                    continue;
+                }
+                let formula = workbook.worksheet_formula(sheet_name).map_err(error)?;
+                let formulas_insert_at =
+                    formula.start().map_or_else(Pos::default, xlsx_range_to_pos);
+                let formula_positions = formula
+                    .used_cells()
+                    .map(|(y, x, _)| Pos {
+                        x: formulas_insert_at.x + x as i64,
+                        y: formulas_insert_at.y + y as i64,
+                    })
+                    .collect::<Vec<_>>();
+                for table in tables.iter() {
+                    let has_formulas = table.data_rect().is_none_or(|data_rect| {
+                        formula_positions.iter().any(|pos| data_rect.contains(*pos))
+                    });
+                    if has_formulas {
+                        flattened_tables.insert(table.name.to_owned());
+                    }
+                }
+                xlsx_tables.extend(tables);
+            }
+        }
+
         // add data from excel file to grid
+        let mut validation_ops = vec![];
         for sheet_name in sheets {
             let sheet = gc
                 .try_sheet_from_name(&sheet_name)
@@ -365,10 +443,21 @@ impl GridController {
                             y: formulas_insert_at.y + y as i64,
                         };
                         let sheet_pos = pos.to_sheet_pos(sheet_id);
+                        let code = if xlsx_tables.is_empty() {
+                            cell.to_string()
+                        } else {
+                            replace_structured_references(
+                                cell,
+                                &sheet_name,
+                                pos,
+                                &xlsx_tables,
+                                |table| flattened_tables.contains(&table.name),
+                            )
+                        };
                         let sheet = gc.try_sheet_mut_result(sheet_id)?;
                         let cell_value = CellValue::Code(CodeCellValue {
                             language: CodeCellLanguage::Formula,
-                            code: cell.to_string(),
+                            code: code.to_owned(),
                         });
 
                         sheet.columns.set_value(&pos, cell_value);
@@ -381,7 +470,7 @@ impl GridController {
                         gc.add_formula_without_eval(
                             &mut transaction,
                             sheet_pos,
-                            cell,
+                            &code,
                             formula_start_name.as_str(),
                         );
                         gc.update_a1_context_table_map(&mut transaction);
@@ -492,6 +581,126 @@ impl GridController {
                 }
             }
 
+            // hyperlinks
+            if let Some(xlsx_package) = xlsx_package.as_mut() {
+                let sheet = gc.try_sheet_mut_result(sheet_id)?;
+                for (rect, hyperlink) in xlsx_package.hyperlinks(&sheet_name)? {
+                    for pos in rect.iter() {
+                        // cells without a value and HYPERLINK formulas are skipped
+                        let Some(cell_value) = sheet.cell_value_ref(pos) else {
+                            continue;
+                        };
+                        if cell_value.is_blank_or_empty_string() || cell_value.is_code() {
+                            continue;
+                        }
+                        let text = cell_value.to_display();
+                        let text = (text != hyperlink.display_text()).then_some(text);
+                        let cell_value = CellValue::Hyperlink(Hyperlink {
+                            target: hyperlink.target.clone(),
+                            text,
+                        });
+                        sheet.columns.set_value(&pos, cell_value);
+                    }
+                }
+            }
+
+            // tables
+            for table in xlsx_tables.iter().filter(|table| {
+                table.sheet_name == sheet_name && !flattened_tables.contains(&table.name)
+            }) {
+                let Some(data_rect) = table.data_rect() else {
+                    continue;
+                };
+                let sheet = gc.try_sheet_mut_result(sheet_id)?;
+                let values = sheet.cell_values_in_rect(&data_rect, false)?;
+                let import = Import::new(table.name.to_owned());
+                let mut data_table = DataTable::new(
+                    DataTableKind::Import(import.to_owned()),
+                    &sanitize_table_name(table.name.to_owned()),
+                    values.into(),
+                    false,
+                    Some(false),
+                    Some(false),
+                    None,
+                );
+
+                // formats are transferred before showing the header row so
+                // they map to the table's data
+                if let Some(format_update) =
+                    data_table.transfer_formats_from_sheet(data_rect.min, data_rect, sheet)
+                {
+                    data_table
+                        .formats
+                        .get_or_insert_default()
+                        .apply_updates(&format_update);
+                }
+                data_table.show_columns = Some(table.header_row);
+                data_table.alternating_colors = table.banded_rows;
+                if table.columns.len() == data_rect.width() as usize {
+                    let column_headers = table
+                        .columns
+                        .iter()
+                        .enumerate()
+                        .map(|(i, name)| {
+                            DataTableColumnHeader::new(name.to_owned(), true, i as u32)
+                        })
+                        .collect();
+                    data_table = data_table.with_column_headers(column_headers);
+                }
+
+                // the values are already sorted, so this only records the sort
+                if !table.sort.is_empty() {
+                    data_table.sort = Some(
+                        table
+                            .sort
+                            .iter()
+                            .map(|&(column_index, descending)| DataTableSort {
+                                column_index,
+                                direction: if descending {
+                                    SortDirection::Descending
+                                } else {
+                                    SortDirection::Ascending
+                                },
+                            })
+                            .collect(),
+                    );
+                    data_table.sort_all()?;
+                }
+
+                // the totals row stays as cells below the table
+                let table_rect = Rect::new_span(table.rect.min, data_rect.max);
+                sheet.columns.delete_values(table_rect);
+                sheet
+                    .formats
+                    .apply_updates(&SheetFormatUpdates::from_selection(
+                        &A1Selection::from_rect(table_rect.to_sheet_rect(sheet_id)),
+                        FormatUpdate::cleared(),
+                    ));
+                sheet
+                    .columns
+                    .set_value(&table.rect.min, CellValue::Import(import));
+                sheet.data_table_insert_full(&table.rect.min, data_table);
+
+                let mut transaction = PendingTransaction {
+                    source: TransactionSource::Server,
+                    ..Default::default()
+                };
+                transaction.add_code_cell(sheet_id, table.rect.min);
+                gc.update_a1_context_table_map(&mut transaction);
+            }
+
+            // data validations are added once the values are final, so their
+            // warnings can be computed
+            if let Some(xlsx_package) = xlsx_package.as_mut() {
+                for data_validation in xlsx_package.data_validations(&sheet_name)? {
+                    if let Some(validation) =
+                        import_excel_data_validation(&data_validation, sheet_id, gc.a1_context())
+                    {
+                        validation_ops.push(Operation::SetValidation { validation });
+                    }
+                }
+            }
+
             // layout
             let layout = workbook.worksheet_layout(&sheet_name).map_err(error)?;
             let sheet = gc.try_sheet_mut_result(sheet_id)?;
@@ -514,6 +723,20 @@ impl GridController {
         // rerun all formulas in-order
         let compute_ops = gc.rerun_all_code_cells_operations();
         gc.server_apply_transaction(compute_ops, None);
+        gc.server_apply_transaction(validation_ops, None);
+
+        // code cells are added after formulas are rerun since they can't run
+        // during import; their flattened output is used as the code run output
+        for code_cell in code_cells {
+            let Some(sheet_id) = gc
+                .try_sheet_from_name(&code_cell.sheet_name)
+                .map(|sheet| sheet.id)
+            else {
+                continue;
+            };
+            let name = unique_data_table_name(&code_cell.name, false, None, self.a1_context());
+            gc.add_excel_code_cell(sheet_id, code_cell, &name)?;
+        }
 
         for sheet in gc.grid.sheets.into_values() {
             ops.push(Operation::AddSheet {
@@ -524,6 +747,100 @@ impl GridController {
         Ok(ops)
     }
 
+    /// Recreates a code cell exported to excel, replacing its flattened output
+    /// with a code run that has the same output.
+    fn add_excel_code_cell(
+        &mut self,
+        sheet_id: SheetId,
+        code_cell: XlsxCodeCell,
+        name: &str,
+    ) -> Result<()> {
+        let pos = code_cell.pos;
+        let sheet = self.try_sheet_mut_result(sheet_id)?;
+        let output = code_cell.output_size.map(|size| {
+            sheet
+                .columns
+                .delete_values(Rect::from_pos_and_size(pos, size))
+        });
+        sheet.columns.set_value(
+            &pos,
+            CellValue::Code(CodeCellValue {
+                language: code_cell.language.to_owned(),
+                code: code_cell.code.to_owned(),
+            }),
+        );
+
+        // charts are exported as pictures, so they are empty until rerun
+        let mut column_headers = None;
+        let mut chart_output = None;
+        let value = match output {
+            _ if code_cell.is_chart => {
+                chart_output = code_cell
+                    .output_size
+                    .map(|size| (size.w.get(), size.h.get() - 1));
+                Value::Single(CellValue::Html(String::new()))
+            }
+            None => Value::Single(CellValue::Blank),
+            Some(output) => {
+                let mut rows = output.rows().map(|row| row.to_vec());
+                if code_cell.show_name {
+                    rows.next();
+                }
+                if code_cell.show_columns {
+                    column_headers = rows.next().map(|headers| {
+                        headers
+                            .iter()
+                            .enumerate()
+                            .map(|(index, header)| {
+                                DataTableColumnHeader::new(header.to_string(), true, index as u32)
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                }
+                let rows = rows.collect::<Vec<_>>();
+                match rows.as_slice() {
+                    [] => Value::Single(CellValue::Blank),
+                    [row] if row.len() == 1 && !code_cell.show_name && !code_cell.show_columns => {
+                        Value::Single(row[0].to_owned())
+                    }
+                    _ => Value::Array(Array::from(rows)),
+                }
+            }
+        };
+
+        let code_run = CodeRun {
+            language: code_cell.language,
+            code: code_cell.code,
+            ..CodeRun::default()
+        };
+        let mut data_table = DataTable::new(
+            DataTableKind::CodeRun(code_run),
+            name,
+            value,
+            false,
+            Some(code_cell.show_name),
+            Some(code_cell.show_columns),
+            chart_output,
+        );
+        if let Some(column_headers) = column_headers {
+            data_table = data_table.with_column_headers(column_headers);
+        }
+
+        let mut transaction = PendingTransaction {
+            source: TransactionSource::Server,
+            ..Default::default()
+        };
+        self.finalize_data_table(
+            &mut transaction,
+            pos.to_sheet_pos(sheet_id),
+            Some(data_table),
+            None,
+        );
+        self.update_a1_context_table_map(&mut transaction);
+
+        Ok(())
+    }
+
     /// Imports a Parquet file into the grid.
     pub fn import_parquet_operations(
         &mut self,
@@ -548,6 +865,31 @@ impl GridController {
 
         Ok(ops)
     }
+
+    /// Imports a JSON or NDJSON file into the grid.
+    pub fn import_json_operations(
+        &mut self,
+        sheet_id: SheetId,
+        file: &[u8],
+        file_name: &str,
+        insert_at: Pos,
+        explode_arrays: bool,
+    ) -> Result<Vec<Operation>> {
+        let cell_values = json_to_array(file, file_name, explode_arrays)?;
+        let context = self.a1_context();
+        let import = Import::new(sanitize_table_name(file_name.into()));
+        let mut data_table = DataTable::from((import.to_owned(), cell_values, context));
+        data_table.apply_first_row_as_header();
+
+        let ops = vec![Operation::AddDataTable {
+            sheet_pos: SheetPos::from((insert_at, sheet_id)),
+            data_table,
+            cell_value: CellValue::Import(import),
+            index: None,
+        }];
+
+        Ok(ops)
+    }
 }
 
 /// Converts Excel number format to our quadratic format.
@@ -833,6 +1175,44 @@ fn import_excel_number_format(sheet: &mut Sheet, pos: Pos, number_format: &Numbe
 
 /// Handles custom number formats that don't have a format_id.
 fn import_excel_number_format_string(sheet: &mut Sheet, pos: Pos, format_string: &str) {
+    let update = excel_number_format_update(format_string);
+
+    if let Some(Some(date_time)) = update.date_time {
+        sheet.formats.date_time.set(pos, Some(date_time));
+
+        // convert numeric value to date, time or datetime if needed
+        let (is_date, is_time, is_datetime) = if is_excel_datetime_format(format_string) {
+            (true, true, true)
+        } else if is_excel_date_format(format_string) {
+            (true, false, false)
+        } else {
+            (false, true, false)
+        };
+        if let Some(CellValue::Number(ref n)) = sheet.cell_value(pos)
+            && let Some(f64_val) = n.to_f64()
+        {
+            let converted_value = excel_serial_to_date_time(f64_val, is_date, is_time, is_datetime);
+            if let Some(new_value) = converted_value {
+                sheet.columns.set_value(&pos, new_value);
+            }
+        }
+        return;
+    }
+
+    if let Some(numeric_format) = update.numeric_format {
+        sheet.formats.numeric_format.set(pos, numeric_format);
+    }
+    if let Some(decimals) = update.numeric_decimals {
+        sheet.formats.numeric_decimals.set(pos, decimals);
+    }
+    if let Some(commas) = update.numeric_commas {
+        sheet.formats.numeric_commas.set(pos, commas);
+    }
+}
+
+/// Converts an Excel number format string (without a format_id) to a
+/// FormatUpdate.
+pub(crate) fn excel_number_format_update(format_string: &str) -> FormatUpdate {
     let count_decimal_places = |format_str: &str| -> i16 {
         if let Some(decimal_pos) = format_str.find('.') {
             let after_decimal = &format_str[decimal_pos + 1..];
@@ -848,77 +1228,43 @@ fn import_excel_number_format_string(sheet: &mut Sheet, pos: Pos, format_string:
     let has_thousands_separator =
         |format_str: &str| -> bool { format_str.contains("#,##") || format_str.contains("0,00") };
 
-    // handle cases where we only have a format string but no format_id
-    if is_excel_datetime_format(format_string) {
-        let chrono_format = excel_to_chrono_format(format_string);
-        sheet.formats.date_time.set(pos, Some(chrono_format));
-
-        // convert numeric value to datetime if needed
-        if let Some(CellValue::Number(ref n)) = sheet.cell_value(pos)
-            && let Some(f64_val) = n.to_f64()
-        {
-            let converted_value = excel_serial_to_date_time(f64_val, true, true, true);
-            if let Some(new_value) = converted_value {
-                sheet.columns.set_value(&pos, new_value);
-            }
-        }
-    } else if is_excel_date_format(format_string) {
-        let chrono_format = excel_to_chrono_format(format_string);
-        sheet.formats.date_time.set(pos, Some(chrono_format));
-
-        // convert numeric value to date if needed
-        if let Some(CellValue::Number(ref n)) = sheet.cell_value(pos)
-            && let Some(f64_val) = n.to_f64()
-        {
-            let converted_value = excel_serial_to_date_time(f64_val, true, false, false);
-            if let Some(new_value) = converted_value {
-                sheet.columns.set_value(&pos, new_value);
-            }
-        }
-    } else if is_excel_time_format(format_string) {
-        let chrono_format = excel_to_chrono_format(format_string);
-        sheet.formats.date_time.set(pos, Some(chrono_format));
+    let mut update = FormatUpdate::default();
 
-        // convert numeric value to time if needed
-        if let Some(CellValue::Number(ref n)) = sheet.cell_value(pos)
-            && let Some(f64_val) = n.to_f64()
-        {
-            let converted_value = excel_serial_to_date_time(f64_val, false, true, false);
-            if let Some(new_value) = converted_value {
-                sheet.columns.set_value(&pos, new_value);
-            }
-        }
+    // handle cases where we only have a format string but no format_id
+    if is_excel_datetime_format(format_string)
+        || is_excel_date_format(format_string)
+        || is_excel_time_format(format_string)
+    {
+        update.date_time = Some(Some(excel_to_chrono_format(format_string)));
     } else {
         // handle numeric formats
         if format_string.contains('%') {
-            let numeric_format = NumericFormat {
+            update.numeric_format = Some(Some(NumericFormat {
                 kind: NumericFormatKind::Percentage,
                 symbol: None,
-            };
-            sheet.formats.numeric_format.set(pos, Some(numeric_format));
+            }));
         } else if format_string.contains('$') {
-            let numeric_format = NumericFormat {
+            update.numeric_format = Some(Some(NumericFormat {
                 kind: NumericFormatKind::Currency,
                 symbol: Some("$".to_string()),
-            };
-            sheet.formats.numeric_format.set(pos, Some(numeric_format));
+            }));
         } else if format_string.to_uppercase().contains('E') {
-            let numeric_format = NumericFormat {
+            update.numeric_format = Some(Some(NumericFormat {
                 kind: NumericFormatKind::Exponential,
                 symbol: None,
-            };
-            sheet.formats.numeric_format.set(pos, Some(numeric_format));
+            }));
         }
 
         // set decimal places
-        let decimals = count_decimal_places(format_string);
-        sheet.formats.numeric_decimals.set(pos, Some(decimals));
+        update.numeric_decimals = Some(Some(count_decimal_places(format_string)));
 
         // set thousands separator
         if has_thousands_separator(format_string) {
-            sheet.formats.numeric_commas.set(pos, Some(true));
+            update.numeric_commas = Some(Some(true));
         }
     }
+
+    update
 }
 
 /// Converts Excel format strings to Chrono format strings
@@ -1250,6 +1596,213 @@ fn excel_serial_to_date_time(
     None
 }
 
+/// The inclusive bounds of an xlsx data validation comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExcelValidationBounds {
+    Range(Option<f64>, Option<f64>),
+    Equal(f64),
+    NotEqual(f64),
+}
+
+/// Reads the bounds of an xlsx data validation. Quadratic's ranges are
+/// inclusive, so strict comparisons are moved by `step` (e.g., 1 for whole
+/// numbers, 0 for decimals). Returns None if a bound is not a constant
+/// (e.g., a cell reference).
+fn excel_validation_bounds(
+    data_validation: &XlsxDataValidation,
+    step: f64,
+) -> Option<Vec<ExcelValidationBounds>> {
+    let value = |formula: &Option<String>| formula.as_deref()?.parse::<f64>().ok();
+    let a = value(&data_validation.formula1)?;
+    let bounds = match data_validation.operator.as_deref().unwrap_or("between") {
+        "between" => {
+            let b = value(&data_validation.formula2)?;
+            vec![ExcelValidationBounds::Range(Some(a), Some(b))]
+        }
+        "notBetween" => {
+            let b = value(&data_validation.formula2)?;
+            vec![
+                ExcelValidationBounds::Range(None, Some(a - step)),
+                ExcelValidationBounds::Range(Some(b + step), None),
+            ]
+        }
+        "equal" => vec![ExcelValidationBounds::Equal(a)],
+        "notEqual" => vec![ExcelValidationBounds::NotEqual(a)],
+        "greaterThan" => vec![ExcelValidationBounds::Range(Some(a + step), None)],
+        "greaterThanOrEqual" => vec![ExcelValidationBounds::Range(Some(a), None)],
+        "lessThan" => vec![ExcelValidationBounds::Range(None, Some(a - step))],
+        "lessThanOrEqual" => vec![ExcelValidationBounds::Range(None, Some(a))],
+        _ => return None,
+    };
+    Some(bounds)
+}
+
+/// Converts an Excel serial date to a date. Excel counts the nonexistent
+/// 1900-02-29 as serial 60.
+fn excel_serial_to_naive_date(serial: f64) -> Option<NaiveDate> {
+    let days = serial.floor() as i64;
+    let base_date = if days < 61 {
+        NaiveDate::from_ymd_opt(1899, 12, 31)?
+    } else {
+        NaiveDate::from_ymd_opt(1899, 12, 30)?
+    };
+    base_date.checked_add_days(chrono::Days::new(u64::try_from(days).ok()?))
+}
+
+/// Converts an xlsx data validation to a validation. Returns None for
+/// validations that Quadratic can't represent (e.g., custom formulas or
+/// bounds that reference cells).
+fn import_excel_data_validation(
+    data_validation: &XlsxDataValidation,
+    sheet_id: SheetId,
+    a1_context: &A1Context,
+) -> Option<Validation> {
+    let selection = A1Selection::from_rects(data_validation.rects.clone(), sheet_id, a1_context)?;
+    let ignore_blank = data_validation.allow_blank;
+
+    let rule = match data_validation.validation_type.as_deref() {
+        None | Some("none") => ValidationRule::None,
+        Some("list") => {
+            let formula = data_validation.formula1.as_deref()?;
+            match formula
+                .strip_prefix('"')
+                .and_then(|list| list.strip_suffix('"'))
+            {
+                Some(list) => {
+                    let list = list
+                        .replace("\"\"", "\"")
+                        .split(',')
+                        .map(|item| item.trim().to_string())
+                        .collect::<Vec<_>>();
+
+                    // Quadratic exports logical validations as TRUE/FALSE lists
+                    let is_logical = list.len() == 2
+                        && list.iter().any(|item| item.eq_ignore_ascii_case("TRUE"))
+                        && list.iter().any(|item| item.eq_ignore_ascii_case("FALSE"));
+                    if is_logical {
+                        ValidationRule::Logical(ValidationLogical {
+                            show_checkbox: true,
+                            ignore_blank,
+                        })
+                    } else {
+                        ValidationRule::List(ValidationList {
+                            source: ValidationListSource::List(list),
+                            ignore_blank,
+                            drop_down: data_validation.show_dropdown,
+                        })
+                    }
+                }
+                None => ValidationRule::List(ValidationList {
+                    source: ValidationListSource::Selection(
+                        A1Selection::parse_a1(formula, sheet_id, a1_context).ok()?,
+                    ),
+                    ignore_blank,
+                    drop_down: data_validation.show_dropdown,
+                }),
+            }
+        }
+        Some(validation_type @ ("whole" | "decimal")) => {
+            let step = if validation_type == "whole" { 1.0 } else { 0.0 };
+            let ranges = excel_validation_bounds(data_validation, step)?
+                .into_iter()
+                .map(|bounds| match bounds {
+                    ExcelValidationBounds::Range(min, max) => NumberRange::Range(min, max),
+                    ExcelValidationBounds::Equal(value) => NumberRange::Equal(vec![value]),
+                    ExcelValidationBounds::NotEqual(value) => NumberRange::NotEqual(vec![value]),
+                })
+                .collect();
+            ValidationRule::Number(ValidationNumber {
+                ignore_blank,
+                ranges,
+            })
+        }
+        Some("textLength") => {
+            let length = |value: Option<f64>| value.map(|value| value.round().max(0.0) as i16);
+            let (min, max) = match excel_validation_bounds(data_validation, 1.0)?.as_slice() {
+                [ExcelValidationBounds::Range(min, max)] => (length(*min), length(*max)),
+                [ExcelValidationBounds::Equal(value)] => {
+                    (length(Some(*value)), length(Some(*value)))
+                }
+                // text length rules can't exclude a range
+                _ => return None,
+            };
+            ValidationRule::Text(ValidationText {
+                ignore_blank,
+                text_match: vec![TextMatch::TextLength { min, max }],
+            })
+        }
+        Some(validation_type @ ("date" | "time")) => {
+            let is_date = validation_type == "date";
+            let step = if is_date { 1.0 } else { 1.0 / 86400.0 };
+            let date = |serial: Option<f64>| match serial {
+                Some(serial) => excel_serial_to_naive_date(serial)
+                    .and_then(naive_date_to_i64)
+                    .map(Some),
+                None => Some(None),
+            };
+            let time = |serial: Option<f64>| match serial {
+                Some(serial) => Some(Some((serial.fract() * 86400.0).round() as i32)),
+                None => Some(None),
+            };
+            let ranges = excel_validation_bounds(data_validation, step)?
+                .into_iter()
+                .map(|bounds| {
+                    Some(match bounds {
+                        ExcelValidationBounds::Range(min, max) if is_date => {
+                            DateTimeRange::DateRange(date(min)?, date(max)?)
+                        }
+                        ExcelValidationBounds::Range(min, max) => {
+                            DateTimeRange::TimeRange(time(min)?, time(max)?)
+                        }
+                        ExcelValidationBounds::Equal(value) if is_date => {
+                            DateTimeRange::DateEqual(vec![date(Some(value))??])
+                        }
+                        ExcelValidationBounds::Equal(value) => {
+                            DateTimeRange::TimeEqual(vec![time(Some(value))??])
+                        }
+                        ExcelValidationBounds::NotEqual(value) if is_date => {
+                            DateTimeRange::DateNotEqual(vec![date(Some(value))??])
+                        }
+                        ExcelValidationBounds::NotEqual(value) => {
+                            DateTimeRange::TimeNotEqual(vec![time(Some(value))??])
+                        }
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            ValidationRule::DateTime(ValidationDateTime {
+                ignore_blank,
+                ranges,
+                ..Default::default()
+            })
+        }
+        // custom formulas are not supported
+        _ => return None,
+    };
+
+    let style = match data_validation.error_style.as_deref() {
+        Some("warning") => ValidationStyle::Warning,
+        Some("information") => ValidationStyle::Information,
+        _ => ValidationStyle::Stop,
+    };
+
+    Some(Validation {
+        id: Uuid::new_v4(),
+        selection,
+        rule,
+        message: ValidationMessage {
+            show: data_validation.show_input_message,
+            title: data_validation.prompt_title.to_owned(),
+            message: data_validation.prompt.to_owned(),
+        },
+        error: ValidationError {
+            show: data_validation.show_error_message,
+            style,
+            title: data_validation.error_title.to_owned(),
+            message: data_validation.error.to_owned(),
+        },
+    })
+}
+
 /// Converts calamine border styles to Quadratic border styles
 fn convert_excel_border_style(excel_style: calamine::BorderStyle) -> CellBorderLine {
     use calamine::BorderStyle;
@@ -1308,8 +1861,10 @@ fn convert_excel_borders_to_quadratic(borders: &calamine::Borders) -> BorderStyl
 mod test {
     use super::*;
     use crate::{
-        ArraySize, CellValue, controller::user_actions::import::tests::simple_csv_at,
-        number::decimal_from_str, test_util::*,
+        ArraySize, CellValue,
+        controller::{operations::csv::CsvColumnType, user_actions::import::tests::simple_csv_at},
+        number::decimal_from_str,
+        test_util::*,
     };
     use calamine::{BorderStyle, Color};
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
@@ -1344,8 +1899,11 @@ mod test {
                 SIMPLE_CSV.as_bytes(),
                 file_name,
                 pos,
-                Some(b','),
-                Some(true),
+                &CsvImportOptions {
+                    delimiter: Some(b','),
+                    header_is_first_row: Some(true),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -1395,8 +1953,11 @@ mod test {
                 csv.as_bytes(),
                 file_name,
                 pos,
-                Some(b','),
-                Some(true),
+                &CsvImportOptions {
+                    delimiter: Some(b','),
+                    header_is_first_row: Some(true),
+                    ..Default::default()
+                },
             )
             .unwrap();
 
@@ -1416,6 +1977,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn previews_a_csv() {
+        let csv = "name,zip\nAlice,02134\nBob,00501\nCarol,10001\n";
+        let options = CsvImportOptions {
+            column_types: vec![CsvColumnType::Auto, CsvColumnType::Text],
+            ..Default::default()
+        };
+
+        let preview = GridController::preview_csv(csv.as_bytes(), "zips.csv", &options, 3).unwrap();
+        assert_eq!(preview.delimiter, b',');
+        assert!(preview.header_is_first_row);
+        assert_eq!(preview.width, 2);
+        assert_eq!(preview.height, 4);
+        assert_eq!(
+            preview.rows,
+            vec![
+                vec!["name".to_string(), "zip".to_string()],
+                vec!["Alice".to_string(), "02134".to_string()],
+                vec!["Bob".to_string(), "00501".to_string()],
+            ]
+        );
+    }
+
     #[test]
     fn import_csv_date_time() {
         let mut gc = GridController::test();
@@ -6,11 +6,14 @@ pub mod autocomplete;
 pub mod borders;
 pub mod cell_value;
 pub mod clipboard;
+mod clipboard_html;
 pub mod code_cell;
-mod csv;
+pub mod csv;
 pub mod data_table;
+mod fill_series;
 pub mod formats;
 pub mod import;
 pub mod operation;
 pub mod sheets;
 pub mod tracked_operation;
+pub(crate) mod xlsx;
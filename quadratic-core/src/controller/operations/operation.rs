@@ -420,4 +420,9 @@ pub enum Operation {
         #[serde(default)]
         copy_formats: CopyFormats,
     },
+
+    /// Replaces the file's custom lists used by autocomplete and fill series.
+    SetCustomLists {
+        custom_lists: Vec<Vec<String>>,
+    },
 }
@@ -142,6 +142,7 @@ impl TrackedOperation {
             // Sheet operations
             Operation::AddSheetSchema { schema } => Some(Self::AddSheet {
                 sheet_name: match schema.as_ref() {
+                    SheetSchema::V1_12(schema) => schema.name.to_string(),
                     SheetSchema::V1_11(schema) => schema.name.to_string(),
                     SheetSchema::V1_10(schema) => schema.name.to_string(),
                     SheetSchema::V1_9(schema) => schema.id.to_string(),
@@ -326,6 +327,9 @@ impl TrackedOperation {
                 selection: sheet_pos_to_selection(*sheet_pos, gc),
             }),
 
+            // file settings that don't change sheet content
+            Operation::SetCustomLists { .. } => None,
+
             // Deprecated operations that we don't need to support
             Operation::SetChartSize { .. }
             | Operation::SetChartCellSize { .. }
@@ -0,0 +1,1099 @@
+//! XLSX utilities to read parts of the file that calamine does not expose.
+//!
+//! An xlsx file is a zip archive of xml parts. Worksheets are found through
+//! `xl/workbook.xml` and its relationships; each worksheet may have its own
+//! relationships (e.g., for external hyperlinks and tables).
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+};
+
+use anyhow::{Result, anyhow};
+use quick_xml::{Reader, events::BytesStart, events::Event};
+use zip::ZipArchive;
+
+use crate::{
+    ArraySize, Hyperlink, Pos, Rect,
+    a1::{column_name, quote_sheet_name},
+    grid::CodeCellLanguage,
+};
+
+const WORKBOOK_PATH: &str = "xl/workbook.xml";
+const WORKBOOK_RELS_PATH: &str = "xl/_rels/workbook.xml.rels";
+
+/// The hidden worksheet that stores non-formula code cells on export.
+pub(crate) const CODE_CELLS_SHEET_NAME: &str = "_quadratic_code";
+
+/// Excel limits a cell to 32,767 characters.
+const CODE_CHUNK_LENGTH: usize = 32_000;
+
+/// A relationship from one part of the package to another part or to an
+/// external resource.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XlsxRelationship {
+    pub(crate) target: String,
+    pub(crate) external: bool,
+}
+
+/// An Excel table (ListObject) defined in a worksheet.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XlsxTable {
+    /// The name used by formulas (the table's `displayName`).
+    pub(crate) name: String,
+    pub(crate) sheet_name: String,
+
+    /// The full range of the table, including the header and totals rows.
+    pub(crate) rect: Rect,
+    pub(crate) header_row: bool,
+    pub(crate) totals_row: bool,
+    pub(crate) columns: Vec<String>,
+    pub(crate) banded_rows: bool,
+
+    /// Sort conditions as (column index within the table, descending).
+    pub(crate) sort: Vec<(usize, bool)>,
+}
+
+impl XlsxTable {
+    /// Returns the rows of the table between the header and totals rows, or
+    /// None if the table has no data rows.
+    pub(crate) fn data_rect(&self) -> Option<Rect> {
+        let mut rect = self.rect;
+        if self.header_row {
+            rect.min.y += 1;
+        }
+        if self.totals_row {
+            rect.max.y -= 1;
+        }
+        (rect.min.y <= rect.max.y).then_some(rect)
+    }
+}
+
+/// A data validation defined in a worksheet, as stored in the xlsx file.
+/// Formulas are stored without a leading `=`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct XlsxDataValidation {
+    pub(crate) rects: Vec<Rect>,
+
+    /// The type of validation (e.g., `list`, `whole`, `date`). Excel omits it
+    /// for validations that only show an input message.
+    pub(crate) validation_type: Option<String>,
+    pub(crate) operator: Option<String>,
+    pub(crate) formula1: Option<String>,
+    pub(crate) formula2: Option<String>,
+    pub(crate) allow_blank: bool,
+
+    /// Excel's `showDropDown` attribute hides the in-cell dropdown when set,
+    /// so this is its inverse.
+    pub(crate) show_dropdown: bool,
+
+    pub(crate) show_input_message: bool,
+    pub(crate) prompt_title: Option<String>,
+    pub(crate) prompt: Option<String>,
+
+    pub(crate) show_error_message: bool,
+    pub(crate) error_style: Option<String>,
+    pub(crate) error_title: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+impl XlsxDataValidation {
+    /// Reads the attributes of a `dataValidation` element. The ranges of
+    /// `x14` validations are read later from their `sqref` child.
+    fn from_element(element: &BytesStart<'_>) -> Self {
+        let attributes = attributes(element);
+        Self {
+            rects: attributes
+                .get("sqref")
+                .map(|sqref| parse_xlsx_ranges(sqref))
+                .unwrap_or_default(),
+            validation_type: attributes.get("type").cloned(),
+            operator: attributes.get("operator").cloned(),
+            formula1: None,
+            formula2: None,
+            allow_blank: is_true(attributes.get("allowBlank")),
+            show_dropdown: !is_true(attributes.get("showDropDown")),
+            show_input_message: is_true(attributes.get("showInputMessage")),
+            prompt_title: attributes.get("promptTitle").cloned(),
+            prompt: attributes.get("prompt").cloned(),
+            show_error_message: is_true(attributes.get("showErrorMessage")),
+            error_style: attributes.get("errorStyle").cloned(),
+            error_title: attributes.get("errorTitle").cloned(),
+            error: attributes.get("error").cloned(),
+        }
+    }
+}
+
+/// A non-formula code cell (e.g., Python) stored as a row of the hidden code
+/// cells sheet, since excel can only keep the flattened output.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XlsxCodeCell {
+    pub(crate) sheet_name: String,
+    pub(crate) pos: Pos,
+    pub(crate) language: CodeCellLanguage,
+    pub(crate) name: String,
+    pub(crate) show_name: bool,
+    pub(crate) show_columns: bool,
+    pub(crate) is_chart: bool,
+
+    /// The size of the flattened output, including the name and column
+    /// header rows. None if the output wasn't exported (e.g., an error).
+    pub(crate) output_size: Option<ArraySize>,
+    pub(crate) code: String,
+}
+
+impl XlsxCodeCell {
+    pub(crate) const HEADERS: [&'static str; 9] = [
+        "Sheet",
+        "Cell",
+        "Language",
+        "Name",
+        "Show Name",
+        "Show Columns",
+        "Chart",
+        "Output",
+        "Code",
+    ];
+
+    /// Returns the text of each cell of the row. Code longer than excel's
+    /// cell limit is split across the remaining cells.
+    pub(crate) fn to_row(&self) -> Vec<String> {
+        let language = match serde_json::to_value(&self.language) {
+            Ok(serde_json::Value::String(language)) => language,
+            Ok(language) => language.to_string(),
+            Err(_) => String::new(),
+        };
+        let output_size = self
+            .output_size
+            .map(|size| format!("{}x{}", size.w, size.h))
+            .unwrap_or_default();
+        let mut row = vec![
+            self.sheet_name.to_owned(),
+            self.pos.a1_string(),
+            language,
+            self.name.to_owned(),
+            self.show_name.to_string(),
+            self.show_columns.to_string(),
+            self.is_chart.to_string(),
+            output_size,
+        ];
+        let code = self.code.chars().collect::<Vec<_>>();
+        row.extend(
+            code.chunks(CODE_CHUNK_LENGTH)
+                .map(|chunk| chunk.iter().collect::<String>()),
+        );
+        row
+    }
+
+    /// Parses a row written by [`Self::to_row`].
+    pub(crate) fn from_row(row: &[String]) -> Option<Self> {
+        let [
+            sheet_name,
+            cell,
+            language,
+            name,
+            show_name,
+            show_columns,
+            is_chart,
+            output_size,
+            code @ ..,
+        ] = row
+        else {
+            return None;
+        };
+
+        // simple languages are plain strings (e.g., `Python`), others are json
+        let language = serde_json::from_str(language)
+            .or_else(|_| serde_json::from_value(serde_json::Value::String(language.to_owned())))
+            .ok()?;
+        let output_size = output_size
+            .split_once('x')
+            .and_then(|(w, h)| ArraySize::new(w.parse().ok()?, h.parse().ok()?));
+
+        Some(Self {
+            sheet_name: sheet_name.to_owned(),
+            pos: Pos::try_a1_string(cell)?,
+            language,
+            name: name.to_owned(),
+            show_name: show_name.parse().ok()?,
+            show_columns: show_columns.parse().ok()?,
+            is_chart: is_chart.parse().ok()?,
+            output_size,
+            code: code.concat(),
+        })
+    }
+}
+
+pub(crate) struct XlsxPackage<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+
+    /// Sheet name to the path of its worksheet part.
+    sheet_paths: HashMap<String, String>,
+}
+
+impl<'a> XlsxPackage<'a> {
+    pub(crate) fn new(file: &'a [u8]) -> Result<Self> {
+        let archive = ZipArchive::new(Cursor::new(file))
+            .map_err(|e| anyhow!("Error reading xlsx archive: {e}"))?;
+        let mut package = Self {
+            archive,
+            sheet_paths: HashMap::new(),
+        };
+
+        let workbook_rels = package.relationships_at(WORKBOOK_RELS_PATH, "xl")?;
+        let workbook = package.read_part(WORKBOOK_PATH)?.unwrap_or_default();
+        let mut reader = Reader::from_str(&workbook);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e) | Event::Empty(e)) if e.local_name().as_ref() == b"sheet" => {
+                    let attributes = attributes(&e);
+                    if let (Some(name), Some(rel)) = (
+                        attributes.get("name"),
+                        attributes.get("id").and_then(|id| workbook_rels.get(id)),
+                    ) {
+                        package
+                            .sheet_paths
+                            .insert(name.to_owned(), rel.target.to_owned());
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("Error reading {WORKBOOK_PATH}: {e}")),
+                _ => (),
+            }
+        }
+
+        Ok(package)
+    }
+
+    /// Reads a part of the package as a string. Returns None if the part does
+    /// not exist.
+    pub(crate) fn read_part(&mut self, path: &str) -> Result<Option<String>> {
+        let Ok(mut file) = self.archive.by_name(path) else {
+            return Ok(None);
+        };
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .map_err(|e| anyhow!("Error reading {path}: {e}"))?;
+        Ok(Some(contents))
+    }
+
+    /// Returns the path of the worksheet part for a sheet.
+    pub(crate) fn sheet_path(&self, sheet_name: &str) -> Option<&str> {
+        self.sheet_paths.get(sheet_name).map(String::as_str)
+    }
+
+    /// Reads the relationships of a part (e.g., a worksheet). Internal targets
+    /// are resolved to paths within the package.
+    pub(crate) fn relationships(
+        &mut self,
+        part_path: &str,
+    ) -> Result<HashMap<String, XlsxRelationship>> {
+        let (dir, file_name) = part_path.rsplit_once('/').unwrap_or(("", part_path));
+        self.relationships_at(&format!("{dir}/_rels/{file_name}.rels"), dir)
+    }
+
+    fn relationships_at(
+        &mut self,
+        rels_path: &str,
+        base_dir: &str,
+    ) -> Result<HashMap<String, XlsxRelationship>> {
+        let mut relationships = HashMap::new();
+        let Some(xml) = self.read_part(rels_path)? else {
+            return Ok(relationships);
+        };
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e) | Event::Empty(e))
+                    if e.local_name().as_ref() == b"Relationship" =>
+                {
+                    let mut attributes = attributes(&e);
+                    let external = attributes
+                        .get("TargetMode")
+                        .is_some_and(|mode| mode == "External");
+                    if let (Some(id), Some(target)) =
+                        (attributes.remove("Id"), attributes.remove("Target"))
+                    {
+                        let target = if external {
+                            target
+                        } else {
+                            resolve_path(base_dir, &target)
+                        };
+                        relationships.insert(id, XlsxRelationship { target, external });
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("Error reading {rels_path}: {e}")),
+                _ => (),
+            }
+        }
+        Ok(relationships)
+    }
+
+    /// Returns the hyperlinks of a sheet along with the cells they cover.
+    pub(crate) fn hyperlinks(&mut self, sheet_name: &str) -> Result<Vec<(Rect, Hyperlink)>> {
+        let mut hyperlinks = vec![];
+        let Some(sheet_path) = self.sheet_path(sheet_name).map(str::to_owned) else {
+            return Ok(hyperlinks);
+        };
+        let Some(xml) = self.read_part(&sheet_path)? else {
+            return Ok(hyperlinks);
+        };
+        let relationships = self.relationships(&sheet_path)?;
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e) | Event::Empty(e))
+                    if e.local_name().as_ref() == b"hyperlink" =>
+                {
+                    let attributes = attributes(&e);
+                    let Some(rect) = attributes
+                        .get("ref")
+                        .and_then(|range| parse_xlsx_range(range))
+                    else {
+                        continue;
+                    };
+                    let location = attributes.get("location");
+                    let url = attributes
+                        .get("id")
+                        .and_then(|id| relationships.get(id))
+                        .filter(|rel| rel.external)
+                        .map(|rel| rel.target.to_owned());
+                    let hyperlink = match (url, location) {
+                        (Some(url), Some(location)) => {
+                            Hyperlink::new_url(format!("{url}#{location}"), None)
+                        }
+                        (Some(url), None) => Hyperlink::new_url(url, None),
+                        (None, Some(location)) => Hyperlink::new_internal(location, None),
+                        (None, None) => continue,
+                    };
+                    hyperlinks.push((rect, hyperlink));
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("Error reading {sheet_path}: {e}")),
+                _ => (),
+            }
+        }
+        Ok(hyperlinks)
+    }
+
+    /// Returns the tables defined in a sheet.
+    pub(crate) fn tables(&mut self, sheet_name: &str) -> Result<Vec<XlsxTable>> {
+        let mut tables = vec![];
+        let Some(sheet_path) = self.sheet_path(sheet_name).map(str::to_owned) else {
+            return Ok(tables);
+        };
+        let Some(xml) = self.read_part(&sheet_path)? else {
+            return Ok(tables);
+        };
+        let relationships = self.relationships(&sheet_path)?;
+        let mut table_paths = vec![];
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e) | Event::Empty(e))
+                    if e.local_name().as_ref() == b"tablePart" =>
+                {
+                    if let Some(rel) = attributes(&e)
+                        .get("id")
+                        .and_then(|id| relationships.get(id))
+                        && !rel.external
+                    {
+                        table_paths.push(rel.target.to_owned());
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("Error reading {sheet_path}: {e}")),
+                _ => (),
+            }
+        }
+        for table_path in table_paths {
+            if let Some(table) = self.table(&table_path, sheet_name)? {
+                tables.push(table);
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Reads a table part. Returns None if the part does not exist or does
+    /// not define a table.
+    fn table(&mut self, table_path: &str, sheet_name: &str) -> Result<Option<XlsxTable>> {
+        let Some(xml) = self.read_part(table_path)? else {
+            return Ok(None);
+        };
+        let mut table: Option<XlsxTable> = None;
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e) | Event::Empty(e)) => {
+                    let attributes = attributes(&e);
+                    match e.local_name().as_ref() {
+                        b"table" => {
+                            let (Some(name), Some(rect)) = (
+                                attributes.get("displayName").or(attributes.get("name")),
+                                attributes
+                                    .get("ref")
+                                    .and_then(|range| parse_xlsx_range(range)),
+                            ) else {
+                                return Ok(None);
+                            };
+                            table = Some(XlsxTable {
+                                name: name.to_owned(),
+                                sheet_name: sheet_name.to_owned(),
+                                rect,
+                                header_row: attributes
+                                    .get("headerRowCount")
+                                    .is_none_or(|count| count != "0"),
+                                totals_row: attributes
+                                    .get("totalsRowCount")
+                                    .is_some_and(|count| count != "0"),
+                                columns: vec![],
+                                banded_rows: false,
+                                sort: vec![],
+                            });
+                        }
+                        b"tableColumn" => {
+                            if let (Some(table), Some(name)) =
+                                (table.as_mut(), attributes.get("name"))
+                            {
+                                table.columns.push(name.to_owned());
+                            }
+                        }
+                        b"sortCondition" => {
+                            if let Some(table) = table.as_mut()
+                                && let Some(rect) = attributes
+                                    .get("ref")
+                                    .and_then(|range| parse_xlsx_range(range))
+                                && rect.min.x >= table.rect.min.x
+                            {
+                                let column = (rect.min.x - table.rect.min.x) as usize;
+                                let descending = is_true(attributes.get("descending"));
+                                table.sort.push((column, descending));
+                            }
+                        }
+                        b"tableStyleInfo" => {
+                            if let Some(table) = table.as_mut() {
+                                table.banded_rows = is_true(attributes.get("showRowStripes"));
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("Error reading {table_path}: {e}")),
+                _ => (),
+            }
+        }
+        Ok(table)
+    }
+
+    /// Returns the data validations of a sheet. Validations that reference
+    /// other sheets are stored by Excel in an `x14` extension; both forms
+    /// are read.
+    pub(crate) fn data_validations(&mut self, sheet_name: &str) -> Result<Vec<XlsxDataValidation>> {
+        let mut data_validations = vec![];
+        let Some(sheet_path) = self.sheet_path(sheet_name).map(str::to_owned) else {
+            return Ok(data_validations);
+        };
+        let Some(xml) = self.read_part(&sheet_path)? else {
+            return Ok(data_validations);
+        };
+        let mut current: Option<XlsxDataValidation> = None;
+
+        // the child element (formula1, formula2 or sqref) whose text is read
+        let mut text_target: Option<Vec<u8>> = None;
+        let mut text = String::new();
+
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Empty(e)) if e.local_name().as_ref() == b"dataValidation" => {
+                    data_validations.push(XlsxDataValidation::from_element(&e));
+                }
+                Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                    b"dataValidation" => current = Some(XlsxDataValidation::from_element(&e)),
+                    name @ (b"formula1" | b"formula2" | b"sqref") if current.is_some() => {
+                        text_target = Some(name.to_vec());
+                        text.clear();
+                    }
+                    _ => (),
+                },
+                Ok(Event::Text(e)) if text_target.is_some() => {
+                    let value = e
+                        .unescape()
+                        .map_err(|e| anyhow!("Error reading {sheet_path}: {e}"))?;
+                    text.push_str(&value);
+                }
+                Ok(Event::End(e)) => match e.local_name().as_ref() {
+                    b"dataValidation" => {
+                        data_validations.extend(current.take());
+                        text_target = None;
+                    }
+                    name if text_target.as_deref() == Some(name) => {
+                        if let Some(data_validation) = current.as_mut() {
+                            let value = std::mem::take(&mut text).trim().to_string();
+                            match name {
+                                b"formula1" => data_validation.formula1 = Some(value),
+                                b"formula2" => data_validation.formula2 = Some(value),
+                                _ => data_validation.rects.extend(parse_xlsx_ranges(&value)),
+                            }
+                        }
+                        text_target = None;
+                    }
+                    _ => (),
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("Error reading {sheet_path}: {e}")),
+                _ => (),
+            }
+        }
+        Ok(data_validations)
+    }
+}
+
+/// Returns true if an xsd:boolean attribute is set.
+fn is_true(value: Option<&String>) -> bool {
+    value.is_some_and(|value| value == "1" || value == "true")
+}
+
+/// Returns the unescaped attributes of an element, keyed by local name (i.e.,
+/// `r:id` is keyed as `id`).
+pub(crate) fn attributes(element: &BytesStart<'_>) -> HashMap<String, String> {
+    element
+        .attributes()
+        .flatten()
+        .filter_map(|attribute| {
+            let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string();
+            let value = attribute.unescape_value().ok()?.to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Resolves a relationship target relative to the directory of its source
+/// part. Targets that start with `/` are relative to the package root.
+fn resolve_path(base_dir: &str, target: &str) -> String {
+    if let Some(target) = target.strip_prefix('/') {
+        return target.to_string();
+    }
+    let mut parts = base_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    for part in target.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => (),
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Parses an xlsx cell range (e.g., `A1`, `$A$1:$B$2`).
+pub(crate) fn parse_xlsx_range(range: &str) -> Option<Rect> {
+    let range = range.replace('$', "");
+    match range.split_once(':') {
+        Some((start, end)) => Some(Rect::new_span(
+            Pos::try_a1_string(start)?,
+            Pos::try_a1_string(end)?,
+        )),
+        None => Pos::try_a1_string(&range).map(Rect::single_pos),
+    }
+}
+
+/// Parses a space-separated list of xlsx cell ranges (e.g., `A1:B2 D4`).
+pub(crate) fn parse_xlsx_ranges(ranges: &str) -> Vec<Rect> {
+    ranges
+        .split_whitespace()
+        .filter_map(parse_xlsx_range)
+        .collect()
+}
+
+/// Replaces the structured references (e.g., `Table1[Col]`) in an xlsx
+/// formula at `pos` on `sheet_name` with A1 references for the tables where
+/// `replace` returns true. `#This Row` and `#Totals` references are replaced
+/// for all tables since Quadratic's table references don't support them.
+pub(crate) fn replace_structured_references(
+    formula: &str,
+    sheet_name: &str,
+    pos: Pos,
+    tables: &[XlsxTable],
+    replace: impl Fn(&XlsxTable) -> bool,
+) -> String {
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '.' || c == '\\';
+    let chars = formula.chars().collect::<Vec<_>>();
+    let mut replaced = String::with_capacity(formula.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+
+        // strings and quoted sheet names are copied as is
+        if c == '"' || c == '\'' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&next| next == c)
+                .map_or(chars.len(), |offset| i + offset + 2);
+            replaced.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        if !is_name_char(c) {
+            replaced.push(c);
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && is_name_char(chars[i]) {
+            i += 1;
+        }
+        let name = chars[start..i].iter().collect::<String>();
+        let Some(table) = tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(&name))
+        else {
+            replaced.push_str(&name);
+            continue;
+        };
+
+        // a bare table name refers to the table's data; a name followed by
+        // `(` or `!` is a function or sheet name
+        let (specifier, end) = match chars.get(i) {
+            Some('[') => match bracketed(&chars, i) {
+                Some((specifier, end)) => (specifier, end),
+                None => {
+                    replaced.push_str(&name);
+                    continue;
+                }
+            },
+            Some('(' | '!') => {
+                replaced.push_str(&name);
+                continue;
+            }
+            _ => (String::new(), i),
+        };
+        match StructuredReference::parse(&specifier)
+            .and_then(|reference| reference.to_a1(table, sheet_name, pos, replace(table)))
+        {
+            Some(a1) => replaced.push_str(&a1),
+            None => {
+                replaced.push_str(&name);
+                replaced.extend(&chars[i..end]);
+            }
+        }
+        i = end;
+    }
+    replaced
+}
+
+/// Returns the contents of the brackets that open at `chars[open]` and the
+/// index after the closing bracket. `'` escapes the next character.
+fn bracketed(chars: &[char], open: usize) -> Option<(String, usize)> {
+    let mut depth = 0;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i] {
+            '\'' => i += 1,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((chars[open + 1..i].iter().collect(), i + 1));
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The parsed specifier of a structured reference (the part in brackets).
+#[derive(Debug, Default, PartialEq)]
+struct StructuredReference {
+    all: bool,
+    data: bool,
+    headers: bool,
+    totals: bool,
+    this_row: bool,
+
+    /// The first and (optionally) last column of the reference. All columns
+    /// are referenced if empty.
+    columns: Vec<String>,
+}
+
+impl StructuredReference {
+    fn parse(specifier: &str) -> Option<Self> {
+        let mut reference = Self::default();
+        let specifier = specifier.trim();
+        if let Some(column) = specifier.strip_prefix('@') {
+            // `@` is shorthand for `[#This Row],`
+            reference.this_row = true;
+            let column = column.trim();
+            if let Some(column) = column.strip_prefix('[') {
+                reference.add_item(column.strip_suffix(']')?)?;
+            } else if !column.is_empty() {
+                reference.add_item(column)?;
+            }
+        } else if specifier.starts_with('[') {
+            let chars = specifier.chars().collect::<Vec<_>>();
+            let mut i = 0;
+            while i < chars.len() {
+                match chars[i] {
+                    '[' => {
+                        let (item, end) = bracketed(&chars, i)?;
+                        reference.add_item(&item)?;
+                        i = end;
+                    }
+                    ',' | ':' => i += 1,
+                    c if c.is_whitespace() => i += 1,
+                    _ => return None,
+                }
+            }
+        } else if !specifier.is_empty() {
+            reference.add_item(specifier)?;
+        }
+        (reference.columns.len() <= 2).then_some(reference)
+    }
+
+    fn add_item(&mut self, item: &str) -> Option<()> {
+        let item = item.trim();
+        match item.to_ascii_uppercase().as_str() {
+            "#ALL" => self.all = true,
+            "#DATA" => self.data = true,
+            "#HEADERS" => self.headers = true,
+            "#TOTALS" => self.totals = true,
+            "#THIS ROW" => self.this_row = true,
+            _ if item.starts_with('#') => return None,
+            _ => {
+                // remove the escape character
+                let mut column = String::with_capacity(item.len());
+                let mut chars = item.chars();
+                while let Some(c) = chars.next() {
+                    column.push(if c == '\'' { chars.next()? } else { c });
+                }
+                self.columns.push(column);
+            }
+        }
+        Some(())
+    }
+
+    /// Returns the A1 reference, or None if the reference should be kept
+    /// (or can't be resolved).
+    fn to_a1(
+        &self,
+        table: &XlsxTable,
+        sheet_name: &str,
+        pos: Pos,
+        replace_table: bool,
+    ) -> Option<String> {
+        if !replace_table && !self.this_row && !self.totals {
+            return None;
+        }
+
+        let column_x = |name: &String| {
+            table
+                .columns
+                .iter()
+                .position(|column| column.eq_ignore_ascii_case(name))
+                .map(|index| table.rect.min.x + index as i64)
+        };
+        let (min_x, max_x) = match self.columns.as_slice() {
+            [] => (table.rect.min.x, table.rect.max.x),
+            [column] => (column_x(column)?, column_x(column)?),
+            [first, last] => {
+                let (first, last) = (column_x(first)?, column_x(last)?);
+                (first.min(last), first.max(last))
+            }
+            _ => return None,
+        };
+
+        let (min_y, max_y) = if self.this_row {
+            (pos.y, pos.y)
+        } else if self.all {
+            (table.rect.min.y, table.rect.max.y)
+        } else {
+            let mut rows = vec![];
+            if self.headers {
+                table.header_row.then_some(())?;
+                rows.push(table.rect.min.y);
+            }
+            if self.data || !(self.headers || self.totals) {
+                let data_rect = table.data_rect()?;
+                rows.extend([data_rect.min.y, data_rect.max.y]);
+            }
+            if self.totals {
+                table.totals_row.then_some(())?;
+                rows.push(table.rect.max.y);
+            }
+            (*rows.iter().min()?, *rows.iter().max()?)
+        };
+
+        // structured references don't move when copied, except for `#This Row`
+        let row_anchor = if self.this_row { "" } else { "$" };
+        let cell = |x: i64, y: i64| format!("${}{row_anchor}{y}", column_name(x));
+        let mut a1 = cell(min_x, min_y);
+        if (min_x, min_y) != (max_x, max_y) {
+            a1 = format!("{a1}:{}", cell(max_x, max_y));
+        }
+        if table.sheet_name != sheet_name {
+            a1 = format!("{}!{a1}", quote_sheet_name(&table.sheet_name));
+        }
+        Some(a1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_xlsxwriter::{
+        DataValidation, DataValidationErrorStyle, DataValidationRule, Table, TableColumn, Url,
+        Workbook,
+    };
+
+    use super::*;
+    use crate::grid::ConnectionKind;
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(
+            resolve_path("xl", "worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+        assert_eq!(
+            resolve_path("xl", "/xl/worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+        assert_eq!(
+            resolve_path("xl/worksheets", "../tables/table1.xml"),
+            "xl/tables/table1.xml"
+        );
+    }
+
+    #[test]
+    fn test_parse_xlsx_range() {
+        assert_eq!(parse_xlsx_range("A1"), Some(Rect::test_a1("A1")));
+        assert_eq!(parse_xlsx_range("$B$2:$C$4"), Some(Rect::test_a1("B2:C4")));
+        assert_eq!(parse_xlsx_range("not a range"), None);
+        assert_eq!(
+            parse_xlsx_ranges("A1:B2 D4"),
+            vec![Rect::test_a1("A1:B2"), Rect::test_a1("D4")]
+        );
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet().set_name("Data").unwrap();
+        worksheet
+            .write_url(
+                0,
+                0,
+                Url::new("https://quadratichq.com").set_text("Quadratic"),
+            )
+            .unwrap();
+        worksheet
+            .write_url(2, 1, Url::new("internal:Data!C5"))
+            .unwrap();
+        let file = workbook.save_to_buffer().unwrap();
+
+        let mut package = XlsxPackage::new(&file).unwrap();
+        assert_eq!(package.sheet_path("Data"), Some("xl/worksheets/sheet1.xml"));
+
+        let hyperlinks = package.hyperlinks("Data").unwrap();
+        assert_eq!(
+            hyperlinks,
+            vec![
+                (
+                    Rect::test_a1("A1"),
+                    Hyperlink::new_url("https://quadratichq.com", None)
+                ),
+                (
+                    Rect::test_a1("B3"),
+                    Hyperlink::new_internal("Data!C5", None)
+                ),
+            ]
+        );
+        assert!(package.hyperlinks("Missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tables() {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet().set_name("Data").unwrap();
+        let columns = [
+            TableColumn::new().set_header("Name"),
+            TableColumn::new().set_header("Score"),
+        ];
+        let table = Table::new()
+            .set_name("Scores")
+            .set_columns(&columns)
+            .set_banded_rows(false)
+            .set_total_row(true);
+        worksheet.add_table(0, 0, 3, 1, &table).unwrap();
+        let file = workbook.save_to_buffer().unwrap();
+
+        let mut package = XlsxPackage::new(&file).unwrap();
+        let tables = package.tables("Data").unwrap();
+        assert_eq!(
+            tables,
+            vec![XlsxTable {
+                name: "Scores".to_string(),
+                sheet_name: "Data".to_string(),
+                rect: Rect::test_a1("A1:B4"),
+                header_row: true,
+                totals_row: true,
+                columns: vec!["Name".to_string(), "Score".to_string()],
+                banded_rows: false,
+                sort: vec![],
+            }]
+        );
+        assert_eq!(tables[0].data_rect(), Some(Rect::test_a1("A2:B3")));
+        assert!(package.tables("Missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_data_validations() {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet().set_name("Data").unwrap();
+        let list = DataValidation::new()
+            .allow_list_strings(&["Open", "Closed"])
+            .unwrap()
+            .show_dropdown(false)
+            .set_input_title("Status")
+            .unwrap()
+            .set_error_style(DataValidationErrorStyle::Warning);
+        worksheet.add_data_validation(0, 0, 4, 0, &list).unwrap();
+        let number = DataValidation::new()
+            .allow_whole_number(DataValidationRule::Between(1, 10))
+            .ignore_blank(false);
+        worksheet.add_data_validation(0, 1, 0, 1, &number).unwrap();
+        let file = workbook.save_to_buffer().unwrap();
+
+        let mut package = XlsxPackage::new(&file).unwrap();
+        let data_validations = package.data_validations("Data").unwrap();
+        assert_eq!(
+            data_validations,
+            vec![
+                XlsxDataValidation {
+                    rects: vec![Rect::test_a1("A1:A5")],
+                    validation_type: Some("list".to_string()),
+                    formula1: Some("\"Open,Closed\"".to_string()),
+                    allow_blank: true,
+                    show_dropdown: false,
+                    show_input_message: true,
+                    prompt_title: Some("Status".to_string()),
+                    show_error_message: true,
+                    error_style: Some("warning".to_string()),
+                    ..Default::default()
+                },
+                XlsxDataValidation {
+                    rects: vec![Rect::test_a1("B1")],
+                    validation_type: Some("whole".to_string()),
+                    formula1: Some("1".to_string()),
+                    formula2: Some("10".to_string()),
+                    allow_blank: false,
+                    show_dropdown: true,
+                    show_input_message: true,
+                    show_error_message: true,
+                    ..Default::default()
+                },
+            ]
+        );
+        assert!(package.data_validations("Missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replace_structured_references() {
+        let tables = vec![XlsxTable {
+            name: "Scores".to_string(),
+            sheet_name: "Data".to_string(),
+            rect: Rect::test_a1("A1:B4"),
+            header_row: true,
+            totals_row: true,
+            columns: vec!["Name".to_string(), "Score".to_string()],
+            banded_rows: true,
+            sort: vec![],
+        }];
+        let replace = |formula: &str, sheet_name: &str, replace_tables: bool| {
+            replace_structured_references(formula, sheet_name, pos![B3], &tables, |_| {
+                replace_tables
+            })
+        };
+
+        // references Quadratic supports are kept for tables that are imported
+        assert_eq!(
+            replace("SUM(Scores[Score])", "Data", false),
+            "SUM(Scores[Score])"
+        );
+        assert_eq!(
+            replace("SUM(Scores[Score])", "Data", true),
+            "SUM($B$2:$B$3)"
+        );
+        assert_eq!(replace("COUNTA(scores)", "Data", true), "COUNTA($A$2:$B$3)");
+        assert_eq!(
+            replace("Scores[[#Headers],[Name]:[Score]]", "Data", true),
+            "$A$1:$B$1"
+        );
+        assert_eq!(
+            replace("SUM(Scores[Score])", "Summary", true),
+            "SUM(Data!$B$2:$B$3)"
+        );
+
+        // #This Row and #Totals are always replaced
+        assert_eq!(
+            replace("Scores[[#This Row],[Score]]*2", "Data", false),
+            "$B3*2"
+        );
+        assert_eq!(replace("Scores[@Score]*2", "Data", false), "$B3*2");
+        assert_eq!(replace("Scores[#Totals]", "Data", false), "$A$4:$B$4");
+
+        // strings and unknown columns are left as is
+        assert_eq!(
+            replace("\"Scores[Score]\" & Scores[Missing]", "Data", true),
+            "\"Scores[Score]\" & Scores[Missing]"
+        );
+    }
+
+    #[test]
+    fn test_xlsx_code_cell_row() {
+        let code_cell = XlsxCodeCell {
+            sheet_name: "Sheet 1".to_string(),
+            pos: Pos { x: 2, y: 3 },
+            language: CodeCellLanguage::Python,
+            name: "Python1".to_string(),
+            show_name: true,
+            show_columns: false,
+            is_chart: false,
+            output_size: ArraySize::new(2, 4),
+            code: "x".repeat(CODE_CHUNK_LENGTH + 1),
+        };
+        let row = code_cell.to_row();
+        assert_eq!(row.len(), XlsxCodeCell::HEADERS.len() + 1);
+        assert_eq!(
+            row[..8],
+            [
+                "Sheet 1", "B3", "Python", "Python1", "true", "false", "false", "2x4"
+            ]
+        );
+        assert_eq!(XlsxCodeCell::from_row(&row), Some(code_cell));
+
+        let code_cell = XlsxCodeCell {
+            language: CodeCellLanguage::Connection {
+                kind: ConnectionKind::Postgres,
+                id: "connection-id".to_string(),
+            },
+            output_size: None,
+            code: "SELECT 1".to_string(),
+            ..XlsxCodeCell::from_row(&row).unwrap()
+        };
+        let row = code_cell.to_row();
+        assert_eq!(row[7], "");
+        assert_eq!(XlsxCodeCell::from_row(&row), Some(code_cell));
+
+        assert_eq!(XlsxCodeCell::from_row(&row[..4]), None);
+    }
+}
@@ -1,5 +1,7 @@
 use crate::controller::GridController;
 use crate::controller::active_transactions::transaction_name::TransactionName;
+use crate::controller::operations::operation::Operation;
+use crate::grid::series::fill_series::FillSeriesOptions;
 use crate::{Rect, grid::SheetId};
 use anyhow::Result;
 
@@ -23,6 +25,68 @@ impl GridController {
         self.start_user_ai_transaction(ops, cursor, TransactionName::Autocomplete, is_ai);
         Ok(())
     }
+
+    /// Fills each column (or row) of range from its first cell using an
+    /// explicit step, series type, and optional stop value.
+    pub fn fill_series(
+        &mut self,
+        sheet_id: SheetId,
+        range: Rect,
+        options: FillSeriesOptions,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) -> Result<()> {
+        let ops = self.fill_series_operations(sheet_id, range, &options)?;
+        self.start_user_ai_transaction(ops, cursor, TransactionName::Autocomplete, is_ai);
+        Ok(())
+    }
+
+    /// Fills the blank cells of the target_x column in the rows of source by
+    /// inferring how the source columns are transformed into the column's
+    /// existing values. Returns false if nothing was filled because no
+    /// transformation fits the examples or they are ambiguous.
+    pub fn flash_fill(
+        &mut self,
+        sheet_id: SheetId,
+        source: Rect,
+        target_x: i64,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) -> Result<bool> {
+        let ops = self.flash_fill_operations(sheet_id, source, target_x)?;
+        if ops.is_empty() {
+            return Ok(false);
+        }
+        self.start_user_ai_transaction(ops, cursor, TransactionName::Autocomplete, is_ai);
+        Ok(true)
+    }
+
+    /// Returns the file's custom lists used by autocomplete and fill series.
+    pub fn custom_lists(&self) -> &[Vec<String>] {
+        &self.grid.custom_lists
+    }
+
+    /// Replaces the file's custom lists. Entries are trimmed, and blank
+    /// entries and lists with fewer than two entries are dropped.
+    pub fn set_custom_lists(
+        &mut self,
+        custom_lists: Vec<Vec<String>>,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) {
+        let custom_lists = custom_lists
+            .into_iter()
+            .map(|list| {
+                list.into_iter()
+                    .map(|entry| entry.trim().to_string())
+                    .filter(|entry| !entry.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|list| list.len() >= 2)
+            .collect();
+        let ops = vec![Operation::SetCustomLists { custom_lists }];
+        self.start_user_ai_transaction(ops, cursor, TransactionName::SetCustomLists, is_ai);
+    }
 }
 
 #[cfg(test)]
@@ -35,6 +99,7 @@ mod tests {
         controller::user_actions::import::tests::simple_csv,
         grid::{
             CodeCellLanguage, CodeCellValue,
+            series::fill_series::{FillDateUnit, FillDirection, FillSeriesType},
             sheet::borders::{BorderSelection, BorderStyle},
         },
         test_util::{
@@ -879,4 +944,117 @@ mod tests {
             assert_cell_value_row(&grid, sheet_id, 1, 6, y, expected.clone());
         }
     }
+
+    #[test]
+    fn test_autocomplete_custom_list() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_custom_lists(
+            vec![
+                vec![" Low".into(), "Medium".into(), "".into(), "High ".into()],
+                vec!["ignored".into()],
+            ],
+            None,
+            false,
+        );
+        assert_eq!(
+            gc.custom_lists(),
+            &[vec![
+                "Low".to_string(),
+                "Medium".to_string(),
+                "High".to_string()
+            ]]
+        );
+
+        gc.set_cell_value(pos![sheet_id!A1], "Medium".into(), None, false);
+        gc.autocomplete(
+            sheet_id,
+            Rect::test_a1("A1"),
+            Rect::test_a1("A1:A4"),
+            None,
+            false,
+        )
+        .unwrap();
+        assert_cell_value_row(&gc, sheet_id, 1, 1, 2, vec!["High"]);
+        assert_cell_value_row(&gc, sheet_id, 1, 1, 3, vec!["Low"]);
+        assert_cell_value_row(&gc, sheet_id, 1, 1, 4, vec!["Medium"]);
+    }
+
+    #[test]
+    fn test_flash_fill() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_values(
+            pos![sheet_id!A1],
+            vec![
+                vec!["Ada".into(), "Lovelace".into(), "Lovelace, A".into()],
+                vec!["Alan".into(), "Turing".into()],
+                vec!["".into()],
+                vec!["Grace".into(), "Hopper".into()],
+            ],
+            None,
+            false,
+        );
+
+        let filled = gc
+            .flash_fill(sheet_id, Rect::test_a1("A1:B4"), 3, None, false)
+            .unwrap();
+        assert!(filled);
+        assert_display_cell_value(&gc, sheet_id, 3, 1, "Lovelace, A");
+        assert_display_cell_value(&gc, sheet_id, 3, 2, "Turing, A");
+        assert_display_cell_value(&gc, sheet_id, 3, 3, "");
+        assert_display_cell_value(&gc, sheet_id, 3, 4, "Hopper, G");
+
+        gc.undo(1, None, false);
+        assert_display_cell_value(&gc, sheet_id, 3, 2, "");
+        assert_display_cell_value(&gc, sheet_id, 3, 1, "Lovelace, A");
+    }
+
+    #[test]
+    fn test_flash_fill_ambiguous() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_values(
+            pos![sheet_id!A1],
+            vec![vec!["a b c".into(), "b".into()], vec!["d e f g".into()]],
+            None,
+            false,
+        );
+
+        let filled = gc
+            .flash_fill(sheet_id, Rect::test_a1("A1:A2"), 2, None, false)
+            .unwrap();
+        assert!(!filled);
+        assert_display_cell_value(&gc, sheet_id, 2, 2, "");
+
+        assert!(
+            gc.flash_fill(sheet_id, Rect::test_a1("A1:B2"), 2, None, false)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fill_series_undo() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![sheet_id!A1], "100".into(), None, false);
+        gc.set_bold(&A1Selection::test_a1("A1"), Some(true), None, false)
+            .unwrap();
+
+        let options = FillSeriesOptions {
+            direction: FillDirection::Right,
+            series_type: FillSeriesType::Growth,
+            date_unit: FillDateUnit::Day,
+            step: 0.5,
+            stop_value: None,
+        };
+        gc.fill_series(sheet_id, Rect::test_a1("A1:C1"), options, None, false)
+            .unwrap();
+        assert_cell_value_row(&gc, sheet_id, 1, 3, 1, vec!["100", "50", "25"]);
+        assert_cell_format_bold_row(&gc, sheet_id, 1, 3, 1, vec![true, true, true]);
+
+        gc.undo(1, None, false);
+        assert_cell_value_row(&gc, sheet_id, 1, 3, 1, vec!["100", "", ""]);
+        assert_cell_format_bold_row(&gc, sheet_id, 1, 3, 1, vec![true, false, false]);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::controller::GridController;
 use crate::controller::active_transactions::transaction_name::TransactionName;
-use crate::controller::operations::clipboard::{Clipboard, PasteSpecial};
+use crate::controller::operations::clipboard::{Clipboard, PasteSpecial, PasteSpecialOptions};
 use crate::grid::js_types::JsClipboard;
 use crate::grid::{GridBounds, SheetId};
 use crate::{Pos, Rect, SheetPos, SheetRect, a1::A1Selection};
@@ -34,15 +34,46 @@ impl GridController {
         special: PasteSpecial,
         cursor: Option<String>,
         is_ai: bool,
+    ) {
+        self.paste_special_from_clipboard(
+            selection,
+            js_clipboard,
+            special,
+            PasteSpecialOptions::default(),
+            cursor,
+            is_ai,
+        );
+    }
+
+    /// using a selection, paste the contents from the clipboard on the grid
+    /// with paste special options (transpose, skip blanks and arithmetic)
+    pub fn paste_special_from_clipboard(
+        &mut self,
+        selection: &A1Selection,
+        js_clipboard: JsClipboard,
+        special: PasteSpecial,
+        options: PasteSpecialOptions,
+        cursor: Option<String>,
+        is_ai: bool,
     ) {
         let rect = selection.largest_rect_finite(self.a1_context());
         let insert_at = rect.min;
         let end_pos = rect.max;
 
-        // first try html
-        if let Ok(clipboard) = Clipboard::decode(&js_clipboard.html)
-            && let Ok((ops, data_table_ops)) =
-                self.paste_html_operations(insert_at, end_pos, selection, clipboard, special)
+        // first try html, either from quadratic or a table from another
+        // spreadsheet or web page (column widths are only known for quadratic
+        // html)
+        let clipboard = Clipboard::decode(&js_clipboard.html).or_else(|e| {
+            if matches!(special, PasteSpecial::ColumnWidths) {
+                Err(e)
+            } else {
+                Clipboard::decode_html_table(&js_clipboard.html, selection.sheet_id)
+            }
+        });
+        if let Ok(clipboard) = clipboard
+            && let Ok((ops, data_table_ops)) = self.paste_special_html_operations(
+                insert_at, end_pos, selection, clipboard, special, options,
+            )
         {
             self.start_user_ai_transaction(
                 ops,
@@ -63,13 +94,14 @@ impl GridController {
             return;
         }
 
-        // if not quadratic html, then use the plain text
+        // if there is no html table, then use the plain text
         if let Ok(ops) = self.paste_plain_text_operations(
             insert_at.to_sheet_pos(selection.sheet_id),
             end_pos,
             selection,
             js_clipboard.plain_text,
             special,
+            options,
         ) {
             self.start_user_ai_transaction(ops, cursor, TransactionName::PasteClipboard, is_ai);
         }
@@ -685,6 +717,41 @@ mod test {
         assert_eq!(cell21.unwrap(), CellValue::Number(12.into()));
     }
 
+    #[test]
+    fn test_paste_from_html_table() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let js_clipboard = JsClipboard {
+            plain_text: "Name\tAmount\nApples\t1,234.50".to_string(),
+            html: r#"<table><tr><td style="font-weight:bold">Name</td><td>Amount</td></tr><tr><td>Apples</td><td x:num="1234.5">1,234.50</td></tr></table>"#.to_string(),
+        };
+        gc.paste_from_clipboard(
+            &A1Selection::test_a1("B2"),
+            js_clipboard,
+            PasteSpecial::None,
+            None,
+            false,
+        );
+
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(pos![B2]),
+            Some(CellValue::Text("Name".into()))
+        );
+        assert_eq!(
+            sheet.display_value(pos![C3]),
+            Some(CellValue::Number(
+                crate::number::decimal_from_str("1234.5").unwrap()
+            ))
+        );
+        assert_eq!(sheet.cell_format_summary(pos![B2]).bold, Some(true));
+        assert_eq!(sheet.cell_format_summary(pos![C3]).commas, Some(true));
+
+        gc.undo(1, None, false);
+        assert_eq!(gc.sheet(sheet_id).display_value(pos![B2]), None);
+    }
+
     // | 1 | A1           |
     // | 2 | [paste here] |
     //
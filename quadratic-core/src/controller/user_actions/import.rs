@@ -3,6 +3,7 @@ use anyhow::Result;
 use crate::Pos;
 use crate::controller::GridController;
 use crate::controller::active_transactions::transaction_name::TransactionName;
+use crate::controller::operations::csv::CsvImportOptions;
 use crate::grid::SheetId;
 
 impl GridController {
@@ -22,14 +23,32 @@ impl GridController {
         header_is_first_row: Option<bool>,
         is_ai: bool,
     ) -> Result<()> {
-        let ops = self.import_csv_operations(
-            sheet_id,
-            file,
-            file_name,
-            insert_at,
+        let options = CsvImportOptions {
             delimiter,
             header_is_first_row,
-        )?;
+            ..Default::default()
+        };
+        self.import_csv_with_options(
+            sheet_id, file, file_name, insert_at, cursor, &options, is_ai,
+        )
+    }
+
+    /// Imports a CSV file into the grid using the import options.
+    ///
+    /// Using `cursor` here also as a flag to denote import into new / existing file.
+    #[allow(clippy::too_many_arguments)]
+    #[function_timer::function_timer]
+    pub fn import_csv_with_options(
+        &mut self,
+        sheet_id: SheetId,
+        file: &[u8],
+        file_name: &str,
+        insert_at: Pos,
+        cursor: Option<String>,
+        options: &CsvImportOptions,
+        is_ai: bool,
+    ) -> Result<()> {
+        let ops = self.import_csv_operations(sheet_id, file, file_name, insert_at, options)?;
         if cursor.is_some() {
             self.start_user_ai_transaction(ops, cursor, TransactionName::Import, is_ai);
         } else {
@@ -83,6 +102,31 @@ impl GridController {
 
         Ok(())
     }
+
+    /// Imports a JSON or NDJSON file into the grid.
+    ///
+    /// Using `cursor` here also as a flag to denote import into new / existing file.
+    #[allow(clippy::too_many_arguments)]
+    pub fn import_json(
+        &mut self,
+        sheet_id: SheetId,
+        file: &[u8],
+        file_name: &str,
+        insert_at: Pos,
+        cursor: Option<String>,
+        explode_arrays: bool,
+        is_ai: bool,
+    ) -> Result<()> {
+        let ops =
+            self.import_json_operations(sheet_id, file, file_name, insert_at, explode_arrays)?;
+        if cursor.is_some() {
+            self.start_user_ai_transaction(ops, cursor, TransactionName::Import, is_ai);
+        } else {
+            self.server_apply_transaction(ops, Some(TransactionName::Import));
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -301,8 +345,11 @@ pub(crate) mod tests {
                 csv.as_bytes(),
                 "bad line",
                 Pos { x: 0, y: 0 },
-                Some(b','),
-                Some(false),
+                &CsvImportOptions {
+                    delimiter: Some(b','),
+                    header_is_first_row: Some(false),
+                    ..Default::default()
+                },
             )
             .unwrap();
         let op = &ops[0];
@@ -764,4 +811,18 @@ pub(crate) mod tests {
         .unwrap();
         assert_table_count(&gc, sheet_id, 1);
     }
+
+    #[test]
+    fn imports_a_json_file() {
+        let mut gc = test_create_gc();
+        let sheet_id = first_sheet_id(&gc);
+        let file =
+            br#"[{"name": "a", "stats": {"count": 1}}, {"name": "b", "stats": {"count": 2.5}}]"#;
+        gc.import_json(sheet_id, file, "stats.json", pos![A1], None, false, false)
+            .unwrap();
+
+        assert_table_count(&gc, sheet_id, 1);
+        assert_cell_value_row(&gc, sheet_id, 1, 2, 2, vec!["name", "stats.count"]);
+        assert_cell_value_row(&gc, sheet_id, 1, 2, 4, vec!["b", "2.5"]);
+    }
 }
@@ -112,6 +112,10 @@ impl Criterion {
             CellValue::Code(_) => false,
             CellValue::Image(_) => false,
             CellValue::Import(_) => false,
+            CellValue::Hyperlink(rhs) => compare_fn.compare(
+                &lhs.to_string().to_ascii_lowercase(),
+                &rhs.display_text().to_ascii_lowercase(),
+            ),
         }
     }
 
@@ -257,6 +261,7 @@ impl CompareFn {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Hyperlink;
 
     fn make_criterion(v: impl Into<CellValue>) -> Criterion {
         Criterion::try_from(Spanned::new(0, 0, &v.into())).unwrap()
@@ -292,6 +297,13 @@ mod tests {
         // assert!(!matches(&c, "true"));
         assert!(!matches(&c, "false"));
 
+        // Test hyperlink (compares against the display text, like a string)
+        let link = Hyperlink::new_url("https://quadratichq.com", Some("Quadratic".to_string()));
+        let c = make_criterion(CellValue::Hyperlink(link.clone()));
+        assert!(matches(&c, "quadratic"));
+        assert!(matches(&c, CellValue::Hyperlink(link)));
+        assert!(!matches(&c, "https://quadratichq.com"));
+
         // Test string equality
         for prefix in ["", "=", "=="] {
             let c = make_criterion(format!("{prefix}Blue"));
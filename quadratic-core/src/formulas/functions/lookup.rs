@@ -2,7 +2,7 @@ use itertools::Itertools;
 use regex::Regex;
 use smallvec::smallvec;
 
-use crate::{ArraySize, CodeResultExt, a1::SheetCellRefRange};
+use crate::{ArraySize, CodeResultExt, Hyperlink, a1::SheetCellRefRange};
 
 use super::*;
 
@@ -31,6 +31,26 @@ fn get_functions() -> Vec<FormulaFunction> {
                 ctx.get_cell_array(sheet_rect, span)?.inner
             }
         ),
+        formula_fn!(
+            /// Creates a link to a web page or to a location in the file.
+            ///
+            /// `link_location` may be a URL or, for a link within the file, a
+            /// `#` followed by a sheet, range, or table reference (such as
+            /// `#Sheet2!A1` or `#Table1`). The cell displays `friendly_name`
+            /// if it is provided; otherwise it displays `link_location`.
+            #[examples(
+                "HYPERLINK(\"https://www.quadratichq.com\")",
+                "HYPERLINK(\"https://www.quadratichq.com\", \"Quadratic\")",
+                "HYPERLINK(\"#Sheet2!A1\", \"Go to Sheet2\")"
+            )]
+            #[zip_map]
+            fn HYPERLINK([link_location]: (Spanned<String>), [friendly_name]: (Option<String>)) {
+                if link_location.inner.trim().is_empty() {
+                    return Err(RunErrorMsg::InvalidArgument.with_span(link_location.span));
+                }
+                CellValue::Hyperlink(Hyperlink::parse(&link_location.inner, friendly_name))
+            }
+        ),
         formula_fn!(
             /// Searches for a value in the first vertical column of a range and
             /// return the corresponding cell in another vertical column, or an
@@ -641,7 +661,7 @@ mod tests {
     use lazy_static::lazy_static;
     use smallvec::smallvec;
 
-    use crate::{Pos, controller::GridController, formulas::tests::*};
+    use crate::{Hyperlink, Pos, controller::GridController, formulas::tests::*};
 
     lazy_static! {
         static ref NUMBERS_LOOKUP_ARRAY: Array = array![
@@ -671,6 +691,44 @@ mod tests {
             vec![(-987.0).into(), "this shouldn't match anything".into()];
     }
 
+    #[test]
+    fn test_formula_hyperlink() {
+        let g = GridController::new();
+
+        assert_eq!(
+            eval(&g, "HYPERLINK(\"https://quadratichq.com\", \"Quadratic\")"),
+            Value::Single(CellValue::Hyperlink(Hyperlink::new_url(
+                "https://quadratichq.com",
+                Some("Quadratic".to_string()),
+            ))),
+        );
+        assert_eq!(
+            eval(&g, "HYPERLINK(\"#Sheet1!A1\")"),
+            Value::Single(CellValue::Hyperlink(Hyperlink::new_internal(
+                "Sheet1!A1",
+                None
+            ))),
+        );
+        assert_eq!(
+            "Quadratic",
+            eval_to_string(&g, "HYPERLINK(\"https://quadratichq.com\", \"Quadratic\")")
+        );
+        assert_eq!(
+            RunErrorMsg::InvalidArgument,
+            eval_to_err(&g, "HYPERLINK(\"\")").msg,
+        );
+
+        // the formula representation of a hyperlink evaluates to the same link
+        let link = Hyperlink::new_url(
+            "https://quadratichq.com/?q=\"a\\b\"",
+            Some("Say \"hi\"".to_string()),
+        );
+        assert_eq!(
+            eval(&g, &link.repr()),
+            Value::Single(CellValue::Hyperlink(link)),
+        );
+    }
+
     #[test]
     fn test_formula_indirect() {
         let mut g = GridController::new();
@@ -131,11 +131,11 @@ lazy_static! {
 const NUMERIC_LITERAL_PATTERN: &str = r"(\d+(\.\d*)?|\.\d+)([eE][+-]?\d+)?";
 
 /// Single-quoted string. Note that like Rust strings, this can span multiple
-/// lines.
-const SINGLE_QUOTE_STRING_LITERAL_PATTERN: &str = r"'([^'\\]|\\[\s\S])*'";
+/// lines. A doubled quote is an escaped quote, like in Excel.
+const SINGLE_QUOTE_STRING_LITERAL_PATTERN: &str = r"'([^'\\]|\\[\s\S]|'')*'";
 /// Double-quoted string. Note that like Rust strings, this can span multiple
-/// lines.
-const DOUBLE_QUOTE_STRING_LITERAL_PATTERN: &str = r#""([^"\\]|\\[\s\S])*""#;
+/// lines. A doubled quote is an escaped quote, like in Excel.
+const DOUBLE_QUOTE_STRING_LITERAL_PATTERN: &str = r#""([^"\\]|\\[\s\S]|"")*""#;
 /// Unquoted sheet reference, such as `Sheet1!`. A quoted sheet reference such
 /// as `'Sheet1'!` is parsed as a string followed by a sheet reference operator
 /// `!`.
@@ -460,6 +460,7 @@ mod tests {
     use itertools::Itertools;
 
     use super::*;
+    use crate::formulas::parse_string_literal;
 
     #[test]
     fn test_lex_block_comment() {
@@ -475,6 +476,36 @@ mod tests {
         test_block_comment(false, "/* /*");
         test_block_comment(false, "/*/");
     }
+
+    #[test]
+    fn test_lex_string_literals() {
+        // backslash escapes
+        test_string_literal(r#""say \"hi\"""#, r#"say "hi""#);
+        test_string_literal(r"'it\'s'", "it's");
+        test_string_literal(r#""back\\slash""#, r"back\slash");
+        test_string_literal(r#""\"""#, "\"");
+        test_string_literal(r#""\\""#, "\\");
+
+        // doubled quotes, as in formulas from Excel
+        test_string_literal(r#""say ""hi""""#, r#"say "hi""#);
+        test_string_literal("'it''s'", "it's");
+        test_string_literal(r#""""""#, "\"");
+        test_string_literal(r#""it's""#, "it's");
+        test_string_literal(r#"'say "hi"'"#, r#"say "hi""#);
+
+        // separate strings are still separate tokens
+        let s = r#""a"&"b""#;
+        let tokens = tokenize(s).collect_vec();
+        assert_eq!(3, tokens.len(), "Wrong number of tokens: {tokens:?}");
+        assert_eq!("\"a\"", tokens[0].span.of_str(s));
+    }
+    fn test_string_literal(s: &str, expected: &str) {
+        let tokens = tokenize(s).collect_vec();
+        assert_eq!(1, tokens.len(), "Too many tokens: {tokens:?}");
+        assert_eq!(Token::StringLiteral, tokens[0].inner, "Token is: {s:?}");
+        assert_eq!(Some(expected.to_string()), parse_string_literal(s));
+    }
+
     fn test_block_comment(expected_to_end: bool, s: &str) {
         let tokens = tokenize(s).collect_vec();
         if expected_to_end {
@@ -45,6 +45,8 @@ pub fn parse_string_literal(s: &str) -> Option<String> {
     loop {
         match chars.next()? {
             '\\' => string_contents.push(chars.next()?),
+            // a doubled quote is an escaped quote
+            c if c == quote && chars.next_if_eq(&quote).is_some() => string_contents.push(quote),
             c if c == quote => break,
             c => string_contents.push(c),
         }
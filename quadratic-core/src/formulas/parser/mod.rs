@@ -13,7 +13,8 @@ use rules::SyntaxRule;
 
 use super::*;
 use crate::{
-    CodeResult, CoerceInto, RefError, RunError, RunErrorMsg, SheetPos, Span, Spanned, TableRef,
+    CodeResult, CoerceInto, Pos, RefError, RunError, RunErrorMsg, SheetPos, Span, Spanned,
+    TableRef,
     a1::{A1Context, CellRefRange, RefRangeBounds, SheetCellRefRange},
     controller::GridController,
     grid::{RefAdjust, SheetId},
@@ -157,6 +158,19 @@ pub fn adjust_references(
     })
 }
 
+/// Transposes all cell references in a formula at `pos` that is pasted
+/// transposed to `target`. If a reference is out of bounds after the
+/// transpose, it is replaced with an error.
+#[must_use = "this method returns a new value instead of modifying its input"]
+pub fn transpose_references(source: &str, ctx: &A1Context, pos: SheetPos, target: Pos) -> String {
+    let source = convert_rc_to_a1(source, ctx, pos); // remove this if we ever remove RC support completely
+    replace_cell_range_references(&source, ctx, pos, |range_ref| {
+        Ok(range_ref
+            .transpose(pos.into(), target)?
+            .to_a1_string(Some(pos.sheet_id), ctx))
+    })
+}
+
 #[must_use = "this method returns a new value instead of modifying its input"]
 pub fn replace_table_name(
     source: &str,
@@ -190,6 +204,88 @@ pub fn replace_column_name(
     })
 }
 
+/// Replaces table references in a formula with A1 references, except for
+/// those where `keep` returns true. References to tables that no longer exist
+/// are replaced with an error.
+#[must_use = "this method returns a new value instead of modifying its input"]
+pub fn convert_table_refs_to_a1(
+    source: &str,
+    ctx: &A1Context,
+    pos: SheetPos,
+    keep: impl Fn(&TableRef) -> bool,
+) -> String {
+    replace_cell_range_references(source, ctx, pos, |range_ref| match &range_ref.cells {
+        CellRefRange::Table { range } if !keep(range) => {
+            let table = ctx.try_table(&range.table_name).ok_or(RefError)?;
+            let bounds = range
+                .convert_to_ref_range_bounds(false, ctx, false, false)
+                .ok_or(RefError)?;
+            let sheet_range_ref = SheetCellRefRange {
+                sheet_id: table.sheet_id,
+                cells: CellRefRange::Sheet { range: bounds },
+                explicit_sheet_name: false,
+            };
+            Ok(sheet_range_ref.to_a1_string(Some(pos.sheet_id), ctx))
+        }
+        _ => Ok(range_ref.to_a1_string(Some(pos.sheet_id), ctx)),
+    })
+}
+
+/// Converts a formula to OpenFormula syntax for OpenDocument spreadsheets.
+/// Cell references are written as `[.A1:.B2]`, table references are replaced
+/// with their cell range, and arguments are separated by `;` (array rows by
+/// `|`).
+#[must_use = "this method returns a new value instead of modifying its input"]
+pub fn convert_to_open_formula(source: &str, ctx: &A1Context, pos: SheetPos) -> String {
+    let source = convert_table_refs_to_a1(source, ctx, pos, |_| false);
+
+    let mut replacements = find_cell_references(&source, ctx, pos)
+        .into_iter()
+        .map(|Spanned { span, inner }| {
+            let new_str = match inner {
+                Ok(range_ref) => {
+                    open_formula_reference(&range_ref.to_a1_string(Some(pos.sheet_id), ctx))
+                }
+                Err(RefError) => RefError.to_string(),
+            };
+            (span, new_str)
+        })
+        .collect_vec();
+
+    let mut array_depth = 0_usize;
+    for token in lexer::tokenize(&source) {
+        match token.inner {
+            Token::LBrace => array_depth += 1,
+            Token::RBrace => array_depth = array_depth.saturating_sub(1),
+            Token::ArgSep => replacements.push((token.span, ";".to_string())),
+            Token::RowSep if array_depth > 0 => replacements.push((token.span, "|".to_string())),
+            _ => (),
+        }
+    }
+
+    // replace in reverse order to preserve previous span indexes into string
+    let mut replaced = source.clone();
+    for (span, new_str) in replacements
+        .into_iter()
+        .sorted_by_key(|(span, _)| std::cmp::Reverse(span.start))
+    {
+        replaced.replace_range::<Range<usize>>(span.into(), &new_str);
+    }
+
+    replaced
+}
+
+/// Converts an A1 reference (e.g., `'Sheet 2'!A1:B2`) to an OpenFormula
+/// reference (e.g., `[$'Sheet 2'.A1:.B2]`).
+fn open_formula_reference(a1: &str) -> String {
+    let (sheet, range) = match a1.rsplit_once('!') {
+        Some((sheet, range)) => (format!("${sheet}"), range),
+        None => (String::new(), a1),
+    };
+    let range = range.split(':').map(|part| format!(".{part}")).join(":");
+    format!("[{sheet}{range}]")
+}
+
 #[must_use = "this method returns a new value instead of modifying its input"]
 fn replace_table_references(
     source: &str,
@@ -527,6 +623,55 @@ mod tests {
         assert_eq!(replaced, expected);
     }
 
+    #[test]
+    fn test_transpose_references() {
+        let ctx = A1Context::test(&[], &[]);
+        let pos = pos![B2].to_sheet_pos(SheetId::new());
+
+        // B2 is pasted transposed to B3
+        let src = "SUM(A2:B2) + B1 + $A$1 + $A2";
+        let replaced = transpose_references(src, &ctx, pos, pos![B3]);
+        let expected = "SUM(B2:B3) + A3 + $A$1 + $A3";
+        assert_eq!(replaced, expected);
+
+        let replaced = transpose_references("A1", &ctx, pos, pos![A1]);
+        assert_eq!(replaced, "#REF!");
+    }
+
+    #[test]
+    fn test_convert_table_refs_to_a1() {
+        let ctx = A1Context::test(
+            &[("Sheet1", SheetId::TEST)],
+            &[("Table1", &["a", "b"], crate::Rect::test_a1("A1:B4"))],
+        );
+        let pos = SheetPos::test();
+
+        // the table's name and column headers are on rows 1 and 2
+        let keep =
+            |table_ref: &TableRef| table_ref.col_range == crate::a1::ColRange::Col("a".to_string());
+        let replaced = convert_table_refs_to_a1("SUM(Table1[a], Table1[b])", &ctx, pos, keep);
+        assert_eq!(replaced, "SUM(Table1[a], B3:B4)");
+    }
+
+    #[test]
+    fn test_convert_to_open_formula() {
+        let sheet_2 = SheetId::new();
+        let ctx = A1Context::test(
+            &[("Sheet1", SheetId::TEST), ("Sheet 2", sheet_2)],
+            &[("Table1", &["a", "b"], crate::Rect::test_a1("A1:B4"))],
+        );
+        let pos = SheetPos::test();
+
+        assert_eq!(
+            convert_to_open_formula("SUM(A1:B2, 'Sheet 2'!C3, Table1[b])", &ctx, pos),
+            "SUM([.A1:.B2]; [$'Sheet 2'.C3]; [.B3:.B4])"
+        );
+        assert_eq!(
+            convert_to_open_formula("IF($A$1 > 0, \"a,b\", {1, 2; 3, 4})", &ctx, pos),
+            "IF([.$A$1] > 0; \"a,b\"; {1; 2| 3; 4})"
+        );
+    }
+
     #[test]
     fn check_formula() {
         assert!(simple_parse_and_check_formula("SUM(10)"));
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::a1::{A1Context, SheetCellRefRange};
 use crate::grid::CodeCellLanguage;
-use crate::{RefError, SheetPos};
+use crate::{Pos, RefError, SheetPos};
 
 use super::SheetId;
 
@@ -147,6 +147,26 @@ impl CodeCellValue {
         }
     }
 
+    /// Transposes references in a code cell at `pos` that is pasted
+    /// transposed to `target` (within the same sheet).
+    pub fn transpose_references(&mut self, a1_context: &A1Context, pos: SheetPos, target: Pos) {
+        if self.language == CodeCellLanguage::Formula {
+            self.code = crate::formulas::transpose_references(&self.code, a1_context, pos, target);
+        } else if self.language.has_q_cells() {
+            self.replace_q_cells_a1_selection(pos, a1_context, |cell_ref| {
+                Ok(cell_ref
+                    .transpose(pos.into(), target)?
+                    .to_a1_string(Some(pos.sheet_id), a1_context))
+            });
+        } else if self.language.has_handle_bars() {
+            self.replace_handle_bars_a1_selection(pos, a1_context, |cell_ref| {
+                Ok(cell_ref
+                    .transpose(pos.into(), target)?
+                    .to_a1_string(Some(pos.sheet_id), a1_context))
+            });
+        }
+    }
+
     /// Replaces the sheet name in the code cell references.
     pub fn replace_sheet_name_in_cell_references(
         &mut self,
@@ -700,6 +720,28 @@ mod tests {
         assert_eq!(code.code, r#"SELECT * FROM {{ 'Sheet1_new'!A1:B2 }}"#);
     }
 
+    #[test]
+    fn test_transpose_references() {
+        let sheet_id = SheetId::new();
+        let mut a1_context = A1Context::default();
+        a1_context.sheet_map.insert_parts("This", sheet_id);
+        let pos = SheetPos {
+            x: 2,
+            y: 2,
+            sheet_id,
+        };
+        let target = Pos { x: 2, y: 3 };
+
+        let mut code = CodeCellValue::new_python("q.cells('C2:D2')".to_string());
+        code.transpose_references(&a1_context, pos, target);
+        assert_eq!(code.code, r#"q.cells("B4:B5")"#);
+
+        let mut code =
+            CodeCellValue::new_connection("SELECT * FROM {{ B1 }} WHERE {{ $A$1 }}".to_string());
+        code.transpose_references(&a1_context, pos, target);
+        assert_eq!(code.code, "SELECT * FROM {{ A3 }} WHERE {{ $A$1 }}");
+    }
+
     #[test]
     fn test_replace_table_name_in_cell_references() {
         let sheet_id = SheetId::TEST;
@@ -329,6 +329,23 @@ impl<T: Default + Clone + PartialEq + fmt::Debug> Contiguous2D<T> {
         }
     }
 
+    /// Returns the values within `rect` transposed around the top-left corner
+    /// of `rect` (i.e., rows become columns). Values outside `rect` are
+    /// dropped.
+    pub fn transpose_rect(&self, rect: Rect) -> Self {
+        let mut transposed = Self::default();
+        for (r, value) in self.nondefault_rects_in_rect(rect) {
+            transposed.set_rect(
+                rect.min.x + r.min.y - rect.min.y,
+                rect.min.y + r.min.x - rect.min.x,
+                Some(rect.min.x + r.max.y - rect.min.y),
+                Some(rect.min.y + r.max.x - rect.min.x),
+                value,
+            );
+        }
+        transposed
+    }
+
     /// Sets a rectangle to the same value and returns the blocks to set undo
     /// it.
     ///
@@ -941,6 +958,21 @@ mod tests {
         assert_eq!(c.get(pos![Z1000]), Some(true));
     }
 
+    #[test]
+    fn test_transpose_rect() {
+        let mut c = Contiguous2D::<Option<bool>>::new();
+        c.set_rect(3, 2, Some(5), Some(2), Some(true));
+        c.set(pos![B3], Some(false));
+        c.set(pos![Z26], Some(true));
+
+        let t = c.transpose_rect(Rect::test_a1("B2:E4"));
+        assert_eq!(t.get(pos![B2]), None);
+        assert_eq!(t.get(pos![B3]), Some(true));
+        assert_eq!(t.get(pos![B5]), Some(true));
+        assert_eq!(t.get(pos![C2]), Some(false));
+        assert_eq!(t.get(pos![Z26]), None);
+    }
+
     #[test]
     fn test_set_column() {
         let mut c = Contiguous2D::<Option<bool>>::new();
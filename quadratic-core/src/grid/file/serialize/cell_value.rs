@@ -1,6 +1,6 @@
 use super::current;
 use crate::{
-    CellValue, Duration,
+    CellValue, Duration, Hyperlink, HyperlinkTarget,
     cellvalue::Import,
     grid::{CodeCellLanguage, CodeCellValue, ConnectionKind},
     number::decimal_from_str,
@@ -54,6 +54,33 @@ pub fn export_cell_value(cell_value: CellValue) -> current::CellValueSchema {
         CellValue::Import(import) => current::CellValueSchema::Import(current::ImportSchema {
             file_name: import.file_name,
         }),
+        CellValue::Hyperlink(hyperlink) => {
+            current::CellValueSchema::Hyperlink(export_hyperlink(hyperlink))
+        }
+    }
+}
+
+pub fn export_hyperlink(hyperlink: Hyperlink) -> current::HyperlinkSchema {
+    current::HyperlinkSchema {
+        target: match hyperlink.target {
+            HyperlinkTarget::Url(url) => current::HyperlinkTargetSchema::Url(url),
+            HyperlinkTarget::Internal(reference) => {
+                current::HyperlinkTargetSchema::Internal(reference)
+            }
+        },
+        text: hyperlink.text,
+    }
+}
+
+pub fn import_hyperlink(hyperlink: current::HyperlinkSchema) -> Hyperlink {
+    Hyperlink {
+        target: match hyperlink.target {
+            current::HyperlinkTargetSchema::Url(url) => HyperlinkTarget::Url(url),
+            current::HyperlinkTargetSchema::Internal(reference) => {
+                HyperlinkTarget::Internal(reference)
+            }
+        },
+        text: hyperlink.text,
     }
 }
 
@@ -117,11 +144,15 @@ pub fn import_cell_value(value: current::CellValueSchema) -> CellValue {
         current::CellValueSchema::Import(current::ImportSchema { file_name }) => {
             CellValue::Import(Import::new(file_name))
         }
+        current::CellValueSchema::Hyperlink(hyperlink) => {
+            CellValue::Hyperlink(import_hyperlink(hyperlink))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::{a1::A1Selection, controller::GridController, grid::file};
 
     #[test]
@@ -140,4 +171,19 @@ mod tests {
         let imported = file::import(exported).unwrap();
         assert_eq!(imported, *gc.grid());
     }
+
+    #[test]
+    fn test_import_and_export_hyperlink() {
+        let values = vec![
+            CellValue::Hyperlink(Hyperlink::new_url(
+                "https://quadratichq.com",
+                Some("Quadratic".to_string()),
+            )),
+            CellValue::Hyperlink(Hyperlink::new_internal("Sheet1!A1:B2", None)),
+        ];
+        for value in values {
+            let exported = export_cell_value(value.clone());
+            assert_eq!(import_cell_value(exported), value);
+        }
+    }
 }
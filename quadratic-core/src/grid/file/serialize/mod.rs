@@ -28,6 +28,7 @@ pub fn import(file: current::GridSchema) -> Result<Grid> {
             .map(import_sheet)
             .map_ok(|sheet| (sheet.id, sheet))
             .collect::<Result<_>>()?,
+        custom_lists: file.custom_lists,
     };
     let a1_context = grid.expensive_make_a1_context();
     for sheet in grid.sheets.values_mut() {
@@ -40,5 +41,6 @@ pub fn export(grid: Grid) -> Result<current::GridSchema> {
     Ok(current::GridSchema {
         version: Some(CURRENT_VERSION.into()),
         sheets: grid.sheets.into_values().map(export_sheet).collect(),
+        custom_lists: grid.custom_lists,
     })
 }
@@ -1,3 +1,5 @@
 mod schema;
+mod upgrade;
 
 pub use schema::*;
+pub use upgrade::*;
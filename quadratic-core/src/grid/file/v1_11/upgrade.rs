@@ -0,0 +1,118 @@
+use anyhow::Result;
+
+use crate::grid::file::v1_11 as current;
+use crate::grid::file::v1_12;
+
+fn upgrade_column(column: current::ColumnSchema) -> v1_12::ColumnSchema {
+    column
+        .into_iter()
+        .map(|(y, value)| (y, value.into()))
+        .collect()
+}
+
+fn upgrade_output_value(value: current::OutputValueSchema) -> v1_12::OutputValueSchema {
+    match value {
+        current::OutputValueSchema::Single(value) => v1_12::OutputValueSchema::Single(value.into()),
+        current::OutputValueSchema::Array(array) => {
+            v1_12::OutputValueSchema::Array(v1_12::OutputArraySchema {
+                size: array.size,
+                values: array.values.into_iter().map(Into::into).collect(),
+            })
+        }
+    }
+}
+
+fn upgrade_data_table(data_table: current::DataTableSchema) -> v1_12::DataTableSchema {
+    v1_12::DataTableSchema {
+        kind: data_table.kind,
+        name: data_table.name,
+        value: upgrade_output_value(data_table.value),
+        last_modified: data_table.last_modified,
+        header_is_first_row: data_table.header_is_first_row,
+        show_name: data_table.show_name,
+        show_columns: data_table.show_columns,
+        columns: data_table.columns.map(|columns| {
+            columns
+                .into_iter()
+                .map(|column| v1_12::DataTableColumnSchema {
+                    name: column.name.into(),
+                    display: column.display,
+                    value_index: column.value_index,
+                })
+                .collect()
+        }),
+        sort: data_table.sort,
+        sort_dirty: data_table.sort_dirty,
+        display_buffer: data_table.display_buffer,
+        spill_value: data_table.spill_value,
+        spill_data_table: data_table.spill_data_table,
+        alternating_colors: data_table.alternating_colors,
+        formats: data_table.formats,
+        borders: data_table.borders,
+        chart_pixel_output: data_table.chart_pixel_output,
+        chart_output: data_table.chart_output,
+    }
+}
+
+pub fn upgrade_sheet(sheet: current::SheetSchema) -> v1_12::SheetSchema {
+    v1_12::SheetSchema {
+        id: sheet.id,
+        name: sheet.name,
+        color: sheet.color,
+        order: sheet.order,
+        offsets: sheet.offsets,
+        validations: sheet.validations,
+        columns: sheet
+            .columns
+            .into_iter()
+            .map(|(x, column)| (x, upgrade_column(column)))
+            .collect(),
+        data_tables: sheet
+            .data_tables
+            .into_iter()
+            .map(|(pos, data_table)| (pos, upgrade_data_table(data_table)))
+            .collect(),
+        rows_resize: sheet.rows_resize,
+        borders: sheet.borders,
+        formats: sheet.formats,
+    }
+}
+
+/// Adds the hyperlink cell value.
+pub fn upgrade(grid: current::GridSchema) -> Result<v1_12::GridSchema> {
+    let new_grid = v1_12::GridSchema {
+        version: Some("1.12".to_string()),
+        sheets: grid.sheets.into_iter().map(upgrade_sheet).collect(),
+        custom_lists: vec![],
+    };
+    Ok(new_grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_cell_values() {
+        let sheet = current::SheetSchema {
+            columns: vec![(
+                1,
+                vec![(1, current::CellValueSchema::Text("hello".to_string()))],
+            )],
+            ..Default::default()
+        };
+        let grid = current::GridSchema {
+            sheets: vec![sheet],
+            version: Some("1.11".to_string()),
+        };
+        let upgraded = upgrade(grid).unwrap();
+        assert_eq!(upgraded.version, Some("1.12".to_string()));
+        assert_eq!(
+            upgraded.sheets[0].columns,
+            vec![(
+                1,
+                vec![(1, v1_12::CellValueSchema::Text("hello".to_string()))]
+            )]
+        );
+    }
+}
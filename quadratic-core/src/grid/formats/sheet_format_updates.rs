@@ -418,6 +418,33 @@ impl SheetFormatUpdates {
         Self::translate_rect_item(&mut self.strike_through, x, y);
     }
 
+    fn transpose_rect_item<T>(item: &mut SheetFormatUpdatesType<T>, rect: Rect)
+    where
+        T: Clone + Debug + PartialEq,
+    {
+        if let Some(item) = item.as_mut() {
+            *item = item.transpose_rect(rect);
+        }
+    }
+
+    /// Transposes the updates within `rect` around its top-left corner.
+    /// Updates outside `rect` are dropped.
+    pub fn transpose_in_place(&mut self, rect: Rect) {
+        Self::transpose_rect_item(&mut self.align, rect);
+        Self::transpose_rect_item(&mut self.vertical_align, rect);
+        Self::transpose_rect_item(&mut self.wrap, rect);
+        Self::transpose_rect_item(&mut self.numeric_format, rect);
+        Self::transpose_rect_item(&mut self.numeric_decimals, rect);
+        Self::transpose_rect_item(&mut self.numeric_commas, rect);
+        Self::transpose_rect_item(&mut self.bold, rect);
+        Self::transpose_rect_item(&mut self.italic, rect);
+        Self::transpose_rect_item(&mut self.text_color, rect);
+        Self::transpose_rect_item(&mut self.fill_color, rect);
+        Self::transpose_rect_item(&mut self.date_time, rect);
+        Self::transpose_rect_item(&mut self.underline, rect);
+        Self::transpose_rect_item(&mut self.strike_through, rect);
+    }
+
     /// Merges another SheetFormatUpdates into this one.
     fn merge_item<T>(item: &mut SheetFormatUpdatesType<T>, other: &SheetFormatUpdatesType<T>)
     where
@@ -658,4 +685,21 @@ mod tests {
         );
         assert!(updates.has_fills());
     }
+
+    #[test]
+    fn test_transpose_in_place() {
+        let mut updates = SheetFormatUpdates::from_selection(
+            &A1Selection::test_a1("B2:D2"),
+            FormatUpdate {
+                bold: Some(Some(true)),
+                ..Default::default()
+            },
+        );
+        updates.transpose_in_place(Rect::test_a1("B2:D4"));
+
+        assert_eq!(updates.format_update(pos![B2]).bold, Some(Some(true)));
+        assert_eq!(updates.format_update(pos![B4]).bold, Some(Some(true)));
+        assert_eq!(updates.format_update(pos![C2]).bold, None);
+        assert!(updates.italic.is_none());
+    }
 }
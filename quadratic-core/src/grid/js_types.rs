@@ -74,6 +74,7 @@ pub enum JsCellValueKind {
     Code,
     Image,
     Import,
+    Hyperlink,
 }
 
 impl From<CellValue> for JsCellValueKind {
@@ -93,6 +94,7 @@ impl From<CellValue> for JsCellValueKind {
             CellValue::Code(_) => JsCellValueKind::Code,
             CellValue::Image(_) => JsCellValueKind::Image,
             CellValue::Import(_) => JsCellValueKind::Import,
+            CellValue::Hyperlink(_) => JsCellValueKind::Hyperlink,
         }
     }
 }
@@ -210,6 +212,10 @@ pub struct JsRenderCell {
     pub table_name: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub column_header: Option<bool>,
+
+    /// Link target (URL or `#`-prefixed internal reference) for hyperlinks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
 }
 
 #[cfg(test)]
@@ -51,6 +51,11 @@ pub mod sheets;
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Grid {
     pub sheets: IndexMap<SheetId, Sheet>,
+
+    /// User-defined lists used by autocomplete and fill series (eg, regions
+    /// or a fiscal month order).
+    #[serde(default)]
+    pub custom_lists: Vec<Vec<String>>,
 }
 impl Default for Grid {
     fn default() -> Self {
@@ -66,6 +71,7 @@ impl Grid {
     pub fn new_blank() -> Self {
         Grid {
             sheets: IndexMap::new(),
+            custom_lists: vec![],
         }
     }
 
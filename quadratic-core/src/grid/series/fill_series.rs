@@ -0,0 +1,498 @@
+//! Explicit fill series: fills from a start value using a step and an
+//! optional stop value, instead of inferring the series from the selection
+//! like [`super::find_auto_complete`].
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, TimeDelta, Weekday};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{CellValue, number::normalize};
+
+/// Direction to fill from the start cell of each row or column.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum FillDirection {
+    #[default]
+    Down,
+    Right,
+    Up,
+    Left,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum FillSeriesType {
+    /// Adds the step to the previous value.
+    #[default]
+    Linear,
+
+    /// Multiplies the previous value by the step.
+    Growth,
+
+    /// Adds the step to a date in [`FillDateUnit`]s.
+    Date,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum FillDateUnit {
+    #[default]
+    Day,
+
+    /// Days, skipping Saturdays and Sundays.
+    Weekday,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub struct FillSeriesOptions {
+    pub direction: FillDirection,
+
+    #[serde(default)]
+    pub series_type: FillSeriesType,
+
+    #[serde(default)]
+    pub date_unit: FillDateUnit,
+
+    /// Step between values (a factor for [`FillSeriesType::Growth`]).
+    pub step: f64,
+
+    /// The series stops before passing this value. It is parsed using the
+    /// same rules as user input (eg, "1,000" or "2024-12-31").
+    #[serde(default)]
+    pub stop_value: Option<String>,
+}
+
+impl FillSeriesOptions {
+    /// Returns up to `count` values that follow `start`, or None if `start`
+    /// can't be used with this series type.
+    pub fn series(&self, start: &CellValue, count: usize) -> Option<Vec<CellValue>> {
+        let stop = self
+            .stop_value
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| CellValue::string_to_cell_value(s, false, false).0);
+
+        match (self.series_type, start) {
+            (FillSeriesType::Linear | FillSeriesType::Growth, CellValue::Number(start)) => {
+                let step = Decimal::from_f64(self.step)?;
+                let stop = match stop {
+                    Some(CellValue::Number(stop)) => Some(stop),
+                    Some(_) => return None,
+                    None => None,
+                };
+                Some(
+                    self.number_series(*start, step, stop, count)
+                        .into_iter()
+                        .map(CellValue::Number)
+                        .collect(),
+                )
+            }
+            (FillSeriesType::Date, CellValue::Date(start)) => {
+                let start = start.and_hms_opt(0, 0, 0)?;
+                let stop = date_time_stop(stop)?;
+                Some(
+                    self.date_series(start, stop, count)
+                        .into_iter()
+                        .map(|dt| CellValue::Date(dt.date()))
+                        .collect(),
+                )
+            }
+            (FillSeriesType::Date, CellValue::DateTime(start)) => {
+                let stop = date_time_stop(stop)?;
+                Some(
+                    self.date_series(*start, stop, count)
+                        .into_iter()
+                        .map(CellValue::DateTime)
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    fn number_series(
+        &self,
+        start: Decimal,
+        step: Decimal,
+        stop: Option<Decimal>,
+        count: usize,
+    ) -> Vec<Decimal> {
+        let mut results = vec![];
+        let mut current = start;
+        for i in 1..=count {
+            let next = match self.series_type {
+                FillSeriesType::Growth => current.checked_mul(step),
+                _ => Decimal::from(i)
+                    .checked_mul(step)
+                    .and_then(|delta| start.checked_add(delta)),
+            };
+            let Some(next) = next.map(normalize) else {
+                break;
+            };
+            if stop.is_some_and(|stop| passed_stop(current, next, stop)) {
+                break;
+            }
+            results.push(next);
+            current = next;
+        }
+        results
+    }
+
+    fn date_series(
+        &self,
+        start: NaiveDateTime,
+        stop: Option<NaiveDateTime>,
+        count: usize,
+    ) -> Vec<NaiveDateTime> {
+        let step = self.step.trunc() as i64;
+        if step == 0 {
+            return vec![];
+        }
+
+        let mut results = vec![];
+        let mut current = start;
+        for i in 1..=count as i64 {
+            // months and years are calculated from the start so that the
+            // day of the month doesn't drift (eg, Jan 31, Feb 29, Mar 31)
+            let next = match self.date_unit {
+                FillDateUnit::Day => {
+                    TimeDelta::try_days(step).and_then(|delta| current.checked_add_signed(delta))
+                }
+                FillDateUnit::Weekday => add_weekdays(current, step),
+                FillDateUnit::Month => add_months(start, step.saturating_mul(i)),
+                FillDateUnit::Year => add_months(start, step.saturating_mul(i * 12)),
+            };
+            let Some(next) = next else {
+                break;
+            };
+            if stop.is_some_and(|stop| passed_stop(current, next, stop)) {
+                break;
+            }
+            results.push(next);
+            current = next;
+        }
+        results
+    }
+}
+
+/// Whether `next` has moved past `stop` in the direction the series is
+/// moving.
+fn passed_stop<T: PartialOrd>(current: T, next: T, stop: T) -> bool {
+    if next > current {
+        next > stop
+    } else if next < current {
+        next < stop
+    } else {
+        false
+    }
+}
+
+/// Converts a parsed stop value to a date time. Returns None if the stop
+/// value is not a date; Some(None) if there is no stop value.
+fn date_time_stop(stop: Option<CellValue>) -> Option<Option<NaiveDateTime>> {
+    match stop {
+        None => Some(None),
+        Some(CellValue::Date(date)) => Some(date.and_hms_opt(0, 0, 0)),
+        Some(CellValue::DateTime(date_time)) => Some(Some(date_time)),
+        Some(_) => None,
+    }
+}
+
+fn add_months(date_time: NaiveDateTime, months: i64) -> Option<NaiveDateTime> {
+    let delta = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months < 0 {
+        date_time.checked_sub_months(delta)
+    } else {
+        date_time.checked_add_months(delta)
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn add_weekdays(date_time: NaiveDateTime, weekdays: i64) -> Option<NaiveDateTime> {
+    let day = TimeDelta::days(weekdays.signum());
+    let next_weekday = |date_time: NaiveDateTime| {
+        let mut next = date_time.checked_add_signed(day)?;
+        while is_weekend(next.date()) {
+            next = next.checked_add_signed(day)?;
+        }
+        Some(next)
+    };
+
+    let mut remaining = weekdays.unsigned_abs();
+    let mut current = date_time;
+
+    // whole weeks of 5 weekdays only line up when starting on a weekday
+    if remaining > 0 && is_weekend(current.date()) {
+        current = next_weekday(current)?;
+        remaining -= 1;
+    }
+
+    let weeks = i64::try_from(remaining / 5).ok()? * weekdays.signum();
+    current = current.checked_add_signed(TimeDelta::try_weeks(weeks)?)?;
+
+    for _ in 0..remaining % 5 {
+        current = next_weekday(current)?;
+    }
+    Some(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(
+        series_type: FillSeriesType,
+        step: f64,
+        stop_value: Option<&str>,
+    ) -> FillSeriesOptions {
+        FillSeriesOptions {
+            direction: FillDirection::Down,
+            series_type,
+            date_unit: FillDateUnit::Day,
+            step,
+            stop_value: stop_value.map(|s| s.to_string()),
+        }
+    }
+
+    fn numbers(values: Vec<f64>) -> Vec<CellValue> {
+        values
+            .into_iter()
+            .map(|v| CellValue::Number(Decimal::from_f64(v).unwrap()))
+            .collect()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> CellValue {
+        CellValue::Date(NaiveDate::from_ymd_opt(y, m, d).unwrap())
+    }
+
+    #[test]
+    fn test_linear_series() {
+        let start = CellValue::Number(1.into());
+        let options = options(FillSeriesType::Linear, 2.5, None);
+        assert_eq!(
+            options.series(&start, 3),
+            Some(numbers(vec![3.5, 6.0, 8.5]))
+        );
+
+        let options = FillSeriesOptions {
+            step: -1.0,
+            ..options
+        };
+        assert_eq!(options.series(&start, 2), Some(numbers(vec![0.0, -1.0])));
+    }
+
+    #[test]
+    fn test_linear_series_stop_value() {
+        let start = CellValue::Number(1.into());
+        let options = options(FillSeriesType::Linear, 2.0, Some("6"));
+        assert_eq!(options.series(&start, 10), Some(numbers(vec![3.0, 5.0])));
+
+        let options = FillSeriesOptions {
+            stop_value: Some("1,000".to_string()),
+            step: 250.0,
+            ..options
+        };
+        assert_eq!(options.series(&start, 10).unwrap().len(), 3);
+
+        // a stop value that isn't a number can't be used
+        let options = FillSeriesOptions {
+            stop_value: Some("abc".to_string()),
+            ..options
+        };
+        assert_eq!(options.series(&start, 10), None);
+    }
+
+    #[test]
+    fn test_growth_series() {
+        let start = CellValue::Number(3.into());
+        let options = options(FillSeriesType::Growth, 2.0, None);
+        assert_eq!(
+            options.series(&start, 4),
+            Some(numbers(vec![6.0, 12.0, 24.0, 48.0]))
+        );
+
+        let options = FillSeriesOptions {
+            stop_value: Some("30".to_string()),
+            ..options
+        };
+        assert_eq!(
+            options.series(&start, 10),
+            Some(numbers(vec![6.0, 12.0, 24.0]))
+        );
+
+        // decreasing growth stops below the stop value
+        let start = CellValue::Number(100.into());
+        let options = FillSeriesOptions {
+            step: 0.5,
+            stop_value: Some("10".to_string()),
+            ..options
+        };
+        assert_eq!(
+            options.series(&start, 10),
+            Some(numbers(vec![50.0, 25.0, 12.5]))
+        );
+    }
+
+    #[test]
+    fn test_series_type_mismatch() {
+        let options = options(FillSeriesType::Linear, 1.0, None);
+        assert_eq!(options.series(&date(2024, 1, 1), 2), None);
+        assert_eq!(options.series(&CellValue::Text("a".into()), 2), None);
+
+        let options = FillSeriesOptions {
+            series_type: FillSeriesType::Date,
+            ..options
+        };
+        assert_eq!(options.series(&CellValue::Number(1.into()), 2), None);
+    }
+
+    #[test]
+    fn test_date_series_days() {
+        let options = options(FillSeriesType::Date, 7.0, None);
+        assert_eq!(
+            options.series(&date(2024, 12, 20), 2),
+            Some(vec![date(2024, 12, 27), date(2025, 1, 3)])
+        );
+
+        let options = FillSeriesOptions {
+            stop_value: Some("2025-01-10".to_string()),
+            ..options
+        };
+        assert_eq!(
+            options.series(&date(2024, 12, 20), 10),
+            Some(vec![
+                date(2024, 12, 27),
+                date(2025, 1, 3),
+                date(2025, 1, 10)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_date_series_stops_on_overflow() {
+        let options = options(FillSeriesType::Date, 1e18, None);
+        assert_eq!(options.series(&date(2024, 12, 20), 2), Some(vec![]));
+
+        let options = FillSeriesOptions {
+            date_unit: FillDateUnit::Weekday,
+            ..options
+        };
+        assert_eq!(options.series(&date(2024, 12, 20), 2), Some(vec![]));
+
+        // stops at the end of the supported date range
+        let options = FillSeriesOptions {
+            step: 200.0,
+            date_unit: FillDateUnit::Day,
+            ..options
+        };
+        let start = NaiveDate::from_ymd_opt(262_142, 1, 1).unwrap();
+        assert_eq!(
+            options.series(&CellValue::Date(start), 3),
+            Some(vec![CellValue::Date(start + TimeDelta::days(200))])
+        );
+    }
+
+    #[test]
+    fn test_date_series_weekdays() {
+        let options = FillSeriesOptions {
+            date_unit: FillDateUnit::Weekday,
+            ..options(FillSeriesType::Date, 1.0, None)
+        };
+
+        // Thursday, Jan 2 2025
+        assert_eq!(
+            options.series(&date(2025, 1, 2), 3),
+            Some(vec![date(2025, 1, 3), date(2025, 1, 6), date(2025, 1, 7)])
+        );
+
+        let options = FillSeriesOptions {
+            step: -1.0,
+            ..options
+        };
+        assert_eq!(
+            options.series(&date(2025, 1, 7), 2),
+            Some(vec![date(2025, 1, 6), date(2025, 1, 3)])
+        );
+
+        // steps of more than a week, including from a weekend
+        let options = FillSeriesOptions {
+            step: 7.0,
+            ..options
+        };
+        assert_eq!(
+            options.series(&date(2025, 1, 2), 2),
+            Some(vec![date(2025, 1, 13), date(2025, 1, 22)])
+        );
+
+        let options = FillSeriesOptions {
+            step: 6.0,
+            ..options
+        };
+        assert_eq!(
+            options.series(&date(2025, 1, 4), 1),
+            Some(vec![date(2025, 1, 13)])
+        );
+
+        let options = FillSeriesOptions {
+            step: -6.0,
+            ..options
+        };
+        assert_eq!(
+            options.series(&date(2025, 1, 5), 1),
+            Some(vec![date(2024, 12, 27)])
+        );
+    }
+
+    #[test]
+    fn test_date_series_months_and_years() {
+        let options = FillSeriesOptions {
+            date_unit: FillDateUnit::Month,
+            ..options(FillSeriesType::Date, 1.0, None)
+        };
+        assert_eq!(
+            options.series(&date(2024, 1, 31), 3),
+            Some(vec![
+                date(2024, 2, 29),
+                date(2024, 3, 31),
+                date(2024, 4, 30)
+            ])
+        );
+
+        let options = FillSeriesOptions {
+            date_unit: FillDateUnit::Year,
+            ..options
+        };
+        assert_eq!(
+            options.series(&date(2024, 2, 29), 2),
+            Some(vec![date(2025, 2, 28), date(2026, 2, 28)])
+        );
+
+        let start = CellValue::DateTime(
+            NaiveDate::from_ymd_opt(2024, 3, 15)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap(),
+        );
+        let options = FillSeriesOptions {
+            date_unit: FillDateUnit::Month,
+            step: -2.0,
+            ..options
+        };
+        assert_eq!(
+            options.series(&start, 1),
+            Some(vec![CellValue::DateTime(
+                NaiveDate::from_ymd_opt(2024, 1, 15)
+                    .unwrap()
+                    .and_hms_opt(9, 30, 0)
+                    .unwrap()
+            )])
+        );
+    }
+}
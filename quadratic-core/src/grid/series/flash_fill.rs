@@ -0,0 +1,568 @@
+//! Flash fill: infers a string program from a few example outputs and applies
+//! it to the remaining rows.
+//!
+//! A program is a concatenation of atoms. Each atom is either a constant or a
+//! substring of one of the row's inputs (the whole input, the nth token
+//! between a delimiter, the nth run of digits or letters, or a range of
+//! character positions), optionally with a case change.
+//!
+//! Programs are found with a shortest-path search over the examples' output
+//! offsets, so every program found is consistent with all examples. All
+//! programs with the lowest cost are kept; if they disagree on any remaining
+//! row, the examples are ambiguous and nothing is filled.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Delimiters used to split an input into tokens.
+const DELIMITERS: [char; 12] = [' ', ',', '.', '-', '_', '/', '@', '(', ')', ':', ';', '|'];
+
+/// Maximum number of lowest-cost programs compared against each other.
+const MAX_PROGRAMS: usize = 256;
+
+/// Inputs longer than this (in chars) are not split into positional atoms.
+const MAX_POSITION_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum CharClass {
+    Digits,
+    Letters,
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Digits => c.is_ascii_digit(),
+            CharClass::Letters => c.is_alphabetic(),
+        }
+    }
+}
+
+/// A character offset counted from the start or the end of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Bound {
+    Start(usize),
+    End(usize),
+}
+
+impl Bound {
+    fn resolve(&self, len: usize) -> Option<usize> {
+        match self {
+            Bound::Start(offset) => (*offset <= len).then_some(*offset),
+            Bound::End(offset) => len.checked_sub(*offset),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Extract {
+    Whole,
+
+    /// The nth token between `delimiter`. Negative indexes count from the end.
+    Token {
+        delimiter: char,
+        index: i64,
+    },
+
+    /// The nth run of characters of `class`. Negative indexes count from the
+    /// end.
+    Class {
+        class: CharClass,
+        index: i64,
+    },
+
+    Position {
+        start: Bound,
+        end: Bound,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Case {
+    Same,
+    Upper,
+    Lower,
+    Title,
+}
+
+impl Case {
+    const ALL: [Case; 4] = [Case::Same, Case::Upper, Case::Lower, Case::Title];
+
+    fn apply(&self, s: &str) -> String {
+        match self {
+            Case::Same => s.to_string(),
+            Case::Upper => s.to_uppercase(),
+            Case::Lower => s.to_lowercase(),
+            Case::Title => {
+                let mut result = String::with_capacity(s.len());
+                let mut start_of_word = true;
+                for c in s.chars() {
+                    if start_of_word {
+                        result.extend(c.to_uppercase());
+                    } else {
+                        result.extend(c.to_lowercase());
+                    }
+                    start_of_word = !c.is_alphanumeric();
+                }
+                result
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Atom {
+    Const(String),
+    Substring {
+        input: usize,
+        extract: Extract,
+        case: Case,
+    },
+}
+
+/// Returns the nth item, counting from the end for negative indexes.
+fn nth<T>(items: &[T], index: i64) -> Option<&T> {
+    if index < 0 {
+        let from_end = usize::try_from(-index).ok()?;
+        items.len().checked_sub(from_end).and_then(|i| items.get(i))
+    } else {
+        items.get(usize::try_from(index).ok()?)
+    }
+}
+
+fn class_runs(s: &str, class: CharClass) -> Vec<&str> {
+    let mut runs = vec![];
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match (class.matches(c), start) {
+            (true, None) => start = Some(i),
+            (false, Some(begin)) => {
+                runs.push(&s[begin..i]);
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(begin) = start {
+        runs.push(&s[begin..]);
+    }
+    runs
+}
+
+impl Extract {
+    fn eval(&self, s: &str) -> Option<String> {
+        let value = match self {
+            Extract::Whole => s.to_string(),
+            Extract::Token { delimiter, index } => {
+                // the delimiter must be present for the token to be meaningful
+                let tokens = s.split(*delimiter).collect::<Vec<_>>();
+                if tokens.len() < 2 {
+                    return None;
+                }
+                nth(&tokens, *index)?.to_string()
+            }
+            Extract::Class { class, index } => nth(&class_runs(s, *class), *index)?.to_string(),
+            Extract::Position { start, end } => {
+                let chars = s.chars().collect::<Vec<_>>();
+                let start = start.resolve(chars.len())?;
+                let end = end.resolve(chars.len())?;
+                if start >= end {
+                    return None;
+                }
+                chars[start..end].iter().collect()
+            }
+        };
+        (!value.is_empty()).then_some(value)
+    }
+
+    /// Lower costs are preferred when more than one program fits the
+    /// examples. The first and last tokens are more likely to be intended
+    /// than a token in the middle, and positions are more likely to be
+    /// counted from the start.
+    fn cost(&self) -> u32 {
+        match self {
+            Extract::Whole => 1,
+            Extract::Token { index, .. } | Extract::Class { index, .. } => {
+                if *index == 0 || *index == -1 { 2 } else { 3 }
+            }
+            Extract::Position { start, end } => {
+                4 + [start, end]
+                    .iter()
+                    .filter(|bound| matches!(bound, Bound::End(_)))
+                    .count() as u32
+            }
+        }
+    }
+}
+
+impl Atom {
+    fn eval(&self, inputs: &[String]) -> Option<String> {
+        match self {
+            Atom::Const(value) => Some(value.clone()),
+            Atom::Substring {
+                input,
+                extract,
+                case,
+            } => extract
+                .eval(inputs.get(*input)?)
+                .map(|value| case.apply(&value)),
+        }
+    }
+
+    fn cost(&self) -> u32 {
+        match self {
+            // separators are cheap; text that could have come from the input
+            // is expensive so that substrings are preferred
+            Atom::Const(value) => {
+                if value.chars().all(|c| !c.is_alphanumeric()) {
+                    1
+                } else {
+                    10 + value.chars().count() as u32
+                }
+            }
+            Atom::Substring { extract, case, .. } => {
+                extract.cost() + if *case == Case::Same { 0 } else { 1 }
+            }
+        }
+    }
+}
+
+/// A program that transforms a row's inputs into an output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlashFillProgram {
+    atoms: Vec<Atom>,
+}
+
+impl FlashFillProgram {
+    /// Applies the program to a row's inputs. Returns None if any atom can't
+    /// be extracted from the inputs.
+    pub fn apply(&self, inputs: &[String]) -> Option<String> {
+        self.atoms
+            .iter()
+            .map(|atom| atom.eval(inputs))
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| parts.concat())
+    }
+
+    /// Returns all lowest-cost programs that produce every example's output
+    /// from its inputs (limited to [`MAX_PROGRAMS`]).
+    pub fn synthesize(examples: &[(Vec<String>, String)]) -> Vec<FlashFillProgram> {
+        let Some((first_inputs, first_output)) = examples.first() else {
+            return vec![];
+        };
+        if examples.iter().any(|(_, output)| output.is_empty()) {
+            return vec![];
+        }
+
+        // only atoms that produce part of the first output can be used
+        let atoms = candidate_atoms(first_inputs)
+            .into_iter()
+            .filter_map(|atom| {
+                let values = examples
+                    .iter()
+                    .map(|(inputs, _)| atom.eval(inputs))
+                    .collect::<Option<Vec<_>>>()?;
+                first_output
+                    .contains(values[0].as_str())
+                    .then_some((atom, values))
+            })
+            .collect::<Vec<_>>();
+
+        let goal = examples
+            .iter()
+            .map(|(_, output)| output.len())
+            .collect::<Vec<_>>();
+        let start = vec![0; examples.len()];
+
+        // shortest paths over the offsets into each example's output; ties
+        // are kept so that ambiguous examples can be detected
+        let mut best: HashMap<Vec<usize>, u32> = HashMap::from([(start.clone(), 0)]);
+        let mut preds: HashMap<Vec<usize>, Vec<(Vec<usize>, Atom)>> = HashMap::new();
+        let mut heap = BinaryHeap::from([Reverse((0, start.clone()))]);
+
+        while let Some(Reverse((cost, offsets))) = heap.pop() {
+            if best.get(&offsets).is_some_and(|b| *b < cost) {
+                continue;
+            }
+            if offsets == goal {
+                break;
+            }
+
+            let mut edges = vec![];
+            for (atom, values) in atoms.iter() {
+                let next = examples
+                    .iter()
+                    .zip(values)
+                    .zip(offsets.iter())
+                    .map(|(((_, output), value), offset)| {
+                        output[*offset..]
+                            .starts_with(value.as_str())
+                            .then_some(offset + value.len())
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if let Some(next) = next {
+                    edges.push((next, atom.clone()));
+                }
+            }
+
+            let first_remaining = &first_output[offsets[0]..];
+            for (end, c) in first_remaining.char_indices() {
+                let value = &first_remaining[..end + c.len_utf8()];
+                let next = examples
+                    .iter()
+                    .zip(offsets.iter())
+                    .map(|((_, output), offset)| {
+                        output[*offset..]
+                            .starts_with(value)
+                            .then_some(offset + value.len())
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if let Some(next) = next {
+                    edges.push((next, Atom::Const(value.to_string())));
+                }
+            }
+
+            for (next, atom) in edges {
+                let next_cost = cost + atom.cost();
+                match best.get(&next) {
+                    Some(b) if *b < next_cost => (),
+                    Some(b) if *b == next_cost => {
+                        preds.entry(next).or_default().push((offsets.clone(), atom));
+                    }
+                    _ => {
+                        best.insert(next.clone(), next_cost);
+                        preds.insert(next.clone(), vec![(offsets.clone(), atom)]);
+                        heap.push(Reverse((next_cost, next)));
+                    }
+                }
+            }
+        }
+
+        if !best.contains_key(&goal) {
+            return vec![];
+        }
+
+        // walk the predecessors back from the goal to build the programs
+        let mut programs = vec![];
+        let mut stack = vec![(goal, vec![])];
+        while let Some((offsets, suffix)) = stack.pop() {
+            if programs.len() >= MAX_PROGRAMS {
+                break;
+            }
+            if offsets == start {
+                let mut atoms: Vec<Atom> = suffix;
+                atoms.reverse();
+                programs.push(FlashFillProgram { atoms });
+                continue;
+            }
+            for (prev, atom) in preds.get(&offsets).into_iter().flatten() {
+                let mut suffix = suffix.clone();
+                suffix.push(atom.clone());
+                stack.push((prev.clone(), suffix));
+            }
+        }
+        programs
+    }
+}
+
+/// Lists every substring atom that produces a value from the inputs.
+fn candidate_atoms(inputs: &[String]) -> Vec<Atom> {
+    let mut extracts = vec![];
+    for (input, s) in inputs.iter().enumerate() {
+        if s.is_empty() {
+            continue;
+        }
+        extracts.push((input, Extract::Whole));
+
+        for delimiter in DELIMITERS {
+            let count = s.split(delimiter).count() as i64;
+            if count > 1 {
+                for index in 0..count {
+                    extracts.push((input, Extract::Token { delimiter, index }));
+                    extracts.push((
+                        input,
+                        Extract::Token {
+                            delimiter,
+                            index: index - count,
+                        },
+                    ));
+                }
+            }
+        }
+
+        for class in [CharClass::Digits, CharClass::Letters] {
+            let count = class_runs(s, class).len() as i64;
+            for index in 0..count {
+                extracts.push((input, Extract::Class { class, index }));
+                extracts.push((
+                    input,
+                    Extract::Class {
+                        class,
+                        index: index - count,
+                    },
+                ));
+            }
+        }
+
+        let len = s.chars().count();
+        if len <= MAX_POSITION_LEN {
+            for start in 0..len {
+                for end in start + 1..=len {
+                    for start in [Bound::Start(start), Bound::End(len - start)] {
+                        for end in [Bound::Start(end), Bound::End(len - end)] {
+                            extracts.push((input, Extract::Position { start, end }));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    extracts
+        .into_iter()
+        .flat_map(|(input, extract)| {
+            Case::ALL.into_iter().map(move |case| Atom::Substring {
+                input,
+                extract: extract.clone(),
+                case,
+            })
+        })
+        .collect()
+}
+
+/// Infers a program from `examples` (each row's inputs and expected output)
+/// and applies it to `inputs`. Returns None if no program fits the examples
+/// or if the best programs disagree on any of the inputs. A row's output is
+/// None if the program can't be applied to it.
+pub fn flash_fill(
+    examples: &[(Vec<String>, String)],
+    inputs: &[Vec<String>],
+) -> Option<Vec<Option<String>>> {
+    let programs = FlashFillProgram::synthesize(examples);
+    let (first, rest) = programs.split_first()?;
+
+    inputs
+        .iter()
+        .map(|row| {
+            let output = first.apply(row);
+            rest.iter()
+                .all(|program| program.apply(row) == output)
+                .then_some(output)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn example(inputs: &[&str], output: &str) -> (Vec<String>, String) {
+        (row(inputs), output.to_string())
+    }
+
+    fn fill(examples: &[(Vec<String>, String)], inputs: &[&[&str]]) -> Option<Vec<Option<String>>> {
+        let inputs = inputs.iter().map(|r| row(r)).collect::<Vec<_>>();
+        flash_fill(examples, &inputs)
+    }
+
+    fn some(values: &[&str]) -> Option<Vec<Option<String>>> {
+        Some(values.iter().map(|s| Some(s.to_string())).collect())
+    }
+
+    #[test]
+    fn test_first_and_last_names() {
+        let examples = [example(&["John Smith"], "John")];
+        assert_eq!(
+            fill(&examples, &[&["Jane Doe"], &["Mary Ann Lee"]]),
+            some(&["Jane", "Mary"])
+        );
+
+        let examples = [example(&["John Smith"], "Smith")];
+        assert_eq!(
+            fill(&examples, &[&["Jane Doe"], &["Mary Ann Lee"]]),
+            some(&["Doe", "Lee"])
+        );
+    }
+
+    #[test]
+    fn test_concatenation_and_case() {
+        let examples = [example(&["john smith"], "Smith, J")];
+        assert_eq!(
+            fill(&examples, &[&["jane doe"], &["bob jones"]]),
+            some(&["Doe, J", "Jones, B"])
+        );
+
+        let examples = [example(&["Ada", "Lovelace"], "ADA LOVELACE")];
+        assert_eq!(
+            fill(&examples, &[&["Alan", "Turing"]]),
+            some(&["ALAN TURING"])
+        );
+    }
+
+    #[test]
+    fn test_phone_numbers() {
+        let examples = [example(&["(555) 123-4567"], "555.123.4567")];
+        assert_eq!(
+            fill(&examples, &[&["(206) 555-0100"]]),
+            some(&["206.555.0100"])
+        );
+    }
+
+    #[test]
+    fn test_email_domain() {
+        let examples = [
+            example(&["ann@example.com"], "example"),
+            example(&["bo@quadratic.to"], "quadratic"),
+        ];
+        assert_eq!(fill(&examples, &[&["cy@rust.org"]]), some(&["rust"]));
+    }
+
+    #[test]
+    fn test_ambiguous_examples() {
+        // the middle token and the second-to-last token agree on the example
+        // but not on the inputs
+        let examples = [example(&["a b c"], "b")];
+        assert_eq!(fill(&examples, &[&["d e f g"]]), None);
+
+        // a second example resolves the ambiguity
+        let examples = [example(&["a b c"], "b"), example(&["h i j k"], "i")];
+        assert_eq!(fill(&examples, &[&["d e f g"]]), some(&["e"]));
+    }
+
+    #[test]
+    fn test_no_program() {
+        assert_eq!(fill(&[], &[&["a"]]), None);
+        assert_eq!(
+            FlashFillProgram::synthesize(&[example(&["abc"], "")]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_examples_fall_back_to_constants() {
+        // the only program that fits both examples is a constant
+        let examples = [example(&["abc"], "xyz"), example(&["def"], "xyz")];
+        assert_eq!(fill(&examples, &[&["ghi"]]), some(&["xyz"]));
+
+        let examples = [example(&["abc"], "x"), example(&["def"], "y")];
+        assert_eq!(fill(&examples, &[&["ghi"]]), None);
+    }
+
+    #[test]
+    fn test_missing_token() {
+        let examples = [example(&["a-b"], "b")];
+        assert_eq!(
+            fill(&examples, &[&["c-d"], &["5"]]),
+            Some(vec![Some("d".to_string()), None])
+        );
+    }
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(Case::Title.apply("mcDONALD o'neil"), "Mcdonald O'Neil");
+    }
+}
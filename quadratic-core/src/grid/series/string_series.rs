@@ -119,14 +119,24 @@ fn get_series_next_key(last_key: &str, all_keys: &&[&str], negative: bool) -> Re
     Ok(next_key.to_string())
 }
 
-pub fn find_string_series(options: &SeriesOptions) -> Option<Vec<CellValue>> {
+/// Finds a text series. User-defined custom lists are checked before the
+/// built-in lists so they can override them (eg, a fiscal month order).
+pub fn find_string_series(
+    options: &SeriesOptions,
+    custom_lists: &[Vec<String>],
+) -> Option<Vec<CellValue>> {
     let mut results: Vec<CellValue> = vec![];
     let SeriesOptions {
         series,
         spaces,
         negative,
     } = options;
-    let text_series: &[&[&str]] = &[
+    let custom_lists = custom_lists
+        .iter()
+        .filter(|list| list.len() >= 2)
+        .map(|list| list.iter().map(|s| s.as_str()).collect::<Vec<&str>>())
+        .collect::<Vec<_>>();
+    let built_in: &[&[&str]] = &[
         &ALPHABET_LOWER,
         &ALPHABET_UPPER,
         &MONTHS_SHORT,
@@ -138,6 +148,11 @@ pub fn find_string_series(options: &SeriesOptions) -> Option<Vec<CellValue>> {
         &DAYS_FULL,
         &DAYS_FULL_UPPER,
     ];
+    let text_series = custom_lists
+        .iter()
+        .map(|list| list.as_slice())
+        .chain(built_in.iter().copied())
+        .collect::<Vec<&[&str]>>();
 
     let mut possible_text_series = text_series.iter().map(|_| Some(vec![])).collect::<Vec<_>>();
 
@@ -211,7 +226,9 @@ pub fn find_string_series(options: &SeriesOptions) -> Option<Vec<CellValue>> {
 
 #[cfg(test)]
 mod tests {
-    use crate::grid::series::{cell_value_text, find_auto_complete};
+    use crate::grid::series::{
+        cell_value_text, find_auto_complete, find_auto_complete_with_custom_lists,
+    };
 
     use super::*;
 
@@ -302,4 +319,53 @@ mod tests {
         let results = find_auto_complete(options);
         assert_eq!(results, cell_value_text(vec!["DECEMBER", "JANUARY"]));
     }
+
+    #[test]
+    fn find_a_text_series_custom_list() {
+        let custom_lists = vec![vec![
+            "North".to_string(),
+            "East".to_string(),
+            "South".to_string(),
+            "West".to_string(),
+        ]];
+        let options = SeriesOptions {
+            series: cell_value_text(vec!["East", "South"]),
+            spaces: 3,
+            negative: false,
+        };
+        let results = find_auto_complete_with_custom_lists(options.clone(), &custom_lists);
+        assert_eq!(results, cell_value_text(vec!["West", "North", "East"]));
+
+        let options = SeriesOptions {
+            negative: true,
+            ..options
+        };
+        let results = find_auto_complete_with_custom_lists(options.clone(), &custom_lists);
+        assert_eq!(results, cell_value_text(vec!["South", "West", "North"]));
+
+        // without the custom list, the values are copied
+        let results = find_auto_complete(options);
+        assert_eq!(results, cell_value_text(vec!["South", "East", "South"]));
+    }
+
+    #[test]
+    fn find_a_text_series_custom_list_takes_priority() {
+        // quarter-end months overlap the built-in short month list
+        let custom_lists = vec![vec![
+            "Mar".to_string(),
+            "Jun".to_string(),
+            "Sep".to_string(),
+            "Dec".to_string(),
+        ]];
+        let options = SeriesOptions {
+            series: cell_value_text(vec!["Dec"]),
+            spaces: 2,
+            negative: false,
+        };
+        let results = find_auto_complete_with_custom_lists(options.clone(), &custom_lists);
+        assert_eq!(results, cell_value_text(vec!["Mar", "Jun"]));
+
+        let results = find_auto_complete(options);
+        assert_eq!(results, cell_value_text(vec!["Jan", "Feb"]));
+    }
 }
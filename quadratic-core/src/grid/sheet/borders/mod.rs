@@ -192,6 +192,23 @@ impl BordersUpdates {
             bottom.translate_in_place(x, y);
         }
     }
+
+    /// Transposes the borders within `rect` around its top-left corner. Left
+    /// and top borders (and right and bottom borders) swap sides.
+    pub fn transpose_in_place(&mut self, rect: Rect) {
+        std::mem::swap(&mut self.left, &mut self.top);
+        std::mem::swap(&mut self.right, &mut self.bottom);
+        for item in [
+            &mut self.left,
+            &mut self.right,
+            &mut self.top,
+            &mut self.bottom,
+        ] {
+            if let Some(item) = item.as_mut() {
+                *item = item.transpose_rect(rect);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -214,4 +231,22 @@ mod tests {
         };
         assert!(!updates.is_empty());
     }
+
+    #[test]
+    fn test_transpose_in_place() {
+        let mut updates = BordersUpdates::default();
+        updates.set_style_cell(
+            pos![C2],
+            BorderStyleCell {
+                left: Some(BorderStyleTimestamp::default()),
+                ..Default::default()
+            },
+        );
+        updates.transpose_in_place(Rect::test_a1("B2:C3"));
+
+        assert!(updates.left.is_none());
+        let top = updates.top.unwrap();
+        assert!(top.get(pos![B3]).is_some());
+        assert!(top.get(pos![C2]).is_none());
+    }
 }
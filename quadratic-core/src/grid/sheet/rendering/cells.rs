@@ -65,6 +65,7 @@ impl Sheet {
         };
 
         let mut number: Option<JsNumber> = None;
+        let mut link: Option<String> = None;
         let value = match value {
             CellValue::Number(_) => {
                 // get numeric_format and numeric_decimal to turn number into a string
@@ -76,6 +77,12 @@ impl Sheet {
             CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Time(_) => {
                 Self::value_date_time(value, format.date_time)
             }
+            CellValue::Hyperlink(hyperlink) => {
+                // links are underlined unless the user explicitly turned it off
+                format.underline = format.underline.or(Some(true));
+                link = Some(hyperlink.href());
+                value.to_display()
+            }
             _ => value.to_display(),
         };
         JsRenderCell {
@@ -95,6 +102,7 @@ impl Sheet {
             strike_through: format.strike_through,
             table_name: None,
             column_header: None,
+            link,
         }
     }
 
@@ -730,4 +738,33 @@ mod tests {
         Sheet::ensure_lists_are_clipped(&mut format, &special);
         assert_eq!(format.wrap, None);
     }
+
+    #[test]
+    fn test_get_render_cells_hyperlink() {
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(
+            pos![A1],
+            CellValue::Hyperlink(crate::Hyperlink::new_url(
+                "https://quadratichq.com",
+                Some("Quadratic".to_string()),
+            )),
+        );
+        sheet.set_cell_value(
+            pos![A2],
+            CellValue::Hyperlink(crate::Hyperlink::new_internal("Sheet1!B5", None)),
+        );
+        sheet.formats.underline.set(pos![A2], Some(false));
+
+        let cells = sheet.get_render_cells(Rect::new(1, 1, 1, 2), &A1Context::default());
+        assert_eq!(cells.len(), 2);
+
+        assert_eq!(cells[0].value, "Quadratic");
+        assert_eq!(cells[0].link, Some("https://quadratichq.com".to_string()));
+        assert_eq!(cells[0].underline, Some(true));
+
+        // explicit formatting wins over the default link underline
+        assert_eq!(cells[1].value, "Sheet1!B5");
+        assert_eq!(cells[1].link, Some("#Sheet1!B5".to_string()));
+        assert_eq!(cells[1].underline, Some(false));
+    }
 }
@@ -2,17 +2,24 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use arrow_array::{
-    Array, ArrayRef,
+    Array, ArrayRef, BooleanArray, Date32Array, Decimal128Array, DurationMicrosecondArray,
+    Float64Array, Int64Array, IntervalMonthDayNanoArray, RecordBatch, StringArray,
+    Time64MicrosecondArray, TimestampMicrosecondArray,
     cast::AsArray,
-    types::{Date32Type, Date64Type},
+    types::{
+        Date32Type, Date64Type, Decimal128Type, DurationMicrosecondType, DurationMillisecondType,
+        DurationNanosecondType, DurationSecondType, IntervalDayTimeType, IntervalMonthDayNano,
+        IntervalMonthDayNanoType,
+    },
 };
 use arrow_buffer::ArrowNativeType;
 use arrow_data::ArrayData;
-use arrow_schema::{DataType, TimeUnit};
-use chrono::{NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
-use rust_decimal::Decimal;
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DECIMAL128_MAX_PRECISION, DataType, IntervalUnit, TimeUnit};
+use chrono::{NaiveDate, NaiveTime, TimeDelta, TimeZone, Timelike, Utc};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
 
-use crate::{CellValue, cell_values::CellValues};
+use crate::{CellValue, Duration, cell_values::CellValues};
 
 use super::time::map_local_result;
 
@@ -54,6 +61,10 @@ pub fn arrow_col_to_cell_value_vec(array: &ArrayRef) -> Result<Vec<CellValue>> {
         DataType::Time32(unit) => arrow_time_unit_to_cell_values::<i32>(array_data, unit),
         DataType::Time64(unit) => arrow_time_unit_to_cell_values::<i64>(array_data, unit),
         DataType::Timestamp(unit, extra) => arrow_timestamp_to_cell_value(array_data, unit, extra),
+        DataType::Decimal128(_, scale) => Ok(arrow_decimal_to_cell_values(array, *scale)),
+        DataType::Duration(unit) => Ok(arrow_duration_to_cell_values(array, unit)),
+        DataType::Interval(IntervalUnit::MonthDayNano) => Ok(arrow_interval_to_cell_values(array)),
+        DataType::Interval(IntervalUnit::DayTime) => Ok(arrow_day_time_to_cell_values(array)),
         // unsupported data type
         _ => {
             dbgjs!(format!(
@@ -247,3 +258,235 @@ fn arrow_timestamp_to_cell_value(
 
     Ok(values)
 }
+
+fn arrow_decimal_to_cell_values(col: &ArrayRef, scale: i8) -> Vec<CellValue> {
+    let to_decimal = |value: i128| match u32::try_from(scale) {
+        Ok(scale) => Decimal::try_from_i128_with_scale(value, scale).ok(),
+        // negative scales multiply the value by a power of ten
+        Err(_) => Decimal::try_from_i128_with_scale(
+            value.checked_mul(10_i128.checked_pow(scale.unsigned_abs() as u32)?)?,
+            0,
+        )
+        .ok(),
+    };
+
+    col.as_primitive::<Decimal128Type>()
+        .iter()
+        .map(|value| {
+            value
+                .and_then(to_decimal)
+                .map_or(CellValue::Blank, CellValue::Number)
+        })
+        .collect()
+}
+
+fn arrow_duration_to_cell_values(col: &ArrayRef, unit: &TimeUnit) -> Vec<CellValue> {
+    let seconds = |value: i64| match unit {
+        TimeUnit::Second => value as f64,
+        TimeUnit::Millisecond => value as f64 / 1e3,
+        TimeUnit::Microsecond => value as f64 / 1e6,
+        TimeUnit::Nanosecond => value as f64 / 1e9,
+    };
+    let values = match unit {
+        TimeUnit::Second => col
+            .as_primitive::<DurationSecondType>()
+            .iter()
+            .collect::<Vec<_>>(),
+        TimeUnit::Millisecond => col
+            .as_primitive::<DurationMillisecondType>()
+            .iter()
+            .collect(),
+        TimeUnit::Microsecond => col
+            .as_primitive::<DurationMicrosecondType>()
+            .iter()
+            .collect(),
+        TimeUnit::Nanosecond => col
+            .as_primitive::<DurationNanosecondType>()
+            .iter()
+            .collect(),
+    };
+    values
+        .into_iter()
+        .map(|value| {
+            value.map_or(CellValue::Blank, |value| {
+                CellValue::Duration(Duration {
+                    months: 0,
+                    seconds: seconds(value),
+                })
+            })
+        })
+        .collect()
+}
+
+fn arrow_interval_to_cell_values(col: &ArrayRef) -> Vec<CellValue> {
+    col.as_primitive::<IntervalMonthDayNanoType>()
+        .iter()
+        .map(|value| {
+            value.map_or(CellValue::Blank, |value| {
+                CellValue::Duration(Duration {
+                    months: value.months,
+                    seconds: value.days as f64 * 86_400.0 + value.nanoseconds as f64 / 1e9,
+                })
+            })
+        })
+        .collect()
+}
+
+fn arrow_day_time_to_cell_values(col: &ArrayRef) -> Vec<CellValue> {
+    col.as_primitive::<IntervalDayTimeType>()
+        .iter()
+        .map(|value| {
+            value.map_or(CellValue::Blank, |value| {
+                CellValue::Duration(Duration {
+                    months: 0,
+                    seconds: value.days as f64 * 86_400.0 + value.milliseconds as f64 / 1e3,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Converts a column of cell values to an arrow array. The type of the array
+/// is inferred from the column's values, ignoring blanks:
+///
+/// - whole numbers are Int64 and other numbers are Decimal128, or Float64 if
+///   they don't all fit in a Decimal128 with the column's scale
+/// - logicals, dates, times and durations keep their type
+/// - dates mixed with date times are timestamps
+/// - anything else is text
+///
+/// Blank cells are null.
+pub fn cell_values_to_arrow_col(values: &[CellValue]) -> Result<ArrayRef> {
+    let present = || {
+        values
+            .iter()
+            .filter(|value| !value.is_blank_or_empty_string())
+    };
+    let all =
+        |is_type: fn(&CellValue) -> bool| present().next().is_some() && present().all(is_type);
+
+    let array: ArrayRef = if all(|value| matches!(value, CellValue::Number(_))) {
+        let numbers = || {
+            values.iter().map(|value| match value {
+                CellValue::Number(number) => Some(*number),
+                _ => None,
+            })
+        };
+        let scale = numbers()
+            .flatten()
+            .map(|number| number.normalize().scale())
+            .max()
+            .unwrap_or_default();
+        if scale == 0 && numbers().flatten().all(|number| number.to_i64().is_some()) {
+            Arc::new(Int64Array::from_iter(
+                numbers().map(|number| number.and_then(|number| number.to_i64())),
+            ))
+        } else {
+            // rescaling rounds numbers that would overflow at the column's
+            // scale, so those columns fall back to floats
+            let rescale = |number: Decimal| {
+                let mut rescaled = number;
+                rescaled.rescale(scale);
+                let mantissa = rescaled.mantissa();
+                let digits = mantissa.unsigned_abs().checked_ilog10().unwrap_or(0) + 1;
+                let fits = rescaled == number
+                    && rescaled.scale() == scale
+                    && digits <= DECIMAL128_MAX_PRECISION as u32;
+                fits.then_some(mantissa)
+            };
+            let decimals = numbers()
+                .map(|number| number.map(rescale))
+                .collect::<Vec<_>>();
+
+            if decimals
+                .iter()
+                .all(|decimal| !matches!(decimal, Some(None)))
+            {
+                Arc::new(
+                    Decimal128Array::from_iter(decimals.into_iter().map(Option::flatten))
+                        .with_precision_and_scale(DECIMAL128_MAX_PRECISION, scale as i8)?,
+                )
+            } else {
+                Arc::new(Float64Array::from_iter(
+                    numbers().map(|number| number.and_then(|number| number.to_f64())),
+                ))
+            }
+        }
+    } else if all(|value| matches!(value, CellValue::Logical(_))) {
+        Arc::new(BooleanArray::from_iter(values.iter().map(
+            |value| match value {
+                CellValue::Logical(logical) => Some(*logical),
+                _ => None,
+            },
+        )))
+    } else if all(|value| matches!(value, CellValue::Date(_))) {
+        Arc::new(Date32Array::from_iter(values.iter().map(
+            |value| match value {
+                CellValue::Date(date) => Some(Date32Type::from_naive_date(*date)),
+                _ => None,
+            },
+        )))
+    } else if all(|value| matches!(value, CellValue::Date(_) | CellValue::DateTime(_))) {
+        Arc::new(TimestampMicrosecondArray::from_iter(values.iter().map(
+            |value| match value {
+                CellValue::Date(date) => {
+                    Some(date.and_time(NaiveTime::MIN).and_utc().timestamp_micros())
+                }
+                CellValue::DateTime(date_time) => Some(date_time.and_utc().timestamp_micros()),
+                _ => None,
+            },
+        )))
+    } else if all(|value| matches!(value, CellValue::Time(_))) {
+        Arc::new(Time64MicrosecondArray::from_iter(values.iter().map(
+            |value| match value {
+                CellValue::Time(time) => Some(
+                    time.num_seconds_from_midnight() as i64 * 1_000_000
+                        + time.nanosecond() as i64 / 1_000,
+                ),
+                _ => None,
+            },
+        )))
+    } else if all(|value| matches!(value, CellValue::Duration(_))) {
+        let durations = || {
+            values.iter().map(|value| match value {
+                CellValue::Duration(duration) => Some(*duration),
+                _ => None,
+            })
+        };
+        // durations with months can't be expressed as a fixed length of time
+        if durations().flatten().all(|duration| duration.months == 0) {
+            Arc::new(DurationMicrosecondArray::from_iter(durations().map(
+                |duration| duration.map(|duration| (duration.seconds * 1e6).round() as i64),
+            )))
+        } else {
+            Arc::new(IntervalMonthDayNanoArray::from_iter(durations().map(
+                |duration| {
+                    duration.map(|duration| {
+                        IntervalMonthDayNano::new(
+                            duration.months,
+                            0,
+                            (duration.seconds * 1e9).round() as i64,
+                        )
+                    })
+                },
+            )))
+        }
+    } else {
+        Arc::new(StringArray::from_iter(values.iter().map(
+            |value| match value {
+                CellValue::Blank => None,
+                value => Some(value.to_string()),
+            },
+        )))
+    };
+
+    Ok(array)
+}
+
+/// Writes a record batch to an Arrow IPC file.
+pub fn record_batch_to_arrow_ipc(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let mut writer = FileWriter::try_new(vec![], &batch.schema())?;
+    writer.write(batch)?;
+    writer.finish()?;
+    Ok(writer.into_inner()?)
+}
@@ -141,6 +141,16 @@ impl CellValues {
         })
     }
 
+    /// Returns the values with rows and columns swapped.
+    pub fn transpose(self) -> Self {
+        let (w, h) = (self.h, self.w);
+        let mut columns = vec![BTreeMap::new(); w as usize];
+        for (x, y, value) in self.into_owned_iter() {
+            columns[y as usize].insert(x as u64, value);
+        }
+        Self { columns, w, h }
+    }
+
     pub fn into_owned_vec(self) -> Vec<Vec<CellValue>> {
         let mut vec = vec![vec![CellValue::Blank; self.w as usize]; self.h as usize];
         for (x, col) in self.columns.into_iter().enumerate() {
@@ -354,6 +364,16 @@ mod test {
         assert_eq!(cell_values.get(1, 2), Some(&CellValue::from("a")));
     }
 
+    #[test]
+    fn transpose() {
+        let cell_values = CellValues::from(vec![vec!["a", "b", "c"], vec!["d", "e", "f"]]);
+        let transposed = cell_values.transpose();
+        assert_eq!(transposed.w, 3);
+        assert_eq!(transposed.h, 2);
+        assert_eq!(transposed.get(1, 0), Some(&CellValue::from("b")));
+        assert_eq!(transposed.get(2, 1), Some(&CellValue::from("f")));
+    }
+
     #[test]
     fn into_iter() {
         let cell_values = CellValues::from(vec![vec!["a", "b"], vec!["c", "d"]]);
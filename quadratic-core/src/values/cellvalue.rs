@@ -7,7 +7,7 @@ use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use super::number::decimal_from_str;
-use super::{Duration, Instant, IsBlank};
+use super::{Duration, Hyperlink, Instant, IsBlank};
 use crate::grid::formats::FormatUpdate;
 use crate::grid::{CodeCellLanguage, CodeCellValue};
 use crate::{
@@ -83,6 +83,9 @@ pub enum CellValue {
     Image(String),
     #[cfg_attr(test, proptest(skip))]
     Import(Import),
+    /// Link to a URL or to a location within the file.
+    #[cfg_attr(test, proptest(skip))]
+    Hyperlink(Hyperlink),
 }
 impl fmt::Display for CellValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -102,6 +105,7 @@ impl fmt::Display for CellValue {
             CellValue::Code(code) => write!(f, "{code:?}"),
             CellValue::Image(s) => write!(f, "{s}"),
             CellValue::Import(import) => write!(f, "{import:?}"),
+            CellValue::Hyperlink(link) => write!(f, "{link}"),
         }
     }
 }
@@ -131,6 +135,7 @@ impl CellValue {
             CellValue::Time(_) => "time",
             CellValue::DateTime(_) => "date time",
             CellValue::Import(_) => "import",
+            CellValue::Hyperlink(_) => "hyperlink",
         }
     }
 
@@ -150,6 +155,8 @@ impl CellValue {
             CellValue::Time(_) => 10,
             CellValue::Instant(_) | CellValue::DateTime(_) => 11,
             CellValue::Import(_) => 12,
+            // languages only receive the display text of a hyperlink
+            CellValue::Hyperlink(_) => 1,
         }
     }
 
@@ -171,6 +178,7 @@ impl CellValue {
             CellValue::Time(d) => d.to_string(),
             CellValue::DateTime(d) => d.to_string(),
             CellValue::Import(import) => import.to_string(),
+            CellValue::Hyperlink(link) => link.repr(),
         }
     }
 
@@ -215,6 +223,7 @@ impl CellValue {
             CellValue::Time(d) => d.format(DEFAULT_TIME_FORMAT).to_string(),
             CellValue::DateTime(d) => d.format(DEFAULT_DATE_TIME_FORMAT).to_string(),
             CellValue::Import(import) => import.to_string(),
+            CellValue::Hyperlink(link) => link.display_text().to_string(),
 
             // these should not render
             CellValue::Code(_) => String::new(),
@@ -320,6 +329,7 @@ impl CellValue {
             CellValue::Duration(d) => d.to_string(),
             CellValue::Error(_) => "[error]".to_string(),
             CellValue::Import(import) => import.to_string(),
+            CellValue::Hyperlink(link) => link.display_text().to_string(),
 
             // this should not be editable
             CellValue::Code(_) => String::new(),
@@ -342,6 +352,7 @@ impl CellValue {
             CellValue::DateTime(t) => t.format("%Y-%m-%dT%H:%M:%S%.3f").to_string(),
             CellValue::Duration(d) => d.to_string(),
             CellValue::Error(_) => "[error]".to_string(),
+            CellValue::Hyperlink(link) => link.display_text().to_string(),
 
             // these should not return a value
             CellValue::Code(_) => String::new(),
@@ -521,6 +532,7 @@ impl CellValue {
             CellValue::Code(_) => 10,
             CellValue::Image(_) => 11,
             CellValue::Import(_) => 12,
+            CellValue::Hyperlink(_) => 13,
         }
     }
 
@@ -563,6 +575,11 @@ impl CellValue {
             (CellValue::Date(a), CellValue::Date(b)) => a.cmp(b),
             (CellValue::Time(a), CellValue::Time(b)) => a.cmp(b),
             (CellValue::Duration(a), CellValue::Duration(b)) => a.cmp(b),
+            (CellValue::Hyperlink(a), CellValue::Hyperlink(b)) => {
+                let a = crate::util::case_fold(a.display_text());
+                let b = crate::util::case_fold(b.display_text());
+                a.cmp(&b)
+            }
             _ => self.type_id().cmp(&other.type_id()),
         }
     }
@@ -739,6 +756,10 @@ impl CellValue {
         matches!(self, CellValue::Import(_))
     }
 
+    pub fn is_hyperlink(&self) -> bool {
+        matches!(self, CellValue::Hyperlink(_))
+    }
+
     /// Returns the contained error, or panics the value is not an error.
     #[cfg(test)]
     #[track_caller]
@@ -772,6 +793,7 @@ impl CellValue {
         match self.as_non_error_value()? {
             CellValue::Blank => Ok(CellValue::Number(0.into())),
             CellValue::Text(s) => Ok(CellValue::parse_from_str(s)),
+            CellValue::Hyperlink(link) => Ok(CellValue::parse_from_str(link.display_text())),
             CellValue::Logical(false) => Ok(CellValue::Number(0.into())),
             CellValue::Logical(true) => Ok(CellValue::Number(1.into())),
             _ => Ok(self.clone()),
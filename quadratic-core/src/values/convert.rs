@@ -140,6 +140,7 @@ impl<'a> TryFrom<&'a CellValue> for String {
             CellValue::Code(_) => Ok(String::new()),
             CellValue::Image(_) => Ok(String::new()),
             CellValue::Import(_) => Ok(String::new()),
+            CellValue::Hyperlink(link) => Ok(link.display_text().to_string()),
         }
     }
 }
@@ -183,6 +184,9 @@ impl<'a> TryFrom<&'a CellValue> for Decimal {
             CellValue::Code(_) => Ok(Decimal::zero()),
             CellValue::Image(_) => Ok(Decimal::zero()),
             CellValue::Import(_) => Ok(Decimal::zero()),
+            CellValue::Hyperlink(link) => {
+                Decimal::try_from(&CellValue::Text(link.display_text().to_string()))
+            }
         }
     }
 }
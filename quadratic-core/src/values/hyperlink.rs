@@ -0,0 +1,191 @@
+//! Hyperlinks stored in a cell.
+//!
+//! A hyperlink either points to an external URL or to a location within the
+//! file (a sheet, a range, or a table). Internal links use the same convention
+//! as Excel: a target that starts with `#` is a reference, e.g.
+//! `#'Sheet 2'!A1:B4` or `#Table1`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    a1::{A1Context, A1Error, A1Selection},
+    grid::SheetId,
+};
+
+/// Prefix used to mark internal links in a link target string.
+pub const INTERNAL_LINK_PREFIX: char = '#';
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HyperlinkTarget {
+    /// External URL, e.g. `https://quadratichq.com`.
+    Url(String),
+
+    /// Reference to a sheet, range, or table within the file, in A1 notation
+    /// (without the leading `#`).
+    Internal(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperlink {
+    pub target: HyperlinkTarget,
+
+    /// Text displayed in the cell. When `None`, the target is displayed.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub text: Option<String>,
+}
+
+impl Hyperlink {
+    pub fn new_url(url: impl Into<String>, text: Option<String>) -> Self {
+        Self {
+            target: HyperlinkTarget::Url(url.into()),
+            text,
+        }
+    }
+
+    pub fn new_internal(reference: impl Into<String>, text: Option<String>) -> Self {
+        Self {
+            target: HyperlinkTarget::Internal(reference.into()),
+            text,
+        }
+    }
+
+    /// Creates a hyperlink from a link target string. Targets that start with
+    /// `#` are internal links; everything else is treated as a URL.
+    pub fn parse(target: &str, text: Option<String>) -> Self {
+        let target = target.trim();
+        let text = text.filter(|text| !text.is_empty());
+        match target.strip_prefix(INTERNAL_LINK_PREFIX) {
+            Some(reference) => Self::new_internal(reference, text),
+            None => Self::new_url(target, text),
+        }
+    }
+
+    pub fn is_internal(&self) -> bool {
+        matches!(self.target, HyperlinkTarget::Internal(_))
+    }
+
+    /// Returns the link target as a string. Internal links are prefixed with
+    /// `#` so the result can be passed back to [`Hyperlink::parse`].
+    pub fn href(&self) -> String {
+        match &self.target {
+            HyperlinkTarget::Url(url) => url.to_owned(),
+            HyperlinkTarget::Internal(reference) => format!("{INTERNAL_LINK_PREFIX}{reference}"),
+        }
+    }
+
+    /// Returns the text displayed in the cell.
+    pub fn display_text(&self) -> &str {
+        match (&self.text, &self.target) {
+            (Some(text), _) => text,
+            (None, HyperlinkTarget::Url(url)) => url,
+            (None, HyperlinkTarget::Internal(reference)) => reference,
+        }
+    }
+
+    /// Resolves an internal link to a selection. Returns `None` for URLs.
+    pub fn internal_selection(
+        &self,
+        default_sheet_id: SheetId,
+        a1_context: &A1Context,
+    ) -> Option<Result<A1Selection, A1Error>> {
+        match &self.target {
+            HyperlinkTarget::Url(_) => None,
+            HyperlinkTarget::Internal(reference) => Some(A1Selection::parse_a1(
+                reference,
+                default_sheet_id,
+                a1_context,
+            )),
+        }
+    }
+
+    /// Returns a formula-source-code representation of the hyperlink.
+    pub fn repr(&self) -> String {
+        match &self.text {
+            Some(text) => format!(
+                "HYPERLINK({}, {})",
+                escape_hyperlink_string(&self.href()),
+                escape_hyperlink_string(text)
+            ),
+            None => format!("HYPERLINK({})", escape_hyperlink_string(&self.href())),
+        }
+    }
+}
+
+/// Escapes a string in a HYPERLINK formula. Quotes are doubled like in Excel
+/// (where these formulas come from), and backslashes are escaped since they
+/// escape the next character in a formula string.
+fn escape_hyperlink_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\"\""))
+}
+
+impl fmt::Display for Hyperlink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let link = Hyperlink::parse("https://quadratichq.com", None);
+        assert_eq!(
+            link.target,
+            HyperlinkTarget::Url("https://quadratichq.com".to_string())
+        );
+        assert!(!link.is_internal());
+        assert_eq!(link.display_text(), "https://quadratichq.com");
+
+        let link = Hyperlink::parse("#Sheet1!A1:B2", Some("Go".to_string()));
+        assert_eq!(
+            link.target,
+            HyperlinkTarget::Internal("Sheet1!A1:B2".to_string())
+        );
+        assert!(link.is_internal());
+        assert_eq!(link.href(), "#Sheet1!A1:B2");
+        assert_eq!(link.display_text(), "Go");
+
+        // empty text falls back to the target
+        let link = Hyperlink::parse("https://quadratichq.com", Some(String::new()));
+        assert_eq!(link.text, None);
+    }
+
+    #[test]
+    fn test_repr() {
+        let link = Hyperlink::new_url("https://quadratichq.com", Some("Quadratic".to_string()));
+        assert_eq!(
+            link.repr(),
+            "HYPERLINK(\"https://quadratichq.com\", \"Quadratic\")"
+        );
+
+        let link = Hyperlink::new_internal("Table1", None);
+        assert_eq!(link.repr(), "HYPERLINK(\"#Table1\")");
+
+        // quotes are doubled, like in Excel
+        let link = Hyperlink::new_url("https://quadratichq.com", Some("Say \"hi\"".to_string()));
+        assert_eq!(
+            link.repr(),
+            "HYPERLINK(\"https://quadratichq.com\", \"Say \"\"hi\"\"\")"
+        );
+    }
+
+    #[test]
+    fn test_internal_selection() {
+        let context = A1Context::default();
+        let sheet_id = SheetId::TEST;
+
+        let link = Hyperlink::new_internal("B2:C3", None);
+        let selection = link
+            .internal_selection(sheet_id, &context)
+            .unwrap()
+            .unwrap();
+        assert_eq!(selection, A1Selection::test_a1("B2:C3"));
+
+        let link = Hyperlink::new_url("https://quadratichq.com", None);
+        assert!(link.internal_selection(sheet_id, &context).is_none());
+    }
+}
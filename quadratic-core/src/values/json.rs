@@ -0,0 +1,392 @@
+//! JSON and NDJSON conversion for importing and exporting data tables.
+//!
+//! Each JSON object is a row. Nested objects are flattened into columns with
+//! dotted names (`{"a": {"b": 1}}` becomes column `a.b`), and columns are
+//! ordered by when their key first appears.
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use indexmap::{IndexMap, IndexSet};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::{Array, ArraySize, CellValue, number::decimal_from_str};
+
+/// The most rows a JSON file can be flattened into. Exploding several arrays
+/// of one object produces every combination of their elements, so a small
+/// file can otherwise flatten into far more rows than fit in memory.
+pub const MAX_JSON_ROWS: usize = 1_000_000;
+
+/// A JSON value whose objects keep the order of their keys, so that columns
+/// follow the order of the file (and exported objects follow the table).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonNode {
+    Object(IndexMap<String, JsonNode>),
+    Array(Vec<JsonNode>),
+    Value(JsonValue),
+}
+
+/// A flattened JSON object as (column name, value) pairs.
+type JsonRow = Vec<(String, CellValue)>;
+
+/// Parses a JSON array of objects, a single JSON object, or NDJSON (one JSON
+/// value per line) into an array whose first row is the column names.
+///
+/// When `explode_arrays` is true, each element of a nested array becomes its
+/// own row (repeating the rest of the object); otherwise arrays are kept as
+/// JSON text.
+pub fn json_to_array(file: &[u8], file_name: &str, explode_arrays: bool) -> Result<Array> {
+    let error = |message: String| anyhow!("Error parsing JSON file {}: {}", file_name, message);
+
+    let records = match serde_json::from_slice::<JsonNode>(file) {
+        Ok(JsonNode::Array(records)) => records,
+        Ok(record) => vec![record],
+        // not a single JSON document, so try NDJSON
+        Err(_) => String::from_utf8_lossy(file)
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<JsonNode>(line)
+                    .map_err(|e| error(format!("line {}: {}", index + 1, e)))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+
+    let mut rows = vec![];
+    for record in records.iter() {
+        rows.extend(flatten_json(record, "", explode_arrays).map_err(|e| error(e.to_string()))?);
+
+        if rows.len() > MAX_JSON_ROWS {
+            return Err(error(too_many_rows()));
+        }
+    }
+
+    let columns = rows
+        .iter()
+        .flat_map(|row| row.iter().map(|(name, _)| name.as_str()))
+        .collect::<IndexSet<_>>();
+
+    if rows.is_empty() || columns.is_empty() {
+        return Err(error("File is empty".to_string()));
+    }
+
+    let array_size = ArraySize::new_or_err(columns.len() as u32, rows.len() as u32 + 1)
+        .map_err(|e| error(e.to_string()))?;
+    let mut array = Array::new_empty(array_size);
+
+    let headers = columns.iter().map(|&name| name.into()).collect::<Vec<_>>();
+    array.set_row(0, &headers)?;
+
+    for (y, row) in rows.iter().enumerate() {
+        for (name, value) in row {
+            if let Some(x) = columns.get_index_of(name.as_str()) {
+                array.set(x as u32, y as u32 + 1, value.to_owned(), false)?;
+            }
+        }
+    }
+
+    Ok(array)
+}
+
+/// Flattens a JSON value into one or more rows. Only exploded arrays produce
+/// more than one row; an object with several exploded arrays produces every
+/// combination of their elements, up to [`MAX_JSON_ROWS`].
+fn flatten_json(value: &JsonNode, prefix: &str, explode_arrays: bool) -> Result<Vec<JsonRow>> {
+    let name = if prefix.is_empty() { "value" } else { prefix };
+
+    let rows = match value {
+        JsonNode::Object(object) if !object.is_empty() => {
+            let mut rows = vec![vec![]];
+            for (key, value) in object {
+                let key = if prefix.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                let children = flatten_json(value, &key, explode_arrays)?;
+
+                if rows.len().saturating_mul(children.len()) > MAX_JSON_ROWS {
+                    bail!(too_many_rows());
+                }
+
+                rows = rows
+                    .iter()
+                    .flat_map(|row| {
+                        children.iter().map(move |child| {
+                            let mut row: JsonRow = row.clone();
+                            row.extend(child.iter().cloned());
+                            row
+                        })
+                    })
+                    .collect();
+            }
+            rows
+        }
+        // an empty array keeps its row, with its columns left blank
+        JsonNode::Array(values) if explode_arrays && values.is_empty() => vec![vec![]],
+        JsonNode::Array(values) if explode_arrays => {
+            let mut rows = vec![];
+            for value in values {
+                rows.extend(flatten_json(value, prefix, explode_arrays)?);
+
+                if rows.len() > MAX_JSON_ROWS {
+                    bail!(too_many_rows());
+                }
+            }
+            rows
+        }
+        JsonNode::Value(value) => vec![vec![(name.to_owned(), json_to_cell_value(value))]],
+        // arrays that aren't exploded and empty objects are kept as JSON text
+        value => vec![vec![(
+            name.to_owned(),
+            CellValue::Text(serde_json::to_string(value)?),
+        )]],
+    };
+
+    Ok(rows)
+}
+
+fn too_many_rows() -> String {
+    format!("File flattens into more than {MAX_JSON_ROWS} rows")
+}
+
+/// Converts a JSON value to a cell value. Strings are checked for ISO 8601
+/// dates, date times, and times; nested arrays and objects are kept as JSON
+/// text.
+pub fn json_to_cell_value(value: &JsonValue) -> CellValue {
+    match value {
+        JsonValue::Null => CellValue::Blank,
+        JsonValue::Bool(logical) => CellValue::Logical(*logical),
+        JsonValue::Number(number) => {
+            let text = number.to_string();
+            decimal_from_str(&text)
+                .ok()
+                .or_else(|| Decimal::from_scientific(&text).ok())
+                .map_or(CellValue::Text(text), CellValue::Number)
+        }
+        JsonValue::String(text) => iso_to_cell_value(text),
+        value => CellValue::Text(value.to_string()),
+    }
+}
+
+fn iso_to_cell_value(text: &str) -> CellValue {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        CellValue::Date(date)
+    } else if let Ok(date_time) = DateTime::parse_from_rfc3339(text) {
+        CellValue::DateTime(date_time.naive_utc())
+    } else if let Ok(date_time) = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f") {
+        CellValue::DateTime(date_time)
+    } else if let Ok(time) = NaiveTime::parse_from_str(text, "%H:%M:%S%.f") {
+        CellValue::Time(time)
+    } else {
+        CellValue::Text(text.to_owned())
+    }
+}
+
+/// Converts a cell value to a JSON value. Dates and times are written as ISO
+/// 8601 strings.
+pub fn cell_value_to_json(value: &CellValue) -> JsonValue {
+    match value {
+        CellValue::Blank => JsonValue::Null,
+        CellValue::Logical(logical) => JsonValue::Bool(*logical),
+        CellValue::Number(number) => {
+            if number.is_integer()
+                && let Some(number) = number.to_i64()
+            {
+                JsonValue::from(number)
+            } else {
+                number.to_f64().map_or(JsonValue::Null, JsonValue::from)
+            }
+        }
+        CellValue::Date(date) => JsonValue::String(date.format("%Y-%m-%d").to_string()),
+        CellValue::DateTime(date_time) => {
+            JsonValue::String(date_time.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+        }
+        CellValue::Time(time) => JsonValue::String(time.format("%H:%M:%S%.f").to_string()),
+        value => JsonValue::String(value.to_string()),
+    }
+}
+
+/// Converts rows of cell values to a JSON array of objects, nesting dotted
+/// column names (the reverse of [`json_to_array`]'s flattening). Keys are in
+/// the order of the columns.
+pub fn rows_to_json(columns: &[String], rows: &[Vec<CellValue>]) -> JsonNode {
+    let records = rows
+        .iter()
+        .map(|row| {
+            let mut object = IndexMap::new();
+            for (name, value) in columns.iter().zip(row) {
+                insert_dotted(
+                    &mut object,
+                    name,
+                    JsonNode::Value(cell_value_to_json(value)),
+                );
+            }
+            JsonNode::Object(object)
+        })
+        .collect();
+
+    JsonNode::Array(records)
+}
+
+/// Inserts a value at a dotted path, falling back to the full name as a key
+/// when part of the path is already a value.
+fn insert_dotted(object: &mut IndexMap<String, JsonNode>, name: &str, value: JsonNode) {
+    let parts = name.split('.').collect::<Vec<_>>();
+    let Some((last, path)) = parts.split_last() else {
+        return;
+    };
+
+    let mut blocked = path.is_empty() || parts.iter().any(|part| part.is_empty());
+    let mut current = Some(&*object);
+    for part in path {
+        match current.and_then(|object| object.get(*part)) {
+            Some(JsonNode::Object(child)) => current = Some(child),
+            Some(_) => blocked = true,
+            None => current = None,
+        }
+    }
+    if blocked {
+        object.insert(name.to_owned(), value);
+        return;
+    }
+
+    let mut current = object;
+    for part in path {
+        let entry = current
+            .entry(part.to_string())
+            .or_insert_with(|| JsonNode::Object(IndexMap::new()));
+        let JsonNode::Object(child) = entry else {
+            return;
+        };
+        current = child;
+    }
+    current.insert(last.to_string(), value);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn row(array: &Array, y: u32) -> Vec<CellValue> {
+        (0..array.width())
+            .map(|x| array.get(x, y).unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_to_array() {
+        let file = br#"[
+            {"name": "a", "count": 1, "active": true, "address": {"city": "Paris"}},
+            {"name": "b", "created": "2024-01-02", "tags": ["x", "y"]}
+        ]"#;
+        let array = json_to_array(file, "test.json", false).unwrap();
+
+        assert_eq!(
+            row(&array, 0),
+            vec![
+                "name".into(),
+                "count".into(),
+                "active".into(),
+                "address.city".into(),
+                "created".into(),
+                "tags".into(),
+            ]
+        );
+        assert_eq!(
+            row(&array, 1),
+            vec![
+                "a".into(),
+                CellValue::Number(1.into()),
+                CellValue::Logical(true),
+                "Paris".into(),
+                CellValue::Blank,
+                CellValue::Blank,
+            ]
+        );
+        assert_eq!(
+            array.get(4, 2).unwrap(),
+            &CellValue::Date(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap())
+        );
+        assert_eq!(
+            array.get(5, 2).unwrap(),
+            &CellValue::Text(r#"["x","y"]"#.into())
+        );
+    }
+
+    #[test]
+    fn test_ndjson_to_array_exploding_arrays() {
+        let file = b"{\"id\": 1, \"items\": [{\"sku\": \"a\"}, {\"sku\": \"b\"}]}\n\n{\"id\": 2, \"items\": []}\n";
+        let array = json_to_array(file, "test.ndjson", true).unwrap();
+
+        assert_eq!(row(&array, 0), vec!["id".into(), "items.sku".into()]);
+        assert_eq!(array.height(), 4);
+        assert_eq!(array.get(1, 2).unwrap(), &CellValue::Text("b".into()));
+        assert_eq!(array.get(0, 2).unwrap(), &CellValue::Number(1.into()));
+        assert_eq!(array.get(0, 3).unwrap(), &CellValue::Number(2.into()));
+
+        assert!(json_to_array(b"{\"a\": 1}\n{oops", "bad.ndjson", false).is_err());
+        assert!(json_to_array(b"[]", "empty.json", false).is_err());
+    }
+
+    #[test]
+    fn test_json_to_array_keeps_key_order() {
+        let file = br#"{"z": 1, "a": {"y": 2, "b": 3}, "m": [{"x": 1, "c": 2}]}"#;
+        let array = json_to_array(file, "test.json", false).unwrap();
+
+        assert_eq!(
+            row(&array, 0),
+            vec!["z".into(), "a.y".into(), "a.b".into(), "m".into()]
+        );
+        assert_eq!(
+            array.get(3, 1).unwrap(),
+            &CellValue::Text(r#"[{"x":1,"c":2}]"#.into())
+        );
+    }
+
+    #[test]
+    fn test_json_to_array_limits_exploded_rows() {
+        let values = (0..1001)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let file = format!(r#"{{"a": [{values}], "b": [{values}]}}"#);
+
+        let error = json_to_array(file.as_bytes(), "big.json", true).unwrap_err();
+        assert!(error.to_string().contains("more than"));
+
+        // the same arrays are fine when they aren't exploded
+        let array = json_to_array(file.as_bytes(), "big.json", false).unwrap();
+        assert_eq!(array.height(), 2);
+    }
+
+    #[test]
+    fn test_rows_to_json() {
+        let columns = vec!["name".to_string(), "address.city".to_string()];
+        let rows = vec![
+            vec!["a".into(), "Paris".into()],
+            vec![CellValue::Number(Decimal::new(15, 1)), CellValue::Blank],
+        ];
+
+        assert_eq!(
+            serde_json::to_value(rows_to_json(&columns, &rows)).unwrap(),
+            json!([
+                {"name": "a", "address": {"city": "Paris"}},
+                {"name": 1.5, "address": {"city": null}},
+            ])
+        );
+
+        // keys follow the order of the columns
+        let columns = vec!["z".to_string(), "a".to_string()];
+        let rows = vec![vec!["1".into(), "2".into()]];
+        assert_eq!(
+            serde_json::to_string(&rows_to_json(&columns, &rows)).unwrap(),
+            r#"[{"z":"1","a":"2"}]"#
+        );
+    }
+}
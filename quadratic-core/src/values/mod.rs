@@ -13,7 +13,9 @@ mod convert;
 pub mod date_time;
 pub mod empty_values_cache;
 mod from_js;
+pub mod hyperlink;
 mod isblank;
+pub mod json;
 pub mod number;
 pub mod parquet;
 mod time;
@@ -22,6 +24,7 @@ pub use array::Array;
 pub use array_size::{ArraySize, Axis};
 pub use cellvalue::{CellValue, CellValueHash};
 pub use convert::CoerceInto;
+pub use hyperlink::{Hyperlink, HyperlinkTarget};
 pub use isblank::IsBlank;
 pub use time::{Duration, Instant};
 
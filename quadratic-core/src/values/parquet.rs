@@ -1,6 +1,18 @@
+use std::sync::Arc;
+
 use anyhow::{Result, anyhow};
+use arrow_array::{
+    ArrayRef, IntervalDayTimeArray, RecordBatch, StringArray,
+    cast::AsArray,
+    types::{DurationMicrosecondType, IntervalDayTime},
+};
+use arrow_schema::{DataType, Field, IntervalUnit, Schema, TimeUnit};
 use bytes::Bytes;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::{
+    arrow::{ArrowWriter, arrow_reader::ParquetRecordBatchReaderBuilder},
+    basic::Compression,
+    file::properties::WriterProperties,
+};
 
 use crate::{CellValue, arrow::arrow_col_to_cell_value_vec};
 
@@ -66,6 +78,65 @@ pub fn parquet_to_array(
 
     Ok(cell_values)
 }
+
+/// Parquet can't store arrow durations or month/day/nano intervals, so
+/// durations are written as day/millisecond intervals and intervals with
+/// months are written as text.
+fn parquet_compatible_col(col: &ArrayRef) -> Result<ArrayRef> {
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+    Ok(match col.data_type() {
+        DataType::Duration(TimeUnit::Microsecond) => Arc::new(IntervalDayTimeArray::from_iter(
+            col.as_primitive::<DurationMicrosecondType>()
+                .iter()
+                .map(|value| {
+                    value.map(|micros| {
+                        IntervalDayTime::new(
+                            (micros / MICROS_PER_DAY) as i32,
+                            ((micros % MICROS_PER_DAY) / 1_000) as i32,
+                        )
+                    })
+                }),
+        )),
+        DataType::Interval(IntervalUnit::MonthDayNano) => Arc::new(StringArray::from_iter(
+            arrow_col_to_cell_value_vec(col)?
+                .into_iter()
+                .map(|value| match value {
+                    CellValue::Blank => None,
+                    value => Some(value.to_string()),
+                }),
+        )),
+        _ => col.clone(),
+    })
+}
+
+/// Writes a record batch to a Parquet file.
+pub fn record_batch_to_parquet(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let columns = batch
+        .columns()
+        .iter()
+        .map(parquet_compatible_col)
+        .collect::<Result<Vec<_>>>()?;
+    let fields = batch
+        .schema()
+        .fields()
+        .iter()
+        .zip(&columns)
+        .map(|(field, col)| Field::new(field.name(), col.data_type().clone(), true))
+        .collect::<Vec<_>>();
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut buffer = vec![];
+    let mut writer = ArrowWriter::try_new(&mut buffer, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(buffer)
+}
+
 #[cfg(test)]
 mod test {
     use std::fs::File;
@@ -2,7 +2,10 @@ use std::str::FromStr;
 use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
 
 use crate::{
-    Rect, controller::GridController, grid::SheetId, wasm_bindings::error::handle_core_result,
+    Rect,
+    controller::GridController,
+    grid::{SheetId, js_types::JsSnackbarSeverity, series::fill_series::FillSeriesOptions},
+    wasm_bindings::{error::handle_core_result, js::jsClientMessage},
 };
 
 #[allow(non_snake_case)]
@@ -29,4 +32,75 @@ impl GridController {
         });
         Ok(())
     }
+
+    /// Fills each column (or row) of range from its first cell using an
+    /// explicit step, series type, and optional stop value.
+    #[wasm_bindgen(js_name = "fillSeries")]
+    pub fn js_fill_series(
+        &mut self,
+        sheet_id: String,
+        range: String,
+        options: String,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) -> Result<(), JsValue> {
+        handle_core_result(|| -> Result<(), String> {
+            let range: Rect = serde_json::from_str(&range).map_err(|e| e.to_string())?;
+            let options: FillSeriesOptions =
+                serde_json::from_str(&options).map_err(|e| e.to_string())?;
+            let sheet_id = SheetId::from_str(&sheet_id).map_err(|e| e.to_string())?;
+            self.fill_series(sheet_id, range, options, cursor, is_ai)
+                .map_err(|e| e.to_string())
+        });
+        Ok(())
+    }
+
+    /// Fills the blank cells of the target column by inferring how the source
+    /// columns are transformed into the column's existing values.
+    #[wasm_bindgen(js_name = "flashFill")]
+    pub fn js_flash_fill(
+        &mut self,
+        sheet_id: String,
+        source: String,
+        target_x: i64,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) -> Result<(), JsValue> {
+        handle_core_result(|| -> Result<(), String> {
+            let source: Rect = serde_json::from_str(&source).map_err(|e| e.to_string())?;
+            let sheet_id = SheetId::from_str(&sheet_id).map_err(|e| e.to_string())?;
+            let filled = self
+                .flash_fill(sheet_id, source, target_x, cursor, is_ai)
+                .map_err(|e| e.to_string())?;
+            if !filled {
+                let severity = JsSnackbarSeverity::Warning;
+                jsClientMessage(
+                    "Flash fill could not find a pattern. Try adding another example.".into(),
+                    severity.to_string(),
+                );
+            }
+            Ok(())
+        });
+        Ok(())
+    }
+
+    /// Returns the file's custom lists as a string[][].
+    #[wasm_bindgen(js_name = "getCustomLists")]
+    pub fn js_get_custom_lists(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(self.custom_lists()).map_err(|_| JsValue::UNDEFINED)
+    }
+
+    /// Replaces the file's custom lists with a string[][].
+    #[wasm_bindgen(js_name = "setCustomLists")]
+    pub fn js_set_custom_lists(
+        &mut self,
+        custom_lists: JsValue,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) -> Result<(), JsValue> {
+        let custom_lists: Vec<Vec<String>> = serde_wasm_bindgen::from_value(custom_lists)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.set_custom_lists(custom_lists, cursor, is_ai);
+        Ok(())
+    }
 }
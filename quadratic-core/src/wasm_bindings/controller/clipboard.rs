@@ -7,7 +7,10 @@ use crate::grid::js_types::JsClipboard;
 use crate::wasm_bindings::capture_core_error;
 use crate::{
     SheetPos, SheetRect,
-    controller::{GridController, operations::clipboard::PasteSpecial},
+    controller::{
+        GridController,
+        operations::clipboard::{PasteSpecial, PasteSpecialOptions},
+    },
     grid::SheetId,
 };
 
@@ -61,6 +64,34 @@ impl GridController {
         Ok(())
     }
 
+    #[wasm_bindgen(js_name = "pasteSpecialFromClipboard")]
+    pub fn js_paste_special_from_clipboard(
+        &mut self,
+        selection: String,
+        js_clipboard: Vec<u8>,
+        special: &str,
+        options: String,
+        cursor: Option<String>,
+        is_ai: bool,
+    ) -> Result<(), JsValue> {
+        let special = PasteSpecial::from(special);
+        let options = serde_json::from_str::<PasteSpecialOptions>(&options)
+            .map_err(|_| "Unable to parse PasteSpecialOptions")?;
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        let js_clipboard =
+            serde_json::from_slice(&js_clipboard).map_err(|_| "Unable to parse js_clipboard")?;
+        self.paste_special_from_clipboard(
+            &selection,
+            js_clipboard,
+            special,
+            options,
+            cursor,
+            is_ai,
+        );
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = "moveCells")]
     pub fn js_move_cells(
         &mut self,
@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
 
 use crate::a1::A1Selection;
 use crate::controller::GridController;
+use crate::controller::export::{CsvExportOptions, ExcelExportOptions};
 
 #[wasm_bindgen]
 impl GridController {
@@ -15,6 +18,53 @@ impl GridController {
             .map_err(|e| e.to_string())?;
         Ok(output)
     }
+
+    /// Returns a CSV string of the selection using the CsvExportOptions
+    #[wasm_bindgen(js_name = "exportCsvSelectionWithOptions")]
+    pub fn js_export_csv_selection_with_options(
+        &self,
+        selection: String,
+        options: String,
+    ) -> Result<String, JsValue> {
+        let mut selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        let options = serde_json::from_str::<CsvExportOptions>(&options)
+            .map_err(|_| "Unable to parse CsvExportOptions")?;
+        let output = self
+            .export_csv_selection_with_options(&mut selection, &options)
+            .map_err(|e| e.to_string())?;
+        Ok(output)
+    }
+
+    /// Returns a zip file with a CSV file for each sheet
+    #[wasm_bindgen(js_name = "exportCsvSheetsZip")]
+    pub fn js_export_csv_sheets_zip(&self, options: String) -> Result<Vec<u8>, JsValue> {
+        let options = serde_json::from_str::<CsvExportOptions>(&options)
+            .map_err(|_| "Unable to parse CsvExportOptions")?;
+        self.export_csv_sheets_zip(&options)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns a Markdown table of the selection
+    #[wasm_bindgen(js_name = "exportMarkdownSelection")]
+    pub fn js_export_markdown_selection(&self, selection: String) -> Result<String, JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        self.export_markdown_selection(&selection)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Returns an HTML table of the selection
+    #[wasm_bindgen(js_name = "exportHtmlSelection")]
+    pub fn js_export_html_selection(&self, selection: String) -> Result<String, JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        self.export_html_selection(&selection)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 }
 
 #[wasm_bindgen]
@@ -27,4 +77,68 @@ impl GridController {
             Err(e) => Err(JsValue::from_str(&e.to_string())),
         }
     }
+
+    /// Returns an excel file with charts embedded as pictures. `chart_images`
+    /// is a JSON object of fallback images (data URLs) for HTML charts, keyed
+    /// by the chart's name.
+    #[wasm_bindgen(js_name = "exportExcelWithChartImages")]
+    pub fn js_export_excel_with_chart_images(
+        &self,
+        chart_images: String,
+    ) -> Result<Vec<u8>, JsValue> {
+        let chart_images = serde_json::from_str::<HashMap<String, String>>(&chart_images)
+            .map_err(|_| "Unable to parse chart images")?;
+        self.export_excel_with_chart_images(&chart_images)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Returns an excel file using the ExcelExportOptions
+    #[wasm_bindgen(js_name = "exportExcelWithOptions")]
+    pub fn js_export_excel_with_options(&self, options: String) -> Result<Vec<u8>, JsValue> {
+        let options = serde_json::from_str::<ExcelExportOptions>(&options)
+            .map_err(|_| "Unable to parse ExcelExportOptions")?;
+        self.export_excel_with_options(&options)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns a Parquet file of the selection
+    #[wasm_bindgen(js_name = "exportParquetSelection")]
+    pub fn js_export_parquet_selection(&self, selection: String) -> Result<Vec<u8>, JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        self.export_parquet_selection(&selection)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Returns an Arrow IPC file of the selection
+    #[wasm_bindgen(js_name = "exportArrowSelection")]
+    pub fn js_export_arrow_selection(&self, selection: String) -> Result<Vec<u8>, JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        self.export_arrow_selection(&selection)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns a JSON array of objects, one for each row of the table
+    #[wasm_bindgen(js_name = "exportTableJson")]
+    pub fn js_export_table_json(&self, table_name: &str) -> Result<String, JsValue> {
+        self.export_table_json(table_name)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns an OpenDocument spreadsheet of the grid
+    #[wasm_bindgen(js_name = "exportOds")]
+    pub fn js_export_ods(&self) -> Result<Vec<u8>, JsValue> {
+        self.export_ods()
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 }
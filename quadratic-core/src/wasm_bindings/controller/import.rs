@@ -5,6 +5,7 @@ use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::Pos;
 use crate::controller::GridController;
+use crate::controller::operations::csv::CsvImportOptions;
 use crate::grid::{Grid, SheetId};
 use crate::wasm_bindings::capture_core_error;
 use crate::wasm_bindings::js::jsImportProgress;
@@ -73,6 +74,70 @@ impl GridController {
     }
 }
 
+#[wasm_bindgen]
+impl GridController {
+    #[wasm_bindgen(js_name = "importCsvWithOptions")]
+    pub fn js_import_csv_with_options(
+        file: &[u8],
+        file_name: &str,
+        options: &str,
+    ) -> Result<GridController, JsValue> {
+        let options = serde_json::from_str::<CsvImportOptions>(options)
+            .map_err(|_| "Unable to parse CsvImportOptions")?;
+        let mut grid = Grid::new_blank();
+        let sheet_id = grid.add_sheet(None);
+        let insert_at = pos![A1];
+
+        let mut grid_controller = GridController::from_grid(grid, 0);
+        grid_controller
+            .import_csv_with_options(sheet_id, file, file_name, insert_at, None, &options, false)
+            .map_err(|e| e.to_string())?;
+
+        Ok(grid_controller)
+    }
+
+    #[wasm_bindgen(js_name = "importCsvWithOptionsIntoExistingFile")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn js_import_csv_with_options_into_existing_file(
+        &mut self,
+        file: &[u8],
+        file_name: &str,
+        sheet_id: &str,
+        insert_at: &str,
+        cursor: Option<String>,
+        options: &str,
+        is_ai: bool,
+    ) -> Result<(), JsValue> {
+        let sheet_id = SheetId::from_str(sheet_id).map_err(|e| e.to_string())?;
+        let insert_at = serde_json::from_str::<Pos>(insert_at).map_err(|e| e.to_string())?;
+        let options = serde_json::from_str::<CsvImportOptions>(options)
+            .map_err(|_| "Unable to parse CsvImportOptions")?;
+        self.import_csv_with_options(
+            sheet_id, file, file_name, insert_at, cursor, &options, is_ai,
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+
+    /// Returns the first rows of a CSV file as a CsvPreview, without
+    /// importing it.
+    #[wasm_bindgen(js_name = "previewCsv")]
+    pub fn js_preview_csv(
+        file: &[u8],
+        file_name: &str,
+        options: &str,
+        max_rows: u32,
+    ) -> Result<JsValue, JsValue> {
+        let options = serde_json::from_str::<CsvImportOptions>(options)
+            .map_err(|_| "Unable to parse CsvImportOptions")?;
+        let preview = GridController::preview_csv(file, file_name, &options, max_rows)
+            .map_err(|e| e.to_string())?;
+
+        serde_wasm_bindgen::to_value(&preview).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
 #[wasm_bindgen]
 impl GridController {
     #[wasm_bindgen(js_name = "importExcel")]
@@ -150,3 +215,63 @@ impl GridController {
         Ok(())
     }
 }
+
+#[wasm_bindgen]
+impl GridController {
+    #[wasm_bindgen(js_name = "importJson")]
+    pub fn js_import_json(
+        file: &[u8],
+        file_name: &str,
+        explode_arrays: bool,
+    ) -> Result<GridController, JsValue> {
+        let mut grid = Grid::new_blank();
+        let sheet_id = grid.add_sheet(None);
+        let insert_at = pos![A1];
+
+        let mut grid_controller = GridController::from_grid(grid, 0);
+        grid_controller
+            .import_json(
+                sheet_id,
+                file,
+                file_name,
+                insert_at,
+                None,
+                explode_arrays,
+                false,
+            )
+            .map_err(|e| e.to_string())?;
+
+        Ok(grid_controller)
+    }
+}
+
+#[wasm_bindgen]
+impl GridController {
+    #[wasm_bindgen(js_name = "importJsonIntoExistingFile")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn js_import_json_into_existing_file(
+        &mut self,
+        file: &[u8],
+        file_name: &str,
+        sheet_id: &str,
+        insert_at: &str,
+        cursor: Option<String>,
+        explode_arrays: bool,
+        is_ai: bool,
+    ) -> Result<(), JsValue> {
+        let sheet_id = SheetId::from_str(sheet_id).map_err(|e| e.to_string())?;
+        let insert_at = serde_json::from_str::<Pos>(insert_at).map_err(|e| e.to_string())?;
+        self.import_json(
+            sheet_id,
+            file,
+            file_name,
+            insert_at,
+            cursor,
+            explode_arrays,
+            is_ai,
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
@@ -13,7 +13,9 @@ use axum_extra::{
 };
 use jsonwebtoken::jwk::JwkSet;
 use quadratic_rust_shared::auth::jwt::authorize;
+use quadratic_rust_shared::quadratic_api::{FilePermRole, can_edit, can_view, get_file_perms};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::error::{FilesError, Result};
 
@@ -65,5 +67,65 @@ where
     }
 }
 
+/// Validate that the user can view a file
+pub(crate) fn validate_can_view_file(roles: &[FilePermRole]) -> Result<()> {
+    if !can_view(roles) && !can_edit(roles) {
+        return Err(FilesError::FilePermissions(
+            "You do not have permission to view this file".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate that the user can edit a file
+pub(crate) fn validate_can_edit_file(roles: &[FilePermRole]) -> Result<()> {
+    if !can_edit(roles) {
+        return Err(FilesError::FilePermissions(
+            "You do not have permission to edit this file".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get the user's permissions on a file from the quadratic api.
+pub(crate) async fn get_permissions(
+    quadratic_api_uri: &str,
+    jwt: &str,
+    file_id: Uuid,
+) -> Result<Vec<FilePermRole>> {
+    // default to all roles for tests
+    if cfg!(test) {
+        return Ok(vec![FilePermRole::FileView, FilePermRole::FileEdit]);
+    }
+
+    let (permissions, _) = get_file_perms(quadratic_api_uri, jwt.to_owned(), file_id)
+        .await
+        .map_err(|e| FilesError::FilePermissions(e.to_string()))?;
+
+    Ok(permissions)
+}
+
 #[cfg(test)]
-pub(crate) mod tests {}
+pub(crate) mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_file_permissions() {
+        let view = [FilePermRole::FileView];
+        let edit = [FilePermRole::FileView, FilePermRole::FileEdit];
+
+        assert!(validate_can_view_file(&view).is_ok());
+        assert!(validate_can_view_file(&edit).is_ok());
+        assert!(validate_can_view_file(&[]).is_err());
+
+        assert!(validate_can_edit_file(&edit).is_ok());
+        assert_eq!(
+            validate_can_edit_file(&view),
+            Err(FilesError::FilePermissions(
+                "You do not have permission to edit this file".into()
+            ))
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Checkpoint Garbage Collection
+//!
+//! Every time transactions are processed a new checkpoint is written, which
+//! supersedes the previous one.  Superseded checkpoints are deleted once they
+//! have been superseded for `checkpoint_gc_age_s`, giving clients that were
+//! loading them time to finish, unless they are kept as versions.
+
+use chrono::{DateTime, Duration, Utc};
+use quadratic_rust_shared::storage::{Storage, StorageContainer};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::version::get_versions;
+
+/// Parse the sequence number from a checkpoint key of the file, or None if the
+/// key isn't one of the file's checkpoints.
+pub(crate) fn checkpoint_sequence_num(file_id: Uuid, key: &str) -> Option<u64> {
+    key.strip_prefix(&format!("{file_id}-"))?
+        .strip_suffix(".grid")?
+        .parse()
+        .ok()
+}
+
+/// Delete a file's superseded checkpoints, keeping the latest checkpoint and
+/// any checkpoint kept as a version.  Returns the deleted keys.
+pub(crate) async fn collect_checkpoints(
+    storage: &StorageContainer,
+    file_id: Uuid,
+    min_age: Duration,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>> {
+    let versions = get_versions(storage, file_id)
+        .await?
+        .into_iter()
+        .map(|version| version.key)
+        .collect::<HashSet<_>>();
+
+    let mut checkpoints = storage
+        .list(&format!("{file_id}-"))
+        .await?
+        .into_iter()
+        .filter_map(|object| Some((checkpoint_sequence_num(file_id, &object.key)?, object)))
+        .collect::<Vec<_>>();
+    checkpoints.sort_by_key(|(sequence_num, _)| *sequence_num);
+
+    // a checkpoint is superseded when the next one is written
+    let superseded = checkpoints
+        .windows(2)
+        .filter_map(|pair| {
+            let (checkpoint, next) = (&pair[0].1, &pair[1].1);
+            let superseded_at = next.last_modified?;
+            let is_old = superseded_at + min_age <= now;
+
+            (is_old && !versions.contains(&checkpoint.key)).then(|| checkpoint.key.to_owned())
+        })
+        .collect::<Vec<_>>();
+
+    for key in superseded.iter() {
+        storage.delete(key).await?;
+    }
+
+    if !superseded.is_empty() {
+        tracing::info!(
+            "Deleted {} superseded checkpoint(s) of file {file_id}",
+            superseded.len()
+        );
+    }
+
+    Ok(superseded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::key;
+    use crate::version::{RetentionPolicy, add_version};
+    use quadratic_rust_shared::storage::file_system::{FileSystem, FileSystemConfig};
+
+    #[test]
+    fn parses_checkpoint_keys() {
+        let file_id = Uuid::new_v4();
+
+        assert_eq!(
+            checkpoint_sequence_num(file_id, &key(file_id, 12)),
+            Some(12)
+        );
+        assert_eq!(
+            checkpoint_sequence_num(file_id, &format!("{file_id}-versions.json")),
+            None
+        );
+        assert_eq!(
+            checkpoint_sequence_num(Uuid::new_v4(), &key(file_id, 12)),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn collects_superseded_checkpoints() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let storage = StorageContainer::FileSystem(FileSystem::new(FileSystemConfig {
+            path: path.to_string_lossy().to_string(),
+            encryption_keys: vec![],
+        }));
+        let file_id = Uuid::new_v4();
+        let policy = RetentionPolicy {
+            hourly_retention_hours: 24,
+            daily_retention_days: 30,
+        };
+
+        for sequence_num in [1, 2, 3, 4] {
+            let body = sequence_num.to_string().into_bytes();
+            storage
+                .write(&key(file_id, sequence_num), &body.into())
+                .await
+                .unwrap();
+        }
+
+        // checkpoint 2 is kept as a version
+        add_version(&storage, &policy, file_id, 2, Utc::now())
+            .await
+            .unwrap();
+
+        // nothing has been superseded for long enough yet
+        let deleted = collect_checkpoints(&storage, file_id, Duration::hours(1), Utc::now())
+            .await
+            .unwrap();
+        assert!(deleted.is_empty());
+
+        let later = Utc::now() + Duration::hours(2);
+        let deleted = collect_checkpoints(&storage, file_id, Duration::hours(1), later)
+            .await
+            .unwrap();
+        assert_eq!(deleted, vec![key(file_id, 1), key(file_id, 3)]);
+
+        assert!(!storage.exists(&key(file_id, 1)).await.unwrap());
+        assert!(storage.exists(&key(file_id, 2)).await.unwrap());
+        assert!(!storage.exists(&key(file_id, 3)).await.unwrap());
+        assert!(storage.exists(&key(file_id, 4)).await.unwrap());
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+}
@@ -8,6 +8,7 @@
 use crate::error::{FilesError, Result};
 use dotenv::dotenv;
 use quadratic_rust_shared::environment::Environment;
+use quadratic_rust_shared::pubsub::connection::PubSubType;
 use serde::Deserialize;
 use strum_macros::Display;
 
@@ -27,8 +28,17 @@ pub(crate) struct Config {
     pub(crate) files_per_check: i64,
     pub(crate) truncate_file_check_s: i64,
     pub(crate) truncate_transaction_age_days: i64,
+    #[serde(default = "default_version_hourly_retention_hours")]
+    pub(crate) version_hourly_retention_hours: i64,
+    #[serde(default = "default_version_daily_retention_days")]
+    pub(crate) version_daily_retention_days: i64,
+    #[serde(default = "default_checkpoint_gc_age_s")]
+    pub(crate) checkpoint_gc_age_s: i64,
     pub(crate) environment: Environment,
 
+    // PubSub Type: redis-streams or memory
+    #[serde(default)]
+    pub(crate) pubsub_type: PubSubType,
     pub(crate) pubsub_host: String,
     pub(crate) pubsub_port: String,
     pub(crate) pubsub_password: String,
@@ -53,6 +63,18 @@ pub(crate) struct Config {
     pub(crate) storage_encryption_keys: Option<Vec<String>>,
 }
 
+fn default_version_hourly_retention_hours() -> i64 {
+    24
+}
+
+fn default_version_daily_retention_days() -> i64 {
+    30
+}
+
+fn default_checkpoint_gc_age_s() -> i64 {
+    3600
+}
+
 /// Load the global configuration from the environment into Config.
 pub(crate) fn config() -> Result<Config> {
     let filename = if cfg!(test) { ".env.test" } else { ".env" };
@@ -13,7 +13,9 @@ use quadratic_rust_shared::{SharedError, clean_errors, storage::error::Storage a
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-pub(crate) type Result<T> = std::result::Result<T, FilesError>;
+use crate::metrics::METRICS;
+
+pub type Result<T> = std::result::Result<T, FilesError>;
 
 #[derive(Error, Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum FilesError {
@@ -32,6 +34,9 @@ pub enum FilesError {
     #[error("Unable to export file {0}: {1}")]
     ExportFile(String, String),
 
+    #[error("File permissions error: {0}")]
+    FilePermissions(String),
+
     #[error("Unable to import file {0}: {1}")]
     ImportFile(String, String),
 
@@ -78,6 +83,7 @@ impl IntoResponse for FilesError {
     fn into_response(self) -> Response {
         let (status, error) = match &self {
             FilesError::Authentication(error) => (StatusCode::UNAUTHORIZED, clean_errors(error)),
+            FilesError::FilePermissions(error) => (StatusCode::FORBIDDEN, clean_errors(error)),
             FilesError::InternalServer(error) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, clean_errors(error))
             }
@@ -86,6 +92,7 @@ impl IntoResponse for FilesError {
         };
 
         tracing::warn!("{:?}", self);
+        METRICS.error(&self);
 
         (status, error).into_response()
     }
@@ -99,7 +106,9 @@ impl From<SharedError> for FilesError {
             SharedError::PubSub(error) => FilesError::PubSub(error),
             SharedError::QuadraticApi(error) => FilesError::QuadraticApi(error),
             SharedError::Storage(error) => match error {
-                StorageError::Read(key, _) => FilesError::NotFound(format!("File {key} not found")),
+                StorageError::Read(key, _) | StorageError::NotFound(key) => {
+                    FilesError::NotFound(format!("File {key} not found"))
+                }
                 _ => FilesError::Storage(error.to_string()),
             },
             _ => FilesError::Unknown(format!("Unknown SharedError: {error}")),
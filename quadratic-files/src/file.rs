@@ -1,5 +1,6 @@
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use std::sync::Arc;
+use std::time::Instant;
 use uuid::Uuid;
 
 use quadratic_core::{
@@ -9,24 +10,30 @@ use quadratic_core::{
         transaction::{Transaction, TransactionServer},
     },
     grid::{
-        Grid,
+        Grid, SheetId,
         file::{CURRENT_VERSION, export, import},
     },
 };
 use quadratic_rust_shared::{
-    pubsub::PubSub as PubSubTrait,
+    pubsub::{FileSheets, PubSub as PubSubTrait, file_sheets_key},
     quadratic_api::{get_file_checkpoint, set_file_checkpoint},
     storage::{Storage, StorageContainer},
 };
 
 use crate::{
+    checkpoint::collect_checkpoints,
     error::{FilesError, Result},
+    metrics::{METRICS, record_checkpoint, record_transactions},
     state::{State, settings::Settings},
     truncate::{add_processed_transaction, processed_transaction_key},
+    version::add_version,
 };
 
 pub static GROUP_NAME: &str = "quadratic-file-service-1";
 
+/// How long a file's sheet ids are kept after its last checkpoint.
+pub(crate) const FILE_SHEETS_EXPIRE_S: u64 = 30 * 24 * 60 * 60;
+
 /// Load a .grid file
 pub(crate) fn load_file(key: &str, file: Vec<u8>) -> Result<Grid> {
     import(file).map_err(|e| FilesError::ImportFile(key.into(), e.to_string()))
@@ -61,14 +68,16 @@ pub(crate) fn key(file_id: Uuid, sequence: u64) -> String {
     format!("{file_id}-{sequence}.grid")
 }
 
-/// Load a file from S3, add it to memory, process transactions and upload it back to S3
+/// Load a file from S3, add it to memory, process transactions and upload it back to S3.
+/// Returns the final sequence_num and the file's sheet ids.
 pub(crate) async fn process_transactions(
     storage: &StorageContainer,
     file_id: Uuid,
     checkpoint_sequence_num: u64,
     final_sequence_num: u64,
     operations: Vec<Operation>,
-) -> Result<u64> {
+) -> Result<(u64, Vec<SheetId>)> {
+    let start = Instant::now();
     let mut grid = get_and_load_object(
         storage,
         &key(file_id, checkpoint_sequence_num),
@@ -78,11 +87,14 @@ pub(crate) async fn process_transactions(
     let key = key(file_id, final_sequence_num);
 
     apply_transaction(&mut grid, operations);
+    let sheet_ids = grid.sheet_ids();
     let body = export_file(&key, grid.into_grid())?;
+    let size = body.len();
 
     storage.write(&key, &body.into()).await?;
+    record_checkpoint(start.elapsed(), size);
 
-    Ok(final_sequence_num)
+    Ok((final_sequence_num, sheet_ids))
 }
 
 /// Process outstanding transactions in the queue
@@ -126,6 +138,8 @@ pub(crate) async fn process_queue_for_room(
         return Ok(None);
     }
 
+    record_transactions(transactions.len());
+
     let sequence_numbers = transactions
         .iter()
         .map(|transaction| transaction.sequence_num)
@@ -159,7 +173,7 @@ pub(crate) async fn process_queue_for_room(
 
     // process the transactions and save the file to S3
     let start_processing = Utc::now();
-    let last_sequence_num = process_transactions(
+    let (last_sequence_num, sheet_ids) = process_transactions(
         storage,
         file_id,
         checkpoint_sequence_num,
@@ -173,6 +187,21 @@ pub(crate) async fn process_queue_for_room(
         (Utc::now() - start_processing).num_milliseconds()
     );
 
+    // let multiplayer know which sheets exist so it can validate transactions,
+    // before acking so that a failed write is retried with the transactions
+    let file_sheets = FileSheets {
+        sequence_num: last_sequence_num,
+        sheet_ids: sheet_ids.iter().map(ToString::to_string).collect(),
+    };
+    pubsub
+        .connection
+        .set(
+            &file_sheets_key(channel),
+            &serde_json::to_vec(&file_sheets)?,
+            FILE_SHEETS_EXPIRE_S,
+        )
+        .await?;
+
     // convert keys to &str requires 2 iterations
     let keys = sequence_numbers
         .iter()
@@ -201,6 +230,30 @@ pub(crate) async fn process_queue_for_room(
     )
     .await?;
 
+    // keep the checkpoint as a version if the retention policy calls for one,
+    // collecting superseded checkpoints at the same (at most hourly) cadence
+    match add_version(
+        storage,
+        &state.settings.version_retention,
+        file_id,
+        last_sequence_num,
+        Utc::now(),
+    )
+    .await
+    {
+        Ok(true) => {
+            let min_age = Duration::seconds(state.settings.checkpoint_gc_age_s);
+
+            if let Err(error) = collect_checkpoints(storage, file_id, min_age, Utc::now()).await {
+                tracing::warn!("Error collecting checkpoints of file {file_id}: {error}");
+            }
+        }
+        Ok(false) => {}
+        Err(error) => {
+            tracing::warn!("Error adding version {last_sequence_num} of file {file_id}: {error}");
+        }
+    }
+
     // add FILE_ID.SEQUENCE_NUM to the processed transactions channel
     let message = processed_transaction_key(&file_id.to_string(), &last_sequence_num.to_string());
     let processed_transactions_channel = state
@@ -225,6 +278,61 @@ pub(crate) async fn process_queue_for_room(
     Ok(Some(last_sequence_num))
 }
 
+/// Load the latest state of a file: its checkpoint plus any transactions
+/// still waiting in the queue
+pub(crate) async fn get_current_file(state: &Arc<State>, file_id: Uuid) -> Result<GridController> {
+    let Settings {
+        storage,
+        quadratic_api_uri,
+        m2m_auth_token,
+        ..
+    } = &state.settings;
+
+    let checkpoint_sequence_num = get_file_checkpoint(quadratic_api_uri, m2m_auth_token, &file_id)
+        .await?
+        .sequence_number;
+
+    let transactions = state
+        .pubsub
+        .lock()
+        .await
+        .connection
+        .get_messages_from(
+            &file_id.to_string(),
+            &(checkpoint_sequence_num + 1).to_string(),
+            false,
+        )
+        .await?
+        .into_iter()
+        .map(|(_, message)| {
+            Transaction::process_incoming(&message)
+                .map_err(|e| FilesError::Serialization(e.to_string()))
+        })
+        .collect::<Result<Vec<TransactionServer>>>()?;
+
+    let mut grid = get_and_load_object(
+        storage,
+        &key(file_id, checkpoint_sequence_num),
+        checkpoint_sequence_num,
+    )
+    .await?;
+
+    let operations = transactions
+        .into_iter()
+        .map(|transaction| {
+            Transaction::decompress_and_deserialize::<Vec<Operation>>(&transaction.operations)
+                .map_err(|e| FilesError::Serialization(e.to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<Operation>>();
+
+    apply_transaction(&mut grid, operations);
+
+    Ok(grid)
+}
+
 pub(crate) async fn get_files_to_process(
     state: &Arc<State>,
     active_channels: &str,
@@ -259,6 +367,7 @@ pub(crate) async fn process(state: &Arc<State>, active_channels: &str) -> Result
             // TODO(ddimaria): instead of logging the error, move the file to a dead letter queue
             if let Err(error) = process_queue_for_room(&state, file_id, &active_channels).await {
                 tracing::error!("Error processing file {file_id}: {error}");
+                METRICS.error(&error);
             };
         });
     }
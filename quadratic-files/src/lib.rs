@@ -0,0 +1,37 @@
+//! Quadratic File Service
+//!
+//! A file servic for that consumes transactions from a queue, applies them to
+//! a grid and writes them to S3.
+
+mod auth;
+mod checkpoint;
+mod config;
+mod error;
+mod file;
+mod health;
+mod metrics;
+mod server;
+mod state;
+mod storage;
+#[cfg(test)]
+mod test_util;
+mod truncate;
+mod version;
+
+use quadratic_rust_shared::pubsub::connection::PubSubType;
+
+pub use error::{FilesError, Result};
+
+/// Start the file service, configured from the environment.
+pub async fn serve() -> Result<()> {
+    server::serve().await
+}
+
+/// Run the file service against the in-memory PubSub backend, sharing it with
+/// the other services in this process.  Tracing is left to the caller.
+pub async fn serve_single_node() -> Result<()> {
+    let mut config = config::config()?;
+    config.pubsub_type = PubSubType::Memory;
+
+    server::run(config).await
+}
@@ -3,21 +3,9 @@
 //! A file servic for that consumes transactions from a queue, applies them to
 //! a grid and writes them to S3.
 
-mod auth;
-mod config;
-mod error;
-mod file;
-mod health;
-mod server;
-mod state;
-mod storage;
-#[cfg(test)]
-mod test_util;
-mod truncate;
-
-use error::Result;
+use quadratic_files::{Result, serve};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    server::serve().await
+    serve().await
 }
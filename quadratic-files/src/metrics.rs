@@ -0,0 +1,93 @@
+//! Metrics
+//!
+//! Prometheus metrics for the file service, served at `/metrics`.
+
+use axum::{Extension, http::header::CONTENT_TYPE, response::IntoResponse};
+use quadratic_rust_shared::metrics::{self, Registry};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use crate::state::State;
+
+pub(crate) static METRICS: LazyLock<Registry> = LazyLock::new(|| Registry::new("quadratic_files"));
+
+/// Record a checkpoint written to storage.
+pub(crate) fn record_checkpoint(duration: Duration, size: usize) {
+    METRICS.inc("checkpoints_total", "Checkpoints written", &[]);
+    METRICS.observe(
+        "checkpoint_duration_seconds",
+        "Time to apply transactions and write a checkpoint",
+        &[],
+        metrics::DURATION_BUCKETS,
+        duration.as_secs_f64(),
+    );
+    METRICS.observe(
+        "checkpoint_size_bytes",
+        "Size of a checkpoint",
+        &[],
+        metrics::SIZE_BUCKETS,
+        size as f64,
+    );
+}
+
+/// Record a batch of transactions processed from a file's queue.
+pub(crate) fn record_transactions(count: usize) {
+    METRICS.inc_by(
+        "transactions_total",
+        "Transactions processed",
+        &[],
+        count as f64,
+    );
+    METRICS.observe(
+        "transaction_batch_size",
+        "Transactions processed together from a file's queue",
+        &[],
+        metrics::COUNT_BUCKETS,
+        count as f64,
+    );
+}
+
+/// Render all metrics, refreshing the gauges from the current state.
+pub(crate) async fn metrics(Extension(state): Extension<Arc<State>>) -> impl IntoResponse {
+    let stats = state.stats.lock().await.to_owned();
+
+    METRICS.set(
+        "files_to_process",
+        "Files with transactions waiting in the queue",
+        &[],
+        stats.files_to_process_in_pubsub as f64,
+    );
+    METRICS.set(
+        "channels_to_truncate",
+        "Channels with processed transactions to truncate",
+        &[],
+        stats.channels_to_truncate_in_pubsub as f64,
+    );
+
+    ([(CONTENT_TYPE, metrics::CONTENT_TYPE)], METRICS.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use crate::test_util::new_arc_state;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn renders_metrics() {
+        let state = new_arc_state().await;
+        record_checkpoint(Duration::from_millis(20), 1_000);
+        record_transactions(3);
+
+        let response = metrics(Extension(state)).await.into_response();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.contains("# TYPE quadratic_files_files_to_process gauge\n"));
+        assert!(body.contains("# TYPE quadratic_files_checkpoint_duration_seconds histogram\n"));
+        assert!(body.contains("quadratic_files_checkpoint_size_bytes_count"));
+        assert!(body.contains("quadratic_files_transaction_batch_size_count"));
+    }
+}
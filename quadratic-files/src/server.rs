@@ -6,7 +6,10 @@
 use axum::Json;
 use axum::http::Method;
 use axum::response::IntoResponse;
-use axum::{Extension, Router, routing::get};
+use axum::{
+    Extension, Router,
+    routing::{get, post},
+};
 use quadratic_rust_shared::auth::jwt::get_jwks;
 use quadratic_rust_shared::storage::Storage;
 use std::time::Duration;
@@ -19,12 +22,14 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::file::get_files_to_process;
 use crate::health::{full_healthcheck, healthcheck};
+use crate::metrics::metrics;
 use crate::state::stats::StatsResponse;
 use crate::storage::{get_presigned_storage, get_storage};
 use crate::truncate::truncate_processed_transactions;
+use crate::version::{get_version, list_versions, restore_version};
 use crate::{
     auth::get_middleware,
-    config::config,
+    config::{Config, config},
     error::{FilesError, Result},
     file::process,
     state::State,
@@ -66,6 +71,18 @@ pub(crate) fn app(state: Arc<State>) -> Router {
                 .post(upload_storage),
         )
         //
+        // list a file's versions
+        .route("/versions/{file_id}", get(list_versions))
+        //
+        // download a version of a file
+        .route("/versions/{file_id}/{sequence_num}", get(get_version))
+        //
+        // get the transaction that restores a version of a file
+        .route(
+            "/versions/{file_id}/{sequence_num}/restore",
+            post(restore_version),
+        )
+        //
         // auth middleware
         .route_layer(auth)
         //
@@ -80,6 +97,9 @@ pub(crate) fn app(state: Arc<State>) -> Router {
         // stats
         .route("/stats", get(stats))
         //
+        // prometheus metrics
+        .route("/metrics", get(metrics))
+        //
         // presigned urls
         .route("/storage/presigned/{key}", get(get_presigned_storage))
         //
@@ -96,8 +116,7 @@ pub(crate) fn app(state: Arc<State>) -> Router {
         )
 }
 
-/// Start the websocket server.  This is the entrypoint for the application.
-#[tracing::instrument(level = "trace")]
+/// Start the server.  This is the entrypoint for the application.
 pub(crate) async fn serve() -> Result<()> {
     let tracing_layer = if config()?.environment.is_production() {
         tracing_subscriber::fmt::layer().json().boxed()
@@ -113,7 +132,12 @@ pub(crate) async fn serve() -> Result<()> {
         .with(tracing_layer)
         .init();
 
-    let config = config()?;
+    run(config()?).await
+}
+
+/// Run the server with the given config.  Tracing is left to the caller.
+#[tracing::instrument(level = "trace", skip(config))]
+pub(crate) async fn run(config: Config) -> Result<()> {
     let jwks = get_jwks(&config.jwks_uri).await?;
     let state = Arc::new(State::new(&config, Some(jwks)).await?);
     let app = app(Arc::clone(&state));
@@ -8,8 +8,6 @@ pub mod settings;
 pub mod stats;
 
 use jsonwebtoken::jwk::JwkSet;
-use quadratic_rust_shared::pubsub::Config as PubSubConfig;
-use quadratic_rust_shared::pubsub::redis_streams::RedisStreamsConfig;
 use tokio::sync::Mutex;
 
 use crate::config::Config;
@@ -28,12 +26,12 @@ pub(crate) struct State {
 
 impl State {
     pub(crate) async fn new(config: &Config, jwks: Option<JwkSet>) -> Result<Self> {
-        let pubsub_config = PubSubConfig::RedisStreams(RedisStreamsConfig {
-            host: config.pubsub_host.to_owned(),
-            port: config.pubsub_port.to_owned(),
-            password: config.pubsub_password.to_owned(),
-            active_channels: config.pubsub_active_channels.to_owned(),
-        });
+        let pubsub_config = config.pubsub_type.config(
+            &config.pubsub_host,
+            &config.pubsub_port,
+            &config.pubsub_password,
+            &config.pubsub_active_channels,
+        );
 
         Ok(State {
             pubsub: Mutex::new(PubSub::new(pubsub_config).await?),
@@ -6,6 +6,7 @@ use quadratic_rust_shared::storage::file_system::{FileSystem, FileSystemConfig};
 use quadratic_rust_shared::storage::s3::{S3, S3Config};
 
 use crate::config::{Config, StorageType};
+use crate::version::RetentionPolicy;
 
 #[derive(Debug)]
 pub(crate) struct Settings {
@@ -14,6 +15,8 @@ pub(crate) struct Settings {
     pub(crate) m2m_auth_token: String,
     pub(crate) storage: StorageContainer,
     pub(crate) pubsub_processed_transactions_channel: String,
+    pub(crate) version_retention: RetentionPolicy,
+    pub(crate) checkpoint_gc_age_s: i64,
 }
 
 impl Settings {
@@ -58,6 +61,11 @@ impl Settings {
             pubsub_processed_transactions_channel: config
                 .pubsub_processed_transactions_channel
                 .to_owned(),
+            version_retention: RetentionPolicy {
+                hourly_retention_hours: config.version_hourly_retention_hours,
+                daily_retention_days: config.version_daily_retention_days,
+            },
+            checkpoint_gc_age_s: config.checkpoint_gc_age_s,
         }
     }
 }
@@ -1,12 +1,15 @@
 use axum::{
     Extension, Json,
-    body::to_bytes,
+    body::Body,
     extract::{Path, Request},
+    http::header::CONTENT_TYPE,
     response::IntoResponse,
 };
+use futures_util::TryStreamExt;
 use quadratic_rust_shared::{
+    SharedError,
     crypto::aes_cbc::decrypt_from_api,
-    storage::{Storage, StorageConfig},
+    storage::{Storage, StorageConfig, error::Storage as StorageError},
 };
 use serde::Serialize;
 use std::sync::Arc;
@@ -27,8 +30,12 @@ pub(crate) async fn get_storage(
 ) -> Result<impl IntoResponse> {
     tracing::trace!("Get file {}", file_name);
 
-    let file = state.settings.storage.read(&file_name).await?;
-    Ok(file.into_response())
+    let file = state.settings.storage.read_stream(&file_name).await?;
+
+    Ok((
+        [(CONTENT_TYPE, "application/octet-stream")],
+        Body::from_stream(file),
+    ))
 }
 
 /// Get a file from storage from a presigned URL (encrypted)
@@ -69,11 +76,15 @@ pub(crate) async fn upload_storage(
         state.settings.storage.path()
     );
 
-    let bytes = to_bytes(request.into_body(), usize::MAX)
-        .await
-        .map_err(|e| FilesError::Storage(e.to_string()))?;
+    let body = request.into_body().into_data_stream().map_err(|e| {
+        SharedError::Storage(StorageError::Write(file_name.to_owned(), e.to_string()))
+    });
 
-    state.settings.storage.write(&file_name, &bytes).await?;
+    state
+        .settings
+        .storage
+        .write_stream(&file_name, Box::pin(body))
+        .await?;
 
     Ok(Json(UploadStorageResponse {
         bucket: state.settings.storage.path().to_owned(),
@@ -0,0 +1,418 @@
+//! Version History
+//!
+//! Checkpoints are kept as versions of a file according to a retention policy:
+//! one per hour for the last `version_hourly_retention_hours` hours, then one
+//! per day for the last `version_daily_retention_days` days.  A file's versions
+//! are listed, newest first, in a manifest stored next to its checkpoints.
+
+use axum::{
+    Extension, Json, body::Body, extract::Path, http::header::CONTENT_TYPE, response::IntoResponse,
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use chrono::{DateTime, Duration, Utc};
+use quadratic_core::{
+    controller::{operations::operation::Operation, transaction::Transaction},
+    grid::{
+        Grid, Sheet, SheetId,
+        file::{CURRENT_VERSION, sheet_schema::export_sheet},
+    },
+};
+use quadratic_rust_shared::storage::{Storage, StorageContainer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::auth::{get_permissions, validate_can_edit_file, validate_can_view_file};
+use crate::error::{FilesError, Result};
+use crate::file::{get_and_load_object, get_current_file, key};
+use crate::state::State;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Version {
+    pub(crate) sequence_num: u64,
+    pub(crate) key: String,
+    pub(crate) version: String,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RetentionPolicy {
+    pub(crate) hourly_retention_hours: i64,
+    pub(crate) daily_retention_days: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Bucket {
+    Hour(i64),
+    Day(i64),
+}
+
+fn hour(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(3600)
+}
+
+fn day(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(86400)
+}
+
+impl RetentionPolicy {
+    /// The bucket a version falls in, or None if it's too old to keep.
+    fn bucket(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> Option<Bucket> {
+        let age = now - created_at;
+
+        if age < Duration::hours(self.hourly_retention_hours) {
+            Some(Bucket::Hour(hour(created_at)))
+        } else if age < Duration::days(self.daily_retention_days) {
+            Some(Bucket::Day(day(created_at)))
+        } else {
+            None
+        }
+    }
+
+    /// Keep the newest version in each bucket, dropping versions that are too
+    /// old.  Returns the versions newest first.
+    pub(crate) fn apply(&self, mut versions: Vec<Version>, now: DateTime<Utc>) -> Vec<Version> {
+        let mut buckets = HashSet::new();
+        versions.sort_by(|a, b| b.sequence_num.cmp(&a.sequence_num));
+
+        versions
+            .into_iter()
+            .filter(|version| {
+                self.bucket(version.created_at, now)
+                    .is_some_and(|bucket| buckets.insert(bucket))
+            })
+            .collect()
+    }
+
+    /// A checkpoint is kept as a version if there isn't one yet this hour.
+    pub(crate) fn is_due(&self, versions: &[Version], now: DateTime<Utc>) -> bool {
+        versions
+            .iter()
+            .map(|version| hour(version.created_at))
+            .max()
+            .is_none_or(|latest| latest < hour(now))
+    }
+}
+
+/// The key of a file's version manifest.
+pub(crate) fn versions_key(file_id: Uuid) -> String {
+    format!("{file_id}-versions.json")
+}
+
+/// Get a file's versions, newest first.
+pub(crate) async fn get_versions(
+    storage: &StorageContainer,
+    file_id: Uuid,
+) -> Result<Vec<Version>> {
+    match storage.read(&versions_key(file_id)).await {
+        Ok(body) => Ok(serde_json::from_slice(&body)?),
+        Err(error) => match FilesError::from(error) {
+            // files start without any versions
+            FilesError::NotFound(_) => Ok(vec![]),
+            error => Err(error),
+        },
+    }
+}
+
+/// Get a single version of a file.
+pub(crate) async fn find_version(
+    storage: &StorageContainer,
+    file_id: Uuid,
+    sequence_num: u64,
+) -> Result<Version> {
+    get_versions(storage, file_id)
+        .await?
+        .into_iter()
+        .find(|version| version.sequence_num == sequence_num)
+        .ok_or_else(|| {
+            FilesError::NotFound(format!(
+                "Version {sequence_num} of file {file_id} not found"
+            ))
+        })
+}
+
+/// Keep a checkpoint as a version if the retention policy calls for one,
+/// pruning versions the policy no longer keeps.  Returns true if the
+/// checkpoint was kept.
+pub(crate) async fn add_version(
+    storage: &StorageContainer,
+    policy: &RetentionPolicy,
+    file_id: Uuid,
+    sequence_num: u64,
+    now: DateTime<Utc>,
+) -> Result<bool> {
+    let mut versions = get_versions(storage, file_id).await?;
+
+    if !policy.is_due(&versions, now) {
+        return Ok(false);
+    }
+
+    versions.push(Version {
+        sequence_num,
+        key: key(file_id, sequence_num),
+        version: CURRENT_VERSION.into(),
+        created_at: now,
+    });
+
+    let versions = policy.apply(versions, now);
+    let body = serde_json::to_vec(&versions)?;
+    storage.write(&versions_key(file_id), &body.into()).await?;
+
+    tracing::info!("Added version {sequence_num} of file {file_id}");
+
+    Ok(true)
+}
+
+/// The operations that turn a file into a snapshot of it.  The current sheets
+/// are deleted before the snapshot's sheets are added, so that sheet and table
+/// names are restored unchanged.  A placeholder sheet keeps the file from ever
+/// being empty, since deleting the last sheet in a user transaction creates a
+/// new one.
+pub(crate) fn restore_operations(current: &Grid, snapshot: &Grid) -> Vec<Operation> {
+    let placeholder_id = SheetId::new();
+    let placeholder = Sheet::new(
+        placeholder_id,
+        format!("Restoring {placeholder_id}"),
+        current.end_order(),
+    );
+
+    let add_placeholder = Operation::AddSheet {
+        sheet: Box::new(placeholder),
+    };
+
+    let delete_sheets = current
+        .sheets()
+        .values()
+        .map(|sheet| Operation::DeleteSheet {
+            sheet_id: sheet.id,
+            sheet_name: Some(sheet.name.to_owned()),
+        });
+
+    let add_sheets = snapshot
+        .sheets()
+        .values()
+        .map(|sheet| Operation::AddSheetSchema {
+            schema: Box::new(export_sheet(sheet.to_owned())),
+        });
+
+    let delete_placeholder = Operation::DeleteSheet {
+        sheet_id: placeholder_id,
+        sheet_name: None,
+    };
+
+    std::iter::once(add_placeholder)
+        .chain(delete_sheets)
+        .chain(add_sheets)
+        .chain(std::iter::once(delete_placeholder))
+        .collect()
+}
+
+/// List a file's versions, newest first
+pub(crate) async fn list_versions(
+    Path(file_id): Path<Uuid>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<Json<Vec<Version>>> {
+    tracing::trace!("List versions of file {}", file_id);
+
+    let permissions =
+        get_permissions(&state.settings.quadratic_api_uri, bearer.token(), file_id).await?;
+    validate_can_view_file(&permissions)?;
+
+    let versions = get_versions(&state.settings.storage, file_id).await?;
+
+    Ok(Json(versions))
+}
+
+/// Download a version of a file
+pub(crate) async fn get_version(
+    Path((file_id, sequence_num)): Path<(Uuid, u64)>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<impl IntoResponse> {
+    tracing::trace!("Get version {} of file {}", sequence_num, file_id);
+
+    let permissions =
+        get_permissions(&state.settings.quadratic_api_uri, bearer.token(), file_id).await?;
+    validate_can_view_file(&permissions)?;
+
+    let storage = &state.settings.storage;
+    let version = find_version(storage, file_id, sequence_num).await?;
+    let file = storage.read_stream(&version.key).await?;
+
+    Ok((
+        [(CONTENT_TYPE, "application/octet-stream")],
+        Body::from_stream(file),
+    ))
+}
+
+/// Get the transaction that restores a version of a file, as compressed
+/// operations.  The client sends it through multiplayer so that it is
+/// sequenced and broadcast like any other transaction.
+pub(crate) async fn restore_version(
+    Path((file_id, sequence_num)): Path<(Uuid, u64)>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<impl IntoResponse> {
+    tracing::trace!("Restore version {} of file {}", sequence_num, file_id);
+
+    let permissions =
+        get_permissions(&state.settings.quadratic_api_uri, bearer.token(), file_id).await?;
+    validate_can_edit_file(&permissions)?;
+
+    let storage = &state.settings.storage;
+    let version = find_version(storage, file_id, sequence_num).await?;
+    let snapshot = get_and_load_object(storage, &version.key, version.sequence_num).await?;
+    let current = get_current_file(&state, file_id).await?;
+
+    let operations = restore_operations(current.grid(), snapshot.grid());
+    let operations = Transaction::serialize_and_compress(&operations)
+        .map_err(|e| FilesError::Serialization(e.to_string()))?;
+
+    Ok(([(CONTENT_TYPE, "application/octet-stream")], operations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::load_file;
+    use quadratic_core::controller::{
+        GridController, active_transactions::transaction_name::TransactionName,
+    };
+    use quadratic_core::{Pos, SheetPos};
+    use quadratic_rust_shared::storage::file_system::{FileSystem, FileSystemConfig};
+
+    const POLICY: RetentionPolicy = RetentionPolicy {
+        hourly_retention_hours: 24,
+        daily_retention_days: 30,
+    };
+
+    fn version(sequence_num: u64, created_at: DateTime<Utc>) -> Version {
+        Version {
+            sequence_num,
+            key: key(Uuid::nil(), sequence_num),
+            version: CURRENT_VERSION.into(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn keeps_versions_by_the_retention_policy() {
+        let now = DateTime::parse_from_rfc3339("2024-06-15T12:30:00Z")
+            .unwrap()
+            .to_utc();
+        let versions = vec![
+            // two in the same hour, the newest is kept
+            version(1, now - Duration::minutes(20)),
+            version(2, now - Duration::minutes(10)),
+            // hourly
+            version(3, now - Duration::hours(2)),
+            version(4, now - Duration::hours(3)),
+            // two on the same day, a few days ago
+            version(5, now - Duration::days(3)),
+            version(6, now - Duration::days(3) + Duration::minutes(5)),
+            // too old
+            version(7, now - Duration::days(31)),
+        ];
+
+        let kept = POLICY
+            .apply(versions, now)
+            .iter()
+            .map(|version| version.sequence_num)
+            .collect::<Vec<_>>();
+
+        assert_eq!(kept, vec![6, 4, 3, 2]);
+    }
+
+    #[test]
+    fn versions_are_due_hourly() {
+        let now = DateTime::parse_from_rfc3339("2024-06-15T12:30:00Z")
+            .unwrap()
+            .to_utc();
+
+        assert!(POLICY.is_due(&[], now));
+        assert!(!POLICY.is_due(&[version(1, now - Duration::minutes(20))], now));
+        assert!(POLICY.is_due(&[version(1, now - Duration::minutes(40))], now));
+    }
+
+    #[tokio::test]
+    async fn adds_versions_to_the_manifest() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let storage = StorageContainer::FileSystem(FileSystem::new(FileSystemConfig {
+            path: path.to_string_lossy().to_string(),
+            encryption_keys: vec![],
+        }));
+        let file_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        assert!(get_versions(&storage, file_id).await.unwrap().is_empty());
+
+        let added = add_version(&storage, &POLICY, file_id, 10, now).await;
+        assert!(added.unwrap());
+
+        // not due again until the next hour
+        let added = add_version(&storage, &POLICY, file_id, 20, now).await;
+        assert!(!added.unwrap());
+
+        let added = add_version(&storage, &POLICY, file_id, 30, now + Duration::hours(1)).await;
+        assert!(added.unwrap());
+
+        let versions = get_versions(&storage, file_id).await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].sequence_num, 30);
+        assert_eq!(versions[0].key, key(file_id, 30));
+        assert_eq!(
+            find_version(&storage, file_id, 10).await.unwrap(),
+            versions[1]
+        );
+        assert!(matches!(
+            find_version(&storage, file_id, 20).await,
+            Err(FilesError::NotFound(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn restores_a_snapshot() {
+        let snapshot = load_file(
+            "snapshot",
+            include_bytes!("../../quadratic-rust-shared/data/grid/v1_4_simple.grid").to_vec(),
+        )
+        .unwrap();
+
+        // change the file after the snapshot
+        let mut current = GridController::from_grid(snapshot.clone(), 0);
+        let sheet_id = current.sheet_ids().first().unwrap().to_owned();
+        let pos = Pos { x: 1, y: 2 };
+        current.set_cell_value(
+            SheetPos::new(sheet_id, pos.x, pos.y),
+            "changed".to_string(),
+            None,
+            false,
+        );
+        current.add_sheet(None, None, None, false);
+        assert_ne!(current.sheet_ids().len(), snapshot.sheets().len());
+
+        // the client applies the restore as a user transaction
+        let operations = restore_operations(current.grid(), &snapshot);
+        current.start_user_ai_transaction(operations, None, TransactionName::Unknown, false);
+
+        let expected = snapshot.try_sheet(sheet_id).unwrap().display_value(pos);
+        let restored = current.grid().try_sheet(sheet_id).unwrap();
+        assert_eq!(restored.display_value(pos), expected);
+
+        // no sheet is created for the deleted sheets, and no placeholder is left
+        let names = |grid: &Grid| {
+            grid.sheets()
+                .values()
+                .map(|sheet| (sheet.id, sheet.name.to_owned()))
+                .collect::<HashSet<_>>()
+        };
+        assert_eq!(names(current.grid()), names(&snapshot));
+    }
+}
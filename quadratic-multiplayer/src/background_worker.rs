@@ -3,17 +3,14 @@ use std::{sync::Arc, time::Duration};
 use tokio::{task::JoinHandle, time};
 use uuid::Uuid;
 
-use crate::{
-    error::Result,
-    get_room,
-    message::{broadcast, response::MessageResponse},
-    state::State,
-};
+use crate::{error::Result, message::broadcast, state::State};
 
 const BACKGROUND_WORKER_INTERVAL_MS: u64 = 1000;
 
 /// In a separate thread:
 ///   * Check for stale users in rooms and remove them.
+///   * Refresh the heartbeats of this instance's users in the cluster, and
+///     keep their sessions from expiring.
 #[tracing::instrument(level = "trace")]
 pub(crate) fn start(
     state: Arc<State>,
@@ -60,6 +57,24 @@ pub(crate) fn start(
                             error
                         );
                     }
+
+                    // keep the room's users visible to other instances
+                    if let Err(error) = state.refresh_cluster_users(*file_id).await {
+                        tracing::warn!(
+                            "Error refreshing cluster users in room {}: {:?}",
+                            file_id,
+                            error
+                        );
+                    }
+
+                    // keep the sessions of connected users from expiring
+                    if let Err(error) = state.refresh_sessions(*file_id).await {
+                        tracing::warn!(
+                            "Error refreshing sessions in room {}: {:?}",
+                            file_id,
+                            error
+                        );
+                    }
                 }
             });
 
@@ -90,8 +105,7 @@ async fn remove_stale_users_in_room(
         return Ok(None);
     }
 
-    let users = get_room!(state, file_id)?.users.to_owned();
-    let message = MessageResponse::from((users, &state.settings.version));
+    let message = state.users_in_room(file_id).await?;
 
     Ok(Some(broadcast(
         vec![],
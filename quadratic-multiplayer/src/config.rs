@@ -6,9 +6,12 @@
 //! panic at startup.
 
 use crate::error::{MpError, Result};
+use crate::message::validate::MAX_OPERATIONS_SIZE;
 use dotenv::dotenv;
 use quadratic_rust_shared::environment::Environment;
+use quadratic_rust_shared::pubsub::connection::PubSubType;
 use serde::Deserialize;
+use std::time::Duration;
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct Config {
@@ -18,7 +21,24 @@ pub(crate) struct Config {
     pub(crate) authenticate_jwt: bool,
     pub(crate) heartbeat_timeout_s: i64,
     pub(crate) environment: Environment,
+    #[serde(default = "default_resume_grace_period_s")]
+    pub(crate) resume_grace_period_s: i64,
 
+    // Limits per connection
+    #[serde(default = "default_rate_limit_per_s")]
+    pub(crate) rate_limit_per_s: f64,
+    #[serde(default = "default_rate_limit_burst")]
+    pub(crate) rate_limit_burst: f64,
+    #[serde(default = "default_rate_limit_max_violations")]
+    pub(crate) rate_limit_max_violations: usize,
+    #[serde(default = "default_presence_updates_per_s")]
+    pub(crate) presence_updates_per_s: f64,
+    #[serde(default = "default_max_transaction_size_bytes")]
+    pub(crate) max_transaction_size_bytes: usize,
+
+    // PubSub Type: redis-streams or memory
+    #[serde(default)]
+    pub(crate) pubsub_type: PubSubType,
     pub(crate) pubsub_host: String,
     pub(crate) pubsub_port: String,
     pub(crate) pubsub_password: String,
@@ -29,6 +49,63 @@ pub(crate) struct Config {
     pub(crate) m2m_auth_token: String,
 }
 
+fn default_resume_grace_period_s() -> i64 {
+    30
+}
+
+fn default_rate_limit_per_s() -> f64 {
+    50.0
+}
+
+fn default_rate_limit_burst() -> f64 {
+    100.0
+}
+
+fn default_rate_limit_max_violations() -> usize {
+    50
+}
+
+fn default_presence_updates_per_s() -> f64 {
+    10.0
+}
+
+fn default_max_transaction_size_bytes() -> usize {
+    MAX_OPERATIONS_SIZE
+}
+
+impl Config {
+    /// Check the limits that would otherwise panic or misbehave at runtime.
+    pub(crate) fn validate(&self) -> Result<()> {
+        validate_rate("RATE_LIMIT_PER_S", self.rate_limit_per_s)?;
+        validate_rate("PRESENCE_UPDATES_PER_S", self.presence_updates_per_s)?;
+
+        // a bucket that can't hold a whole token never allows a message
+        if !(self.rate_limit_burst.is_finite() && self.rate_limit_burst >= 1.0) {
+            return Err(MpError::Config(format!(
+                "RATE_LIMIT_BURST must be at least 1, got {}",
+                self.rate_limit_burst
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A rate must be positive and finite, and its interval (1 / rate) must fit
+/// in a Duration.
+fn validate_rate(name: &str, value: f64) -> Result<()> {
+    let is_valid = value.is_finite()
+        && value > 0.0
+        && Duration::try_from_secs_f64(1.0 / value).is_ok();
+
+    match is_valid {
+        true => Ok(()),
+        false => Err(MpError::Config(format!(
+            "{name} must be a positive number, got {value}"
+        ))),
+    }
+}
+
 /// Load the global configuration from the environment into Config.
 pub(crate) fn config() -> Result<Config> {
     let filename = if cfg!(test) { ".env.test" } else { ".env" };
@@ -41,6 +118,8 @@ pub(crate) fn config() -> Result<Config> {
         .from_env::<Config>()
         .or_else(|_| envy::from_env::<Config>())
         .map_err(|e| MpError::Config(e.to_string()))?;
+    config.validate()?;
+
     Ok(config)
 }
 
@@ -56,4 +135,23 @@ mod tests {
         let config = config().unwrap();
         assert_eq!(config.host, host.to_string());
     }
+
+    #[test]
+    fn validates_limits() {
+        let mut config = config().unwrap();
+        assert!(config.validate().is_ok());
+
+        for value in [0.0, -1.0, f64::NAN, f64::INFINITY, 1e-300] {
+            config.presence_updates_per_s = value;
+            assert!(matches!(config.validate(), Err(MpError::Config(_))));
+        }
+
+        config.presence_updates_per_s = default_presence_updates_per_s();
+        config.rate_limit_per_s = 0.0;
+        assert!(matches!(config.validate(), Err(MpError::Config(_))));
+
+        config.rate_limit_per_s = default_rate_limit_per_s();
+        config.rate_limit_burst = 0.5;
+        assert!(matches!(config.validate(), Err(MpError::Config(_))));
+    }
 }
@@ -11,7 +11,7 @@ use strum_macros::{Display, EnumString};
 use thiserror::Error;
 use uuid::Uuid;
 
-pub(crate) type Result<T> = std::result::Result<T, MpError>;
+pub type Result<T> = std::result::Result<T, MpError>;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Display, EnumString)]
 pub(crate) enum ErrorLevel {
@@ -22,7 +22,7 @@ pub(crate) enum ErrorLevel {
 impl From<&MpError> for ErrorLevel {
     fn from(error: &MpError) -> Self {
         match error {
-            MpError::PubSub(_) => ErrorLevel::Error,
+            MpError::PubSub(_) | MpError::RateLimitExceeded(_) => ErrorLevel::Error,
             _ => ErrorLevel::Warning,
         }
     }
@@ -38,13 +38,16 @@ impl ErrorLevel {
 }
 
 #[derive(Error, Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub(crate) enum MpError {
+pub enum MpError {
     #[error("Authentication error: {0}")]
     Authentication(String),
 
     #[error("Background service error: {0}")]
     BackgroundService(String),
 
+    #[error("Chat error: {0}")]
+    Chat(String),
+
     #[error("Internal server error: {0}")]
     Config(String),
 
@@ -60,18 +63,30 @@ pub(crate) enum MpError {
     #[error("Internal server error: {0}")]
     InternalServer(String),
 
+    #[error("Invalid transaction {0}: {1}")]
+    InvalidTransaction(Uuid, String),
+
     #[error("Requested {0} transactions but only found {1}")]
     MissingTransactions(String, String),
 
     #[error("PubSub error: {0}")]
     PubSub(String),
 
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Rate limit exceeded, disconnecting: {0}")]
+    RateLimitExceeded(String),
+
     #[error("Error receiving message: {0}")]
     ReceivingMessage(String),
 
     #[error("Error requesting data: {0}")]
     Request(String),
 
+    #[error("Unable to resume session: {0}")]
+    ResumeSession(String),
+
     #[error("Room error: {0}")]
     Room(String),
 
@@ -0,0 +1,35 @@
+//! Quadratic Multiplayer
+//!
+//! A multiplayer server for Quadratic.  Supports user presence and mouse
+//! tracking for a shared file.
+
+mod background_worker;
+mod config;
+mod error;
+mod health;
+mod message;
+mod metrics;
+mod permissions;
+mod rate_limit;
+mod server;
+mod state;
+#[cfg(test)]
+mod test_util;
+
+use quadratic_rust_shared::pubsub::connection::PubSubType;
+
+pub use error::{MpError, Result};
+
+/// Start the multiplayer server, configured from the environment.
+pub async fn serve() -> Result<()> {
+    server::serve().await
+}
+
+/// Run the multiplayer server as the only instance, against the in-memory
+/// PubSub backend.  Tracing is left to the caller.
+pub async fn serve_single_node() -> Result<()> {
+    let mut config = config::config()?;
+    config.pubsub_type = PubSubType::Memory;
+
+    server::run(config).await
+}
@@ -3,20 +3,9 @@
 //! A multiplayer server for Quadratic.  Supports user presence and mouse
 //! tracking for a shared file.
 
-mod background_worker;
-mod config;
-mod error;
-mod health;
-mod message;
-mod permissions;
-mod server;
-mod state;
-#[cfg(test)]
-mod test_util;
-
-use error::Result;
+use quadratic_multiplayer::{Result, serve};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    server::serve().await
+    serve().await
 }
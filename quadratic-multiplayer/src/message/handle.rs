@@ -6,28 +6,67 @@
 //! to all users in a room.
 
 use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use quadratic_core::controller::operations::operation::Operation;
 use quadratic_rust_shared::quadratic_api::{FilePermRole, get_file_perms};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::error::{ErrorLevel, MpError, Result};
 use crate::get_mut_room;
-use crate::message::response::{BinaryTransaction, Transaction};
+use crate::message::response::{BinaryTransaction, ChatMessage, Transaction};
 use crate::message::{
-    broadcast, request::MessageRequest, response::MessageResponse, send_user_message,
-};
-use crate::permissions::{
-    validate_can_edit_or_view_file, validate_user_can_edit_file,
-    validate_user_can_edit_or_view_file,
+    broadcast, request::MessageRequest, response::MessageResponse, send_to_sessions,
+    send_user_message, validate::validate_transaction,
 };
+use crate::metrics::{METRICS, record_transaction};
+use crate::permissions::{validate_can_edit_or_view_file, validate_user_can_edit_or_view_file};
 use crate::state::user::UserSocket;
 use crate::state::{
     State,
     connection::PreConnection,
+    presence::broadcast_presence,
     pubsub::GROUP_NAME,
     user::{User, UserState},
 };
 
+/// Clients asking for more transactions than this (and that support it) are
+/// sent to the latest checkpoint instead of replaying every transaction.
+pub(crate) const FAST_FORWARD_THRESHOLD: u64 = 500;
+
+/// The maximum number of characters in a chat message.
+pub(crate) const MAX_CHAT_MESSAGE_LENGTH: usize = 4_000;
+
+/// Get the connection's permissions on a file and the file's sequence_num.
+async fn get_file_permissions(
+    state: &State,
+    pre_connection: &PreConnection,
+    file_id: Uuid,
+) -> Result<(Vec<FilePermRole>, u64)> {
+    // default to all roles for tests
+    if cfg!(test) {
+        return Ok((vec![FilePermRole::FileView, FilePermRole::FileEdit], 0));
+    }
+
+    let base_url = &state.settings.quadratic_api_uri;
+
+    // anonymous users can log in without a jwt
+    let jwt = pre_connection.jwt.to_owned().unwrap_or_default();
+
+    // get permission and sequence_num from the quadratic api
+    let (permissions, mut sequence_num) = get_file_perms(base_url, jwt, file_id).await?;
+
+    tracing::trace!("permissions: {:?}", permissions);
+
+    if let Ok(pubsub_sequence_num) = state.get_last_message_pubsub(&file_id).await {
+        // ignore parsing errors for now
+        let pubsub_sequence_num = pubsub_sequence_num.0.parse::<u64>().unwrap_or(sequence_num);
+        sequence_num = sequence_num.max(pubsub_sequence_num);
+    }
+
+    Ok((permissions, sequence_num))
+}
+
 /// Handle incoming messages.  All requests and responses are strictly typed.
 #[tracing::instrument(level = "trace")]
 pub(crate) async fn handle_message(
@@ -55,30 +94,8 @@ pub(crate) async fn handle_message(
             follow,
         } => {
             // validate that the user has permission to access the file
-            let base_url = &state.settings.quadratic_api_uri;
-
-            // anonymous users can log in without a jwt
-            let jwt = pre_connection.jwt.to_owned().unwrap_or_default();
-
-            // default to all roles for tests
-            let (permissions, sequence_num) = if cfg!(test) {
-                (vec![FilePermRole::FileView, FilePermRole::FileEdit], 0)
-            } else {
-                // get permission and sequence_num from the quadratic api
-                let (permissions, mut sequence_num) =
-                    get_file_perms(base_url, jwt, file_id).await?;
-
-                tracing::trace!("permissions: {:?}", permissions);
-
-                if let Ok(pubsub_sequence_num) = state.get_last_message_pubsub(&file_id).await {
-                    // ignore parsing errors for now
-                    let pubsub_sequence_num =
-                        pubsub_sequence_num.0.parse::<u64>().unwrap_or(sequence_num);
-                    sequence_num = sequence_num.max(pubsub_sequence_num);
-                }
-
-                (permissions, sequence_num)
-            };
+            let (permissions, sequence_num) =
+                get_file_permissions(&state, &pre_connection, file_id).await?;
 
             validate_can_edit_or_view_file(&permissions)?;
 
@@ -117,10 +134,28 @@ pub(crate) async fn handle_message(
                 tracing::info!("Error subscribing to pubsub channel: {}", error);
             };
 
+            // receive broadcasts for the room from other instances
+            if let Err(error) = state.join_cluster_room(&file_id).await {
+                tracing::warn!("Error joining cluster room {file_id}: {error}");
+            };
+
             let is_new = state
                 .enter_room(file_id, &mut user, pre_connection, sequence_num)
                 .await?;
 
+            // sheet ids are validated against the file's sheets once loaded
+            if let Err(error) = state.load_sheets(file_id).await {
+                tracing::warn!("Error loading sheets for room {file_id}: {error}");
+            }
+
+            // the user can resume this session if their connection drops
+            let resume_token = state.start_session(file_id, session_id).await?;
+
+            // let other instances know the user is in the room
+            if let Err(error) = state.set_cluster_user(file_id, user.to_owned()).await {
+                tracing::warn!("Error adding user {session_id} to the cluster: {error}");
+            };
+
             // direct response to user w/sequence_num after logging in
             send_user_message(
                 session_id,
@@ -129,16 +164,28 @@ pub(crate) async fn handle_message(
                 MessageResponse::EnterRoom {
                     file_id,
                     sequence_num,
+                    resume_token,
                 },
             )
             .await
             .map_err(|e| MpError::SendingMessage(e.to_string()))?;
 
+            // catch the user up on the conversation so far
+            let messages = state.get_chat_messages(&file_id).await?;
+
+            if !messages.is_empty() {
+                send_user_message(
+                    session_id,
+                    file_id,
+                    Arc::clone(&state),
+                    MessageResponse::ChatMessages { file_id, messages },
+                );
+            }
+
             // only broadcast if the user is new to the room
             // response is the variant MessageResponse::UsersInRoom
             if is_new {
-                let room = state.get_room(&file_id).await?;
-                let response = MessageResponse::from((room.users, &state.settings.version));
+                let response = state.users_in_room(&file_id).await?;
 
                 broadcast(vec![], file_id, Arc::clone(&state), response);
             }
@@ -149,6 +196,80 @@ pub(crate) async fn handle_message(
             Ok(Some(response))
         }
 
+        // User reconnects after their connection dropped
+        MessageRequest::ResumeSession {
+            session_id,
+            file_id,
+            resume_token,
+            last_sequence_num,
+            unacknowledged,
+        } => {
+            // the new connection must still have access to the file
+            let file_permissions = get_file_permissions(&state, &pre_connection, file_id).await?;
+
+            validate_can_edit_or_view_file(&file_permissions.0)?;
+
+            let session = state
+                .resume_session(
+                    file_id,
+                    session_id,
+                    resume_token,
+                    Arc::clone(&sender),
+                    pre_connection,
+                    file_permissions,
+                )
+                .await?;
+
+            // the session may have been held by another instance
+            if let Err(error) = state.subscribe_pubsub(&file_id, GROUP_NAME).await {
+                tracing::info!("Error subscribing to pubsub channel: {}", error);
+            };
+
+            if let Err(error) = state.join_cluster_room(&file_id).await {
+                tracing::warn!("Error joining cluster room {file_id}: {error}");
+            };
+
+            let sequence_num = state.get_sequence_num(&file_id).await?;
+            let mut responses = vec![MessageResponse::ResumeSession {
+                file_id,
+                sequence_num,
+            }];
+
+            // resend the acks that were lost with the old connection
+            responses.extend(
+                session
+                    .transactions
+                    .into_iter()
+                    .filter(|(id, _)| unacknowledged.contains(id))
+                    .map(|(id, sequence_num)| MessageResponse::TransactionAck {
+                        id,
+                        file_id,
+                        sequence_num,
+                    }),
+            );
+
+            // replay what was broadcast while the user was gone
+            responses.extend(session.missed);
+            responses.push(state.users_in_room(&file_id).await?);
+
+            for response in responses {
+                send_user_message(session_id, file_id, Arc::clone(&state), response)
+                    .await
+                    .map_err(|e| MpError::SendingMessage(e.to_string()))?;
+            }
+
+            // finally, send the transactions that were missed
+            if last_sequence_num >= sequence_num {
+                return Ok(None);
+            }
+
+            let response =
+                get_binary_transactions(&state, file_id, last_sequence_num + 1, sequence_num)
+                    .await?;
+
+            Ok(Some(response))
+        }
+
         // User leaves a room
         MessageRequest::LeaveRoom {
             session_id,
@@ -157,10 +278,9 @@ pub(crate) async fn handle_message(
             validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
 
             let is_not_empty = state.leave_room(file_id, &session_id).await?;
-            let room = state.get_room(&file_id).await?;
 
             if is_not_empty {
-                let response = MessageResponse::from((room.users, &state.settings.version));
+                let response = state.users_in_room(&file_id).await?;
                 broadcast(vec![session_id], file_id, Arc::clone(&state), response);
             }
 
@@ -175,7 +295,8 @@ pub(crate) async fn handle_message(
             file_id,
             operations,
         } => {
-            validate_user_can_edit_file(Arc::clone(&state), file_id, session_id).await?;
+            // viewers may send some operations, which are checked individually
+            validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
 
             // update the heartbeat
             state.update_user_heartbeat(file_id, &session_id).await?;
@@ -187,18 +308,32 @@ pub(crate) async fn handle_message(
                 &operations
             );
 
-            // get and increment the room's sequence_num
-            let room_sequence_num = get_mut_room!(state, file_id)?.increment_sequence_num();
             let decoded_operations = STANDARD.decode(&operations).map_err(|e| {
                 MpError::Serialization(format!(
                     "Could not decode base64 encoded operations in transaction {id}: {e:?}"
                 ))
             })?;
 
-            // add the transaction to the transaction queue
-            let sequence_num = state
-                .push(id, file_id, decoded_operations, room_sequence_num)
-                .await?;
+            // reject invalid transactions before they get a sequence_num
+            let validated =
+                validate_incoming_transaction(&state, id, file_id, session_id, &decoded_operations)
+                    .await?;
+            let validated_operations = match validated {
+                Ok(operations) => operations,
+                Err(response) => return Ok(Some(response)),
+            };
+
+            // add the transaction to the transaction queue with the room's
+            // next sequence_num across all instances
+            record_transaction(decoded_operations.len());
+            let sequence_num = state.push_next(id, file_id, decoded_operations).await?;
+
+            // only a pushed transaction changes the room's sheets
+            get_mut_room!(state, file_id)?.track_sheets(&validated_operations);
+
+            state
+                .record_session_transaction(&session_id, id, sequence_num)
+                .await;
 
             // broadcast the transaction to all users in the room (except the initiator)
             let response = MessageResponse::Transaction {
@@ -226,7 +361,8 @@ pub(crate) async fn handle_message(
             file_id,
             operations,
         } => {
-            validate_user_can_edit_file(Arc::clone(&state), file_id, session_id).await?;
+            // viewers may send some operations, which are checked individually
+            validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
 
             // update the heartbeat
             state.update_user_heartbeat(file_id, &session_id).await?;
@@ -238,16 +374,30 @@ pub(crate) async fn handle_message(
                 &operations
             );
 
-            // get and increment the room's sequence_num
-            let room_sequence_num = get_mut_room!(state, file_id)?.increment_sequence_num();
+            // reject invalid transactions before they get a sequence_num
+            let validated =
+                validate_incoming_transaction(&state, id, file_id, session_id, &operations).await?;
+            let validated_operations = match validated {
+                Ok(operations) => operations,
+                Err(response) => return Ok(Some(response)),
+            };
 
-            // add the transaction to the transaction queue
+            // add the transaction to the transaction queue with the room's
+            // next sequence_num across all instances
             // we need to clone operations since we broadcast it later
             let start_push_pubsub = std::time::Instant::now();
             let sequence_num = state
-                .push(id, file_id, operations.to_owned(), room_sequence_num)
+                .push_next(id, file_id, operations.to_owned())
                 .await?;
             tracing::trace!("Pushed to pubsub in {:?}", start_push_pubsub.elapsed());
+            record_transaction(operations.len());
+
+            // only a pushed transaction changes the room's sheets
+            get_mut_room!(state, file_id)?.track_sheets(&validated_operations);
+
+            state
+                .record_session_transaction(&session_id, id, sequence_num)
+                .await;
 
             // broadcast the transaction to all users in the room (except the initiator)
             let response = MessageResponse::BinaryTransaction {
@@ -322,7 +472,8 @@ pub(crate) async fn handle_message(
         MessageRequest::GetBinaryTransactions {
             file_id,
             session_id,
-            min_sequence_num,
+            mut min_sequence_num,
+            fast_forward,
         } => {
             validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
 
@@ -331,39 +482,36 @@ pub(crate) async fn handle_message(
 
             let sequence_num = state.get_sequence_num(&file_id).await?;
 
-            // calculate the expected number of transactions to get from redis
-            // add 1 to include the min_sequence_num (inclusive range)
-            let expected_num_transactions = sequence_num
-                .checked_sub(min_sequence_num)
-                .unwrap_or_default()
-                + 1;
-
-            tracing::trace!("min_sequence_num: {}", min_sequence_num);
-            tracing::trace!("sequence_num: {}", sequence_num);
-            tracing::trace!("expected_num_transactions: {}", expected_num_transactions);
-
-            let transactions = state
-                .get_messages_from_pubsub(&file_id, min_sequence_num)
-                .await?
-                .into_iter()
-                .map(|transaction| transaction.into())
-                .collect::<Vec<BinaryTransaction>>();
-
-            tracing::trace!("got: {}", transactions.len());
-
-            // we don't have the expected number of transactions
-            // send an error to the client so they can reload
-            if transactions.len() < expected_num_transactions as usize {
-                return Ok(Some(MessageResponse::Error {
-                    error: MpError::MissingTransactions(
-                        expected_num_transactions.to_string(),
-                        transactions.len().to_string(),
-                    ),
-                    error_level: ErrorLevel::Error,
-                }));
+            // point clients that are far behind at the latest checkpoint
+            if fast_forward
+                && sequence_num.saturating_sub(min_sequence_num) > FAST_FORWARD_THRESHOLD
+            {
+                let checkpoint = state.get_latest_checkpoint(file_id).await?;
+                let checkpoint_sequence_num = checkpoint.sequence_number;
+
+                if checkpoint_sequence_num > min_sequence_num {
+                    send_user_message(
+                        session_id,
+                        file_id,
+                        Arc::clone(&state),
+                        MessageResponse::FastForward {
+                            file_id,
+                            checkpoint_sequence_num,
+                            checkpoint_version: checkpoint.version,
+                            checkpoint_key: checkpoint.s3_key,
+                            sequence_num,
+                        },
+                    )
+                    .await
+                    .map_err(|e| MpError::SendingMessage(e.to_string()))?;
+
+                    // only the transactions after the checkpoint are needed
+                    min_sequence_num = checkpoint_sequence_num + 1;
+                }
             }
 
-            let response = MessageResponse::BinaryTransactions { transactions };
+            let response =
+                get_binary_transactions(&state, file_id, min_sequence_num, sequence_num).await?;
 
             Ok(Some(response))
         }
@@ -383,34 +531,196 @@ pub(crate) async fn handle_message(
                 .update_user_state(&file_id, &session_id, &update)
                 .await?;
 
-            let response = MessageResponse::UserUpdate {
+            broadcast_presence(Arc::clone(&state), file_id, session_id, update).await;
+
+            Ok(None)
+        }
+
+        // User sends a heartbeat
+        MessageRequest::Heartbeat {
+            session_id,
+            file_id,
+        } => {
+            validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
+
+            // update the heartbeat
+            state.update_user_heartbeat(file_id, &session_id).await?;
+            Ok(None)
+        }
+
+        // User sends a chat message to the room
+        MessageRequest::ChatMessage {
+            id,
+            session_id,
+            file_id,
+            text,
+            mentions,
+            anchor,
+        } => {
+            validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
+
+            // update the heartbeat
+            state.update_user_heartbeat(file_id, &session_id).await?;
+
+            let text = text.trim().to_string();
+
+            if text.is_empty() {
+                return Err(MpError::Chat("Chat messages cannot be empty".into()));
+            }
+
+            if text.chars().count() > MAX_CHAT_MESSAGE_LENGTH {
+                return Err(MpError::Chat(format!(
+                    "Chat messages cannot be longer than {MAX_CHAT_MESSAGE_LENGTH} characters"
+                )));
+            }
+
+            let user = state.get_room(&file_id).await?.get_user(&session_id)?;
+            let message = ChatMessage {
+                id,
                 session_id,
-                file_id,
-                update,
+                user_id: user.user_id,
+                first_name: user.first_name,
+                last_name: user.last_name,
+                text,
+                mentions,
+                anchor,
+                created_at: Utc::now(),
             };
 
-            broadcast(vec![session_id], file_id, Arc::clone(&state), response);
+            state.add_chat_message(file_id, message.to_owned()).await?;
+
+            // notify the mentioned users, other than the sender
+            let mentioned = message
+                .mentions
+                .iter()
+                .filter(|mention| **mention != session_id)
+                .copied()
+                .collect::<Vec<Uuid>>();
+
+            if !mentioned.is_empty() {
+                let response = MessageResponse::Mention {
+                    file_id,
+                    message: message.to_owned(),
+                };
+                send_to_sessions(&mentioned, file_id, Arc::clone(&state), response).await?;
+            }
+
+            // broadcast the message to everyone in the room, including the
+            // sender, so all users see the same order
+            let response = MessageResponse::ChatMessage { file_id, message };
+            broadcast(vec![], file_id, Arc::clone(&state), response);
 
             Ok(None)
         }
 
-        // User sends a heartbeat
-        MessageRequest::Heartbeat {
+        // User pings a cell for others in the room to jump to
+        MessageRequest::PingCell {
             session_id,
             file_id,
+            sheet_id,
+            selection,
+            session_ids,
         } => {
             validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
 
             // update the heartbeat
             state.update_user_heartbeat(file_id, &session_id).await?;
+
+            let response = MessageResponse::PingCell {
+                file_id,
+                session_id,
+                sheet_id,
+                selection,
+            };
+
+            if session_ids.is_empty() {
+                broadcast(vec![session_id], file_id, Arc::clone(&state), response);
+            } else {
+                send_to_sessions(&session_ids, file_id, Arc::clone(&state), response).await?;
+            }
+
             Ok(None)
         }
     }
 }
 
+/// Get a room's binary transactions from `min_sequence_num` up to
+/// `sequence_num`.  Returns an error response if any are missing.
+async fn get_binary_transactions(
+    state: &State,
+    file_id: Uuid,
+    min_sequence_num: u64,
+    sequence_num: u64,
+) -> Result<MessageResponse> {
+    // calculate the expected number of transactions to get from redis
+    // add 1 to include the min_sequence_num (inclusive range)
+    let expected_num_transactions = sequence_num
+        .checked_sub(min_sequence_num)
+        .unwrap_or_default()
+        + 1;
+
+    tracing::trace!("min_sequence_num: {}", min_sequence_num);
+    tracing::trace!("sequence_num: {}", sequence_num);
+    tracing::trace!("expected_num_transactions: {}", expected_num_transactions);
+
+    let transactions = state
+        .get_messages_from_pubsub(&file_id, min_sequence_num)
+        .await?
+        .into_iter()
+        .map(|transaction| transaction.into())
+        .collect::<Vec<BinaryTransaction>>();
+
+    tracing::trace!("got: {}", transactions.len());
+
+    // we don't have the expected number of transactions
+    // send an error to the client so they can reload
+    if transactions.len() < expected_num_transactions as usize {
+        return Ok(MessageResponse::Error {
+            error: MpError::MissingTransactions(
+                expected_num_transactions.to_string(),
+                transactions.len().to_string(),
+            ),
+            error_level: ErrorLevel::Error,
+        });
+    }
+
+    Ok(MessageResponse::BinaryTransactions { transactions })
+}
+
+/// Decode and validate a transaction's operations.  Returns the decoded
+/// operations, or an error response if the transaction is rejected.
+async fn validate_incoming_transaction(
+    state: &State,
+    id: Uuid,
+    file_id: Uuid,
+    session_id: Uuid,
+    operations: &[u8],
+) -> Result<std::result::Result<Vec<Operation>, MessageResponse>> {
+    let room = state.get_room(&file_id).await?;
+    let user = room.get_user(&session_id)?;
+
+    match validate_transaction(
+        id,
+        &user.permissions,
+        operations,
+        &room.sheets,
+        state.settings.max_transaction_size_bytes,
+    ) {
+        Ok(operations) => Ok(Ok(operations)),
+        Err(error) => {
+            tracing::warn!("Rejected transaction {id} in room {file_id}: {error}");
+            METRICS.error(&error);
+
+            Ok(Err(MessageResponse::Error {
+                error,
+                error_level: ErrorLevel::Warning,
+            }))
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
-    use quadratic_core::controller::operations::operation::Operation;
     use quadratic_core::controller::transaction::Transaction as CoreTransaction;
     use quadratic_core::grid::SheetId;
     use tokio::net::TcpStream;
@@ -419,7 +729,7 @@ pub(crate) mod tests {
     use uuid::Uuid;
 
     use super::*;
-    use crate::message::response::MinVersion;
+    use crate::message::response::{CellAnchor, MinVersion};
     use crate::state::settings::version;
     use crate::state::user::{CellEdit, UserStateUpdate};
     use crate::test_util::{integration_test_receive, new_user, setup};
@@ -514,29 +824,129 @@ pub(crate) mod tests {
 
         let response = MessageResponse::CurrentTransaction { sequence_num: 0 };
 
-        let broadcast_response = MessageResponse::EnterRoom {
-            file_id,
-            sequence_num: 0,
-        };
-
         let users_in_room = state.get_room(&file_id).await.unwrap().users;
         assert_eq!(users_in_room.len(), 2);
 
         test_handle(
-            socket,
+            socket.clone(),
             state.clone(),
             file_id,
             user_1,
             request,
             Some(response),
-            Some(broadcast_response),
+            None,
         )
         .await;
 
+        // the resume token is random
+        let received = integration_test_receive(&socket, 4).await;
+        assert!(matches!(
+            received,
+            Some(MessageResponse::EnterRoom {
+                file_id: received_file_id,
+                sequence_num: 0,
+                ..
+            }) if received_file_id == file_id
+        ));
+
         let users_in_room = state.get_room(&file_id).await.unwrap().users;
         assert_eq!(users_in_room.len(), 3);
     }
 
+    #[tokio::test]
+    async fn handle_resume_session() {
+        let (_, state, connection_id, file_id, user_1, _) = setup().await;
+        let session_id = user_1.session_id;
+        let user_1 = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        let stream = user_1.socket.clone().unwrap();
+        let resume_token = state
+            .get_session(&session_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .token;
+        let id = Uuid::new_v4();
+        let operations = vec![Operation::SetSheetColor {
+            sheet_id: SheetId::new(),
+            color: Some("red".to_string()),
+        }];
+        let operations = CoreTransaction::serialize_and_compress(&operations).unwrap();
+
+        // the ack for this transaction is lost with the connection
+        let request = MessageRequest::BinaryTransaction {
+            id,
+            session_id,
+            file_id,
+            operations: operations.clone(),
+        };
+        handle_message(
+            request,
+            state.clone(),
+            stream.clone(),
+            PreConnection::new(None),
+        )
+        .await
+        .unwrap();
+        let sequence_num = state.get_sequence_num(&file_id).await.unwrap();
+
+        assert!(
+            state
+                .disconnect_session(file_id, session_id, connection_id)
+                .await
+        );
+
+        // the wrong token can't resume the session
+        let request = MessageRequest::ResumeSession {
+            session_id,
+            file_id,
+            resume_token: Uuid::new_v4(),
+            last_sequence_num: sequence_num - 1,
+            unacknowledged: vec![id],
+        };
+        let handled = handle_message(
+            request,
+            state.clone(),
+            stream.clone(),
+            PreConnection::new(None),
+        )
+        .await;
+        assert!(matches!(handled, Err(MpError::ResumeSession(_))));
+
+        // the missed transaction is replayed
+        let request = MessageRequest::ResumeSession {
+            session_id,
+            file_id,
+            resume_token,
+            last_sequence_num: sequence_num - 1,
+            unacknowledged: vec![id],
+        };
+        let handled = handle_message(request, state.clone(), stream, PreConnection::new(None))
+            .await
+            .unwrap();
+        let response = MessageResponse::BinaryTransactions {
+            transactions: vec![BinaryTransaction {
+                id,
+                file_id,
+                sequence_num,
+                operations,
+            }],
+        };
+        assert_eq!(handled, Some(response));
+
+        // the user kept their place in the room
+        let user = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        assert!(user.socket.is_some());
+        assert_eq!(user.index, user_1.index);
+        let session = state.get_session(&session_id).await.unwrap().unwrap();
+        assert!(session.disconnected_at.is_none());
+    }
+
     #[tokio::test]
     async fn handle_leave_room() {
         let (socket, state, _, file_id, user_1, user_2) = setup().await;
@@ -647,6 +1057,7 @@ pub(crate) mod tests {
             file_id,
             session_id,
             min_sequence_num: 1,
+            fast_forward: false,
         };
         let transaction_1 = BinaryTransaction {
             id,
@@ -709,4 +1120,271 @@ pub(crate) mod tests {
         )
         .await;
     }
+
+    #[tokio::test]
+    async fn handle_invalid_transactions() {
+        let (socket, state, _, file_id, user_1, _) = setup().await;
+        let id = Uuid::new_v4();
+        let session_id = user_1.session_id;
+        let stream = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap()
+            .socket
+            .unwrap();
+
+        // undecodable operations
+        let request = MessageRequest::BinaryTransaction {
+            id,
+            file_id,
+            session_id,
+            operations: vec![1, 2, 3],
+        };
+        let handled = handle_message(
+            request,
+            state.clone(),
+            stream.clone(),
+            PreConnection::new(None),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            handled,
+            Some(MessageResponse::Error {
+                error: MpError::InvalidTransaction(..),
+                error_level: ErrorLevel::Warning,
+            })
+        ));
+
+        // operations on a deleted sheet
+        let sheet_id = SheetId::new();
+        let delete = vec![Operation::DeleteSheet {
+            sheet_id,
+            sheet_name: None,
+        }];
+        let request = MessageRequest::BinaryTransaction {
+            id,
+            file_id,
+            session_id,
+            operations: CoreTransaction::serialize_and_compress(&delete).unwrap(),
+        };
+        let response = MessageResponse::TransactionAck {
+            id,
+            file_id,
+            sequence_num: 1,
+        };
+        test_handle(
+            socket.clone(),
+            state.clone(),
+            file_id,
+            user_1.clone(),
+            request,
+            Some(response),
+            None,
+        )
+        .await;
+
+        let set_color = vec![Operation::SetSheetColor {
+            sheet_id,
+            color: Some("red".to_string()),
+        }];
+        let request = MessageRequest::Transaction {
+            id,
+            file_id,
+            session_id,
+            operations: STANDARD
+                .encode(CoreTransaction::serialize_and_compress(&set_color).unwrap()),
+        };
+        let handled = handle_message(
+            request,
+            state.clone(),
+            stream.clone(),
+            PreConnection::new(None),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            handled,
+            Some(MessageResponse::Error {
+                error: MpError::InvalidTransaction(..),
+                ..
+            })
+        ));
+
+        // rejected transactions don't get a sequence_num
+        assert_eq!(state.get_room(&file_id).await.unwrap().sequence_num, 1);
+    }
+
+    #[tokio::test]
+    async fn handle_fast_forward_transactions() {
+        let (socket, state, _, file_id, user_1, _) = setup().await;
+        let session_id = user_1.session_id;
+        let operations = vec![Operation::SetSheetColor {
+            sheet_id: SheetId::new(),
+            color: Some("red".to_string()),
+        }];
+        let operations = CoreTransaction::serialize_and_compress(&operations).unwrap();
+
+        // the file was checkpointed well after the client's sequence_num
+        let checkpoint_sequence_num = FAST_FORWARD_THRESHOLD + 10;
+        let mut transactions = vec![];
+
+        for sequence_num in checkpoint_sequence_num + 1..=checkpoint_sequence_num + 2 {
+            let id = Uuid::new_v4();
+            state
+                .push(id, file_id, operations.clone(), sequence_num)
+                .await
+                .unwrap();
+            transactions.push(BinaryTransaction {
+                id,
+                file_id,
+                operations: operations.clone(),
+                sequence_num,
+            });
+        }
+
+        {
+            let rooms = state.rooms.lock().await;
+            let mut room = rooms.get_mut(&file_id).unwrap();
+            room.checkpoint_sequence_num = checkpoint_sequence_num;
+            room.sequence_num = checkpoint_sequence_num + 2;
+        }
+
+        // only the transactions after the checkpoint are sent
+        let request = MessageRequest::GetBinaryTransactions {
+            file_id,
+            session_id,
+            min_sequence_num: 1,
+            fast_forward: true,
+        };
+        let response = MessageResponse::BinaryTransactions { transactions };
+
+        test_handle(
+            socket.clone(),
+            state.clone(),
+            file_id,
+            user_1.clone(),
+            request,
+            Some(response),
+            None,
+        )
+        .await;
+
+        // clients that don't support fast-forwarding replay everything
+        let request = MessageRequest::GetBinaryTransactions {
+            file_id,
+            session_id,
+            min_sequence_num: 1,
+            fast_forward: false,
+        };
+        let response = MessageResponse::Error {
+            error: MpError::MissingTransactions(
+                (checkpoint_sequence_num + 2).to_string(),
+                "2".into(),
+            ),
+            error_level: ErrorLevel::Error,
+        };
+
+        test_handle(
+            socket,
+            state,
+            file_id,
+            user_1,
+            request,
+            Some(response),
+            None,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn handle_ping_cell() {
+        let (socket, state, _, file_id, user_1, _) = setup().await;
+        let session_id = user_1.session_id;
+        let sheet_id = Uuid::new_v4();
+
+        let request = MessageRequest::PingCell {
+            session_id,
+            file_id,
+            sheet_id,
+            selection: "A1:B2".into(),
+            session_ids: vec![],
+        };
+
+        let response = MessageResponse::PingCell {
+            file_id,
+            session_id,
+            sheet_id,
+            selection: "A1:B2".into(),
+        };
+
+        test_handle(
+            socket,
+            state,
+            file_id,
+            user_1,
+            request,
+            None,
+            Some(response),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn handle_chat_messages() {
+        let (socket, state, _, file_id, user_1, user_2) = setup().await;
+        let session_id = user_1.session_id;
+        let anchor = CellAnchor {
+            sheet_id: Uuid::new_v4(),
+            selection: "C3".into(),
+        };
+
+        let request = MessageRequest::ChatMessage {
+            id: Uuid::new_v4(),
+            session_id,
+            file_id,
+            text: "  take a look at this  ".into(),
+            mentions: vec![user_2.session_id],
+            anchor: Some(anchor.clone()),
+        };
+
+        test_handle(
+            socket.clone(),
+            state.clone(),
+            file_id,
+            user_1.clone(),
+            request,
+            None,
+            None,
+        )
+        .await;
+
+        // the message is kept for users that join later
+        let messages = state.get_chat_messages(&file_id).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].text, "take a look at this");
+        assert_eq!(messages[0].user_id, user_1.user_id);
+        assert_eq!(messages[0].mentions, vec![user_2.session_id]);
+        assert_eq!(messages[0].anchor, Some(anchor));
+
+        // empty messages are rejected
+        let request = MessageRequest::ChatMessage {
+            id: Uuid::new_v4(),
+            session_id,
+            file_id,
+            text: " ".into(),
+            mentions: vec![],
+            anchor: None,
+        };
+        let stream = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap()
+            .socket
+            .unwrap();
+        let handled =
+            handle_message(request, state.clone(), stream, PreConnection::new(None)).await;
+        assert!(matches!(handled, Err(MpError::Chat(_))));
+        assert_eq!(state.get_chat_messages(&file_id).await.unwrap().len(), 1);
+    }
 }
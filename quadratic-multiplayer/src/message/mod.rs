@@ -14,6 +14,7 @@ pub mod handle;
 pub mod proto;
 pub mod request;
 pub mod response;
+pub mod validate;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub(crate) struct CellEdit {
@@ -50,7 +51,8 @@ pub(crate) struct UserStateUpdate {
     pub viewport: Option<String>,
 }
 
-/// Broadcast a message to all users in a room except the sender.
+/// Broadcast a message to all users in a room except the sender, including
+/// users connected to other instances.
 /// All messages are sent in a separate thread.
 #[tracing::instrument(level = "trace")]
 pub(crate) fn broadcast(
@@ -67,59 +69,106 @@ pub(crate) fn broadcast(
     );
 
     tokio::spawn(async move {
-        if let Ok(room) = state.get_room(&file_id).await {
-            let result = async {
-                let included_users = room
-                    .users
-                    .iter()
-                    .filter(|user| !exclude.contains(&user.session_id));
-
-                if included_users.clone().count() == 0 {
-                    return Ok::<_, MpError>(());
-                }
+        if let Err(e) = state.publish_to_cluster(file_id, &exclude, &message).await {
+            tracing::warn!(
+                "Error publishing message to the cluster: {:?}",
+                e.to_string()
+            );
+        }
 
-                let send_message = match message.is_binary() {
-                    true => {
-                        let serialized_message = encode_message(message)?;
-                        Message::Binary(serialized_message.into())
-                    }
-                    false => {
-                        let serialized_message = serde_json::to_string(&message)?;
-                        Message::Text(serialized_message.into())
-                    }
-                };
-
-                for user in included_users {
-                    if let Some(sender) = &user.socket {
-                        let sent = sender
-                            .lock()
-                            .await
-                            .send(send_message.to_owned())
-                            .await
-                            .map_err(|e| MpError::SendingMessage(e.to_string()));
-
-                        if let Err(error) = sent {
-                            tracing::warn!(
-                                "Error broadcasting to user {} in room {}: {:?}",
-                                user.session_id,
-                                file_id,
-                                error,
-                            );
-
-                            // the user's socket is stale, so remove them from the room
-                            state.leave_room(file_id, &user.session_id).await?;
-                        }
-                    }
-                }
+        if let Err(e) = send_to_local_users(exclude, file_id, state, message).await {
+            tracing::warn!("Error broadcasting message: {:?}", e.to_string());
+        }
+    })
+}
 
-                Ok::<_, MpError>(())
-            };
+/// Send a message to specific users in a room, including users connected to
+/// other instances.  Session ids that aren't in the room are ignored.
+pub(crate) async fn send_to_sessions(
+    session_ids: &[Uuid],
+    file_id: Uuid,
+    state: Arc<State>,
+    message: MessageResponse,
+) -> Result<JoinHandle<()>, MpError> {
+    let exclude = state
+        .get_users_in_room(&file_id)
+        .await?
+        .iter()
+        .map(|user| user.session_id)
+        .filter(|session_id| !session_ids.contains(session_id))
+        .collect::<Vec<Uuid>>();
+
+    Ok(broadcast(exclude, file_id, state, message))
+}
 
-            if let Err(e) = result.await {
-                tracing::warn!("Error broadcasting message: {:?}", e.to_string());
+/// Send a message to the users in a room that are connected to this instance,
+/// except those excluded.
+pub(crate) async fn send_to_local_users(
+    exclude: Vec<Uuid>,
+    file_id: Uuid,
+    state: Arc<State>,
+    message: MessageResponse,
+) -> Result<(), MpError> {
+    let Ok(room) = state.get_room(&file_id).await else {
+        return Ok(());
+    };
+
+    let included_users = room
+        .users
+        .iter()
+        .filter(|user| !exclude.contains(&user.session_id));
+
+    if included_users.clone().count() == 0 {
+        return Ok(());
+    }
+
+    // hold the message for users whose connection dropped until they resume
+    for user in included_users.clone().filter(|user| user.socket.is_none()) {
+        state.add_missed_message(&user.session_id, &message).await;
+    }
+
+    let send_message = match message.is_binary() {
+        true => {
+            let serialized_message = encode_message(message)?;
+            Message::Binary(serialized_message.into())
+        }
+        false => {
+            let serialized_message = serde_json::to_string(&message)?;
+            Message::Text(serialized_message.into())
+        }
+    };
+
+    for user in included_users {
+        if let Some(sender) = &user.socket {
+            let sent = sender
+                .lock()
+                .await
+                .send(send_message.to_owned())
+                .await
+                .map_err(|e| MpError::SendingMessage(e.to_string()));
+
+            if let Err(error) = sent {
+                tracing::warn!(
+                    "Error broadcasting to user {} in room {}: {:?}",
+                    user.session_id,
+                    file_id,
+                    error,
+                );
+
+                // the user's socket is stale, so hold their session for them to
+                // resume, or remove them from the room if they can't
+                let is_held = state
+                    .disconnect_session(file_id, user.session_id, user.connection_id)
+                    .await;
+
+                if !is_held {
+                    state.leave_room(file_id, &user.session_id).await?;
+                }
             }
         }
-    })
+    }
+
+    Ok(())
 }
 
 /// Send a message to a specific user in a room.
@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::message::response::CellAnchor;
 use crate::state::user::{CellEdit, UserStateUpdate};
 
 // NOTE: needs to be kept in sync with multiplayerTypes.ts
@@ -25,6 +26,18 @@ pub(crate) enum MessageRequest {
         viewport: String,
         follow: Option<String>,
     },
+    // reconnect within the grace period to pick up where the session left off
+    ResumeSession {
+        session_id: Uuid,
+        file_id: Uuid,
+        resume_token: Uuid,
+        last_sequence_num: u64,
+
+        // ids of transactions sent without receiving an ack, any that the
+        // server doesn't know about need to be resent
+        #[serde(default)]
+        unacknowledged: Vec<Uuid>,
+    },
     LeaveRoom {
         session_id: Uuid,
         file_id: Uuid,
@@ -55,9 +68,36 @@ pub(crate) enum MessageRequest {
         file_id: Uuid,
         session_id: Uuid,
         min_sequence_num: u64,
+
+        // the client can load the latest checkpoint instead of replaying a
+        // large number of transactions
+        #[serde(default)]
+        fast_forward: bool,
     },
     Heartbeat {
         session_id: Uuid,
         file_id: Uuid,
     },
+    ChatMessage {
+        id: Uuid,
+        session_id: Uuid,
+        file_id: Uuid,
+        text: String,
+
+        // session ids of the users to notify
+        #[serde(default)]
+        mentions: Vec<Uuid>,
+        #[serde(default)]
+        anchor: Option<CellAnchor>,
+    },
+    PingCell {
+        session_id: Uuid,
+        file_id: Uuid,
+        sheet_id: Uuid,
+        selection: String,
+
+        // session ids of the users to ping, or everyone in the room if empty
+        #[serde(default)]
+        session_ids: Vec<Uuid>,
+    },
 }
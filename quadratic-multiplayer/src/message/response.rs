@@ -6,6 +6,7 @@ use crate::error::{ErrorLevel, MpError};
 use crate::state::user::{User, UserStateUpdate};
 
 use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use quadratic_core::controller::transaction::TransactionServer;
 use serde::{Deserialize, Serialize};
@@ -27,6 +28,26 @@ pub(crate) struct BinaryTransaction {
     pub(crate) operations: Vec<u8>,
 }
 
+/// A sheet and A1 selection that recipients can jump to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct CellAnchor {
+    pub(crate) sheet_id: Uuid,
+    pub(crate) selection: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct ChatMessage {
+    pub(crate) id: Uuid,
+    pub(crate) session_id: Uuid,
+    pub(crate) user_id: String,
+    pub(crate) first_name: String,
+    pub(crate) last_name: String,
+    pub(crate) text: String,
+    pub(crate) mentions: Vec<Uuid>,
+    pub(crate) anchor: Option<CellAnchor>,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
 // TODO: to be deleted after the next release
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -77,10 +98,46 @@ pub(crate) enum MessageResponse {
     EnterRoom {
         file_id: Uuid,
         sequence_num: u64,
+
+        // used to resume the session after a dropped connection
+        resume_token: Uuid,
+    },
+    ResumeSession {
+        file_id: Uuid,
+        sequence_num: u64,
     },
     CurrentTransaction {
         sequence_num: u64,
     },
+    // sent ahead of the transactions after a checkpoint when loading the
+    // checkpoint is faster than replaying every missing transaction
+    FastForward {
+        file_id: Uuid,
+        checkpoint_sequence_num: u64,
+        checkpoint_version: String,
+        checkpoint_key: String,
+        sequence_num: u64,
+    },
+    ChatMessage {
+        file_id: Uuid,
+        message: ChatMessage,
+    },
+    // the room's chat history, sent to users when they enter the room
+    ChatMessages {
+        file_id: Uuid,
+        messages: Vec<ChatMessage>,
+    },
+    // sent only to the users mentioned in a chat message
+    Mention {
+        file_id: Uuid,
+        message: ChatMessage,
+    },
+    PingCell {
+        file_id: Uuid,
+        session_id: Uuid,
+        sheet_id: Uuid,
+        selection: String,
+    },
     Error {
         error: MpError,
         error_level: ErrorLevel,
@@ -94,6 +151,16 @@ impl MessageResponse {
             MessageResponse::BinaryTransaction { .. } | MessageResponse::BinaryTransactions { .. }
         )
     }
+
+    pub(crate) fn is_transaction(&self) -> bool {
+        matches!(
+            self,
+            MessageResponse::Transaction { .. }
+                | MessageResponse::BinaryTransaction { .. }
+                | MessageResponse::Transactions { .. }
+                | MessageResponse::BinaryTransactions { .. }
+        )
+    }
 }
 
 impl From<TransactionServer> for Transaction {
@@ -0,0 +1,596 @@
+//! Transaction Validation
+//!
+//! Decode and validate incoming transactions before they are given a sequence
+//! number and pushed to the transaction queue.  The multiplayer server doesn't
+//! load the file, so validation is limited to what can be checked from the
+//! operations alone and the room's sheets.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+use quadratic_core::controller::operations::operation::Operation;
+use quadratic_core::controller::transaction::Transaction;
+use quadratic_core::grid::SheetId;
+use quadratic_core::{SheetPos, SheetRect};
+use quadratic_rust_shared::quadratic_api::FilePermRole;
+use uuid::Uuid;
+
+use crate::error::{MpError, Result};
+use crate::permissions::{validate_can_edit_file, validate_can_edit_or_view_file};
+
+/// The default maximum size of a transaction's compressed operations, in
+/// bytes.  Configurable with `MAX_TRANSACTION_SIZE_BYTES`.
+pub(crate) const MAX_OPERATIONS_SIZE: usize = 50 * 1024 * 1024;
+
+/// The maximum number of operations in a single transaction.
+pub(crate) const MAX_OPERATIONS: usize = 100_000;
+
+/// The sheets a room knows about.  The file's sheets are loaded from its last
+/// checkpoint when available, otherwise only sheets deleted while the room has
+/// been open are rejected.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub(crate) struct RoomSheets {
+    /// The file's sheets, or None if they haven't been loaded.
+    pub(crate) sheet_ids: Option<HashSet<SheetId>>,
+    /// Sheets created by transactions while the room has been open.
+    pub(crate) added_sheet_ids: HashSet<SheetId>,
+    /// Sheets deleted by transactions while the room has been open.
+    pub(crate) deleted_sheet_ids: HashSet<SheetId>,
+}
+
+impl RoomSheets {
+    /// Record a sheet that an operation deletes or (re)creates.
+    pub(crate) fn track(&mut self, operation: &Operation) {
+        match operation {
+            Operation::DeleteSheet { sheet_id, .. } => self.delete(*sheet_id),
+            Operation::AddSheet { sheet } => self.add(sheet.id),
+            Operation::AddSheetSchema { schema } => {
+                if let Ok(sheet) = schema.to_owned().into_latest() {
+                    self.add(sheet.id);
+                }
+            }
+            Operation::DuplicateSheet { new_sheet_id, .. } => self.add(*new_sheet_id),
+            _ => {}
+        }
+    }
+
+    /// Set the file's sheets, applying the sheets created or deleted while
+    /// they were being loaded.
+    pub(crate) fn load(&mut self, sheet_ids: HashSet<SheetId>) {
+        let sheet_ids = sheet_ids
+            .union(&self.added_sheet_ids)
+            .filter(|sheet_id| !self.deleted_sheet_ids.contains(sheet_id))
+            .copied()
+            .collect();
+
+        self.sheet_ids = Some(sheet_ids);
+    }
+
+    fn add(&mut self, sheet_id: SheetId) {
+        self.deleted_sheet_ids.remove(&sheet_id);
+        self.added_sheet_ids.insert(sheet_id);
+
+        if let Some(sheet_ids) = &mut self.sheet_ids {
+            sheet_ids.insert(sheet_id);
+        }
+    }
+
+    fn delete(&mut self, sheet_id: SheetId) {
+        self.added_sheet_ids.remove(&sheet_id);
+        self.deleted_sheet_ids.insert(sheet_id);
+
+        if let Some(sheet_ids) = &mut self.sheet_ids {
+            sheet_ids.remove(&sheet_id);
+        }
+    }
+
+    /// Check that a sheet exists.
+    fn check(&self, sheet_id: &SheetId) -> std::result::Result<(), String> {
+        if self.deleted_sheet_ids.contains(sheet_id) {
+            return Err(format!("sheet {sheet_id} has been deleted"));
+        }
+
+        match &self.sheet_ids {
+            Some(sheet_ids) if !sheet_ids.contains(sheet_id) => {
+                Err(format!("sheet {sheet_id} does not exist"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Decode a transaction's operations and validate them.  Returns the decoded
+/// operations so the caller can record their side effects on the room once
+/// the transaction has been pushed.
+pub(crate) fn validate_transaction(
+    id: Uuid,
+    roles: &[FilePermRole],
+    operations: &[u8],
+    sheets: &RoomSheets,
+    max_size: usize,
+) -> Result<Vec<Operation>> {
+    let invalid = |reason: String| MpError::InvalidTransaction(id, reason);
+
+    validate_can_edit_or_view_file(roles)?;
+
+    if operations.len() > max_size {
+        return Err(invalid(format!(
+            "operations are {} bytes, the maximum is {max_size}",
+            operations.len()
+        )));
+    }
+
+    let operations = Transaction::decompress_and_deserialize::<Vec<Operation>>(operations)
+        .map_err(|e| invalid(format!("could not decode operations: {e}")))?;
+
+    if operations.len() > MAX_OPERATIONS {
+        return Err(invalid(format!(
+            "{} operations, the maximum is {MAX_OPERATIONS}",
+            operations.len()
+        )));
+    }
+
+    // sheets created or deleted earlier in this transaction count too
+    let mut sheets = sheets.to_owned();
+
+    for operation in &operations {
+        validate_operation_permissions(roles, operation)?;
+        validate_operation(operation, &sheets).map_err(invalid)?;
+        sheets.track(operation);
+    }
+
+    Ok(operations)
+}
+
+/// Cursor operations only move the sender's own selection, so viewers can
+/// send them.  Every other operation changes the file.
+fn validate_operation_permissions(roles: &[FilePermRole], operation: &Operation) -> Result<()> {
+    match operation {
+        Operation::SetCursor { .. }
+        | Operation::SetCursorA1 { .. }
+        | Operation::SetCursorSelection { .. } => validate_can_edit_or_view_file(roles),
+        _ => validate_can_edit_file(roles),
+    }
+}
+
+/// Validate a single operation's sheet ids and bounds.
+fn validate_operation(operation: &Operation, sheets: &RoomSheets) -> std::result::Result<(), String> {
+    let sheet = |sheet_id: &SheetId| sheets.check(sheet_id);
+    let sheet_pos = |sheet_pos: &SheetPos| {
+        sheet(&sheet_pos.sheet_id)?;
+        coordinate(sheet_pos.x)?;
+        coordinate(sheet_pos.y)
+    };
+    let sheet_rect = |sheet_rect: &SheetRect| {
+        sheet(&sheet_rect.sheet_id)?;
+        range(sheet_rect.min.x, sheet_rect.max.x)?;
+        range(sheet_rect.min.y, sheet_rect.max.y)
+    };
+
+    match operation {
+        Operation::SetCellValues { sheet_pos: pos, .. }
+        | Operation::SetDataTable { sheet_pos: pos, .. }
+        | Operation::AddDataTable { sheet_pos: pos, .. }
+        | Operation::DeleteDataTable { sheet_pos: pos }
+        | Operation::SetChartSize { sheet_pos: pos, .. }
+        | Operation::SetChartCellSize { sheet_pos: pos, .. }
+        | Operation::SetDataTableAt { sheet_pos: pos, .. }
+        | Operation::FlattenDataTable { sheet_pos: pos }
+        | Operation::SwitchDataTableKind { sheet_pos: pos, .. }
+        | Operation::DataTableMeta { sheet_pos: pos, .. }
+        | Operation::DataTableOptionMeta { sheet_pos: pos, .. }
+        | Operation::DataTableFormats { sheet_pos: pos, .. }
+        | Operation::DataTableBorders { sheet_pos: pos, .. }
+        | Operation::SortDataTable { sheet_pos: pos, .. }
+        | Operation::DataTableFirstRowAsHeader { sheet_pos: pos, .. }
+        | Operation::InsertDataTableColumns { sheet_pos: pos, .. }
+        | Operation::DeleteDataTableColumns { sheet_pos: pos, .. }
+        | Operation::InsertDataTableRows { sheet_pos: pos, .. }
+        | Operation::DeleteDataTableRows { sheet_pos: pos, .. }
+        | Operation::ComputeCode { sheet_pos: pos }
+        | Operation::SetValidationWarning { sheet_pos: pos, .. } => sheet_pos(pos),
+
+        Operation::GridToDataTable { sheet_rect: rect }
+        | Operation::SetCellFormats {
+            sheet_rect: rect, ..
+        }
+        | Operation::SetBorders {
+            sheet_rect: rect, ..
+        }
+        | Operation::SetCursor { sheet_rect: rect } => sheet_rect(rect),
+
+        Operation::MoveCells { source, dest, .. } => {
+            sheet_rect(source)?;
+            sheet_pos(dest)
+        }
+
+        Operation::SetCellFormatsSelection { selection, .. }
+        | Operation::SetBordersSelection { selection, .. }
+        | Operation::SetCursorSelection { selection } => sheet(&selection.sheet_id),
+
+        Operation::SetCursorA1 { selection } => sheet(&selection.sheet_id),
+
+        Operation::SetValidation { validation }
+        | Operation::CreateOrUpdateValidation { validation } => {
+            sheet(&validation.selection.sheet_id)
+        }
+
+        Operation::RemoveValidationSelection {
+            sheet_id,
+            selection,
+        } => {
+            sheet(sheet_id)?;
+            sheet(&selection.sheet_id)
+        }
+
+        Operation::SetCellFormatsA1 { sheet_id, .. }
+        | Operation::SetBordersA1 { sheet_id, .. }
+        | Operation::DuplicateSheet { sheet_id, .. }
+        | Operation::DeleteSheet { sheet_id, .. }
+        | Operation::SetSheetName { sheet_id, .. }
+        | Operation::SetSheetColor { sheet_id, .. }
+        | Operation::ReorderSheet {
+            target: sheet_id, ..
+        }
+        | Operation::RemoveValidation { sheet_id, .. } => sheet(sheet_id),
+
+        Operation::ResizeColumn {
+            sheet_id,
+            column: index,
+            new_size,
+            ..
+        }
+        | Operation::ResizeRow {
+            sheet_id,
+            row: index,
+            new_size,
+            ..
+        } => {
+            sheet(sheet_id)?;
+            coordinate(*index)?;
+            size(*new_size)
+        }
+
+        Operation::ResizeColumns {
+            sheet_id,
+            column_widths,
+        } => {
+            sheet(sheet_id)?;
+            column_widths.iter().try_for_each(|column_width| {
+                coordinate(column_width.column)?;
+                size(column_width.width)
+            })
+        }
+
+        Operation::ResizeRows {
+            sheet_id,
+            row_heights,
+        } => {
+            sheet(sheet_id)?;
+            row_heights.iter().try_for_each(|row_height| {
+                coordinate(row_height.row)?;
+                size(row_height.height)
+            })
+        }
+
+        Operation::DefaultRowSize { sheet_id, size: s }
+        | Operation::DefaultColumnSize { sheet_id, size: s } => {
+            sheet(sheet_id)?;
+            size(*s)
+        }
+
+        Operation::DeleteColumn {
+            sheet_id,
+            column: index,
+            ..
+        }
+        | Operation::DeleteRow {
+            sheet_id,
+            row: index,
+            ..
+        }
+        | Operation::InsertColumn {
+            sheet_id,
+            column: index,
+            ..
+        }
+        | Operation::InsertRow {
+            sheet_id,
+            row: index,
+            ..
+        } => {
+            sheet(sheet_id)?;
+            coordinate(*index)
+        }
+
+        Operation::MoveColumns {
+            sheet_id,
+            col_start: start,
+            col_end: end,
+            to,
+        }
+        | Operation::MoveRows {
+            sheet_id,
+            row_start: start,
+            row_end: end,
+            to,
+        } => {
+            sheet(sheet_id)?;
+            range(*start, *end)?;
+            coordinate(*to)
+        }
+
+        Operation::DeleteColumns {
+            sheet_id,
+            columns: indices,
+            ..
+        }
+        | Operation::DeleteRows {
+            sheet_id,
+            rows: indices,
+            ..
+        } => {
+            sheet(sheet_id)?;
+            indices.iter().try_for_each(|index| coordinate(*index))
+        }
+
+        // new sheets and file-level settings don't reference existing sheets
+        Operation::AddSheet { .. }
+        | Operation::AddSheetSchema { .. }
+        | Operation::SetCustomLists { .. } => Ok(()),
+    }
+}
+
+/// Columns and rows are 1-indexed.
+fn coordinate(value: i64) -> std::result::Result<(), String> {
+    match value >= 1 {
+        true => Ok(()),
+        false => Err(format!("{value} is out of bounds")),
+    }
+}
+
+fn range(start: i64, end: i64) -> std::result::Result<(), String> {
+    coordinate(start)?;
+    coordinate(end)?;
+
+    match start <= end {
+        true => Ok(()),
+        false => Err(format!("range {start}..{end} is inverted")),
+    }
+}
+
+fn size(value: f64) -> std::result::Result<(), String> {
+    match value.is_finite() && value >= 0.0 {
+        true => Ok(()),
+        false => Err(format!("size {value} is invalid")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quadratic_core::controller::GridController;
+
+    use super::*;
+    use crate::test_util::operation;
+
+    fn compress(operations: &[Operation]) -> Vec<u8> {
+        Transaction::serialize_and_compress(operations).unwrap()
+    }
+
+    #[test]
+    fn validates_a_transaction() {
+        let mut grid = GridController::test();
+        let roles = vec![FilePermRole::FileView, FilePermRole::FileEdit];
+        let sheets = RoomSheets::default();
+        let operations = vec![operation(&mut grid, 1, 1, "1")];
+        let id = Uuid::new_v4();
+
+        let decoded = validate_transaction(
+            id,
+            &roles,
+            &compress(&operations),
+            &sheets,
+            MAX_OPERATIONS_SIZE,
+        )
+        .unwrap();
+        assert_eq!(decoded, operations);
+
+        // too large
+        let result = validate_transaction(id, &roles, &compress(&operations), &sheets, 1);
+        assert!(matches!(result, Err(MpError::InvalidTransaction(..))));
+
+        // undecodable operations
+        let result = validate_transaction(id, &roles, &[1, 2, 3], &sheets, MAX_OPERATIONS_SIZE);
+        assert!(matches!(result, Err(MpError::InvalidTransaction(error_id, _)) if error_id == id));
+
+        // out of bounds
+        let operations = vec![operation(&mut grid, 0, 1, "1")];
+        let result = validate_transaction(
+            id,
+            &roles,
+            &compress(&operations),
+            &sheets,
+            MAX_OPERATIONS_SIZE,
+        );
+        assert!(matches!(result, Err(MpError::InvalidTransaction(..))));
+    }
+
+    #[test]
+    fn validates_permissions_per_operation() {
+        let mut grid = GridController::test();
+        let sheet_id = grid.sheet_ids()[0];
+        let sheets = RoomSheets::default();
+        let viewer = vec![FilePermRole::FileView];
+        let id = Uuid::new_v4();
+        let validate = |roles: &[FilePermRole], operations: Vec<Operation>| {
+            validate_transaction(
+                id,
+                roles,
+                &compress(&operations),
+                &sheets,
+                MAX_OPERATIONS_SIZE,
+            )
+        };
+
+        // viewers can move their cursor
+        let set_cursor = Operation::SetCursor {
+            sheet_rect: SheetRect::new(1, 1, 2, 2, sheet_id),
+        };
+        assert!(validate(&viewer, vec![set_cursor.clone()]).is_ok());
+
+        // but can't edit
+        let set_value = operation(&mut grid, 1, 1, "1");
+        let result = validate(&viewer, vec![set_cursor.clone(), set_value.clone()]);
+        assert!(matches!(result, Err(MpError::FilePermissions(_))));
+
+        // editors can do both
+        let editor = vec![FilePermRole::FileView, FilePermRole::FileEdit];
+        assert!(validate(&editor, vec![set_cursor.clone(), set_value]).is_ok());
+
+        // users without access can't do either
+        let result = validate(&[FilePermRole::FileDelete], vec![set_cursor]);
+        assert!(matches!(result, Err(MpError::FilePermissions(_))));
+    }
+
+    #[test]
+    fn rejects_operations_on_deleted_sheets() {
+        let mut grid = GridController::test();
+        let roles = vec![FilePermRole::FileEdit];
+        let sheet_id = grid.sheet_ids()[0];
+        let id = Uuid::new_v4();
+
+        let delete = Operation::DeleteSheet {
+            sheet_id,
+            sheet_name: None,
+        };
+        let set_value = operation(&mut grid, 1, 1, "1");
+
+        // deleted earlier in the same transaction
+        let mut sheets = RoomSheets::default();
+        let operations = vec![delete.clone(), set_value.clone()];
+        let result = validate_transaction(
+            id,
+            &roles,
+            &compress(&operations),
+            &sheets,
+            MAX_OPERATIONS_SIZE,
+        );
+        assert!(matches!(result, Err(MpError::InvalidTransaction(..))));
+
+        // deleted by a previous transaction
+        sheets.track(&delete);
+        let operations = vec![set_value.clone()];
+        let result = validate_transaction(
+            id,
+            &roles,
+            &compress(&operations),
+            &sheets,
+            MAX_OPERATIONS_SIZE,
+        );
+        assert!(matches!(result, Err(MpError::InvalidTransaction(..))));
+
+        // restored by duplicating into the same id (e.g. an undo)
+        sheets.track(&Operation::DuplicateSheet {
+            sheet_id: SheetId::new(),
+            new_sheet_id: sheet_id,
+        });
+        assert!(
+            validate_transaction(
+                id,
+                &roles,
+                &compress(&operations),
+                &sheets,
+                MAX_OPERATIONS_SIZE
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_operations_on_unknown_sheets() {
+        let mut grid = GridController::test();
+        let roles = vec![FilePermRole::FileEdit];
+        let sheet_id = grid.sheet_ids()[0];
+        let other_sheet_id = SheetId::new();
+        let id = Uuid::new_v4();
+        let set_value = operation(&mut grid, 1, 1, "1");
+        let validate = |operations: Vec<Operation>, sheets: &RoomSheets| {
+            validate_transaction(
+                id,
+                &roles,
+                &compress(&operations),
+                sheets,
+                MAX_OPERATIONS_SIZE,
+            )
+        };
+
+        // the room has never seen the sheet
+        let mut sheets = RoomSheets::default();
+        sheets.load(HashSet::from([other_sheet_id]));
+        let result = validate(vec![set_value.clone()], &sheets);
+        assert!(matches!(result, Err(MpError::InvalidTransaction(..))));
+
+        // created earlier in the same transaction
+        let duplicate = Operation::DuplicateSheet {
+            sheet_id: other_sheet_id,
+            new_sheet_id: sheet_id,
+        };
+        assert!(validate(vec![duplicate.clone(), set_value.clone()], &sheets).is_ok());
+
+        // created by a previous transaction
+        sheets.track(&duplicate);
+        assert!(validate(vec![set_value.clone()], &sheets).is_ok());
+
+        // sheets created or deleted before the file's sheets were loaded
+        let mut sheets = RoomSheets::default();
+        sheets.track(&duplicate);
+        sheets.track(&Operation::DeleteSheet {
+            sheet_id: other_sheet_id,
+            sheet_name: None,
+        });
+        sheets.load(HashSet::from([other_sheet_id]));
+        assert_eq!(sheets.sheet_ids, Some(HashSet::from([sheet_id])));
+    }
+
+    #[test]
+    fn validates_bounds_and_sizes() {
+        let sheet_id = SheetId::new();
+        let sheets = RoomSheets::default();
+        let validate = |operation: Operation| validate_operation(&operation, &sheets);
+
+        assert!(
+            validate(Operation::MoveColumns {
+                sheet_id,
+                col_start: 3,
+                col_end: 1,
+                to: 5,
+            })
+            .is_err()
+        );
+        assert!(
+            validate(Operation::ResizeRow {
+                sheet_id,
+                row: 1,
+                new_size: f64::NAN,
+                client_resized: false,
+            })
+            .is_err()
+        );
+        assert!(
+            validate(Operation::DeleteRows {
+                sheet_id,
+                rows: vec![1, -1],
+                copy_formats: Default::default(),
+            })
+            .is_err()
+        );
+        assert!(
+            validate(Operation::InsertColumn {
+                sheet_id,
+                column: 2,
+                copy_formats: Default::default(),
+            })
+            .is_ok()
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Metrics
+//!
+//! Prometheus metrics for the multiplayer server, served at `/metrics`.
+
+use axum::{Extension, http::header::CONTENT_TYPE, response::IntoResponse};
+use quadratic_rust_shared::metrics::{self, Registry};
+use std::sync::{Arc, LazyLock};
+
+use crate::state::State;
+
+pub(crate) static METRICS: LazyLock<Registry> =
+    LazyLock::new(|| Registry::new("quadratic_multiplayer"));
+
+/// Record a transaction that was added to the queue.
+pub(crate) fn record_transaction(size: usize) {
+    METRICS.inc("transactions_total", "Transactions received", &[]);
+    METRICS.observe(
+        "transaction_size_bytes",
+        "Size of the compressed operations in a transaction",
+        &[],
+        metrics::SIZE_BUCKETS,
+        size as f64,
+    );
+}
+
+/// Render all metrics, refreshing the gauges from the current state.
+pub(crate) async fn metrics(Extension(state): Extension<Arc<State>>) -> impl IntoResponse {
+    let stats = state.stats().await;
+
+    METRICS.set("rooms", "Open rooms", &[], stats.num_rooms as f64);
+    METRICS.set("users", "Users in rooms", &[], stats.num_users as f64);
+    METRICS.set(
+        "largest_room_size",
+        "Users in the largest room",
+        &[],
+        stats.largest_room_size as f64,
+    );
+
+    // transactions in rooms that haven't been checkpointed yet
+    let pubsub_lag = state
+        .rooms
+        .lock()
+        .await
+        .iter()
+        .map(|room| {
+            room.sequence_num
+                .saturating_sub(room.checkpoint_sequence_num)
+        })
+        .max()
+        .unwrap_or(0);
+    METRICS.set(
+        "pubsub_lag_transactions",
+        "Most transactions waiting to be checkpointed in a room",
+        &[],
+        pubsub_lag as f64,
+    );
+
+    ([(CONTENT_TYPE, metrics::CONTENT_TYPE)], METRICS.encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use crate::test_util::setup;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn renders_metrics() {
+        let (_, state, _, _, _, _) = setup().await;
+        record_transaction(100);
+
+        let response = metrics(Extension(state)).await.into_response();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.contains("# TYPE quadratic_multiplayer_rooms gauge\n"));
+        assert!(body.contains("# TYPE quadratic_multiplayer_transactions_total counter\n"));
+        assert!(body.contains("quadratic_multiplayer_transaction_size_bytes_count"));
+    }
+}
@@ -38,16 +38,6 @@ pub(crate) async fn validate_user_can_edit_or_view_file(
     validate_can_edit_or_view_file(&user.permissions)
 }
 
-pub(crate) async fn validate_user_can_edit_file(
-    state: Arc<State>,
-    file_id: Uuid,
-    session_id: Uuid,
-) -> Result<()> {
-    let user = state.get_room(&file_id).await?.get_user(&session_id)?;
-
-    validate_can_edit_file(&user.permissions)
-}
-
 #[cfg(test)]
 pub(crate) mod tests {
 
@@ -122,26 +112,4 @@ pub(crate) mod tests {
         let result = validate_user_can_edit_or_view_file(state.clone(), file_id, session_id).await;
         assert!(result.is_err());
     }
-
-    #[tokio::test]
-    async fn validates_user_can_edit_file() {
-        let (_, state, _, file_id, user, _) = setup().await;
-        let session_id = user.session_id;
-
-        let perms = vec![FilePermRole::FileView, FilePermRole::FileEdit];
-        state
-            .update_user_permissions(file_id, &session_id, perms)
-            .await
-            .unwrap();
-        let result = validate_user_can_edit_file(state.clone(), file_id, session_id).await;
-        assert!(result.is_ok());
-
-        let perms = vec![FilePermRole::FileView];
-        state
-            .update_user_permissions(file_id, &session_id, perms)
-            .await
-            .unwrap();
-        let result = validate_user_can_edit_file(state.clone(), file_id, session_id).await;
-        assert!(result.is_err());
-    }
 }
@@ -0,0 +1,187 @@
+//! Rate Limiting
+//!
+//! Limit how fast a single connection can send messages.  Each connection has
+//! a token bucket that refills at a steady rate and allows short bursts.
+//! Messages sent with an empty bucket are rejected, and connections that are
+//! rejected too often within a window are disconnected.
+//!
+//! Presence updates (cursor, selection and viewport) draw from a separate
+//! bucket, so moving around while editing doesn't use up the budget for
+//! transactions.  They're already coalesced before being broadcast, so
+//! rejected presence updates don't count towards disconnecting.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::state::settings::Settings;
+
+/// The window that rejected messages are counted over.
+const VIOLATION_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RateLimit {
+    Allowed,
+    Limited,
+    Exceeded,
+}
+
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_s: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(capacity: f64, refill_per_s: f64) -> Self {
+        TokenBucket {
+            capacity,
+            tokens: capacity,
+            refill_per_s,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token if one is available at `now`.
+    pub(crate) fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_s).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bucket: TokenBucket,
+    presence: TokenBucket,
+    violations: VecDeque<Instant>,
+    max_violations: usize,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(settings: &Settings) -> Self {
+        RateLimiter {
+            bucket: TokenBucket::new(settings.rate_limit_burst, settings.rate_limit_per_s),
+            presence: TokenBucket::new(settings.rate_limit_burst, settings.rate_limit_per_s),
+            violations: VecDeque::new(),
+            max_violations: settings.rate_limit_max_violations,
+        }
+    }
+
+    /// Check whether the connection can send another message.
+    pub(crate) fn check(&mut self) -> RateLimit {
+        self.check_at(Instant::now())
+    }
+
+    /// Check whether the connection can send another presence update.
+    pub(crate) fn check_presence(&mut self) -> RateLimit {
+        self.check_presence_at(Instant::now())
+    }
+
+    pub(crate) fn check_presence_at(&mut self, now: Instant) -> RateLimit {
+        match self.presence.try_take(now) {
+            true => RateLimit::Allowed,
+            false => RateLimit::Limited,
+        }
+    }
+
+    pub(crate) fn check_at(&mut self, now: Instant) -> RateLimit {
+        if self.bucket.try_take(now) {
+            return RateLimit::Allowed;
+        }
+
+        while self
+            .violations
+            .front()
+            .is_some_and(|violation| now.saturating_duration_since(*violation) > VIOLATION_WINDOW)
+        {
+            self.violations.pop_front();
+        }
+
+        self.violations.push_back(now);
+
+        match self.violations.len() > self.max_violations {
+            true => RateLimit::Exceeded,
+            false => RateLimit::Limited,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(burst: f64, per_s: f64, max_violations: usize) -> RateLimiter {
+        RateLimiter {
+            bucket: TokenBucket::new(burst, per_s),
+            presence: TokenBucket::new(burst, per_s),
+            violations: VecDeque::new(),
+            max_violations,
+        }
+    }
+
+    #[test]
+    fn refills_tokens_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 10.0);
+
+        assert!(bucket.try_take(start));
+        assert!(bucket.try_take(start));
+        assert!(!bucket.try_take(start));
+
+        // 100ms refills a single token
+        assert!(bucket.try_take(start + Duration::from_millis(100)));
+        assert!(!bucket.try_take(start + Duration::from_millis(100)));
+
+        // never refills past the capacity
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_take(later));
+        assert!(bucket.try_take(later));
+        assert!(!bucket.try_take(later));
+    }
+
+    #[test]
+    fn disconnects_persistent_violations() {
+        let start = Instant::now();
+        let mut limiter = limiter(1.0, 1.0, 2);
+
+        assert_eq!(limiter.check_at(start), RateLimit::Allowed);
+        assert_eq!(limiter.check_at(start), RateLimit::Limited);
+        assert_eq!(limiter.check_at(start), RateLimit::Limited);
+        assert_eq!(limiter.check_at(start), RateLimit::Exceeded);
+
+        // violations outside of the window are forgotten
+        let later = start + VIOLATION_WINDOW + Duration::from_secs(1);
+        assert_eq!(limiter.check_at(later), RateLimit::Allowed);
+        assert_eq!(limiter.check_at(later), RateLimit::Limited);
+    }
+
+    #[test]
+    fn limits_presence_separately() {
+        let start = Instant::now();
+        let mut limiter = limiter(2.0, 1.0, 0);
+
+        // a burst of presence updates is limited without disconnecting
+        for _ in 0..2 {
+            assert_eq!(limiter.check_presence_at(start), RateLimit::Allowed);
+        }
+        for _ in 0..10 {
+            assert_eq!(limiter.check_presence_at(start), RateLimit::Limited);
+        }
+
+        // and leaves the budget for other messages untouched
+        assert_eq!(limiter.check_at(start), RateLimit::Allowed);
+        assert_eq!(limiter.check_at(start), RateLimit::Allowed);
+        assert_eq!(limiter.check_at(start), RateLimit::Exceeded);
+    }
+}
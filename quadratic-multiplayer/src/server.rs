@@ -26,7 +26,7 @@ use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
     background_worker,
-    config::config,
+    config::{Config, config},
     error::{ErrorLevel, MpError, Result},
     health::{full_healthcheck, healthcheck},
     message::{
@@ -36,7 +36,9 @@ use crate::{
         request::MessageRequest,
         response::MessageResponse,
     },
-    state::{State, connection::PreConnection, user::UserSocket},
+    metrics::{METRICS, metrics},
+    rate_limit::{RateLimit, RateLimiter},
+    state::{State, cluster, connection::PreConnection, user::UserSocket},
 };
 
 const STATS_INTERVAL_S: u64 = 60;
@@ -61,6 +63,9 @@ pub(crate) fn app(state: Arc<State>) -> Router {
         // full healthcheck
         .route("/health/full", get(full_healthcheck))
         //
+        // prometheus metrics
+        .route("/metrics", get(metrics))
+        //
         // state
         .layer(Extension(state))
         //
@@ -72,7 +77,6 @@ pub(crate) fn app(state: Arc<State>) -> Router {
 }
 
 /// Start the websocket server.  This is the entrypoint for the application.
-#[tracing::instrument(level = "trace")]
 pub(crate) async fn serve() -> Result<()> {
     let tracing_layer = if config()?.environment.is_production() {
         tracing_subscriber::fmt::layer().json().boxed()
@@ -88,8 +92,12 @@ pub(crate) async fn serve() -> Result<()> {
         .with(tracing_layer)
         .init();
 
-    let config = config()?;
+    run(config()?).await
+}
 
+/// Run the server with the given config.  Tracing is left to the caller.
+#[tracing::instrument(level = "trace", skip(config))]
+pub(crate) async fn run(config: Config) -> Result<()> {
     // TODO(ddimaria): we do this check for every WS connection.  Does this
     // data change ofter or can it be cached?
     let jwks = get_jwks(&config.jwks_uri).await?;
@@ -120,6 +128,9 @@ pub(crate) async fn serve() -> Result<()> {
         config.heartbeat_timeout_s,
     );
 
+    // in a separate thread, relay broadcasts from other instances
+    cluster::listen(Arc::clone(&state));
+
     // in a separate thread, log stats
     tokio::spawn({
         let state = Arc::clone(&state);
@@ -212,8 +223,8 @@ async fn ws_handler(
         pre_connection.id
     );
 
-    // upgrade the connection
-    let ws = ws.max_message_size(1024 * 1024 * 1000); // 1GB
+    // upgrade the connection, leaving room for base64 encoded transactions
+    let ws = ws.max_message_size(state.settings.max_transaction_size_bytes * 2);
     ws.on_upgrade(move |socket| handle_socket(socket, state, addr, pre_connection))
 }
 
@@ -228,15 +239,34 @@ async fn handle_socket(
     let (sender, mut receiver) = socket.split();
     let sender = Arc::new(Mutex::new(sender));
     let connection_id = pre_connection.id;
+    let mut rate_limiter = RateLimiter::new(&state.settings);
 
     while let Some(Ok(msg)) = receiver.next().await {
-        let response = process_message(
-            msg,
-            Arc::clone(&sender),
-            Arc::clone(&state),
-            pre_connection.to_owned(),
-        )
-        .await;
+        let rate_limit = match &msg {
+            Message::Text(text) if is_presence(text) => rate_limiter.check_presence(),
+            Message::Text(_) | Message::Binary(_) => rate_limiter.check(),
+            _ => RateLimit::Allowed,
+        };
+
+        let response = match rate_limit {
+            RateLimit::Allowed => {
+                process_message(
+                    msg,
+                    Arc::clone(&sender),
+                    Arc::clone(&state),
+                    pre_connection.to_owned(),
+                )
+                .await
+            }
+            RateLimit::Limited => Err(MpError::RateLimited(format!(
+                "more than {} messages per second",
+                state.settings.rate_limit_per_s
+            ))),
+            RateLimit::Exceeded => Err(MpError::RateLimitExceeded(format!(
+                "more than {} rejected messages",
+                state.settings.rate_limit_max_violations
+            ))),
+        };
 
         match response {
             Ok(ControlFlow::Continue(_)) => {}
@@ -244,6 +274,7 @@ async fn handle_socket(
             Err(error) => {
                 let error_level = ErrorLevel::from(&error);
                 error_level.log(&format!("Error processing message: {:?}", &error));
+                METRICS.error(&error);
 
                 if let Ok(message) = serde_json::to_string(&MessageResponse::Error {
                     error: error.to_owned(),
@@ -264,6 +295,7 @@ async fn handle_socket(
                 match error {
                     // kill the ws connection for certain errors
                     MpError::Authentication(_)
+                    | MpError::RateLimitExceeded(_)
                     | MpError::UserNotFound(_, _)
                     | MpError::FilePermissions(_)
                     | MpError::RoomNotFound(_) => {
@@ -276,8 +308,13 @@ async fn handle_socket(
         }
     }
 
-    // websocket is closed, remove the user from any rooms they were in and broadcast
-    if let Ok(connection) = state.get_connection(connection_id).await {
+    // websocket is closed, hold the user's session for them to resume, or
+    // remove the user from any rooms they were in and broadcast
+    if let Ok(connection) = state.get_connection(connection_id).await
+        && !state
+            .disconnect_session(connection.file_id, connection.session_id, connection.id)
+            .await
+    {
         match state.remove_connection(&connection).await {
             Ok(Some(file_id)) => {
                 tracing::info!(
@@ -285,10 +322,9 @@ async fn handle_socket(
                     connection.session_id
                 );
 
-                if let Ok(room) = state.get_room(&file_id).await {
+                if let Ok(message) = state.users_in_room(&file_id).await {
                     tracing::info!("Broadcasting room {file_id} after connection close");
 
-                    let message = MessageResponse::from((room.users, &state.settings.version));
                     if let Err(error) = broadcast(
                         vec![connection.session_id],
                         file_id,
@@ -317,6 +353,18 @@ async fn handle_socket(
 
 /// Based on the incoming message type, perform some action and return a response.
 #[tracing::instrument(level = "trace")]
+/// Whether a text message is a presence update, which is rate limited
+/// separately from other messages.
+fn is_presence(text: &str) -> bool {
+    #[derive(Deserialize)]
+    struct MessageType {
+        r#type: String,
+    }
+
+    serde_json::from_str::<MessageType>(text)
+        .is_ok_and(|message| message.r#type == "UserUpdate")
+}
+
 async fn process_message(
     msg: Message,
     sender: UserSocket,
@@ -440,6 +488,24 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn detects_presence_updates() {
+        let (session_id, file_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let update = MessageRequest::UserUpdate {
+            session_id,
+            file_id,
+            update: UserStateUpdate::default(),
+        };
+        let heartbeat = MessageRequest::Heartbeat {
+            session_id,
+            file_id,
+        };
+
+        assert!(is_presence(&serde_json::to_string(&update).unwrap()));
+        assert!(!is_presence(&serde_json::to_string(&heartbeat).unwrap()));
+        assert!(!is_presence("not json"));
+    }
+
     #[tokio::test]
     async fn test_user_enters_a_room() {
         // user_2 is created using the MessageRequest::EnterRoom message
@@ -0,0 +1,626 @@
+//! Cluster
+//!
+//! Coordinate rooms across multiplayer instances.  Each instance subscribes to
+//! a Redis pub/sub channel for every room it hosts, publishes its broadcasts to
+//! that channel, and forwards messages from other instances to its own users.
+//! Room membership is mirrored in a Redis hash so that `UsersInRoom` includes
+//! users connected to any instance, and sequence numbers are allocated from an
+//! atomic counter in Redis.
+//!
+//! A single-node deployment has no other instances to relay broadcasts to, so
+//! its cluster has no pub/sub connection.  Membership and sequence numbers
+//! still go through the PubSub backend.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use dashmap::DashMap;
+use futures::stream::StreamExt;
+use quadratic_rust_shared::pubsub::{
+    Config as PubSubConfig, PubSub as PubSubTrait,
+    redis::{MessageStream, RedisConnection, Subscriber},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::{sync::Mutex, task::JoinHandle};
+use uuid::Uuid;
+
+use crate::error::{MpError, Result};
+use crate::message::{response::MessageResponse, send_to_local_users};
+use crate::state::{State, user::User};
+use crate::{get_mut_room, get_room};
+
+const RECONNECT_INTERVAL_MS: u64 = 1000;
+
+/// The number of times a transaction is retried with a later sequence number
+/// when other instances take the ones it tried.
+const MAX_PUSH_ATTEMPTS: usize = 20;
+
+/// The Redis channel that broadcasts for a room are published to.
+fn room_channel(file_id: &Uuid) -> String {
+    format!("multiplayer-room:{file_id}")
+}
+
+/// The Redis hash of users in a room, keyed by session id.
+fn room_users_key(file_id: &Uuid) -> String {
+    format!("multiplayer-room:{file_id}:users")
+}
+
+/// The Redis counter that a room's sequence numbers are allocated from.
+fn room_sequence_num_key(file_id: &Uuid) -> String {
+    format!("multiplayer-room:{file_id}:sequence_num")
+}
+
+/// A broadcast that is relayed to the users of other instances.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct ClusterMessage {
+    pub(crate) instance_id: Uuid,
+    pub(crate) file_id: Uuid,
+    pub(crate) exclude: Vec<Uuid>,
+    pub(crate) message: MessageResponse,
+}
+
+/// A user in a room, as seen by every instance.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ClusterUser {
+    instance_id: Uuid,
+    last_heartbeat: i64,
+    user: User,
+}
+
+pub(crate) struct Cluster {
+    pub(crate) instance_id: Uuid,
+    relay: Option<Relay>,
+}
+
+/// The pub/sub connections that relay broadcasts between instances.
+struct Relay {
+    config: PubSubConfig,
+    publisher: Mutex<RedisConnection>,
+    subscriber: Mutex<Subscriber>,
+    messages: Mutex<Option<MessageStream>>,
+}
+
+impl fmt::Debug for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cluster {{ instance_id: {} }}", self.instance_id)
+    }
+}
+
+impl Cluster {
+    /// Connect to the cluster's Redis pub/sub server.
+    pub(crate) async fn new(config: PubSubConfig) -> Result<Self> {
+        let publisher = RedisConnection::new(config.to_owned()).await?;
+        let (subscriber, messages) = Subscriber::new(config.to_owned()).await?;
+
+        Ok(Cluster {
+            instance_id: Uuid::new_v4(),
+            relay: Some(Relay {
+                config,
+                publisher: Mutex::new(publisher),
+                subscriber: Mutex::new(subscriber),
+                messages: Mutex::new(Some(messages)),
+            }),
+        })
+    }
+
+    /// A cluster of one instance, which has nothing to relay.
+    pub(crate) fn single_node() -> Self {
+        Cluster {
+            instance_id: Uuid::new_v4(),
+            relay: None,
+        }
+    }
+}
+
+impl State {
+    /// Subscribe to broadcasts for a room from other instances.
+    pub(crate) async fn join_cluster_room(&self, file_id: &Uuid) -> Result<()> {
+        let Some(relay) = &self.cluster.relay else {
+            return Ok(());
+        };
+
+        relay
+            .subscriber
+            .lock()
+            .await
+            .subscribe(&room_channel(file_id))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stop receiving broadcasts for a room from other instances.
+    pub(crate) async fn leave_cluster_room(&self, file_id: &Uuid) -> Result<()> {
+        let Some(relay) = &self.cluster.relay else {
+            return Ok(());
+        };
+
+        relay
+            .subscriber
+            .lock()
+            .await
+            .unsubscribe(&room_channel(file_id))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Publish a broadcast to the other instances serving a room.
+    pub(crate) async fn publish_to_cluster(
+        &self,
+        file_id: Uuid,
+        exclude: &[Uuid],
+        message: &MessageResponse,
+    ) -> Result<()> {
+        let Some(relay) = &self.cluster.relay else {
+            return Ok(());
+        };
+
+        let cluster_message = ClusterMessage {
+            instance_id: self.cluster.instance_id,
+            file_id,
+            exclude: exclude.to_vec(),
+            message: message.to_owned(),
+        };
+        let payload = serde_json::to_vec(&cluster_message)?;
+
+        relay
+            .publisher
+            .lock()
+            .await
+            .publish(&room_channel(&file_id), "", &payload, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Push a transaction to the transaction queue with the room's next
+    /// sequence number.  The counter is shared by all instances, and never
+    /// falls behind the room's local sequence number.  A sequence number is
+    /// only taken together with the push, so if another instance takes it
+    /// first the transaction is retried with the next one.  Returns the
+    /// sequence number the transaction was pushed with.
+    pub(crate) async fn push_next(
+        &self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+    ) -> Result<u64> {
+        let counter_key = room_sequence_num_key(&file_id);
+        let min_sequence_num = self.get_sequence_num(&file_id).await?;
+        let mut sequence_num = min_sequence_num + 1;
+
+        for _ in 0..MAX_PUSH_ATTEMPTS {
+            let current = self
+                .pubsub
+                .lock()
+                .await
+                .push_protobuf_next(
+                    id,
+                    file_id,
+                    operations.to_owned(),
+                    &counter_key,
+                    min_sequence_num,
+                    sequence_num,
+                )
+                .await?;
+
+            match current {
+                None => {
+                    self.observe_sequence_num(file_id, sequence_num).await?;
+
+                    return Ok(sequence_num);
+                }
+                Some(current) => sequence_num = current + 1,
+            }
+        }
+
+        Err(MpError::PubSub(format!(
+            "Unable to push transaction {id} to room {file_id}: no sequence number after {MAX_PUSH_ATTEMPTS} attempts"
+        )))
+    }
+
+    /// Advance a room's local sequence number to one allocated elsewhere.
+    pub(crate) async fn observe_sequence_num(
+        &self,
+        file_id: Uuid,
+        sequence_num: u64,
+    ) -> Result<()> {
+        get_mut_room!(self, file_id)?.advance_sequence_num(sequence_num);
+
+        Ok(())
+    }
+
+    /// Write this instance's connected users in a room to the cluster,
+    /// refreshing their heartbeats.  Disconnected users are left as they
+    /// were, since their session may be resumed on another instance.
+    pub(crate) async fn refresh_cluster_users(&self, file_id: Uuid) -> Result<()> {
+        let users = get_room!(self, file_id)?
+            .users
+            .iter()
+            .filter(|user| user.socket.is_some())
+            .map(|user| user.to_owned())
+            .collect::<Vec<User>>();
+
+        for user in users {
+            self.set_cluster_user(file_id, user).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Add or update a user in a room's cluster membership.
+    pub(crate) async fn set_cluster_user(&self, file_id: Uuid, user: User) -> Result<()> {
+        let session_id = user.session_id.to_string();
+        let cluster_user = ClusterUser {
+            instance_id: self.cluster.instance_id,
+            last_heartbeat: user.last_heartbeat.timestamp(),
+            user,
+        };
+        let value = serde_json::to_vec(&cluster_user)?;
+
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .set_field(&room_users_key(&file_id), &session_id, &value)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get a user in a room's cluster membership.
+    pub(crate) async fn get_cluster_user(
+        &self,
+        file_id: Uuid,
+        session_id: &Uuid,
+    ) -> Result<Option<User>> {
+        let cluster_user = self
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .field(&room_users_key(&file_id), &session_id.to_string())
+            .await?
+            .map(|value| serde_json::from_slice::<ClusterUser>(&value))
+            .transpose()?;
+
+        Ok(cluster_user.map(|cluster_user| cluster_user.user))
+    }
+
+    /// Remove a user from a room's cluster membership.
+    pub(crate) async fn remove_cluster_user(&self, file_id: Uuid, session_id: &Uuid) -> Result<()> {
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .remove_field(&room_users_key(&file_id), &session_id.to_string())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the users in a room across all instances.  Users that haven't sent
+    /// a heartbeat within the timeout are dropped from the cluster.  Falls
+    /// back to this instance's users if the cluster can't be reached.
+    pub(crate) async fn get_users_in_room(&self, file_id: &Uuid) -> Result<DashMap<Uuid, User>> {
+        let users = self.get_room(file_id).await?.users;
+        let cluster_users = self
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .fields(&room_users_key(file_id))
+            .await;
+
+        let cluster_users = match cluster_users {
+            Ok(cluster_users) => cluster_users,
+            Err(error) => {
+                tracing::warn!("Error getting cluster users in room {file_id}: {error}");
+                return Ok(users);
+            }
+        };
+
+        let oldest_heartbeat = Utc::now().timestamp() - self.settings.heartbeat_timeout_s;
+        let mut stale_session_ids = vec![];
+
+        for (session_id, value) in cluster_users {
+            let Ok(cluster_user) = serde_json::from_slice::<ClusterUser>(&value) else {
+                stale_session_ids.push(session_id);
+                continue;
+            };
+
+            if cluster_user.instance_id == self.cluster.instance_id
+                || users.contains_key(&cluster_user.user.session_id)
+            {
+                continue;
+            }
+
+            if cluster_user.last_heartbeat < oldest_heartbeat {
+                stale_session_ids.push(session_id);
+            } else {
+                users.insert(cluster_user.user.session_id, cluster_user.user);
+            }
+        }
+
+        for session_id in stale_session_ids {
+            tracing::info!("Removing stale cluster user {session_id} from room {file_id}");
+
+            self.pubsub
+                .lock()
+                .await
+                .connection
+                .remove_field(&room_users_key(file_id), &session_id)
+                .await?;
+        }
+
+        Ok(users)
+    }
+
+    /// Build a `UsersInRoom` message for a room across all instances.
+    pub(crate) async fn users_in_room(&self, file_id: &Uuid) -> Result<MessageResponse> {
+        let users = self.get_users_in_room(file_id).await?;
+
+        Ok(MessageResponse::from((users, &self.settings.version)))
+    }
+
+    /// Resubscribe to all of this instance's rooms on a new connection.
+    async fn reconnect_cluster(&self, relay: &Relay) -> Result<MessageStream> {
+        let (mut subscriber, messages) = Subscriber::new(relay.config.to_owned()).await?;
+        let file_ids = self
+            .rooms
+            .lock()
+            .await
+            .iter()
+            .map(|room| room.file_id)
+            .collect::<Vec<Uuid>>();
+
+        for file_id in file_ids.iter() {
+            subscriber.subscribe(&room_channel(file_id)).await?;
+        }
+
+        *relay.subscriber.lock().await = subscriber;
+
+        Ok(messages)
+    }
+}
+
+/// Handle a message published by another instance.
+async fn handle_cluster_message(state: &Arc<State>, payload: &[u8]) -> Result<()> {
+    let ClusterMessage {
+        instance_id,
+        file_id,
+        exclude,
+        message,
+    } = serde_json::from_slice::<ClusterMessage>(payload)?;
+
+    // our own broadcasts are delivered locally when they're sent
+    if instance_id == state.cluster.instance_id || get_room!(state, file_id).is_err() {
+        return Ok(());
+    }
+
+    if let MessageResponse::Transaction { sequence_num, .. }
+    | MessageResponse::BinaryTransaction { sequence_num, .. } = message
+    {
+        state.observe_sequence_num(file_id, sequence_num).await?;
+    }
+
+    // keep the room's sheets in sync for validating later transactions
+    let operations = match &message {
+        MessageResponse::Transaction { operations, .. } => STANDARD.decode(operations).ok(),
+        MessageResponse::BinaryTransaction { operations, .. } => Some(operations.to_owned()),
+        _ => None,
+    };
+
+    if let Some(operations) = operations {
+        state
+            .track_sheets(file_id, &operations)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::warn!("Error tracking sheets in room {file_id}: {error}")
+            });
+    }
+
+    // keep the chat history in sync for users that join on this instance
+    if let MessageResponse::ChatMessage { message, .. } = &message {
+        state.add_chat_message(file_id, message.to_owned()).await?;
+    }
+
+    send_to_local_users(exclude, file_id, Arc::clone(state), message).await
+}
+
+/// In a separate thread, forward messages from other instances to the users
+/// connected to this instance, reconnecting if the subscription drops.
+#[tracing::instrument(level = "trace")]
+pub(crate) fn listen(state: Arc<State>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let Some(relay) = &state.cluster.relay else {
+            tracing::info!("Running as a single node, not listening to the cluster");
+            return;
+        };
+
+        loop {
+            let messages = match relay.messages.lock().await.take() {
+                Some(messages) => Ok(messages),
+                None => state.reconnect_cluster(relay).await,
+            };
+
+            let mut messages = match messages {
+                Ok(messages) => messages,
+                Err(error) => {
+                    tracing::error!("Error reconnecting to the cluster: {error}");
+                    tokio::time::sleep(Duration::from_millis(RECONNECT_INTERVAL_MS)).await;
+                    continue;
+                }
+            };
+
+            while let Some((_, payload)) = messages.next().await {
+                if let Err(error) = handle_cluster_message(&state, &payload).await {
+                    tracing::warn!("Error handling cluster message: {error}");
+                }
+            }
+
+            tracing::error!("Cluster subscription closed");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quadratic_rust_shared::pubsub::connection::PubSubType;
+
+    use crate::config::config;
+    use crate::state::connection::PreConnection;
+    use crate::test_util::{new_state, new_user};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn allocates_sequence_nums_across_instances() {
+        let state_1 = new_state().await;
+        let state_2 = new_state().await;
+        let file_id = Uuid::new_v4();
+
+        for state in [&state_1, &state_2] {
+            state
+                .enter_room(file_id, &mut new_user(), PreConnection::new(None), 0)
+                .await
+                .unwrap();
+        }
+
+        for (sequence_num, state) in [(1, &state_1), (2, &state_2), (3, &state_1)] {
+            let pushed = state.push_next(Uuid::new_v4(), file_id, vec![]).await;
+            assert_eq!(pushed.unwrap(), sequence_num);
+        }
+        assert_eq!(state_2.get_sequence_num(&file_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn interleaved_pushes_get_consecutive_sequence_nums() {
+        let state_1 = Arc::new(new_state().await);
+        let state_2 = Arc::new(new_state().await);
+        let file_id = Uuid::new_v4();
+
+        for state in [&state_1, &state_2] {
+            state
+                .enter_room(file_id, &mut new_user(), PreConnection::new(None), 0)
+                .await
+                .unwrap();
+        }
+
+        // state_2 takes the sequence number state_1 would try first
+        assert_eq!(state_2.push_next(Uuid::new_v4(), file_id, vec![]).await.unwrap(), 1);
+        assert_eq!(state_1.get_sequence_num(&file_id).await.unwrap(), 0);
+        assert_eq!(state_1.push_next(Uuid::new_v4(), file_id, vec![]).await.unwrap(), 2);
+
+        // concurrent pushes from both instances
+        let pushes = (0..20).map(|i| {
+            let state = Arc::clone(if i % 2 == 0 { &state_1 } else { &state_2 });
+            tokio::spawn(async move { state.push_next(Uuid::new_v4(), file_id, vec![]).await })
+        });
+        let mut sequence_nums = futures::future::join_all(pushes)
+            .await
+            .into_iter()
+            .map(|push| push.unwrap().unwrap())
+            .collect::<Vec<_>>();
+        sequence_nums.sort();
+        assert_eq!(sequence_nums, (3..=22).collect::<Vec<_>>());
+
+        // every transaction is in the queue, without gaps
+        let transactions = state_1.get_messages_from_pubsub(&file_id, 0).await.unwrap();
+        let queued = transactions
+            .iter()
+            .map(|transaction| transaction.sequence_num)
+            .collect::<Vec<_>>();
+        assert_eq!(queued, (1..=22).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn users_in_room_include_other_instances() {
+        let state_1 = new_state().await;
+        let state_2 = new_state().await;
+        let file_id = Uuid::new_v4();
+        let mut user_1 = new_user();
+        let mut user_2 = new_user();
+
+        state_1
+            .enter_room(file_id, &mut user_1, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+        state_1.refresh_cluster_users(file_id).await.unwrap();
+        state_2
+            .enter_room(file_id, &mut user_2, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+        state_2.refresh_cluster_users(file_id).await.unwrap();
+
+        let users = state_1.get_users_in_room(&file_id).await.unwrap();
+        assert_eq!(users.len(), 2);
+        assert!(users.contains_key(&user_2.session_id));
+
+        state_2
+            .remove_cluster_user(file_id, &user_2.session_id)
+            .await
+            .unwrap();
+        let users = state_1.get_users_in_room(&file_id).await.unwrap();
+        assert_eq!(users.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn single_node_cluster() {
+        let mut config = config().unwrap();
+        config.pubsub_type = PubSubType::Memory;
+        let state = State::new(&config, None).await.unwrap();
+        let file_id = Uuid::new_v4();
+        let mut user = new_user();
+
+        state
+            .enter_room(file_id, &mut user, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+        state.join_cluster_room(&file_id).await.unwrap();
+        state.set_cluster_user(file_id, user.clone()).await.unwrap();
+        state
+            .publish_to_cluster(
+                file_id,
+                &[],
+                &MessageResponse::CurrentTransaction { sequence_num: 0 },
+            )
+            .await
+            .unwrap();
+
+        let sequence_num = state.push_next(Uuid::new_v4(), file_id, vec![]).await;
+        assert_eq!(sequence_num.unwrap(), 1);
+        assert_eq!(state.get_users_in_room(&file_id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn ignores_its_own_cluster_messages() {
+        let state = Arc::new(new_state().await);
+        let file_id = Uuid::new_v4();
+        state
+            .enter_room(file_id, &mut new_user(), PreConnection::new(None), 0)
+            .await
+            .unwrap();
+
+        let message = |instance_id| ClusterMessage {
+            instance_id,
+            file_id,
+            exclude: vec![],
+            message: MessageResponse::BinaryTransaction {
+                id: Uuid::new_v4(),
+                file_id,
+                sequence_num: 5,
+                operations: vec![],
+            },
+        };
+
+        let own = serde_json::to_vec(&message(state.cluster.instance_id)).unwrap();
+        handle_cluster_message(&state, &own).await.unwrap();
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 0);
+
+        let other = serde_json::to_vec(&message(Uuid::new_v4())).unwrap();
+        handle_cluster_message(&state, &other).await.unwrap();
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 5);
+    }
+}
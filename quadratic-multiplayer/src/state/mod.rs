@@ -3,9 +3,12 @@
 //! Store information about the state of the application in a send + sync
 //! struct.  All access and mutations to state should be performed here.
 
+pub mod cluster;
 pub mod connection;
+pub mod presence;
 pub mod pubsub;
 pub mod room;
+pub mod session;
 pub mod settings;
 pub mod stats;
 pub mod user;
@@ -13,7 +16,8 @@ pub mod user;
 use dashmap::DashMap;
 use jsonwebtoken::jwk::JwkSet;
 use quadratic_rust_shared::pubsub::Config as PubSubConfig;
-use quadratic_rust_shared::pubsub::redis_streams::RedisStreamsConfig;
+use quadratic_rust_shared::pubsub::connection::PubSubType;
+use quadratic_rust_shared::pubsub::redis::RedisConfig;
 use std::collections::HashMap;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -23,30 +27,51 @@ use crate::error::Result;
 use crate::state::room::Room;
 use crate::state::settings::Settings;
 
+use self::cluster::Cluster;
 use self::connection::Connection;
+use self::presence::Presence;
 use self::pubsub::PubSub;
 
 #[derive(Debug)]
 pub(crate) struct State {
     pub(crate) rooms: Mutex<DashMap<Uuid, Room>>,
     pub(crate) connections: Mutex<HashMap<Uuid, Connection>>,
+    pub(crate) presence: Mutex<HashMap<Uuid, Presence>>,
     pub(crate) pubsub: Mutex<PubSub>,
+    pub(crate) cluster: Cluster,
     pub(crate) settings: Settings,
 }
 
 impl State {
     pub(crate) async fn new(config: &Config, jwks: Option<JwkSet>) -> Result<Self> {
-        let pubsub_config = PubSubConfig::RedisStreams(RedisStreamsConfig {
-            host: config.pubsub_host.to_owned(),
-            port: config.pubsub_port.to_owned(),
-            password: config.pubsub_password.to_owned(),
-            active_channels: config.pubsub_active_channels.to_owned(),
-        });
+        let pubsub_config = config.pubsub_type.config(
+            &config.pubsub_host,
+            &config.pubsub_port,
+            &config.pubsub_password,
+            &config.pubsub_active_channels,
+        );
+
+        // rooms are coordinated across instances over plain redis pub/sub,
+        // and the in-memory backend only ever serves a single instance
+        let cluster = match config.pubsub_type {
+            PubSubType::RedisStreams => {
+                Cluster::new(PubSubConfig::Redis(RedisConfig {
+                    host: config.pubsub_host.to_owned(),
+                    port: config.pubsub_port.to_owned(),
+                    password: config.pubsub_password.to_owned(),
+                    active_channels: config.pubsub_active_channels.to_owned(),
+                }))
+                .await?
+            }
+            PubSubType::Memory => Cluster::single_node(),
+        };
 
         Ok(State {
             rooms: Mutex::new(DashMap::new()),
             connections: Mutex::new(HashMap::new()),
+            presence: Mutex::new(HashMap::new()),
             pubsub: Mutex::new(PubSub::new(pubsub_config).await?),
+            cluster,
             settings: Settings::new(config, jwks).await,
         })
     }
@@ -0,0 +1,215 @@
+//! Presence
+//!
+//! Coalesce high-frequency presence updates (mouse position and viewport) so
+//! that each user's updates are broadcast at most `presence_updates_per_s`
+//! times per second.  Updates in between are merged, and the latest state is
+//! flushed once the interval has passed.  Any other change is broadcast
+//! immediately, along with anything still pending.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::message::{broadcast, response::MessageResponse};
+use crate::state::{State, user::UserStateUpdate};
+
+#[derive(Debug, Default)]
+pub(crate) struct Presence {
+    last_broadcast: Option<Instant>,
+    pending: Option<UserStateUpdate>,
+}
+
+/// Updates that only move the mouse or viewport are coalesced.
+fn is_high_frequency(update: &UserStateUpdate) -> bool {
+    let UserStateUpdate {
+        sheet_id,
+        selection,
+        cell_edit,
+        code_running,
+        x,
+        y,
+        visible,
+        viewport,
+        follow,
+    } = update;
+
+    sheet_id.is_none()
+        && selection.is_none()
+        && cell_edit.is_none()
+        && code_running.is_none()
+        && visible.is_none()
+        && follow.is_none()
+        && (x.is_some() || y.is_some() || viewport.is_some())
+}
+
+/// Merge a newer update into an older one, keeping the newest value of each
+/// field.
+fn merge(older: UserStateUpdate, newer: UserStateUpdate) -> UserStateUpdate {
+    UserStateUpdate {
+        sheet_id: newer.sheet_id.or(older.sheet_id),
+        selection: newer.selection.or(older.selection),
+        cell_edit: newer.cell_edit.or(older.cell_edit),
+        code_running: newer.code_running.or(older.code_running),
+        x: newer.x.or(older.x),
+        y: newer.y.or(older.y),
+        visible: newer.visible.or(older.visible),
+        viewport: newer.viewport.or(older.viewport),
+        follow: newer.follow.or(older.follow),
+    }
+}
+
+impl State {
+    /// Get the update to broadcast now, if any.  Otherwise the update is held
+    /// and the returned delay is how long until it should be flushed, which
+    /// is `None` if a flush is already scheduled.
+    async fn coalesce_presence(
+        &self,
+        session_id: Uuid,
+        update: UserStateUpdate,
+        now: Instant,
+    ) -> (Option<UserStateUpdate>, Option<Duration>) {
+        let interval = Duration::from_secs_f64(1.0 / self.settings.presence_updates_per_s);
+        let mut presence = self.presence.lock().await;
+        let presence = presence.entry(session_id).or_default();
+
+        let is_pending = presence.pending.is_some();
+        let update = match presence.pending.take() {
+            Some(pending) => merge(pending, update),
+            None => update,
+        };
+        let elapsed = presence
+            .last_broadcast
+            .map(|last_broadcast| now.saturating_duration_since(last_broadcast));
+
+        match elapsed {
+            Some(elapsed) if elapsed < interval && is_high_frequency(&update) => {
+                presence.pending = Some(update);
+                let delay = (!is_pending).then(|| interval - elapsed);
+
+                (None, delay)
+            }
+            _ => {
+                presence.last_broadcast = Some(now);
+
+                (Some(update), None)
+            }
+        }
+    }
+
+    /// Take a user's pending presence update, if any.
+    async fn take_pending_presence(&self, session_id: Uuid) -> Option<UserStateUpdate> {
+        let mut presence = self.presence.lock().await;
+        let presence = presence.get_mut(&session_id)?;
+        let pending = presence.pending.take()?;
+        presence.last_broadcast = Some(Instant::now());
+
+        Some(pending)
+    }
+
+    /// Forget a user's presence when they leave.
+    pub(crate) async fn remove_presence(&self, session_id: &Uuid) {
+        self.presence.lock().await.remove(session_id);
+    }
+}
+
+/// Broadcast a user's presence update to the rest of the room, coalescing
+/// high-frequency updates.
+pub(crate) async fn broadcast_presence(
+    state: Arc<State>,
+    file_id: Uuid,
+    session_id: Uuid,
+    update: UserStateUpdate,
+) {
+    let (update, delay) = state
+        .coalesce_presence(session_id, update, Instant::now())
+        .await;
+
+    if let Some(update) = update {
+        let response = MessageResponse::UserUpdate {
+            session_id,
+            file_id,
+            update,
+        };
+        broadcast(vec![session_id], file_id, state, response);
+    } else if let Some(delay) = delay {
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+
+            if let Some(update) = state.take_pending_presence(session_id).await {
+                let response = MessageResponse::UserUpdate {
+                    session_id,
+                    file_id,
+                    update,
+                };
+                broadcast(vec![session_id], file_id, state, response);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::new_state;
+
+    use super::*;
+
+    fn mouse(x: f64) -> UserStateUpdate {
+        UserStateUpdate {
+            x: Some(x),
+            y: Some(x),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn coalesces_high_frequency_updates() {
+        let state = new_state().await;
+        let session_id = Uuid::new_v4();
+        let start = Instant::now();
+        let interval = Duration::from_secs_f64(1.0 / state.settings.presence_updates_per_s);
+
+        // the first update is sent immediately
+        let (update, delay) = state.coalesce_presence(session_id, mouse(1.0), start).await;
+        assert_eq!(update, Some(mouse(1.0)));
+        assert_eq!(delay, None);
+
+        // later updates within the interval are held and a flush is scheduled once
+        let (update, delay) = state.coalesce_presence(session_id, mouse(2.0), start).await;
+        assert_eq!(update, None);
+        assert_eq!(delay, Some(interval));
+
+        let (update, delay) = state.coalesce_presence(session_id, mouse(3.0), start).await;
+        assert_eq!(update, None);
+        assert_eq!(delay, None);
+
+        assert_eq!(
+            state.take_pending_presence(session_id).await,
+            Some(mouse(3.0))
+        );
+        assert_eq!(state.take_pending_presence(session_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn sends_other_updates_with_pending_updates() {
+        let state = new_state().await;
+        let session_id = Uuid::new_v4();
+        let start = Instant::now();
+
+        state.coalesce_presence(session_id, mouse(1.0), start).await;
+        state.coalesce_presence(session_id, mouse(2.0), start).await;
+
+        let selection = UserStateUpdate {
+            selection: Some("A1".into()),
+            ..Default::default()
+        };
+        let (update, _) = state.coalesce_presence(session_id, selection, start).await;
+        let expected = UserStateUpdate {
+            selection: Some("A1".into()),
+            ..mouse(2.0)
+        };
+        assert_eq!(update, Some(expected));
+
+        // nothing is left to flush
+        assert_eq!(state.take_pending_presence(session_id).await, None);
+    }
+}
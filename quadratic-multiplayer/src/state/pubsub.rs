@@ -1,6 +1,6 @@
 use quadratic_core::controller::transaction::{Transaction, TransactionServer};
 use quadratic_rust_shared::pubsub::{
-    Config as PubSubConfig, PubSub as PubSubTrait, redis_streams::RedisConnection,
+    Config as PubSubConfig, PubSub as PubSubTrait, connection::Connection,
 };
 use uuid::Uuid;
 
@@ -16,7 +16,7 @@ pub static GROUP_NAME: &str = "quadratic-multiplayer-1";
 #[derive(Debug)]
 pub(crate) struct PubSub {
     pub(crate) config: PubSubConfig,
-    pub(crate) connection: RedisConnection,
+    pub(crate) connection: Connection,
 }
 
 impl PubSub {
@@ -28,19 +28,19 @@ impl PubSub {
     }
 
     /// Connect to the PubSub server
-    pub(crate) async fn connect(config: &PubSubConfig) -> Result<RedisConnection> {
-        let connection = RedisConnection::new(config.to_owned()).await?;
+    pub(crate) async fn connect(config: &PubSubConfig) -> Result<Connection> {
+        let connection = Connection::new(config.to_owned()).await?;
 
         Ok(connection)
     }
 
-    pub(crate) async fn push_protobuf(
-        &mut self,
+    /// Encode a transaction as it's stored in the transaction queue
+    fn encode_transaction(
         id: Uuid,
         file_id: Uuid,
         operations: Vec<u8>,
         sequence_num: u64,
-    ) -> Result<u64> {
+    ) -> Result<Vec<u8>> {
         let transaction = MessageResponse::BinaryTransaction {
             id,
             file_id,
@@ -52,14 +52,29 @@ impl PubSub {
         let encoded = encode_message(transaction)?;
 
         // add header to the message
-        let transaction_compressed =
-            Transaction::add_header(encoded).map_err(|e| MpError::Serialization(e.to_string()))?;
+        Transaction::add_header(encoded).map_err(|e| MpError::Serialization(e.to_string()))
+    }
 
-        // get the active channels name
-        let active_channels = match self.config {
+    /// Get the active channels name
+    fn active_channels(&self) -> &str {
+        match self.config {
             PubSubConfig::RedisStreams(ref config) => config.active_channels.as_str(),
+            PubSubConfig::Memory(ref config) => config.active_channels.as_str(),
             _ => "active_channels",
-        };
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn push_protobuf(
+        &mut self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+        sequence_num: u64,
+    ) -> Result<u64> {
+        let transaction_compressed =
+            Self::encode_transaction(id, file_id, operations, sequence_num)?;
+        let active_channels = self.active_channels().to_owned();
 
         // publish the message to the PubSub server
         self.connection
@@ -67,13 +82,45 @@ impl PubSub {
                 &file_id.to_string(),
                 &sequence_num.to_string(),
                 &transaction_compressed,
-                Some(active_channels),
+                Some(&active_channels),
             )
             .await?;
 
         Ok(sequence_num)
     }
 
+    /// Push a transaction with `sequence_num` if it's the next value of the
+    /// room's counter.  Returns None once pushed, or the counter's current
+    /// value if another instance took `sequence_num` first.
+    pub(crate) async fn push_protobuf_next(
+        &mut self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+        counter_key: &str,
+        min_sequence_num: u64,
+        sequence_num: u64,
+    ) -> Result<Option<u64>> {
+        let transaction_compressed =
+            Self::encode_transaction(id, file_id, operations, sequence_num)?;
+        let active_channels = self.active_channels().to_owned();
+
+        // publish the message to the PubSub server
+        let current = self
+            .connection
+            .publish_next(
+                &file_id.to_string(),
+                counter_key,
+                min_sequence_num,
+                sequence_num,
+                &transaction_compressed,
+                Some(&active_channels),
+            )
+            .await?;
+
+        Ok(current)
+    }
+
     /// Check if the connection is healthy and attempt to reconnect if not
     pub(crate) async fn reconnect_if_unhealthy(&mut self) {
         let is_healthy = self.connection.is_healthy().await;
@@ -107,7 +154,9 @@ impl State {
         Ok(())
     }
 
-    /// Push a transaction to the transaction queue
+    /// Push a transaction to the transaction queue at a given sequence_num,
+    /// for tests that need to control sequence numbers
+    #[cfg(test)]
     pub(crate) async fn push(
         &self,
         id: Uuid,
@@ -216,10 +265,11 @@ mod tests {
         let transaction_2 =
             Transaction::serialize_and_compress(vec![operations_2.clone()]).unwrap();
 
-        state
-            .push(transaction_id_1, file_id, transaction_1.clone(), 1)
+        let sequence_num = state
+            .push_next(transaction_id_1, file_id, transaction_1.clone())
             .await
             .unwrap();
+        assert_eq!(sequence_num, 1);
         let transactions = state.get_messages_from_pubsub(&file_id, 0).await.unwrap();
         let expected_transaction_1 = TransactionServer {
             id: transaction_id_1,
@@ -230,10 +280,11 @@ mod tests {
 
         assert_eq!(transactions[0], expected_transaction_1);
 
-        state
-            .push(transaction_id_2, file_id, transaction_2.clone(), 2)
+        let sequence_num = state
+            .push_next(transaction_id_2, file_id, transaction_2.clone())
             .await
             .unwrap();
+        assert_eq!(sequence_num, 2);
         let transaction = state.get_messages_from_pubsub(&file_id, 0).await.unwrap();
         let expected_transaction_2 = TransactionServer {
             id: transaction_id_2,
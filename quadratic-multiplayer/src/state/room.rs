@@ -1,14 +1,25 @@
 use dashmap::DashMap;
-use quadratic_rust_shared::quadratic_api::get_file_checkpoint;
+use quadratic_core::controller::operations::operation::Operation;
+use quadratic_core::controller::transaction::Transaction;
+use quadratic_core::grid::SheetId;
+use quadratic_rust_shared::pubsub::{FileSheets, PubSub as PubSubTrait, file_sheets_key};
+use quadratic_rust_shared::quadratic_api::{LastCheckpoint, get_file_checkpoint};
 use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
 use uuid::Uuid;
 
 use crate::error::{MpError, Result};
+use crate::message::response::ChatMessage;
+use crate::message::validate::RoomSheets;
 use crate::state::{State, user::User};
 use crate::{get_mut_room, get_room};
 
 use super::connection::{Connection, PreConnection};
 
+/// The number of chat messages a room keeps for users that join later.
+pub(crate) const MAX_CHAT_MESSAGES: usize = 200;
+
 #[derive(Serialize, Debug, Clone)]
 pub(crate) struct Room {
     pub(crate) file_id: Uuid,
@@ -16,6 +27,9 @@ pub(crate) struct Room {
     pub(crate) sequence_num: u64,
     pub(crate) checkpoint_sequence_num: u64,
     pub(crate) user_index: usize,
+    pub(crate) sheets: RoomSheets,
+    /// Chat messages sent while the room has been open, oldest first.
+    pub(crate) chat_messages: VecDeque<ChatMessage>,
 }
 
 #[cfg(test)]
@@ -35,6 +49,8 @@ impl Room {
             sequence_num,
             checkpoint_sequence_num: sequence_num,
             user_index: 0,
+            sheets: RoomSheets::default(),
+            chat_messages: VecDeque::new(),
         }
     }
 
@@ -43,6 +59,32 @@ impl Room {
         self.sequence_num
     }
 
+    /// Record the sheets deleted or created by a pushed transaction.
+    pub fn track_sheets(&mut self, operations: &[Operation]) {
+        for operation in operations {
+            self.sheets.track(operation);
+        }
+    }
+
+    /// Advance the sequence number to one allocated by another instance.
+    pub fn advance_sequence_num(&mut self, sequence_num: u64) {
+        self.sequence_num = self.sequence_num.max(sequence_num);
+    }
+
+    /// Add a chat message to the room's history, dropping the oldest message
+    /// once the history is full.  Messages already in the history are ignored.
+    pub fn add_chat_message(&mut self, message: ChatMessage) {
+        if self.chat_messages.iter().any(|m| m.id == message.id) {
+            return;
+        }
+
+        if self.chat_messages.len() >= MAX_CHAT_MESSAGES {
+            self.chat_messages.pop_front();
+        }
+
+        self.chat_messages.push_back(message);
+    }
+
     pub fn get_user(&self, session_id: &Uuid) -> Result<User> {
         let user = self
             .users
@@ -123,6 +165,16 @@ impl State {
     #[tracing::instrument(level = "trace")]
     pub(crate) async fn leave_room(&self, file_id: Uuid, session_id: &Uuid) -> Result<bool> {
         get_mut_room!(self, file_id)?.users.remove(session_id);
+        self.remove_presence(session_id).await;
+
+        if let Err(error) = self.end_session(session_id).await {
+            tracing::warn!("Error ending session {session_id}: {error}");
+        }
+
+        if let Err(error) = self.remove_cluster_user(file_id, session_id).await {
+            tracing::warn!("Error removing user {session_id} from the cluster: {error}");
+        }
+
         let num_in_room = get_room!(self, file_id)?.users.len();
 
         tracing::info!(
@@ -144,14 +196,110 @@ impl State {
     pub(crate) async fn remove_room(&self, file_id: Uuid) {
         self.rooms.lock().await.remove(&file_id);
 
+        if let Err(error) = self.leave_cluster_room(&file_id).await {
+            tracing::warn!("Error leaving cluster room {file_id}: {error}");
+        }
+
         tracing::info!("Room {file_id} removed");
     }
 
+    /// Add a chat message to a room's history.
+    pub(crate) async fn add_chat_message(&self, file_id: Uuid, message: ChatMessage) -> Result<()> {
+        get_mut_room!(self, file_id)?.add_chat_message(message);
+
+        Ok(())
+    }
+
+    /// Get a room's chat history, oldest first.
+    pub(crate) async fn get_chat_messages(&self, file_id: &Uuid) -> Result<Vec<ChatMessage>> {
+        Ok(get_room!(self, file_id)?
+            .chat_messages
+            .iter()
+            .cloned()
+            .collect())
+    }
+
+    /// Load a room's sheets from the ones the files service recorded at the
+    /// file's last checkpoint, plus the transactions since.  Does nothing if
+    /// they're already loaded or haven't been recorded yet.
+    pub(crate) async fn load_sheets(&self, file_id: Uuid) -> Result<()> {
+        if get_room!(self, file_id)?.sheets.sheet_ids.is_some() {
+            return Ok(());
+        }
+
+        let key = file_sheets_key(&file_id.to_string());
+        let Some(value) = self.pubsub.lock().await.connection.get(&key).await? else {
+            return Ok(());
+        };
+
+        let file_sheets = serde_json::from_slice::<FileSheets>(&value)?;
+        let mut sheets = RoomSheets::default();
+        sheets.load(
+            file_sheets
+                .sheet_ids
+                .iter()
+                .map(|sheet_id| SheetId::from_str(sheet_id))
+                .collect::<std::result::Result<HashSet<_>, _>>()
+                .map_err(|e| MpError::Serialization(e.to_string()))?,
+        );
+
+        let transactions = self
+            .get_messages_from_pubsub(&file_id, file_sheets.sequence_num + 1)
+            .await?;
+
+        for transaction in transactions {
+            let operations =
+                Transaction::decompress_and_deserialize::<Vec<Operation>>(&transaction.operations)
+                    .map_err(|e| MpError::Serialization(e.to_string()))?;
+
+            operations
+                .iter()
+                .for_each(|operation| sheets.track(operation));
+        }
+
+        if let Some(sheet_ids) = sheets.sheet_ids {
+            get_mut_room!(self, file_id)?.sheets.load(sheet_ids);
+        }
+
+        Ok(())
+    }
+
+    /// Record the sheets deleted or created by a transaction pushed by
+    /// another instance.
+    pub(crate) async fn track_sheets(&self, file_id: Uuid, operations: &[u8]) -> Result<()> {
+        let operations = Transaction::decompress_and_deserialize::<Vec<Operation>>(operations)
+            .map_err(|e| MpError::Serialization(e.to_string()))?;
+
+        get_mut_room!(self, file_id)?.track_sheets(&operations);
+
+        Ok(())
+    }
+
     /// Get a room's current sequence number.
     pub(crate) async fn get_sequence_num(&self, file_id: &Uuid) -> Result<u64> {
         Ok(get_room!(self, file_id)?.sequence_num)
     }
 
+    /// Get a file's latest checkpoint.
+    pub(crate) async fn get_latest_checkpoint(&self, file_id: Uuid) -> Result<LastCheckpoint> {
+        if cfg!(test) {
+            return Ok(LastCheckpoint {
+                sequence_number: get_room!(self, file_id)?.checkpoint_sequence_num,
+                version: "test".into(),
+                s3_key: format!("{file_id}-test.grid"),
+                s3_bucket: "test".into(),
+            });
+        }
+
+        let url = &self.settings.quadratic_api_uri;
+        let jwt = &self.settings.m2m_auth_token;
+        let checkpoint = get_file_checkpoint(url, jwt, &file_id).await?;
+
+        get_mut_room!(self, file_id)?.checkpoint_sequence_num = checkpoint.sequence_number;
+
+        Ok(checkpoint)
+    }
+
     /// Get the maximum sequence number for a room.
     /// If the room doesn't exist in memory, get the latest checkpoint from
     /// quadratic api.
@@ -299,4 +447,90 @@ mod tests {
             .unwrap();
         assert_eq!(user2.index, 3);
     }
+
+    #[tokio::test]
+    async fn loads_sheets_from_the_last_checkpoint() {
+        let state = new_state().await;
+        let file_id = Uuid::new_v4();
+        let mut user = new_user();
+
+        state
+            .enter_room(file_id, &mut user, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+
+        // the files service hasn't recorded the sheets yet
+        state.load_sheets(file_id).await.unwrap();
+        let room = state.get_room(&file_id).await.unwrap();
+        assert_eq!(room.sheets.sheet_ids, None);
+
+        let sheet_id = SheetId::new();
+        let new_sheet_id = SheetId::new();
+        let file_sheets = FileSheets {
+            sequence_num: 1,
+            sheet_ids: vec![sheet_id.to_string()],
+        };
+        state
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .set(
+                &file_sheets_key(&file_id.to_string()),
+                &serde_json::to_vec(&file_sheets).unwrap(),
+                60,
+            )
+            .await
+            .unwrap();
+
+        // a sheet created after the checkpoint
+        let operations = vec![Operation::DuplicateSheet {
+            sheet_id,
+            new_sheet_id,
+        }];
+        state
+            .push(
+                Uuid::new_v4(),
+                file_id,
+                Transaction::serialize_and_compress(&operations).unwrap(),
+                2,
+            )
+            .await
+            .unwrap();
+
+        state.load_sheets(file_id).await.unwrap();
+        let room = state.get_room(&file_id).await.unwrap();
+        assert_eq!(
+            room.sheets.sheet_ids,
+            Some(HashSet::from([sheet_id, new_sheet_id]))
+        );
+    }
+
+    #[test]
+    fn keeps_a_bounded_chat_history() {
+        let mut room = Room::new(Uuid::new_v4(), 0);
+        let message = |text: String| ChatMessage {
+            id: Uuid::new_v4(),
+            session_id: Uuid::new_v4(),
+            user_id: "user".into(),
+            first_name: "First".into(),
+            last_name: "Last".into(),
+            text,
+            mentions: vec![],
+            anchor: None,
+            created_at: chrono::Utc::now(),
+        };
+
+        for i in 0..MAX_CHAT_MESSAGES + 1 {
+            room.add_chat_message(message(i.to_string()));
+        }
+
+        assert_eq!(room.chat_messages.len(), MAX_CHAT_MESSAGES);
+        assert_eq!(room.chat_messages.front().unwrap().text, "1");
+
+        // duplicates (e.g. relayed from another instance) are ignored
+        let last = room.chat_messages.back().unwrap().to_owned();
+        room.add_chat_message(last);
+        assert_eq!(room.chat_messages.len(), MAX_CHAT_MESSAGES);
+    }
 }
@@ -0,0 +1,673 @@
+//! Sessions
+//!
+//! Users are issued a resume token when they enter a room.  When their
+//! connection drops, they stay in the room for `resume_grace_period_s` while
+//! broadcasts meant for them are held.  Reconnecting with the token within
+//! that time restores the session: the same user index and state, acks for
+//! transactions whose acks were lost, and the broadcasts that were missed.
+//!
+//! Sessions are stored in the PubSub backend so that a reconnect can land on
+//! any instance.  They expire once their user has neither sent a heartbeat nor
+//! resumed within `heartbeat_timeout_s + resume_grace_period_s`.  The instance
+//! that holds a disconnected session releases its user once the session is
+//! resumed elsewhere.
+
+use chrono::{DateTime, Utc};
+use quadratic_rust_shared::pubsub::PubSub as PubSubTrait;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+use crate::error::{MpError, Result};
+use crate::message::response::MessageResponse;
+use crate::state::connection::{Connection, PreConnection};
+use crate::state::{State, settings::Settings, user::UserSocket};
+use crate::{get_mut_room, get_room};
+use quadratic_rust_shared::quadratic_api::FilePermRole;
+
+/// The number of recent transactions kept per session, so that their acks can
+/// be resent after a reconnect.
+pub(crate) const MAX_SESSION_TRANSACTIONS: usize = 100;
+
+/// The number of broadcasts held for a disconnected session.  The oldest are
+/// dropped first.
+pub(crate) const MAX_MISSED_MESSAGES: usize = 500;
+
+/// The number of times an update to a session is retried when another
+/// instance changes the session at the same time.
+const MAX_SESSION_UPDATE_ATTEMPTS: usize = 10;
+
+/// The key a session is stored under.
+fn session_key(session_id: &Uuid) -> String {
+    format!("multiplayer-session:{session_id}")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct Session {
+    pub(crate) token: Uuid,
+    pub(crate) file_id: Uuid,
+    /// The instance the session is connected to, or held on while disconnected.
+    pub(crate) instance_id: Uuid,
+    pub(crate) disconnected_at: Option<DateTime<Utc>>,
+    /// Recent transactions from the session as (id, sequence_num), oldest first.
+    pub(crate) transactions: VecDeque<(Uuid, u64)>,
+    /// Broadcasts sent to the room while disconnected, oldest first.
+    pub(crate) missed: VecDeque<MessageResponse>,
+}
+
+impl Session {
+    pub(crate) fn new(file_id: Uuid, instance_id: Uuid) -> Self {
+        Session {
+            token: Uuid::new_v4(),
+            file_id,
+            instance_id,
+            disconnected_at: None,
+            transactions: VecDeque::new(),
+            missed: VecDeque::new(),
+        }
+    }
+
+    /// Remember a transaction from the session.
+    pub(crate) fn add_transaction(&mut self, id: Uuid, sequence_num: u64) {
+        if self.transactions.len() >= MAX_SESSION_TRANSACTIONS {
+            self.transactions.pop_front();
+        }
+
+        self.transactions.push_back((id, sequence_num));
+    }
+
+    /// Hold a broadcast until the session is resumed.  Transactions aren't
+    /// held since they're replayed by sequence number.
+    pub(crate) fn add_missed(&mut self, message: &MessageResponse) {
+        if self.disconnected_at.is_none() || message.is_transaction() {
+            return;
+        }
+
+        if self.missed.len() >= MAX_MISSED_MESSAGES {
+            self.missed.pop_front();
+        }
+
+        self.missed.push_back(message.to_owned());
+    }
+
+    /// A disconnected session can no longer be resumed once the grace period
+    /// has passed.
+    pub(crate) fn is_expired(&self, grace_period_s: i64, now: DateTime<Utc>) -> bool {
+        self.disconnected_at.is_some_and(|disconnected_at| {
+            disconnected_at.timestamp() + grace_period_s < now.timestamp()
+        })
+    }
+}
+
+impl State {
+    /// Sessions expire this long after they're last written.
+    fn session_ttl_s(&self) -> u64 {
+        let Settings {
+            heartbeat_timeout_s,
+            resume_grace_period_s,
+            ..
+        } = &self.settings;
+
+        (heartbeat_timeout_s + resume_grace_period_s).max(1) as u64
+    }
+
+    /// Get a session, or None if it ended or expired.
+    pub(crate) async fn get_session(&self, session_id: &Uuid) -> Result<Option<Session>> {
+        let value = self
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .get(&session_key(session_id))
+            .await?;
+
+        Ok(value
+            .map(|value| serde_json::from_slice::<Session>(&value))
+            .transpose()?)
+    }
+
+    /// Update a session, retrying if another instance changes it first.
+    /// Returns None if there is no session to update.
+    async fn update_session<T>(
+        &self,
+        session_id: &Uuid,
+        mut update: impl FnMut(&mut Session) -> T,
+    ) -> Result<Option<T>> {
+        let key = session_key(session_id);
+        let ttl_s = self.session_ttl_s();
+
+        for _ in 0..MAX_SESSION_UPDATE_ATTEMPTS {
+            let mut pubsub = self.pubsub.lock().await;
+
+            let Some(current) = pubsub.connection.get(&key).await? else {
+                return Ok(None);
+            };
+
+            let mut session = serde_json::from_slice::<Session>(&current)?;
+            let updated = update(&mut session);
+            let value = serde_json::to_vec(&session)?;
+
+            if pubsub.connection.set_if(&key, &current, &value, ttl_s).await? {
+                return Ok(Some(updated));
+            }
+        }
+
+        Err(MpError::ResumeSession(format!(
+            "Unable to update session {session_id} after {MAX_SESSION_UPDATE_ATTEMPTS} attempts"
+        )))
+    }
+
+    /// Start a new session for a user entering a room, replacing any previous
+    /// session.  Returns the token needed to resume it.
+    pub(crate) async fn start_session(&self, file_id: Uuid, session_id: Uuid) -> Result<Uuid> {
+        let session = Session::new(file_id, self.cluster.instance_id);
+        let value = serde_json::to_vec(&session)?;
+
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .set(&session_key(&session_id), &value, self.session_ttl_s())
+            .await?;
+
+        Ok(session.token)
+    }
+
+    /// Forget a user's session when they leave.
+    pub(crate) async fn end_session(&self, session_id: &Uuid) -> Result<()> {
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .delete(&session_key(session_id))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Keep the sessions of this instance's connected users in a room from
+    /// expiring.
+    pub(crate) async fn refresh_sessions(&self, file_id: Uuid) -> Result<()> {
+        let session_ids = get_room!(self, file_id)?
+            .users
+            .iter()
+            .filter(|user| user.socket.is_some())
+            .map(|user| user.session_id)
+            .collect::<Vec<Uuid>>();
+
+        for session_id in session_ids {
+            self.update_session(&session_id, |_| ()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remember a transaction from a session.
+    pub(crate) async fn record_session_transaction(
+        &self,
+        session_id: &Uuid,
+        id: Uuid,
+        sequence_num: u64,
+    ) {
+        let recorded = self
+            .update_session(session_id, |session| {
+                session.add_transaction(id, sequence_num)
+            })
+            .await;
+
+        if let Err(error) = recorded {
+            tracing::warn!("Error recording transaction {id} in session {session_id}: {error}");
+        }
+    }
+
+    /// Hold a broadcast for a disconnected session.
+    pub(crate) async fn add_missed_message(&self, session_id: &Uuid, message: &MessageResponse) {
+        let instance_id = self.cluster.instance_id;
+        let held = self
+            .update_session(session_id, |session| {
+                if session.instance_id == instance_id {
+                    session.add_missed(message);
+                }
+            })
+            .await;
+
+        if let Err(error) = held {
+            tracing::warn!("Error holding a message for session {session_id}: {error}");
+        }
+    }
+
+    /// Keep a user in their room after their connection drops so that they
+    /// can resume their session.  Returns false if there is no session to
+    /// keep, in which case the user should leave the room.
+    #[tracing::instrument(level = "trace")]
+    pub(crate) async fn disconnect_session(
+        &self,
+        file_id: Uuid,
+        session_id: Uuid,
+        connection_id: Uuid,
+    ) -> bool {
+        let Ok(user) = get_room!(self, file_id).and_then(|room| room.get_user(&session_id)) else {
+            return false;
+        };
+
+        let instance_id = self.cluster.instance_id;
+        let is_current_connection = user.connection_id == connection_id;
+        let session = self
+            .update_session(&session_id, |session| {
+                if session.file_id == file_id
+                    && session.instance_id == instance_id
+                    && is_current_connection
+                {
+                    session.disconnected_at.get_or_insert_with(Utc::now);
+                }
+
+                session.to_owned()
+            })
+            .await;
+
+        match session {
+            Ok(Some(session)) if session.file_id == file_id => {
+                self.connections.lock().await.remove(&connection_id);
+
+                // the session was already resumed on another instance
+                if session.instance_id != instance_id {
+                    if let Err(error) = self.release_session(file_id, &session_id).await {
+                        tracing::warn!("Error releasing session {session_id}: {error}");
+                    }
+
+                    return true;
+                }
+            }
+            Ok(_) => return false,
+            Err(error) => {
+                tracing::warn!("Error holding session {session_id}: {error}");
+                return false;
+            }
+        }
+
+        // the session was already resumed on a new connection
+        if !is_current_connection {
+            return true;
+        }
+
+        if let Ok(room) = get_mut_room!(self, file_id) {
+            room.users
+                .entry(session_id)
+                .and_modify(|user| user.socket = None);
+        }
+
+        tracing::info!("Holding session {session_id} in room {file_id} to be resumed");
+
+        true
+    }
+
+    /// Stop holding a session that was resumed on another instance.  The
+    /// user is removed from this instance's room, but stays in the cluster
+    /// and keeps their session.
+    pub(crate) async fn release_session(&self, file_id: Uuid, session_id: &Uuid) -> Result<()> {
+        get_mut_room!(self, file_id)?.users.remove(session_id);
+        self.remove_presence(session_id).await;
+
+        tracing::info!("Session {session_id} in room {file_id} was resumed on another instance");
+
+        if get_room!(self, file_id)?.users.is_empty() {
+            self.remove_room(file_id).await;
+        }
+
+        Ok(())
+    }
+
+    /// Resume a session on a new connection, given the connection's checked
+    /// permissions on the file and the file's sequence_num.  The session may
+    /// have been held by another instance, in which case the user joins this
+    /// instance's room.  Returns the session, including the broadcasts missed
+    /// while disconnected.
+    #[tracing::instrument(level = "trace")]
+    pub(crate) async fn resume_session(
+        &self,
+        file_id: Uuid,
+        session_id: Uuid,
+        token: Uuid,
+        socket: UserSocket,
+        mut pre_connection: PreConnection,
+        (permissions, sequence_num): (Vec<FilePermRole>, u64),
+    ) -> Result<Session> {
+        let grace_period_s = self.settings.resume_grace_period_s;
+        let instance_id = self.cluster.instance_id;
+        let now = Utc::now();
+
+        let session = self
+            .update_session(&session_id, |session| {
+                if session.token != token
+                    || session.file_id != file_id
+                    || session.is_expired(grace_period_s, now)
+                {
+                    return None;
+                }
+
+                session.instance_id = instance_id;
+                session.disconnected_at = None;
+                let missed = std::mem::take(&mut session.missed);
+
+                Some(Session {
+                    missed,
+                    ..session.to_owned()
+                })
+            })
+            .await?
+            .flatten()
+            .ok_or_else(|| {
+                MpError::ResumeSession(format!(
+                    "No session {session_id} to resume in room {file_id}"
+                ))
+            })?;
+
+        let is_in_room = get_mut_room!(self, file_id)
+            .map(|room| {
+                room.users
+                    .get_mut(&session_id)
+                    .map(|mut user| {
+                        user.socket = Some(socket.to_owned());
+                        user.connection_id = pre_connection.id;
+                        user.last_heartbeat = now;
+                        user.permissions = permissions.to_owned();
+                    })
+                    .is_some()
+            })
+            .unwrap_or(false);
+
+        if is_in_room {
+            let connection = Connection::new(
+                pre_connection.id,
+                session_id,
+                file_id,
+                pre_connection.jwt.take(),
+            );
+
+            self.connections
+                .lock()
+                .await
+                .insert(connection.id, connection);
+        } else {
+            // the session was held by another instance
+            let Some(mut user) = self.get_cluster_user(file_id, &session_id).await? else {
+                self.end_session(&session_id).await?;
+
+                return Err(MpError::ResumeSession(format!(
+                    "User {session_id} is no longer in room {file_id}"
+                )));
+            };
+
+            let index = user.index;
+            user.socket = Some(socket);
+            user.connection_id = pre_connection.id;
+            user.last_heartbeat = now;
+            user.permissions = permissions;
+
+            self.enter_room(file_id, &mut user, pre_connection, sequence_num)
+                .await?;
+
+            // the user keeps their place in the room
+            get_mut_room!(self, file_id)?
+                .users
+                .entry(session_id)
+                .and_modify(|user| user.index = index);
+            user.index = index;
+
+            self.set_cluster_user(file_id, user).await?;
+        }
+
+        tracing::info!(
+            "Resumed session {session_id} in room {file_id} with {} missed message(s)",
+            session.missed.len()
+        );
+
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{new_arc_state, setup};
+
+    use super::*;
+
+    fn permissions() -> (Vec<FilePermRole>, u64) {
+        (vec![FilePermRole::FileView, FilePermRole::FileEdit], 0)
+    }
+
+    fn ping_cell(file_id: Uuid) -> MessageResponse {
+        MessageResponse::PingCell {
+            file_id,
+            session_id: Uuid::new_v4(),
+            sheet_id: Uuid::new_v4(),
+            selection: "A1".into(),
+        }
+    }
+
+    #[test]
+    fn holds_missed_messages_while_disconnected() {
+        let file_id = Uuid::new_v4();
+        let mut session = Session::new(file_id, Uuid::new_v4());
+
+        // nothing is held while connected
+        session.add_missed(&ping_cell(file_id));
+        assert!(session.missed.is_empty());
+
+        session.disconnected_at = Some(Utc::now());
+        session.add_missed(&ping_cell(file_id));
+        session.add_missed(&MessageResponse::TransactionAck {
+            id: Uuid::new_v4(),
+            file_id,
+            sequence_num: 1,
+        });
+
+        // transactions are replayed by sequence number instead
+        session.add_missed(&MessageResponse::BinaryTransaction {
+            id: Uuid::new_v4(),
+            file_id,
+            sequence_num: 1,
+            operations: vec![],
+        });
+        assert_eq!(session.missed.len(), 2);
+
+        for _ in 0..MAX_MISSED_MESSAGES {
+            session.add_missed(&ping_cell(file_id));
+        }
+        assert_eq!(session.missed.len(), MAX_MISSED_MESSAGES);
+        assert!(matches!(
+            session.missed.front(),
+            Some(MessageResponse::PingCell { .. })
+        ));
+    }
+
+    #[test]
+    fn keeps_recent_transactions() {
+        let mut session = Session::new(Uuid::new_v4(), Uuid::new_v4());
+
+        for sequence_num in 0..MAX_SESSION_TRANSACTIONS as u64 + 10 {
+            session.add_transaction(Uuid::new_v4(), sequence_num);
+        }
+
+        assert_eq!(session.transactions.len(), MAX_SESSION_TRANSACTIONS);
+        assert_eq!(session.transactions.front().unwrap().1, 10);
+    }
+
+    #[test]
+    fn expires_after_the_grace_period() {
+        let mut session = Session::new(Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+        assert!(!session.is_expired(0, now));
+
+        session.disconnected_at = Some(now - chrono::Duration::seconds(10));
+        assert!(!session.is_expired(30, now));
+        assert!(session.is_expired(5, now));
+    }
+
+    #[tokio::test]
+    async fn resumes_a_disconnected_session() {
+        let (_, state, connection_id, file_id, user, _) = setup().await;
+        let session_id = user.session_id;
+        let user = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        let socket = user.socket.clone().unwrap();
+        let token = state.start_session(file_id, session_id).await.unwrap();
+
+        assert!(
+            state
+                .disconnect_session(file_id, session_id, connection_id)
+                .await
+        );
+
+        // the user stays in the room without a socket
+        let disconnected = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        assert!(disconnected.socket.is_none());
+        assert_eq!(disconnected.index, user.index);
+        assert!(state.get_connection(connection_id).await.is_err());
+
+        state
+            .add_missed_message(&session_id, &ping_cell(file_id))
+            .await;
+
+        // the wrong token can't resume the session
+        let resumed = state
+            .resume_session(
+                file_id,
+                session_id,
+                Uuid::new_v4(),
+                socket.clone(),
+                PreConnection::new(None),
+                permissions(),
+            )
+            .await;
+        assert!(matches!(resumed, Err(MpError::ResumeSession(_))));
+
+        let pre_connection = PreConnection::new(None);
+        let session = state
+            .resume_session(
+                file_id,
+                session_id,
+                token,
+                socket,
+                pre_connection.clone(),
+                permissions(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(session.missed.len(), 1);
+        assert!(session.disconnected_at.is_none());
+
+        let resumed = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        assert!(resumed.socket.is_some());
+        assert_eq!(resumed.index, user.index);
+        assert_eq!(resumed.connection_id, pre_connection.id);
+        assert!(state.get_connection(pre_connection.id).await.is_ok());
+
+        // the old connection closing doesn't disconnect the resumed session
+        assert!(
+            state
+                .disconnect_session(file_id, session_id, connection_id)
+                .await
+        );
+        let session = state.get_session(&session_id).await.unwrap().unwrap();
+        assert!(session.disconnected_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn resumes_a_session_held_by_another_instance() {
+        let (_, state_1, connection_id, file_id, user, _) = setup().await;
+        let state_2 = new_arc_state().await;
+        let session_id = user.session_id;
+        let user = state_1
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        let socket = user.socket.clone().unwrap();
+        let token = state_1.start_session(file_id, session_id).await.unwrap();
+        state_1
+            .set_cluster_user(file_id, user.clone())
+            .await
+            .unwrap();
+
+        assert!(
+            state_1
+                .disconnect_session(file_id, session_id, connection_id)
+                .await
+        );
+        state_1
+            .add_missed_message(&session_id, &ping_cell(file_id))
+            .await;
+
+        // the reconnect lands on another instance
+        let pre_connection = PreConnection::new(None);
+        let session = state_2
+            .resume_session(
+                file_id,
+                session_id,
+                token,
+                socket,
+                pre_connection.clone(),
+                permissions(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(session.missed.len(), 1);
+        assert_eq!(session.instance_id, state_2.cluster.instance_id);
+
+        let resumed = state_2
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap();
+        assert!(resumed.socket.is_some());
+        assert_eq!(resumed.index, user.index);
+        assert_eq!(resumed.connection_id, pre_connection.id);
+
+        // the first instance releases the user without ending the session
+        state_1
+            .remove_stale_users_in_room(file_id, 60)
+            .await
+            .unwrap();
+        assert!(
+            state_1
+                ._get_user_in_room(&file_id, &session_id)
+                .await
+                .is_err()
+        );
+        assert!(state_2.get_session(&session_id).await.unwrap().is_some());
+        let cluster_user = state_2.get_cluster_user(file_id, &session_id).await;
+        assert!(cluster_user.unwrap().is_some());
+
+        // broadcasts are no longer held by the first instance
+        state_1
+            .add_missed_message(&session_id, &ping_cell(file_id))
+            .await;
+        let session = state_2.get_session(&session_id).await.unwrap().unwrap();
+        assert!(session.missed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn leaving_the_room_ends_the_session() {
+        let (_, state, connection_id, file_id, user, _) = setup().await;
+        let session_id = user.session_id;
+
+        // entering the room started a session
+        assert!(state.get_session(&session_id).await.unwrap().is_some());
+
+        state.leave_room(file_id, &session_id).await.unwrap();
+
+        assert!(state.get_session(&session_id).await.unwrap().is_none());
+        assert!(
+            !state
+                .disconnect_session(file_id, session_id, connection_id)
+                .await
+        );
+    }
+}
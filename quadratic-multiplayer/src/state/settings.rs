@@ -8,6 +8,13 @@ pub(crate) struct Settings {
     pub(crate) authenticate_jwt: bool,
     pub(crate) quadratic_api_uri: String,
     pub(crate) m2m_auth_token: String,
+    pub(crate) heartbeat_timeout_s: i64,
+    pub(crate) resume_grace_period_s: i64,
+    pub(crate) rate_limit_per_s: f64,
+    pub(crate) rate_limit_burst: f64,
+    pub(crate) rate_limit_max_violations: usize,
+    pub(crate) presence_updates_per_s: f64,
+    pub(crate) max_transaction_size_bytes: usize,
     pub(crate) version: String,
 }
 
@@ -23,6 +30,13 @@ impl Settings {
             authenticate_jwt: config.authenticate_jwt,
             quadratic_api_uri: config.quadratic_api_uri.to_owned(),
             m2m_auth_token: config.m2m_auth_token.to_owned(),
+            heartbeat_timeout_s: config.heartbeat_timeout_s,
+            resume_grace_period_s: config.resume_grace_period_s,
+            rate_limit_per_s: config.rate_limit_per_s,
+            rate_limit_burst: config.rate_limit_burst,
+            rate_limit_max_violations: config.rate_limit_max_violations,
+            presence_updates_per_s: config.presence_updates_per_s,
+            max_transaction_size_bytes: config.max_transaction_size_bytes,
             version: version(),
         }
     }
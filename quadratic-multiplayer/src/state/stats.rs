@@ -4,9 +4,9 @@ use crate::state::State;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub(crate) struct Stats {
-    num_rooms: u64,
-    num_users: u64,
-    largest_room_size: u64,
+    pub(crate) num_rooms: u64,
+    pub(crate) num_users: u64,
+    pub(crate) largest_room_size: u64,
 }
 
 impl State {
@@ -100,6 +100,8 @@ impl State {
     }
 
     /// Remove stale users in a room.  Returns the number of users removed in the room, and the number left.
+    /// Users whose connection dropped are kept until their session can no longer be resumed, and
+    /// released once it is resumed on another instance.
     #[tracing::instrument(level = "trace")]
     pub(crate) async fn remove_stale_users_in_room(
         &self,
@@ -107,21 +109,34 @@ impl State {
         heartbeat_timeout_s: i64,
     ) -> Result<(usize, usize)> {
         let mut num_active_users = 0;
-        let stale_users = get_room!(self, file_id)?
+        let resume_grace_period_s = self.settings.resume_grace_period_s;
+        let now = Utc::now();
+        let users = get_room!(self, file_id)?
             .users
             .iter()
-            .filter(|user| {
-                let no_heartbeat =
-                    user.last_heartbeat.timestamp() + heartbeat_timeout_s < Utc::now().timestamp();
-
-                if !no_heartbeat {
-                    num_active_users += 1;
-                }
-
-                no_heartbeat
-            })
             .map(|user| user.to_owned())
             .collect::<Vec<User>>();
+        let mut stale_users = vec![];
+
+        for user in users {
+            let is_stale = match user.socket {
+                Some(_) => user.last_heartbeat.timestamp() + heartbeat_timeout_s < now.timestamp(),
+                None => match self.get_session(&user.session_id).await? {
+                    Some(session) if session.instance_id != self.cluster.instance_id => {
+                        self.release_session(file_id, &user.session_id).await?;
+                        continue;
+                    }
+                    Some(session) => session.is_expired(resume_grace_period_s, now),
+                    None => true,
+                },
+            };
+
+            if is_stale {
+                stale_users.push(user);
+            } else {
+                num_active_users += 1;
+            }
+        }
 
         for user in stale_users.iter() {
             tracing::info!(
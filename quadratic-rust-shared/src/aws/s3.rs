@@ -4,9 +4,13 @@
 
 use aws_sdk_s3::{
     Client,
-    operation::{get_object::GetObjectOutput, put_object::PutObjectOutput},
+    operation::{
+        get_object::GetObjectOutput, head_object::HeadObjectOutput, put_object::PutObjectOutput,
+    },
     primitives::{ByteStream, SdkBody},
+    types::{CompletedMultipartUpload, CompletedPart, Object},
 };
+use bytes::Bytes;
 
 use crate::aws::error::Aws as AwsError;
 use crate::error::{Result, SharedError};
@@ -49,6 +53,171 @@ pub async fn upload_object(
         })
 }
 
+/// Get an object's metadata from S3, or None if the object doesn't exist
+pub async fn head_object(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<Option<HeadObjectOutput>> {
+    match client.head_object().bucket(bucket).key(key).send().await {
+        Ok(output) => Ok(Some(output)),
+        Err(error)
+            if error
+                .as_service_error()
+                .is_some_and(|error| error.is_not_found()) =>
+        {
+            Ok(None)
+        }
+        Err(error) => Err(SharedError::Aws(AwsError::S3(format!(
+            "Error retrieving metadata for file {key} from bucket {bucket}: {error:?}."
+        )))),
+    }
+}
+
+/// List all objects in S3 whose key starts with the prefix
+pub async fn list_objects(client: &Client, bucket: &str, prefix: &str) -> Result<Vec<Object>> {
+    let mut objects = vec![];
+    let mut pages = client
+        .list_objects_v2()
+        .bucket(bucket)
+        .prefix(prefix)
+        .into_paginator()
+        .send();
+
+    while let Some(page) = pages.next().await {
+        let page = page.map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error listing files with prefix {prefix} in bucket {bucket}: {error:?}."
+            )))
+        })?;
+
+        objects.extend(page.contents().iter().cloned());
+    }
+
+    Ok(objects)
+}
+
+/// Delete an object from S3.  Deleting a missing object is not an error.
+pub async fn delete_object(client: &Client, bucket: &str, key: &str) -> Result<()> {
+    client
+        .delete_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error deleting file {key} from bucket {bucket}: {error:?}."
+            )))
+        })?;
+
+    Ok(())
+}
+
+/// Start a multipart upload to S3, returning the upload id
+pub async fn create_multipart_upload(client: &Client, bucket: &str, key: &str) -> Result<String> {
+    let error = |error: String| {
+        SharedError::Aws(AwsError::S3(format!(
+            "Error starting upload of file {key} to bucket {bucket}: {error}."
+        )))
+    };
+
+    let output = client
+        .create_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| error(format!("{e:?}")))?;
+
+    output
+        .upload_id()
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| error("no upload id".into()))
+}
+
+/// Upload a part of a multipart upload to S3.  Part numbers start at 1.
+pub async fn upload_part(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    part_number: i32,
+    body: Bytes,
+) -> Result<CompletedPart> {
+    let output = client
+        .upload_part()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .part_number(part_number)
+        .body(ByteStream::from(body))
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error uploading part {part_number} of file {key} to bucket {bucket}: {error:?}."
+            )))
+        })?;
+
+    Ok(CompletedPart::builder()
+        .set_e_tag(output.e_tag().map(ToOwned::to_owned))
+        .part_number(part_number)
+        .build())
+}
+
+/// Complete a multipart upload to S3
+pub async fn complete_multipart_upload(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    parts: Vec<CompletedPart>,
+) -> Result<()> {
+    let upload = CompletedMultipartUpload::builder()
+        .set_parts(Some(parts))
+        .build();
+
+    client
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(upload)
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error completing upload of file {key} to bucket {bucket}: {error:?}."
+            )))
+        })?;
+
+    Ok(())
+}
+
+/// Abort a multipart upload to S3, discarding any uploaded parts
+pub async fn abort_multipart_upload(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+) -> Result<()> {
+    client
+        .abort_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error aborting upload of file {key} to bucket {bucket}: {error:?}."
+            )))
+        })?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 pub mod tests {
     // use aws_config::{imds::Client as ImdsClient, provider_config::ProviderConfig};
@@ -18,6 +18,9 @@ pub mod environment;
 
 pub mod error;
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 #[cfg(feature = "net")]
 pub mod net;
 
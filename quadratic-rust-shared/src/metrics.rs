@@ -0,0 +1,320 @@
+//! Metrics
+//!
+//! A small Prometheus registry shared by the Rust services.  Counters, gauges
+//! and histograms are created on first use, keyed by name and labels, and
+//! rendered in the Prometheus text exposition format for a `/metrics`
+//! endpoint.
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Write};
+use std::sync::Mutex;
+
+/// The content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Buckets for durations, in seconds.
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Buckets for sizes, in bytes.
+pub const SIZE_BUCKETS: &[f64] = &[
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+    100_000_000.0,
+    1_000_000_000.0,
+];
+
+/// Buckets for counts, such as the number of messages behind.
+pub const COUNT_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1_000.0, 5_000.0];
+
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Counter(f64),
+    Gauge(f64),
+    Histogram(Histogram),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram(&'static [f64]),
+}
+
+impl Kind {
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram(_) => "histogram",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Family {
+    help: String,
+    kind: Kind,
+    series: BTreeMap<Labels, Value>,
+}
+
+/// A registry of metrics, safe to share between threads.
+#[derive(Debug, Default)]
+pub struct Registry {
+    prefix: String,
+    families: Mutex<BTreeMap<String, Family>>,
+}
+
+impl Registry {
+    /// Create a registry whose metric names all start with `prefix`.
+    pub fn new(prefix: &str) -> Self {
+        Registry {
+            prefix: prefix.to_owned(),
+            families: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Increment a counter by one.
+    pub fn inc(&self, name: &str, help: &str, labels: &[(&str, &str)]) {
+        self.inc_by(name, help, labels, 1.0);
+    }
+
+    /// Increment a counter.
+    pub fn inc_by(&self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        self.update(name, help, Kind::Counter, labels, |metric| {
+            if let Value::Counter(total) = metric {
+                *total += value;
+            }
+        });
+    }
+
+    /// Set a gauge.
+    pub fn set(&self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        self.update(name, help, Kind::Gauge, labels, |metric| {
+            if let Value::Gauge(current) = metric {
+                *current = value;
+            }
+        });
+    }
+
+    /// Record an observation in a histogram.
+    pub fn observe(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        buckets: &'static [f64],
+        value: f64,
+    ) {
+        self.update(name, help, Kind::Histogram(buckets), labels, |metric| {
+            if let Value::Histogram(histogram) = metric {
+                for (count, bound) in histogram.counts.iter_mut().zip(buckets) {
+                    if value <= *bound {
+                        *count += 1;
+                    }
+                }
+
+                histogram.sum += value;
+                histogram.count += 1;
+            }
+        });
+    }
+
+    /// Count an error by its enum variant.
+    pub fn error(&self, error: &impl Debug) {
+        let variant = error_variant(error);
+        self.inc(
+            "errors_total",
+            "Errors by variant",
+            &[("variant", variant.as_str())],
+        );
+    }
+
+    fn update(
+        &self,
+        name: &str,
+        help: &str,
+        kind: Kind,
+        labels: &[(&str, &str)],
+        update: impl FnOnce(&mut Value),
+    ) {
+        let Ok(mut families) = self.families.lock() else {
+            return;
+        };
+
+        let family = families.entry(name.to_owned()).or_insert_with(|| Family {
+            help: help.to_owned(),
+            kind,
+            series: BTreeMap::new(),
+        });
+
+        // a name can only be used for one kind of metric
+        if family.kind != kind {
+            return;
+        }
+
+        let labels = labels
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Labels>();
+
+        let value = family.series.entry(labels).or_insert_with(|| match kind {
+            Kind::Counter => Value::Counter(0.0),
+            Kind::Gauge => Value::Gauge(0.0),
+            Kind::Histogram(buckets) => Value::Histogram(Histogram {
+                counts: vec![0; buckets.len()],
+                sum: 0.0,
+                count: 0,
+            }),
+        });
+
+        update(value);
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let mut output = String::new();
+        let Ok(families) = self.families.lock() else {
+            return output;
+        };
+
+        for (name, family) in families.iter() {
+            let name = format!("{}_{name}", self.prefix);
+            let _ = writeln!(output, "# HELP {name} {}", family.help);
+            let _ = writeln!(output, "# TYPE {name} {}", family.kind.name());
+
+            for (labels, value) in family.series.iter() {
+                match value {
+                    Value::Counter(value) | Value::Gauge(value) => {
+                        let _ = writeln!(output, "{name}{} {value}", format_labels(labels, None));
+                    }
+                    Value::Histogram(histogram) => {
+                        let Kind::Histogram(buckets) = family.kind else {
+                            continue;
+                        };
+
+                        for (bound, count) in buckets.iter().zip(histogram.counts.iter()) {
+                            let le = bound.to_string();
+                            let labels = format_labels(labels, Some(&le));
+                            let _ = writeln!(output, "{name}_bucket{labels} {count}");
+                        }
+
+                        let labels_inf = format_labels(labels, Some("+Inf"));
+                        let labels = format_labels(labels, None);
+                        let _ = writeln!(output, "{name}_bucket{labels_inf} {}", histogram.count);
+                        let _ = writeln!(output, "{name}_sum{labels} {}", histogram.sum);
+                        let _ = writeln!(output, "{name}_count{labels} {}", histogram.count);
+                    }
+                }
+            }
+        }
+
+        output
+    }
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut labels = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>();
+
+    if let Some(le) = le {
+        labels.push(format!("le=\"{le}\""));
+    }
+
+    match labels.is_empty() {
+        true => String::new(),
+        false => format!("{{{}}}", labels.join(",")),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// The name of an enum variant, taken from its `Debug` output.
+pub fn error_variant(error: &impl Debug) -> String {
+    let debug = format!("{error:?}");
+
+    debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum TestError {
+        Query(String),
+        NotFound { id: u64 },
+        Unknown,
+    }
+
+    #[test]
+    fn encodes_counters_and_gauges() {
+        let registry = Registry::new("test");
+        registry.inc("requests_total", "Requests", &[("kind", "postgres")]);
+        registry.inc_by("requests_total", "Requests", &[("kind", "postgres")], 2.0);
+        registry.set("rooms", "Open rooms", &[], 4.0);
+
+        // a name can't change kinds
+        registry.set("requests_total", "Requests", &[("kind", "postgres")], 10.0);
+
+        let encoded = registry.encode();
+        assert!(encoded.contains("# TYPE test_requests_total counter\n"));
+        assert!(encoded.contains("test_requests_total{kind=\"postgres\"} 3\n"));
+        assert!(encoded.contains("# HELP test_rooms Open rooms\n"));
+        assert!(encoded.contains("test_rooms 4\n"));
+    }
+
+    #[test]
+    fn encodes_histograms() {
+        let registry = Registry::new("test");
+        registry.observe("size_bytes", "Sizes", &[], SIZE_BUCKETS, 500.0);
+        registry.observe("size_bytes", "Sizes", &[], SIZE_BUCKETS, 5_000.0);
+
+        let encoded = registry.encode();
+        assert!(encoded.contains("# TYPE test_size_bytes histogram\n"));
+        assert!(encoded.contains("test_size_bytes_bucket{le=\"1000\"} 1\n"));
+        assert!(encoded.contains("test_size_bytes_bucket{le=\"10000\"} 2\n"));
+        assert!(encoded.contains("test_size_bytes_bucket{le=\"+Inf\"} 2\n"));
+        assert!(encoded.contains("test_size_bytes_sum 5500\n"));
+        assert!(encoded.contains("test_size_bytes_count 2\n"));
+    }
+
+    #[test]
+    fn counts_errors_by_variant() {
+        assert_eq!(error_variant(&TestError::Query("bad".into())), "Query");
+        assert_eq!(error_variant(&TestError::NotFound { id: 1 }), "NotFound");
+        assert_eq!(error_variant(&TestError::Unknown), "Unknown");
+
+        let registry = Registry::new("test");
+        registry.error(&TestError::Query("bad \"sql\"".into()));
+        registry.error(&TestError::Query("other".into()));
+
+        let encoded = registry.encode();
+        assert!(encoded.contains("test_errors_total{variant=\"Query\"} 2\n"));
+    }
+}
@@ -0,0 +1,282 @@
+//! PubSub Connection
+//!
+//! A connection to whichever PubSub backend is configured, so services can
+//! run against Redis Streams or, in a single process, the in-memory store.
+
+use serde::Deserialize;
+
+use crate::pubsub::memory::{MemoryConfig, MemoryConnection};
+use crate::pubsub::redis_streams::{RedisConnection, RedisStreamsConfig};
+use crate::pubsub::{Config, PubSub};
+use crate::{SharedError, error::Result};
+
+/// The PubSub backend to use
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PubSubType {
+    #[default]
+    RedisStreams,
+    Memory,
+}
+
+/// The store shared by services that run in the same process.
+pub const MEMORY_STORE_NAME: &str = "quadratic";
+
+impl PubSubType {
+    /// Build the config for this backend.  The in-memory backend ignores the
+    /// connection details.
+    pub fn config(&self, host: &str, port: &str, password: &str, active_channels: &str) -> Config {
+        match self {
+            PubSubType::RedisStreams => Config::RedisStreams(RedisStreamsConfig {
+                host: host.to_owned(),
+                port: port.to_owned(),
+                password: password.to_owned(),
+                active_channels: active_channels.to_owned(),
+            }),
+            PubSubType::Memory => Config::Memory(MemoryConfig {
+                name: MEMORY_STORE_NAME.to_owned(),
+                active_channels: active_channels.to_owned(),
+            }),
+        }
+    }
+}
+
+/// A connection to a Redis Streams or in-memory PubSub backend
+#[derive(Debug)]
+pub enum Connection {
+    RedisStreams(RedisConnection),
+    Memory(MemoryConnection),
+}
+
+macro_rules! delegate {
+    ( $self:ident, $method:ident( $( $arg:expr ),* ) ) => {
+        match $self {
+            Connection::RedisStreams(connection) => connection.$method($( $arg ),*).await,
+            Connection::Memory(connection) => connection.$method($( $arg ),*).await,
+        }
+    };
+}
+
+impl PubSub for Connection {
+    type Connection = Connection;
+
+    /// Create a new connection for the configured backend.
+    async fn new(config: Config) -> Result<Connection> {
+        Self::connect(config).await
+    }
+
+    /// Connect to the configured backend.
+    async fn connect(config: Config) -> Result<Connection> {
+        match config {
+            Config::RedisStreams(_) => Ok(Connection::RedisStreams(
+                RedisConnection::connect(config).await?,
+            )),
+            Config::Memory(_) => Ok(Connection::Memory(MemoryConnection::connect(config).await?)),
+            Config::Redis(_) => Err(SharedError::PubSub(
+                "Config type must be RedisStreamsConfig or MemoryConfig".into(),
+            )),
+        }
+    }
+
+    async fn is_healthy(&mut self) -> bool {
+        delegate!(self, is_healthy())
+    }
+
+    async fn channels(&mut self) -> Result<Vec<String>> {
+        delegate!(self, channels())
+    }
+
+    async fn active_channels(&mut self, set_key: &str) -> Result<Vec<String>> {
+        delegate!(self, active_channels(set_key))
+    }
+
+    async fn upsert_active_channel(&mut self, set_key: &str, channel: &str) -> Result<()> {
+        delegate!(self, upsert_active_channel(set_key, channel))
+    }
+
+    async fn remove_active_channel(&mut self, set_key: &str, channel: &str) -> Result<()> {
+        delegate!(self, remove_active_channel(set_key, channel))
+    }
+
+    async fn subscribe(&mut self, channel: &str, group: &str) -> Result<()> {
+        delegate!(self, subscribe(channel, group))
+    }
+
+    async fn publish(
+        &mut self,
+        channel: &str,
+        key: &str,
+        value: &[u8],
+        active_channel: Option<&str>,
+    ) -> Result<()> {
+        delegate!(self, publish(channel, key, value, active_channel))
+    }
+
+    async fn ack(
+        &mut self,
+        channel: &str,
+        group: &str,
+        keys: Vec<&str>,
+        active_channel: Option<&str>,
+        preserve_sequence: bool,
+    ) -> Result<()> {
+        delegate!(
+            self,
+            ack(channel, group, keys, active_channel, preserve_sequence)
+        )
+    }
+
+    async fn trim(&mut self, channel: &str, key: &str) -> Result<i64> {
+        delegate!(self, trim(channel, key))
+    }
+
+    async fn messages(
+        &mut self,
+        channel: &str,
+        group: &str,
+        consumer: &str,
+        keys: Option<&str>,
+        max_messages: usize,
+        preserve_sequence: bool,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        delegate!(
+            self,
+            messages(
+                channel,
+                group,
+                consumer,
+                keys,
+                max_messages,
+                preserve_sequence
+            )
+        )
+    }
+
+    async fn get_messages_before(
+        &mut self,
+        channel: &str,
+        id: &str,
+        preserve_sequence: bool,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        delegate!(self, get_messages_before(channel, id, preserve_sequence))
+    }
+
+    async fn get_messages_from(
+        &mut self,
+        channel: &str,
+        id: &str,
+        preserve_sequence: bool,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        delegate!(self, get_messages_from(channel, id, preserve_sequence))
+    }
+
+    async fn last_message(
+        &mut self,
+        channel: &str,
+        preserve_sequence: bool,
+    ) -> Result<(String, Vec<u8>)> {
+        delegate!(self, last_message(channel, preserve_sequence))
+    }
+
+    async fn publish_next(
+        &mut self,
+        channel: &str,
+        counter_key: &str,
+        min: u64,
+        sequence_num: u64,
+        value: &[u8],
+        active_channel: Option<&str>,
+    ) -> Result<Option<u64>> {
+        delegate!(
+            self,
+            publish_next(
+                channel,
+                counter_key,
+                min,
+                sequence_num,
+                value,
+                active_channel
+            )
+        )
+    }
+
+    async fn increment(&mut self, key: &str, min: u64) -> Result<u64> {
+        delegate!(self, increment(key, min))
+    }
+
+    async fn set_field(&mut self, key: &str, field: &str, value: &[u8]) -> Result<()> {
+        delegate!(self, set_field(key, field, value))
+    }
+
+    async fn remove_field(&mut self, key: &str, field: &str) -> Result<()> {
+        delegate!(self, remove_field(key, field))
+    }
+
+    async fn field(&mut self, key: &str, field: &str) -> Result<Option<Vec<u8>>> {
+        delegate!(self, field(key, field))
+    }
+
+    async fn fields(&mut self, key: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        delegate!(self, fields(key))
+    }
+
+    async fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+        delegate!(self, get(key))
+    }
+
+    async fn set(&mut self, key: &str, value: &[u8], expire_s: u64) -> Result<()> {
+        delegate!(self, set(key, value, expire_s))
+    }
+
+    async fn set_if(
+        &mut self,
+        key: &str,
+        current: &[u8],
+        value: &[u8],
+        expire_s: u64,
+    ) -> Result<bool> {
+        delegate!(self, set_if(key, current, value, expire_s))
+    }
+
+    async fn delete(&mut self, key: &str) -> Result<()> {
+        delegate!(self, delete(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pubsub::memory::MemoryConfig;
+
+    #[tokio::test]
+    async fn connects_to_the_configured_backend() {
+        let config = Config::Memory(MemoryConfig {
+            name: Uuid::new_v4().to_string(),
+            active_channels: "active_channels".into(),
+        });
+        let mut connection = Connection::new(config).await.unwrap();
+        assert!(matches!(connection, Connection::Memory(_)));
+
+        connection
+            .publish("channel", "1", b"test", None)
+            .await
+            .unwrap();
+        let last_message = connection.last_message("channel", false).await.unwrap();
+        assert_eq!(last_message, ("1".into(), b"test".to_vec()));
+    }
+
+    #[test]
+    fn builds_a_config_for_each_type() {
+        let config = PubSubType::Memory.config("host", "6379", "", "active_channels");
+        assert!(
+            matches!(config, Config::Memory(MemoryConfig { name, .. }) if name == MEMORY_STORE_NAME)
+        );
+
+        let config = PubSubType::RedisStreams.config("host", "6379", "", "active_channels");
+        assert!(
+            matches!(config, Config::RedisStreams(RedisStreamsConfig { host, .. }) if host == "host")
+        );
+    }
+}
@@ -0,0 +1,788 @@
+//! In-Memory PubSub
+//!
+//! An in-process implementation of the PubSub trait with the same stream
+//! semantics as Redis Streams (consumer groups, pending messages, acks, trims
+//! and active channels).  Connections with the same name share a store, so
+//! several services running in one process see the same streams.
+
+use chrono::prelude::*;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{self, Debug},
+    sync::{Arc, LazyLock, Mutex, MutexGuard},
+};
+
+use crate::pubsub::Config;
+use crate::{SharedError, error::Result};
+
+/// In-memory configuration
+#[derive(Debug, Clone)]
+pub struct MemoryConfig {
+    pub name: String,
+    pub active_channels: String,
+}
+
+/// A message consists of a key (String) and a value (Bytes).
+type Message = (String, Vec<u8>);
+
+/// A stream id, (milliseconds, sequence) in Redis terms.
+type Id = (u64, u64);
+
+#[derive(Debug, Default)]
+struct Group {
+    last_delivered: Id,
+    pending: BTreeSet<Id>,
+}
+
+#[derive(Debug, Default)]
+struct Stream {
+    messages: BTreeMap<Id, Vec<u8>>,
+    groups: HashMap<String, Group>,
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    streams: HashMap<String, Stream>,
+    active_channels: HashMap<String, HashMap<String, i64>>,
+    counters: HashMap<String, u64>,
+    hashes: HashMap<String, HashMap<String, Vec<u8>>>,
+    /// Values and when they expire
+    values: HashMap<String, (Vec<u8>, DateTime<Utc>)>,
+}
+
+impl Store {
+    /// Get a value, forgetting it if it has expired.
+    fn value(&mut self, key: &str) -> Option<&Vec<u8>> {
+        if self
+            .values
+            .get(key)
+            .is_some_and(|(_, expires_at)| *expires_at <= Utc::now())
+        {
+            self.values.remove(key);
+        }
+
+        self.values.get(key).map(|(value, _)| value)
+    }
+
+    fn set_value(&mut self, key: &str, value: &[u8], expire_s: u64) {
+        let expires_at = Utc::now() + chrono::Duration::seconds(expire_s as i64);
+        self.values
+            .insert(key.to_owned(), (value.to_vec(), expires_at));
+    }
+}
+
+/// Stores shared by all connections in this process, by name.
+static STORES: LazyLock<Mutex<HashMap<String, Arc<Mutex<Store>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// In-memory connection
+#[derive(Clone)]
+pub struct MemoryConnection {
+    name: String,
+    store: Arc<Mutex<Store>>,
+}
+
+impl Debug for MemoryConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemoryConnection {{ name: {} }}", self.name)
+    }
+}
+
+impl MemoryConnection {
+    fn store(&self) -> Result<MutexGuard<'_, Store>> {
+        self.store
+            .lock()
+            .map_err(|e| SharedError::PubSub(format!("Error locking in-memory store: {e}")))
+    }
+}
+
+/// Parse a key into an id, e.g. "5" or "5-0"
+fn parse_id(key: &str) -> Result<Id> {
+    let invalid = || SharedError::PubSub(format!("Invalid stream id: {key}"));
+    let (ms, seq) = key.split_once('-').unwrap_or((key, "0"));
+
+    Ok((
+        ms.parse().map_err(|_| invalid())?,
+        seq.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// Convert an id to a key, either preserving the sequence or removing it
+fn to_key((ms, seq): Id, preserve_sequence: bool) -> String {
+    if preserve_sequence {
+        format!("{ms}-{seq}")
+    } else {
+        ms.to_string()
+    }
+}
+
+fn to_message((id, value): (&Id, &Vec<u8>), preserve_sequence: bool) -> Message {
+    (to_key(*id, preserve_sequence), value.to_owned())
+}
+
+impl super::PubSub for MemoryConnection {
+    type Connection = MemoryConnection;
+
+    /// Create a new in-memory connection.
+    async fn new(config: Config) -> Result<MemoryConnection> {
+        Self::connect(config).await
+    }
+
+    /// Connect to the named in-memory store, creating it if needed.
+    async fn connect(config: Config) -> Result<MemoryConnection> {
+        let Config::Memory(MemoryConfig { name, .. }) = config else {
+            return Err(SharedError::PubSub(
+                "Config type must be MemoryConfig".into(),
+            ));
+        };
+
+        let store = STORES
+            .lock()
+            .map_err(|e| SharedError::PubSub(format!("Error locking in-memory stores: {e}")))?
+            .entry(name.to_owned())
+            .or_default()
+            .to_owned();
+
+        Ok(MemoryConnection { name, store })
+    }
+
+    /// The in-memory store is always healthy
+    async fn is_healthy(&mut self) -> bool {
+        true
+    }
+
+    /// Get a list of channels
+    async fn channels(&mut self) -> Result<Vec<String>> {
+        let store = self.store()?;
+        let channels = store
+            .streams
+            .keys()
+            .chain(store.active_channels.keys())
+            .chain(store.counters.keys())
+            .chain(store.hashes.keys())
+            .cloned()
+            .collect();
+
+        Ok(channels)
+    }
+
+    /// Get a list of active channels, oldest first
+    async fn active_channels(&mut self, set_key: &str) -> Result<Vec<String>> {
+        let store = self.store()?;
+        let mut channels = store
+            .active_channels
+            .get(set_key)
+            .map(|channels| channels.iter().collect::<Vec<_>>())
+            .unwrap_or_default();
+        channels.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+
+        Ok(channels
+            .into_iter()
+            .map(|(channel, _)| channel.to_owned())
+            .collect())
+    }
+
+    /// Insert or update a key within an active channel
+    async fn upsert_active_channel(&mut self, set_key: &str, channel: &str) -> Result<()> {
+        let score = Utc::now().timestamp_millis();
+        self.store()?
+            .active_channels
+            .entry(set_key.to_owned())
+            .or_default()
+            .insert(channel.to_owned(), score);
+
+        Ok(())
+    }
+
+    /// Remove an a key within an active channel
+    async fn remove_active_channel(&mut self, set_key: &str, channel: &str) -> Result<()> {
+        if let Some(channels) = self.store()?.active_channels.get_mut(set_key) {
+            channels.remove(channel);
+        }
+
+        Ok(())
+    }
+
+    /// Create a group and a key (if it doesn't already exist), start from the
+    /// end of the stream
+    async fn subscribe(&mut self, channel: &str, group: &str) -> Result<()> {
+        let mut store = self.store()?;
+        let stream = store.streams.entry(channel.to_owned()).or_default();
+        let last_id = stream
+            .messages
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or_default();
+
+        stream.groups.entry(group.to_owned()).or_insert(Group {
+            last_delivered: last_id,
+            pending: BTreeSet::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Publish a message to a channel.
+    async fn publish(
+        &mut self,
+        channel: &str,
+        key: &str,
+        value: &[u8],
+        active_channel: Option<&str>,
+    ) -> Result<()> {
+        let id = parse_id(key)?;
+
+        {
+            let mut store = self.store()?;
+            let stream = store.streams.entry(channel.to_owned()).or_default();
+
+            if stream
+                .messages
+                .keys()
+                .next_back()
+                .is_some_and(|last_id| *last_id >= id)
+            {
+                return Err(SharedError::PubSub(format!(
+                    "Error publishing to channel {channel}: id {key} is not greater than the last id"
+                )));
+            }
+
+            stream.messages.insert(id, value.to_vec());
+        }
+
+        // add the channel to the active channels set
+        if let Some(active_channel) = active_channel {
+            self.upsert_active_channel(active_channel, channel).await?
+        }
+
+        Ok(())
+    }
+
+    /// Publish a message as the next value of a counter, atomically, so that
+    /// concurrent publishers can't add ids out of order.
+    async fn publish_next(
+        &mut self,
+        channel: &str,
+        counter_key: &str,
+        min: u64,
+        sequence_num: u64,
+        value: &[u8],
+        active_channel: Option<&str>,
+    ) -> Result<Option<u64>> {
+        {
+            let mut store = self.store()?;
+            let current = store.counters.get(counter_key).copied().unwrap_or_default();
+            let current = current.max(min);
+
+            if sequence_num != current + 1 {
+                return Ok(Some(current));
+            }
+
+            let id = (sequence_num, 0);
+            let stream = store.streams.entry(channel.to_owned()).or_default();
+
+            if stream
+                .messages
+                .keys()
+                .next_back()
+                .is_some_and(|last_id| *last_id >= id)
+            {
+                return Err(SharedError::PubSub(format!(
+                    "Error publishing to channel {channel}: id {sequence_num} is not greater than the last id"
+                )));
+            }
+
+            stream.messages.insert(id, value.to_vec());
+            store.counters.insert(counter_key.to_owned(), sequence_num);
+        }
+
+        // add the channel to the active channels set
+        if let Some(active_channel) = active_channel {
+            self.upsert_active_channel(active_channel, channel).await?
+        }
+
+        Ok(None)
+    }
+
+    /// Acknowledge that a message was processed
+    async fn ack(
+        &mut self,
+        channel: &str,
+        group: &str,
+        keys: Vec<&str>,
+        active_channel: Option<&str>,
+        _preserve_sequence: bool,
+    ) -> Result<()> {
+        if keys.is_empty() {
+            return Err(SharedError::PubSub(
+                "Error acking messages for channel {channel}: no keys provided".into(),
+            ));
+        }
+
+        let ids = keys
+            .into_iter()
+            .map(parse_id)
+            .collect::<Result<Vec<Id>>>()?;
+
+        if let Some(group) = self
+            .store()?
+            .streams
+            .get_mut(channel)
+            .and_then(|stream| stream.groups.get_mut(group))
+        {
+            ids.iter().for_each(|id| {
+                group.pending.remove(id);
+            });
+        }
+
+        // remove the channel from the active channels set
+        if let Some(active_channel) = active_channel {
+            self.remove_active_channel(active_channel, channel).await?
+        }
+
+        Ok(())
+    }
+
+    /// Trim messages before a key from a channel
+    async fn trim(&mut self, channel: &str, key: &str) -> Result<i64> {
+        let min_id = parse_id(key)?;
+        let mut store = self.store()?;
+        let Some(stream) = store.streams.get_mut(channel) else {
+            return Ok(0);
+        };
+
+        let kept = stream.messages.split_off(&min_id);
+        let trimmed = stream.messages.len();
+        stream.messages = kept;
+
+        Ok(trimmed as i64)
+    }
+
+    /// Get unread messages from a channel.  Specify an id to get the
+    /// group's pending messages after it, or None to get all new messages.
+    ///
+    /// After receiving messages, they enter a pending queue.
+    ///
+    /// Once messages are processed, they must be acknowledged with `ack` to
+    /// remove them from the pending queue.
+    async fn messages(
+        &mut self,
+        channel: &str,
+        group: &str,
+        _consumer: &str,
+        maybe_id: Option<&str>,
+        max_messages: usize,
+        preserve_sequence: bool,
+    ) -> Result<Vec<Message>> {
+        let after_id = maybe_id.map(parse_id).transpose()?;
+        let mut store = self.store()?;
+        let no_group = || {
+            SharedError::PubSub(format!(
+                "Error reading messages for channel {channel}: no group {group}"
+            ))
+        };
+        let stream = store.streams.get_mut(channel).ok_or_else(no_group)?;
+        let Stream { messages, groups } = stream;
+        let group = groups.get_mut(group).ok_or_else(no_group)?;
+
+        let messages = match after_id {
+            // pending messages after the id
+            Some(after_id) => group
+                .pending
+                .iter()
+                .filter(|id| **id > after_id)
+                .filter_map(|id| messages.get_key_value(id))
+                .take(max_messages)
+                .map(|message| to_message(message, preserve_sequence))
+                .collect::<Vec<_>>(),
+
+            // new messages, which become pending
+            None => {
+                let new_messages = messages
+                    .range((
+                        std::ops::Bound::Excluded(group.last_delivered),
+                        std::ops::Bound::Unbounded,
+                    ))
+                    .take(max_messages)
+                    .collect::<Vec<_>>();
+
+                if let Some((last_id, _)) = new_messages.last() {
+                    group.last_delivered = **last_id;
+                }
+
+                group
+                    .pending
+                    .extend(new_messages.iter().map(|(id, _)| **id));
+
+                new_messages
+                    .into_iter()
+                    .map(|message| to_message(message, preserve_sequence))
+                    .collect()
+            }
+        };
+
+        Ok(messages)
+    }
+
+    /// Get messages from the beginning of a channel ending at a specific id
+    async fn get_messages_before(
+        &mut self,
+        channel: &str,
+        id: &str,
+        preserve_sequence: bool,
+    ) -> Result<Vec<Message>> {
+        // like Redis, an end id without a sequence includes every sequence
+        let end_id = match id.contains('-') {
+            true => parse_id(id)?,
+            false => (parse_id(id)?.0, u64::MAX),
+        };
+        let messages = self
+            .store()?
+            .streams
+            .get(channel)
+            .map(|stream| {
+                stream
+                    .messages
+                    .range(..=end_id)
+                    .map(|message| to_message(message, preserve_sequence))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(messages)
+    }
+
+    /// Get messages from a channel starting from a specific id
+    async fn get_messages_from(
+        &mut self,
+        channel: &str,
+        id: &str,
+        preserve_sequence: bool,
+    ) -> Result<Vec<Message>> {
+        let id = parse_id(id)?;
+        let messages = self
+            .store()?
+            .streams
+            .get(channel)
+            .map(|stream| {
+                stream
+                    .messages
+                    .range(id..)
+                    .map(|message| to_message(message, preserve_sequence))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(messages)
+    }
+
+    /// Get the last message in a channel
+    async fn last_message(&mut self, channel: &str, preserve_sequence: bool) -> Result<Message> {
+        self.store()?
+            .streams
+            .get(channel)
+            .and_then(|stream| stream.messages.iter().next_back())
+            .map(|message| to_message(message, preserve_sequence))
+            .ok_or_else(|| {
+                SharedError::PubSub("Error getting last message: no messages found".into())
+            })
+    }
+
+    /// Atomically increment a counter, starting from at least `min`
+    async fn increment(&mut self, key: &str, min: u64) -> Result<u64> {
+        let mut store = self.store()?;
+        let counter = store.counters.entry(key.to_owned()).or_default();
+        *counter = (*counter).max(min) + 1;
+
+        Ok(*counter)
+    }
+
+    /// Insert or update a field within a hash
+    async fn set_field(&mut self, key: &str, field: &str, value: &[u8]) -> Result<()> {
+        self.store()?
+            .hashes
+            .entry(key.to_owned())
+            .or_default()
+            .insert(field.to_owned(), value.to_vec());
+
+        Ok(())
+    }
+
+    /// Remove a field from a hash
+    async fn remove_field(&mut self, key: &str, field: &str) -> Result<()> {
+        if let Some(fields) = self.store()?.hashes.get_mut(key) {
+            fields.remove(field);
+        }
+
+        Ok(())
+    }
+
+    /// Get a field within a hash, or None if it doesn't exist
+    async fn field(&mut self, key: &str, field: &str) -> Result<Option<Vec<u8>>> {
+        let value = self
+            .store()?
+            .hashes
+            .get(key)
+            .and_then(|fields| fields.get(field).cloned());
+
+        Ok(value)
+    }
+
+    /// Get all fields within a hash
+    async fn fields(&mut self, key: &str) -> Result<Vec<Message>> {
+        let fields = self
+            .store()?
+            .hashes
+            .get(key)
+            .map(|fields| {
+                fields
+                    .iter()
+                    .map(|(field, value)| (field.to_owned(), value.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(fields)
+    }
+
+    /// Get a value, or None if it doesn't exist or has expired
+    async fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.store()?.value(key).cloned())
+    }
+
+    /// Set a value that expires after `expire_s` seconds
+    async fn set(&mut self, key: &str, value: &[u8], expire_s: u64) -> Result<()> {
+        self.store()?.set_value(key, value, expire_s);
+
+        Ok(())
+    }
+
+    /// Set a value that expires after `expire_s` seconds, only if its current
+    /// value is `current`
+    async fn set_if(
+        &mut self,
+        key: &str,
+        current: &[u8],
+        value: &[u8],
+        expire_s: u64,
+    ) -> Result<bool> {
+        let mut store = self.store()?;
+
+        if store.value(key).is_none_or(|stored| stored != current) {
+            return Ok(false);
+        }
+
+        store.set_value(key, value, expire_s);
+
+        Ok(true)
+    }
+
+    /// Delete a value
+    async fn delete(&mut self, key: &str) -> Result<()> {
+        self.store()?.values.remove(key);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pubsub::PubSub;
+
+    fn setup() -> (Config, String) {
+        let channel = Uuid::new_v4().to_string();
+        let config = Config::Memory(MemoryConfig {
+            name: Uuid::new_v4().to_string(),
+            active_channels: Uuid::new_v4().to_string(),
+        });
+
+        (config, channel)
+    }
+
+    #[tokio::test]
+    async fn memory_connect_subscribe_publish_get_message() {
+        let (config, channel) = setup();
+        let messages = ["test 1".as_bytes(), "test 2".as_bytes()];
+        let group = "group 1";
+        let consumer = "consumer 1";
+
+        let mut connection = MemoryConnection::new(config).await.unwrap();
+        connection.subscribe(&channel, group).await.unwrap();
+
+        for (key, value) in messages.iter().enumerate() {
+            connection
+                .publish(&channel, &(key + 1).to_string(), value, None)
+                .await
+                .unwrap();
+        }
+
+        // ids must increase
+        assert!(
+            connection
+                .publish(&channel, "1", b"test 3", None)
+                .await
+                .is_err()
+        );
+
+        // get all new messages
+        let results = connection
+            .messages(&channel, group, consumer, None, 10, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            results,
+            vec![
+                ("1".into(), messages[0].into()),
+                ("2".into(), messages[1].into())
+            ]
+        );
+
+        // new messages are only delivered once, but stay pending until acked
+        let results = connection
+            .messages(&channel, group, consumer, None, 10, false)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+
+        let results = connection
+            .messages(&channel, group, consumer, Some("0"), 10, false)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+
+        connection
+            .ack(&channel, group, vec!["1", "2"], None, false)
+            .await
+            .unwrap();
+
+        let results = connection
+            .messages(&channel, group, consumer, Some("0"), 10, false)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn memory_ranges_trim_and_last_message() {
+        let (config, channel) = setup();
+        let mut connection = MemoryConnection::new(config).await.unwrap();
+
+        for key in 1..=4 {
+            connection
+                .publish(&channel, &key.to_string(), key.to_string().as_bytes(), None)
+                .await
+                .unwrap();
+        }
+
+        let keys =
+            |messages: Vec<Message>| messages.into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+
+        let from = connection.get_messages_from(&channel, "3", false).await;
+        assert_eq!(keys(from.unwrap()), vec!["3", "4"]);
+
+        let before = connection.get_messages_before(&channel, "2", true).await;
+        assert_eq!(keys(before.unwrap()), vec!["1-0", "2-0"]);
+
+        assert_eq!(connection.trim(&channel, "3").await.unwrap(), 2);
+        let from = connection.get_messages_from(&channel, "0", false).await;
+        assert_eq!(keys(from.unwrap()), vec!["3", "4"]);
+
+        let last_message = connection.last_message(&channel, false).await.unwrap();
+        assert_eq!(last_message, ("4".into(), b"4".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn memory_active_channels() {
+        let (config, _) = setup();
+        let active_channels = Uuid::new_v4().to_string();
+        let channels = [Uuid::new_v4().to_string(), Uuid::new_v4().to_string()];
+
+        let mut connection = MemoryConnection::new(config).await.unwrap();
+
+        for (key, channel) in channels.iter().enumerate() {
+            connection
+                .publish(
+                    channel,
+                    &(key + 1).to_string(),
+                    b"test",
+                    Some(&active_channels),
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut results = connection.active_channels(&active_channels).await.unwrap();
+        results.sort();
+        let mut expected = channels.to_vec();
+        expected.sort();
+        assert_eq!(results, expected);
+
+        connection.subscribe(&channels[0], "group").await.unwrap();
+        connection
+            .ack(
+                &channels[0],
+                "group",
+                vec!["1"],
+                Some(&active_channels),
+                false,
+            )
+            .await
+            .unwrap();
+        let results = connection.active_channels(&active_channels).await.unwrap();
+        assert_eq!(results, vec![channels[1].to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn memory_connections_share_a_store_by_name() {
+        let (config, channel) = setup();
+        let mut connection_1 = MemoryConnection::new(config.clone()).await.unwrap();
+        let mut connection_2 = MemoryConnection::new(config).await.unwrap();
+
+        connection_1
+            .publish(&channel, "1", b"test", None)
+            .await
+            .unwrap();
+        let last_message = connection_2.last_message(&channel, false).await.unwrap();
+        assert_eq!(last_message, ("1".into(), b"test".to_vec()));
+
+        assert_eq!(connection_1.increment("counter", 5).await.unwrap(), 6);
+
+        // a publish with a taken sequence number returns the counter instead
+        let next = connection_2.publish_next(&channel, "counter", 0, 7, b"7", None);
+        assert_eq!(next.await.unwrap(), None);
+        let next = connection_1.publish_next(&channel, "counter", 0, 7, b"7", None);
+        assert_eq!(next.await.unwrap(), Some(7));
+        assert_eq!(connection_1.increment("counter", 0).await.unwrap(), 8);
+        let last_message = connection_1.last_message(&channel, false).await.unwrap();
+        assert_eq!(last_message, ("7".into(), b"7".to_vec()));
+
+        connection_1.set_field("hash", "a", b"1").await.unwrap();
+        connection_2.remove_field("hash", "b").await.unwrap();
+        let fields = connection_2.fields("hash").await.unwrap();
+        assert_eq!(fields, vec![("a".to_string(), b"1".to_vec())]);
+        let field = connection_2.field("hash", "a").await.unwrap();
+        assert_eq!(field, Some(b"1".to_vec()));
+
+        connection_1.set("value", b"1", 60).await.unwrap();
+        assert_eq!(
+            connection_2.get("value").await.unwrap(),
+            Some(b"1".to_vec())
+        );
+        assert!(!connection_2.set_if("value", b"0", b"2", 60).await.unwrap());
+        assert!(connection_2.set_if("value", b"1", b"2", 60).await.unwrap());
+        assert_eq!(
+            connection_1.get("value").await.unwrap(),
+            Some(b"2".to_vec())
+        );
+        connection_1.delete("value").await.unwrap();
+        assert_eq!(connection_2.get("value").await.unwrap(), None);
+
+        // expired values are gone
+        connection_1.set("value", b"1", 0).await.unwrap();
+        assert_eq!(connection_2.get("value").await.unwrap(), None);
+    }
+}
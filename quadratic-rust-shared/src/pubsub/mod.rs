@@ -1,20 +1,39 @@
 //! Pubsub code that implements the PubSub trait
 
+pub mod connection;
 pub mod error;
+pub mod memory;
 pub mod redis;
 pub mod redis_streams;
 
 use futures_util::Future;
+use serde::{Deserialize, Serialize};
 
 use crate::error::Result;
+use crate::pubsub::memory::MemoryConfig;
 use crate::pubsub::redis::RedisConfig;
 use crate::pubsub::redis_streams::RedisStreamsConfig;
 
+/// The sheets in a file as of its latest processed transaction.  The files
+/// service writes this after each checkpoint so that multiplayer can reject
+/// transactions on sheets that don't exist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSheets {
+    pub sequence_num: u64,
+    pub sheet_ids: Vec<String>,
+}
+
+/// The key a file's `FileSheets` are stored under.
+pub fn file_sheets_key(file_id: &str) -> String {
+    format!("file-sheets:{file_id}")
+}
+
 /// Pubsub configuration
 #[derive(Debug, Clone)]
 pub enum Config {
     Redis(RedisConfig),
     RedisStreams(RedisStreamsConfig),
+    Memory(MemoryConfig),
 }
 
 /// Pubsub trait
@@ -65,6 +84,21 @@ pub trait PubSub {
         active_channel: Option<&str>,
     ) -> impl Future<Output = Result<()>> + Send;
 
+    /// Publish a message keyed by `sequence_num` only if it's the next value
+    /// of the counter at `counter_key` (which starts from at least `min`),
+    /// advancing the counter in the same atomic step.  Returns None once
+    /// published, or the counter's current value if another publisher took
+    /// `sequence_num` first.
+    fn publish_next(
+        &mut self,
+        channel: &str,
+        counter_key: &str,
+        min: u64,
+        sequence_num: u64,
+        value: &[u8],
+        active_channel: Option<&str>,
+    ) -> impl Future<Output = Result<Option<u64>>> + Send;
+
     /// Acknowledge a message
     fn ack(
         &mut self,
@@ -111,4 +145,53 @@ pub trait PubSub {
         channel: &str,
         preserve_sequence: bool,
     ) -> impl Future<Output = Result<(String, Vec<u8>)>> + Send;
+
+    /// Atomically increment a counter, starting from at least `min`
+    fn increment(&mut self, key: &str, min: u64) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Insert or update a field within a hash
+    fn set_field(
+        &mut self,
+        key: &str,
+        field: &str,
+        value: &[u8],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Remove a field from a hash
+    fn remove_field(&mut self, key: &str, field: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Get a field within a hash, or None if it doesn't exist
+    fn field(
+        &mut self,
+        key: &str,
+        field: &str,
+    ) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send;
+
+    /// Get all fields within a hash
+    fn fields(&mut self, key: &str) -> impl Future<Output = Result<Vec<(String, Vec<u8>)>>> + Send;
+
+    /// Get a value, or None if it doesn't exist or has expired
+    fn get(&mut self, key: &str) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send;
+
+    /// Set a value that expires after `expire_s` seconds
+    fn set(
+        &mut self,
+        key: &str,
+        value: &[u8],
+        expire_s: u64,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Set a value that expires after `expire_s` seconds, only if its current
+    /// value is `current`, in one atomic step.  Returns false if the value
+    /// was changed (or removed) since `current` was read.
+    fn set_if(
+        &mut self,
+        key: &str,
+        current: &[u8],
+        value: &[u8],
+        expire_s: u64,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Delete a value
+    fn delete(&mut self, key: &str) -> impl Future<Output = Result<()>> + Send;
 }
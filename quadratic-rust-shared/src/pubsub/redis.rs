@@ -1,10 +1,13 @@
+use futures_util::{StreamExt, stream::BoxStream};
 use redis::{
     AsyncCommands, Client,
-    aio::{MultiplexedConnection, PubSub},
+    aio::{MultiplexedConnection, PubSub, PubSubSink},
     cmd,
 };
+use std::collections::HashMap;
 
 use crate::pubsub::Config;
+use crate::pubsub::redis_streams::{INCREMENT_SCRIPT, SET_IF_SCRIPT};
 use crate::{SharedError, error::Result};
 
 #[derive(Debug, Clone)]
@@ -22,6 +25,43 @@ pub struct RedisConnection {
     multiplex: MultiplexedConnection,
 }
 
+/// A stream of (channel, message) pairs received by a `Subscriber`
+pub type MessageStream = BoxStream<'static, (String, Vec<u8>)>;
+
+/// A pubsub subscription whose channels can be changed while its messages
+/// are being consumed elsewhere.
+pub struct Subscriber {
+    sink: PubSubSink,
+}
+
+impl Subscriber {
+    /// Connect to the redis pubsub server, returning the subscriber and the
+    /// stream of messages for all of its channels.
+    pub async fn new(config: Config) -> Result<(Subscriber, MessageStream)> {
+        let (sink, stream) = client(config)?.get_async_pubsub().await?.split();
+        let stream = stream
+            .filter_map(|message| async move {
+                let payload = message.get_payload::<Vec<u8>>().ok()?;
+                Some((message.get_channel_name().to_string(), payload))
+            })
+            .boxed();
+
+        Ok((Subscriber { sink }, stream))
+    }
+
+    /// Subscribe to a channel.
+    pub async fn subscribe(&mut self, channel: &str) -> Result<()> {
+        self.sink.subscribe(channel).await?;
+        Ok(())
+    }
+
+    /// Unsubscribe from a channel.
+    pub async fn unsubscribe(&mut self, channel: &str) -> Result<()> {
+        self.sink.unsubscribe(channel).await?;
+        Ok(())
+    }
+}
+
 fn client(config: Config) -> Result<Client> {
     if let Config::Redis(RedisConfig {
         host,
@@ -101,6 +141,20 @@ impl super::PubSub for RedisConnection {
         Ok(())
     }
 
+    async fn publish_next(
+        &mut self,
+        _channel: &str,
+        _counter_key: &str,
+        _min: u64,
+        _sequence_num: u64,
+        _value: &[u8],
+        _active_channel: Option<&str>,
+    ) -> Result<Option<u64>> {
+        Err(SharedError::PubSub(
+            "publish_next requires redis streams".into(),
+        ))
+    }
+
     /// Acknowledge that a message was processed
     async fn ack(
         &mut self,
@@ -155,6 +209,78 @@ impl super::PubSub for RedisConnection {
         unimplemented!()
     }
 
+    /// Atomically increment a counter, starting from at least `min`
+    async fn increment(&mut self, key: &str, min: u64) -> Result<u64> {
+        let value = redis::Script::new(INCREMENT_SCRIPT)
+            .key(key)
+            .arg(min)
+            .invoke_async(&mut self.multiplex)
+            .await?;
+
+        Ok(value)
+    }
+
+    /// Insert or update a field within a hash
+    async fn set_field(&mut self, key: &str, field: &str, value: &[u8]) -> Result<()> {
+        let () = self.multiplex.hset(key, field, value).await?;
+        Ok(())
+    }
+
+    /// Remove a field from a hash
+    async fn remove_field(&mut self, key: &str, field: &str) -> Result<()> {
+        let () = self.multiplex.hdel(key, field).await?;
+        Ok(())
+    }
+
+    /// Get a field within a hash, or None if it doesn't exist
+    async fn field(&mut self, key: &str, field: &str) -> Result<Option<Vec<u8>>> {
+        let value: Option<Vec<u8>> = self.multiplex.hget(key, field).await?;
+        Ok(value)
+    }
+
+    /// Get all fields within a hash
+    async fn fields(&mut self, key: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let fields: HashMap<String, Vec<u8>> = self.multiplex.hgetall(key).await?;
+        Ok(fields.into_iter().collect())
+    }
+
+    /// Get a value, or None if it doesn't exist or has expired
+    async fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value: Option<Vec<u8>> = self.multiplex.get(key).await?;
+        Ok(value)
+    }
+
+    /// Set a value that expires after `expire_s` seconds
+    async fn set(&mut self, key: &str, value: &[u8], expire_s: u64) -> Result<()> {
+        let () = self.multiplex.set_ex(key, value, expire_s).await?;
+        Ok(())
+    }
+
+    /// Set a value that expires after `expire_s` seconds, only if its current
+    /// value is `current`
+    async fn set_if(
+        &mut self,
+        key: &str,
+        current: &[u8],
+        value: &[u8],
+        expire_s: u64,
+    ) -> Result<bool> {
+        let is_set: i64 = redis::Script::new(SET_IF_SCRIPT)
+            .key(key)
+            .arg(current)
+            .arg(value)
+            .arg(expire_s)
+            .invoke_async(&mut self.multiplex)
+            .await?;
+        Ok(is_set == 1)
+    }
+
+    /// Delete a value
+    async fn delete(&mut self, key: &str) -> Result<()> {
+        let () = self.multiplex.del(key).await?;
+        Ok(())
+    }
+
     // /// Get the next message from the pubsub server.
     // async fn poll<T>(&mut self) -> impl Stream {
     //     self.pubsub.on_message()
@@ -221,4 +347,39 @@ pub mod tests {
 
         assert_eq!(received, messages);
     }
+
+    #[tokio::test]
+    async fn subscriber_receives_messages_while_subscribing() {
+        let (config, channel) = setup();
+        let other_channel = Uuid::new_v4().to_string();
+
+        let (mut subscriber, mut stream) = Subscriber::new(config.clone()).await.unwrap();
+        subscriber.subscribe(&channel).await.unwrap();
+
+        let mut connection = RedisConnection::new(config).await.unwrap();
+        connection
+            .publish(&channel, "", b"test 1", None)
+            .await
+            .unwrap();
+        assert_eq!(
+            stream.next().await.unwrap(),
+            (channel.clone(), b"test 1".to_vec())
+        );
+
+        // channels can be added while the stream is in use
+        subscriber.subscribe(&other_channel).await.unwrap();
+        subscriber.unsubscribe(&channel).await.unwrap();
+        connection
+            .publish(&channel, "", b"test 2", None)
+            .await
+            .unwrap();
+        connection
+            .publish(&other_channel, "", b"test 3", None)
+            .await
+            .unwrap();
+        assert_eq!(
+            stream.next().await.unwrap(),
+            (other_channel, b"test 3".to_vec())
+        );
+    }
 }
@@ -11,6 +11,7 @@ use redis::{
     streams::{StreamId, StreamKey, StreamRangeReply, StreamReadOptions, StreamReadReply},
 };
 use std::{
+    collections::HashMap,
     fmt::{self, Debug},
     vec,
 };
@@ -45,6 +46,36 @@ impl Debug for RedisConnection {
 /// A message consists of a key (String) and a value (Bytes).
 type Message = (String, Vec<u8>);
 
+/// Increment a counter, first raising it to ARGV[1] if it's lower (or unset).
+pub(crate) const INCREMENT_SCRIPT: &str = r#"
+local value = math.max(tonumber(redis.call('GET', KEYS[1]) or '0'), tonumber(ARGV[1]))
+redis.call('SET', KEYS[1], value + 1)
+return value + 1
+"#;
+
+/// Add ARGV[2] to the stream KEYS[1] with the id ARGV[1], only if ARGV[1] is
+/// the next value of the counter KEYS[2] (raised to ARGV[3] if it's lower), and
+/// advance the counter to it.  Returns -1 once added, or the counter's value.
+pub(crate) const PUBLISH_NEXT_SCRIPT: &str = r#"
+local value = math.max(tonumber(redis.call('GET', KEYS[2]) or '0'), tonumber(ARGV[3]))
+if tonumber(ARGV[1]) ~= value + 1 then
+    return value
+end
+redis.call('XADD', KEYS[1], ARGV[1], ARGV[1], ARGV[2])
+redis.call('SET', KEYS[2], ARGV[1])
+return -1
+"#;
+
+/// Set KEYS[1] to ARGV[2], expiring in ARGV[3] seconds, only if its value is
+/// ARGV[1].  Returns 1 once set, or 0 if the value is different.
+pub(crate) const SET_IF_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+return 1
+"#;
+
 /// Create a Redis client
 fn client(config: Config) -> Result<Client> {
     if let Config::RedisStreams(RedisStreamsConfig {
@@ -218,6 +249,38 @@ impl super::PubSub for RedisConnection {
         Ok(())
     }
 
+    /// Publish a message as the next value of a counter, atomically, so that
+    /// concurrent publishers can't add ids out of order.
+    async fn publish_next(
+        &mut self,
+        channel: &str,
+        counter_key: &str,
+        min: u64,
+        sequence_num: u64,
+        value: &[u8],
+        active_channel: Option<&str>,
+    ) -> Result<Option<u64>> {
+        let current: i64 = redis::Script::new(PUBLISH_NEXT_SCRIPT)
+            .key(channel)
+            .key(counter_key)
+            .arg(sequence_num)
+            .arg(value)
+            .arg(min)
+            .invoke_async(&mut self.multiplex)
+            .await?;
+
+        if current >= 0 {
+            return Ok(Some(current as u64));
+        }
+
+        // add the channel to the active channels set
+        if let Some(active_channel) = active_channel {
+            self.upsert_active_channel(active_channel, channel).await?
+        }
+
+        Ok(None)
+    }
+
     /// Acknowledge that a message was processed
     async fn ack(
         &mut self,
@@ -337,6 +400,86 @@ impl super::PubSub for RedisConnection {
 
         Ok(parse_message(id, preserve_sequence))
     }
+
+    /// Atomically increment a counter, starting from at least `min`
+    async fn increment(&mut self, key: &str, min: u64) -> Result<u64> {
+        let value = redis::Script::new(INCREMENT_SCRIPT)
+            .key(key)
+            .arg(min)
+            .invoke_async(&mut self.multiplex)
+            .await?;
+
+        Ok(value)
+    }
+
+    /// Insert or update a field within a hash
+    async fn set_field(&mut self, key: &str, field: &str, value: &[u8]) -> Result<()> {
+        let () = self.multiplex.hset(key, field, value).await?;
+
+        Ok(())
+    }
+
+    /// Remove a field from a hash
+    async fn remove_field(&mut self, key: &str, field: &str) -> Result<()> {
+        let () = self.multiplex.hdel(key, field).await?;
+
+        Ok(())
+    }
+
+    /// Get a field within a hash, or None if it doesn't exist
+    async fn field(&mut self, key: &str, field: &str) -> Result<Option<Vec<u8>>> {
+        let value: Option<Vec<u8>> = self.multiplex.hget(key, field).await?;
+
+        Ok(value)
+    }
+
+    /// Get all fields within a hash
+    async fn fields(&mut self, key: &str) -> Result<Vec<Message>> {
+        let fields: HashMap<String, Vec<u8>> = self.multiplex.hgetall(key).await?;
+
+        Ok(fields.into_iter().collect())
+    }
+
+    /// Get a value, or None if it doesn't exist or has expired
+    async fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value: Option<Vec<u8>> = self.multiplex.get(key).await?;
+
+        Ok(value)
+    }
+
+    /// Set a value that expires after `expire_s` seconds
+    async fn set(&mut self, key: &str, value: &[u8], expire_s: u64) -> Result<()> {
+        let () = self.multiplex.set_ex(key, value, expire_s).await?;
+
+        Ok(())
+    }
+
+    /// Set a value that expires after `expire_s` seconds, only if its current
+    /// value is `current`
+    async fn set_if(
+        &mut self,
+        key: &str,
+        current: &[u8],
+        value: &[u8],
+        expire_s: u64,
+    ) -> Result<bool> {
+        let is_set: i64 = redis::Script::new(SET_IF_SCRIPT)
+            .key(key)
+            .arg(current)
+            .arg(value)
+            .arg(expire_s)
+            .invoke_async(&mut self.multiplex)
+            .await?;
+
+        Ok(is_set == 1)
+    }
+
+    /// Delete a value
+    async fn delete(&mut self, key: &str) -> Result<()> {
+        let () = self.multiplex.del(key).await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -468,6 +611,67 @@ pub mod tests {
         assert_eq!(results, ("2".into(), messages[1].into()));
     }
 
+    #[tokio::test]
+    async fn stream_increment_and_fields() {
+        let (config, key) = setup();
+        let mut connection = RedisConnection::new(config).await.unwrap();
+
+        // the counter starts from the minimum, and never goes backwards
+        assert_eq!(connection.increment(&key, 10).await.unwrap(), 11);
+        assert_eq!(connection.increment(&key, 0).await.unwrap(), 12);
+        assert_eq!(connection.increment(&key, 20).await.unwrap(), 21);
+
+        // a taken sequence number isn't published, and returns the counter
+        let channel = format!("{key}:channel");
+        let next = connection.publish_next(&channel, &key, 0, 22, b"22", None);
+        assert_eq!(next.await.unwrap(), None);
+        let next = connection.publish_next(&channel, &key, 0, 22, b"taken", None);
+        assert_eq!(next.await.unwrap(), Some(22));
+        let next = connection.publish_next(&channel, &key, 0, 24, b"skipped", None);
+        assert_eq!(next.await.unwrap(), Some(22));
+        let last_message = connection.last_message(&channel, false).await.unwrap();
+        assert_eq!(last_message, ("22".into(), b"22".to_vec()));
+
+        let key = format!("{key}:fields");
+        connection.set_field(&key, "a", b"1").await.unwrap();
+        connection.set_field(&key, "b", b"2").await.unwrap();
+        connection.set_field(&key, "a", b"3").await.unwrap();
+        connection.remove_field(&key, "b").await.unwrap();
+
+        let fields = connection.fields(&key).await.unwrap();
+        assert_eq!(fields, vec![("a".to_string(), b"3".to_vec())]);
+        assert_eq!(
+            connection.field(&key, "a").await.unwrap(),
+            Some(b"3".to_vec())
+        );
+        assert_eq!(connection.field(&key, "b").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn stream_get_set_and_delete() {
+        let (config, key) = setup();
+        let mut connection = RedisConnection::new(config).await.unwrap();
+
+        assert_eq!(connection.get(&key).await.unwrap(), None);
+
+        connection.set(&key, b"1", 60).await.unwrap();
+        assert_eq!(connection.get(&key).await.unwrap(), Some(b"1".to_vec()));
+
+        // only set when the value is unchanged
+        assert!(!connection.set_if(&key, b"0", b"2", 60).await.unwrap());
+        assert!(connection.set_if(&key, b"1", b"2", 60).await.unwrap());
+        assert_eq!(connection.get(&key).await.unwrap(), Some(b"2".to_vec()));
+
+        connection.delete(&key).await.unwrap();
+        assert_eq!(connection.get(&key).await.unwrap(), None);
+        assert!(!connection.set_if(&key, b"2", b"3", 60).await.unwrap());
+
+        // values expire
+        connection.set(&key, b"1", 1).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert_eq!(connection.get(&key).await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn stream_get_all_channels() {
         let (config, channel) = setup();
@@ -11,9 +11,18 @@ pub enum Storage {
     #[error("Error creating directory {0}: {1}")]
     CreateDirectory(String, String),
 
+    #[error("Error deleting key {0}: {1}")]
+    Delete(String, String),
+
     #[error("Invalid key: {0}")]
     InvalidKey(String),
 
+    #[error("Error listing prefix {0}: {1}")]
+    List(String, String),
+
+    #[error("Key {0} not found")]
+    NotFound(String),
+
     #[error("Error reading key {0}: {1}")]
     Read(String, String),
 
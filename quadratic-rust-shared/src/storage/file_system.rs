@@ -4,15 +4,24 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream::try_unfold};
+use std::fs::Metadata;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
-use tokio::fs::{File, create_dir_all};
+use tokio::fs::{File, create_dir_all, metadata, read_dir, remove_file, rename};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
 
-use super::Storage;
+use super::{ByteStream, ObjectMetadata, STREAM_CHUNK_SIZE, Storage};
 use crate::SharedError;
 use crate::error::Result;
 use crate::storage::error::Storage as StorageError;
 
+/// Files being written by `write_stream` start with this prefix, and aren't
+/// listed.
+const TEMP_FILE_PREFIX: &str = ".";
+
 /// File System configuration
 #[derive(Debug, Clone)]
 pub struct FileSystemConfig {
@@ -58,6 +67,139 @@ impl Storage for FileSystem {
         Ok(())
     }
 
+    /// Read the file from the file system in chunks.
+    async fn read_stream(&self, key: &str) -> Result<ByteStream> {
+        let file_path = self.full_path(key, false).await?.0;
+        let file = File::open(file_path)
+            .await
+            .map_err(|e| Self::read_error(key, &e))?;
+        let key = key.to_owned();
+
+        let stream = try_unfold(file, move |mut file| {
+            let key = key.to_owned();
+
+            async move {
+                let mut buffer = vec![0; STREAM_CHUNK_SIZE];
+                let read = file
+                    .read(&mut buffer)
+                    .await
+                    .map_err(|e| Self::read_error(&key, &e))?;
+
+                if read == 0 {
+                    return Ok(None);
+                }
+
+                buffer.truncate(read);
+
+                Ok(Some((Bytes::from(buffer), file)))
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Write the stream to a temporary file as it arrives, then move it over
+    /// the file, so that a failed write leaves the existing file untouched.
+    async fn write_stream(&self, key: &str, stream: ByteStream) -> Result<()> {
+        let (file_path, dir) = self.full_path(key, true).await?;
+        let file_name = file_path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp_path = dir.join(format!("{TEMP_FILE_PREFIX}{file_name}.{}", Uuid::new_v4()));
+
+        let written = async {
+            let mut file = File::create(&temp_path)
+                .await
+                .map_err(|e| Self::write_error(key, &e))?;
+            let mut stream = stream;
+
+            while let Some(chunk) = stream.next().await {
+                file.write_all(&chunk?)
+                    .await
+                    .map_err(|e| Self::write_error(key, &e))?;
+            }
+
+            file.flush().await.map_err(|e| Self::write_error(key, &e))?;
+
+            rename(&temp_path, &file_path)
+                .await
+                .map_err(|e| Self::write_error(key, &e))
+        }
+        .await;
+
+        if written.is_err() {
+            // the original error is more useful than a failure to clean up
+            let _ = remove_file(&temp_path).await;
+        }
+
+        written
+    }
+
+    /// List the files whose key starts with the prefix.  Files are stored in
+    /// a directory per uuid, so the prefix must start with one (e.g. `uuid-`).
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMetadata>> {
+        let dir = self.full_path(prefix, false).await?.1;
+        let uuid = dir
+            .file_name()
+            .map(|uuid| uuid.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut entries = match read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(Self::list_error(prefix, &e)),
+        };
+        let mut objects = vec![];
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| Self::list_error(prefix, &e))?
+        {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+
+            if file_name.starts_with(TEMP_FILE_PREFIX) {
+                continue;
+            }
+
+            let key = format!("{uuid}-{file_name}");
+            let metadata = entry
+                .metadata()
+                .await
+                .map_err(|e| Self::list_error(prefix, &e))?;
+
+            if metadata.is_file() && key.starts_with(prefix) {
+                objects.push(object_metadata(key, &metadata));
+            }
+        }
+
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+
+        Ok(objects)
+    }
+
+    /// Get the file's size and modified time.
+    async fn head(&self, key: &str) -> Result<ObjectMetadata> {
+        let file_path = self.full_path(key, false).await?.0;
+
+        match metadata(file_path).await {
+            Ok(metadata) => Ok(object_metadata(key.to_owned(), &metadata)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(Self::not_found_error(key)),
+            Err(e) => Err(Self::read_error(key, &e)),
+        }
+    }
+
+    /// Delete the file from the file system.
+    async fn delete(&self, key: &str) -> Result<()> {
+        let file_path = self.full_path(key, false).await?.0;
+
+        match remove_file(file_path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(Self::delete_error(key, &e)),
+            _ => Ok(()),
+        }
+    }
+
     /// Return the path to the file system.
     fn path(&self) -> &str {
         &self.config.path
@@ -105,9 +247,18 @@ impl FileSystem {
     }
 }
 
+fn object_metadata(key: String, metadata: &Metadata) -> ObjectMetadata {
+    ObjectMetadata {
+        key,
+        size: metadata.len(),
+        last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use tokio::fs::{remove_dir, remove_file};
+    use futures_util::TryStreamExt;
+    use tokio::fs::remove_dir;
     use uuid::Uuid;
 
     use super::*;
@@ -139,4 +290,132 @@ mod tests {
 
         assert_eq!(data, &read_data);
     }
+
+    #[tokio::test]
+    async fn file_system_list_head_and_delete() {
+        let storage = FileSystem { config: config() };
+        let file_id = Uuid::new_v4();
+        let prefix = format!("{file_id}-");
+        let data = Bytes::from("Hello, world!");
+
+        assert!(storage.list(&prefix).await.unwrap().is_empty());
+        assert!(!storage.exists(&format!("{file_id}-0.grid")).await.unwrap());
+
+        for sequence_num in 0..3 {
+            let key = format!("{file_id}-{sequence_num}.grid");
+            storage.write(&key, &data).await.unwrap();
+        }
+
+        let keys = storage
+            .list(&prefix)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|object| object.key)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            vec![
+                format!("{file_id}-0.grid"),
+                format!("{file_id}-1.grid"),
+                format!("{file_id}-2.grid"),
+            ]
+        );
+
+        let key = &format!("{file_id}-1.grid");
+        let head = storage.head(key).await.unwrap();
+        assert_eq!(head.size, data.len() as u64);
+        assert!(head.last_modified.is_some());
+
+        storage.delete(key).await.unwrap();
+        assert!(!storage.exists(key).await.unwrap());
+        assert!(matches!(
+            storage.head(key).await,
+            Err(SharedError::Storage(StorageError::NotFound(_)))
+        ));
+
+        // deleting a missing file is fine
+        storage.delete(key).await.unwrap();
+        assert_eq!(storage.list(&prefix).await.unwrap().len(), 2);
+
+        // cleanup
+        for object in storage.list(&prefix).await.unwrap() {
+            storage.delete(&object.key).await.unwrap();
+        }
+        let (_, dir) = storage.full_path(key, false).await.unwrap();
+        remove_dir(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn file_system_streams() {
+        let storage = FileSystem { config: config() };
+        let key = &format!("{}-0.grid", Uuid::new_v4());
+
+        // more than one chunk
+        let data = (0..STREAM_CHUNK_SIZE * 2 + 10)
+            .map(|i| (i % 256) as u8)
+            .collect::<Vec<u8>>();
+        let chunks = data
+            .chunks(1000)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+
+        storage
+            .write_stream(key, Box::pin(futures_util::stream::iter(chunks)))
+            .await
+            .unwrap();
+
+        let chunks = storage
+            .read_stream(key)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        // cleanup
+        let (full_path, dir) = storage.full_path(key, false).await.unwrap();
+        remove_file(full_path).await.unwrap();
+        remove_dir(dir).await.unwrap();
+
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), data);
+    }
+
+    #[tokio::test]
+    async fn file_system_failed_stream_keeps_the_file() {
+        let storage = FileSystem { config: config() };
+        let uuid = Uuid::new_v4();
+        let key = &format!("{uuid}-0.grid");
+        let data = Bytes::from_static(b"checkpoint");
+
+        storage.write(key, &data).await.unwrap();
+
+        let chunks = vec![
+            Ok(Bytes::from_static(b"partial")),
+            Err(SharedError::Storage(StorageError::Write(
+                key.to_owned(),
+                "aborted".into(),
+            ))),
+        ];
+        let result = storage
+            .write_stream(key, Box::pin(futures_util::stream::iter(chunks)))
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(storage.read(key).await.unwrap(), data);
+
+        // the temporary file is removed
+        let (_, dir) = storage.full_path(key, false).await.unwrap();
+        let mut entries = read_dir(&dir).await.unwrap();
+        let mut file_names = vec![];
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            file_names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        assert_eq!(file_names, vec!["0.grid"]);
+
+        // cleanup
+        storage.delete(key).await.unwrap();
+        remove_dir(dir).await.unwrap();
+    }
 }
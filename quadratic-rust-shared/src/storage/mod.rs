@@ -2,8 +2,11 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use file_system::FileSystemConfig;
+use futures_util::Stream;
 use s3::S3Config;
+use std::pin::Pin;
 
 use crate::{SharedError, error::Result, storage::error::Storage as StorageError};
 
@@ -23,15 +26,49 @@ pub enum StorageContainer {
     FileSystem(file_system::FileSystem),
 }
 
+/// The size of the chunks `read_stream` reads from the file system
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A stream of bytes to or from storage
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+/// Metadata of an object in storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMetadata {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
 #[async_trait]
 pub trait Storage {
     type Config;
 
     async fn read(&self, key: &str) -> Result<Bytes>;
     async fn write<'a>(&self, key: &'a str, data: &'a Bytes) -> Result<()>;
+    async fn read_stream(&self, key: &str) -> Result<ByteStream>;
+    async fn write_stream(&self, key: &str, stream: ByteStream) -> Result<()>;
+
+    /// List the objects whose key starts with the prefix.
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMetadata>>;
+
+    /// Get an object's metadata, or a `NotFound` error if it doesn't exist.
+    async fn head(&self, key: &str) -> Result<ObjectMetadata>;
+
+    /// Delete an object.  Deleting a missing object is not an error.
+    async fn delete(&self, key: &str) -> Result<()>;
+
     fn path(&self) -> &str;
     fn config(&self) -> Self::Config;
 
+    async fn exists(&self, key: &str) -> Result<bool> {
+        match self.head(key).await {
+            Ok(_) => Ok(true),
+            Err(SharedError::Storage(StorageError::NotFound(_))) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
     fn read_error(key: &str, e: impl ToString) -> SharedError {
         SharedError::Storage(StorageError::Read(key.into(), e.to_string()))
     }
@@ -39,6 +76,18 @@ pub trait Storage {
     fn write_error(key: &str, e: impl ToString) -> SharedError {
         SharedError::Storage(StorageError::Write(key.into(), e.to_string()))
     }
+
+    fn list_error(prefix: &str, e: impl ToString) -> SharedError {
+        SharedError::Storage(StorageError::List(prefix.into(), e.to_string()))
+    }
+
+    fn delete_error(key: &str, e: impl ToString) -> SharedError {
+        SharedError::Storage(StorageError::Delete(key.into(), e.to_string()))
+    }
+
+    fn not_found_error(key: &str) -> SharedError {
+        SharedError::Storage(StorageError::NotFound(key.into()))
+    }
 }
 
 // TODO(ddimaria): this is a temp hack to get around some trait issues, do something better
@@ -60,6 +109,41 @@ impl Storage for StorageContainer {
         }
     }
 
+    async fn read_stream(&self, key: &str) -> Result<ByteStream> {
+        match self {
+            Self::S3(s3) => s3.read_stream(key).await,
+            Self::FileSystem(fs) => fs.read_stream(key).await,
+        }
+    }
+
+    async fn write_stream(&self, key: &str, stream: ByteStream) -> Result<()> {
+        match self {
+            Self::S3(s3) => s3.write_stream(key, stream).await,
+            Self::FileSystem(fs) => fs.write_stream(key, stream).await,
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMetadata>> {
+        match self {
+            Self::S3(s3) => s3.list(prefix).await,
+            Self::FileSystem(fs) => fs.list(prefix).await,
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<ObjectMetadata> {
+        match self {
+            Self::S3(s3) => s3.head(key).await,
+            Self::FileSystem(fs) => fs.head(key).await,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match self {
+            Self::S3(s3) => s3.delete(key).await,
+            Self::FileSystem(fs) => fs.delete(key).await,
+        }
+    }
+
     fn path(&self) -> &str {
         match self {
             Self::S3(s3) => s3.path(),
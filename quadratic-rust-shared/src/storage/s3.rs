@@ -3,15 +3,24 @@
 //! Functions to interact with S3
 
 use async_trait::async_trait;
-use aws_sdk_s3::Client;
-use bytes::Bytes;
+use aws_sdk_s3::{Client, primitives::DateTime as AwsDateTime, types::CompletedPart};
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream::try_unfold};
 
-use super::Storage;
+use super::{ByteStream, ObjectMetadata, Storage};
 use crate::{
-    aws::s3::{download_object, upload_object},
+    aws::s3::{
+        abort_multipart_upload, complete_multipart_upload, create_multipart_upload, delete_object,
+        download_object, head_object, list_objects, upload_object, upload_part,
+    },
     error::Result,
 };
 
+/// The size of the parts `write_stream` uploads.  S3 requires every part but
+/// the last to be at least 5 MiB.
+pub const PART_SIZE: usize = 5 * 1024 * 1024;
+
 /// S3 configuration
 #[derive(Debug, Clone)]
 pub struct S3Config {
@@ -58,6 +67,104 @@ impl Storage for S3 {
         Ok(())
     }
 
+    /// Read the file from the S3 bucket as it downloads.
+    async fn read_stream(&self, key: &str) -> Result<ByteStream> {
+        let S3Config { client, bucket } = &self.config;
+
+        let file = download_object(client, bucket, key)
+            .await
+            .map_err(|e| Self::read_error(key, &e))?;
+        let key = key.to_owned();
+
+        let stream = try_unfold(file.body, move |mut body| {
+            let key = key.to_owned();
+
+            async move {
+                let chunk = body
+                    .try_next()
+                    .await
+                    .map_err(|e| Self::read_error(&key, &e))?;
+
+                Ok(chunk.map(|chunk| (chunk, body)))
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Write the stream to the S3 bucket.  A body that fits in one part is
+    /// uploaded in a single request, larger bodies as a multipart upload.
+    /// The multipart upload is aborted if the stream or any part fails.
+    async fn write_stream(&self, key: &str, mut stream: ByteStream) -> Result<()> {
+        let S3Config { client, bucket } = &self.config;
+        let first_part = Self::next_part(&mut stream).await?;
+
+        if first_part.len() < PART_SIZE {
+            return self.write(key, &first_part).await;
+        }
+
+        let upload_id = create_multipart_upload(client, bucket, key)
+            .await
+            .map_err(|e| Self::write_error(key, &e))?;
+
+        match self.upload_parts(key, &upload_id, first_part, stream).await {
+            Ok(parts) => complete_multipart_upload(client, bucket, key, &upload_id, parts)
+                .await
+                .map_err(|e| Self::write_error(key, &e)),
+            Err(error) => {
+                // the original error is more useful than a failure to abort
+                let _ = abort_multipart_upload(client, bucket, key, &upload_id).await;
+
+                Err(error)
+            }
+        }
+    }
+
+    /// List the files in the S3 bucket whose key starts with the prefix.
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMetadata>> {
+        let S3Config { client, bucket } = &self.config;
+
+        let objects = list_objects(client, bucket, prefix)
+            .await
+            .map_err(|e| Self::list_error(prefix, &e))?
+            .into_iter()
+            .filter_map(|object| {
+                Some(ObjectMetadata {
+                    key: object.key()?.to_owned(),
+                    size: object.size().unwrap_or_default() as u64,
+                    last_modified: object.last_modified().and_then(date_time),
+                })
+            })
+            .collect();
+
+        Ok(objects)
+    }
+
+    /// Get the file's size and modified time from the S3 bucket.
+    async fn head(&self, key: &str) -> Result<ObjectMetadata> {
+        let S3Config { client, bucket } = &self.config;
+
+        let head = head_object(client, bucket, key)
+            .await
+            .map_err(|e| Self::read_error(key, &e))?
+            .ok_or_else(|| Self::not_found_error(key))?;
+
+        Ok(ObjectMetadata {
+            key: key.to_owned(),
+            size: head.content_length().unwrap_or_default() as u64,
+            last_modified: head.last_modified().and_then(date_time),
+        })
+    }
+
+    /// Delete the file from the S3 bucket.
+    async fn delete(&self, key: &str) -> Result<()> {
+        let S3Config { client, bucket } = &self.config;
+
+        delete_object(client, bucket, key)
+            .await
+            .map_err(|e| Self::delete_error(key, &e))
+    }
+
     /// Return the S3 bucket.
     fn path(&self) -> &str {
         &self.config.bucket
@@ -74,9 +181,143 @@ impl S3 {
     pub fn new(config: S3Config) -> Self {
         Self { config }
     }
+
+    /// Read the next part from the stream: `PART_SIZE` bytes or more, or
+    /// whatever is left when the stream ends.
+    async fn next_part(stream: &mut ByteStream) -> Result<Bytes> {
+        let mut buffer = BytesMut::new();
+
+        while buffer.len() < PART_SIZE {
+            match stream.next().await.transpose()? {
+                Some(chunk) => buffer.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+
+        Ok(buffer.freeze())
+    }
+
+    /// Upload the first part and then the rest of the stream in parts of at
+    /// least `PART_SIZE`.
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        first_part: Bytes,
+        mut stream: ByteStream,
+    ) -> Result<Vec<CompletedPart>> {
+        let S3Config { client, bucket } = &self.config;
+        let mut parts = vec![];
+        let mut body = first_part;
+
+        // the last part may be smaller
+        while !body.is_empty() {
+            let part_number = parts.len() as i32 + 1;
+            let part = upload_part(client, bucket, key, upload_id, part_number, body)
+                .await
+                .map_err(|e| Self::write_error(key, &e))?;
+            parts.push(part);
+
+            body = Self::next_part(&mut stream).await?;
+        }
+
+        Ok(parts)
+    }
+}
+
+fn date_time(date_time: &AwsDateTime) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(date_time.secs(), date_time.subsec_nanos())
 }
 
 #[cfg(test)]
 mod tests {
-    // TODO(ddimaria): add tests once we have S3 mocks in place
+    use futures_util::TryStreamExt;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::SharedError;
+    use crate::aws::client;
+    use crate::storage::error::Storage as StorageError;
+
+    // runs against localstack (see docker-compose.yml)
+    async fn storage() -> S3 {
+        S3::new(S3Config {
+            client: client("test", "test", "us-east-2", "Quadratic Tests", true).await,
+            bucket: "quadratic-api-docker".into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn s3_write_list_head_and_delete() {
+        let storage = storage().await;
+        let prefix = format!("{}-", Uuid::new_v4());
+        let key = &format!("{prefix}0.grid");
+        let data = &Bytes::from("Hello, world!");
+
+        assert!(!storage.exists(key).await.unwrap());
+
+        storage.write(key, data).await.unwrap();
+        assert_eq!(&storage.read(key).await.unwrap(), data);
+
+        let objects = storage.list(&prefix).await.unwrap();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(&objects[0].key, key);
+        assert_eq!(objects[0].size, data.len() as u64);
+
+        let head = storage.head(key).await.unwrap();
+        assert_eq!(head.size, data.len() as u64);
+        assert!(head.last_modified.is_some());
+
+        storage.delete(key).await.unwrap();
+        assert!(!storage.exists(key).await.unwrap());
+        assert!(matches!(
+            storage.head(key).await,
+            Err(SharedError::Storage(StorageError::NotFound(_)))
+        ));
+        assert!(storage.list(&prefix).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn s3_streams() {
+        let storage = storage().await;
+        let key = &format!("{}-0.grid", Uuid::new_v4());
+
+        // more than one part
+        let data = (0..PART_SIZE + 10)
+            .map(|i| (i % 256) as u8)
+            .collect::<Vec<u8>>();
+        let chunks = data
+            .chunks(64 * 1024)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+
+        storage
+            .write_stream(key, Box::pin(futures_util::stream::iter(chunks)))
+            .await
+            .unwrap();
+
+        let chunks = storage
+            .read_stream(key)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        storage.delete(key).await.unwrap();
+
+        assert_eq!(chunks.concat(), data);
+
+        // less than one part is a single upload
+        let chunks = vec![Ok(Bytes::from("Hello, ")), Ok(Bytes::from("world!"))];
+        storage
+            .write_stream(key, Box::pin(futures_util::stream::iter(chunks)))
+            .await
+            .unwrap();
+
+        let data = storage.read(key).await.unwrap();
+        storage.delete(key).await.unwrap();
+
+        assert_eq!(data, Bytes::from("Hello, world!"));
+    }
 }
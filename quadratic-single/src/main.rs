@@ -0,0 +1,35 @@
+//! Quadratic Single
+//!
+//! Run the multiplayer server and the file service in one process for
+//! single-node deployments.  Both services share the in-memory PubSub
+//! backend in place of Redis.
+
+use std::error::Error;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+                "quadratic_multiplayer=debug,quadratic_files=debug,tower_http=debug".into()
+            }),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    tokio::try_join!(
+        async {
+            quadratic_multiplayer::serve_single_node()
+                .await
+                .map_err(Box::<dyn Error>::from)
+        },
+        async {
+            quadratic_files::serve_single_node()
+                .await
+                .map_err(Box::<dyn Error>::from)
+        },
+    )?;
+
+    Ok(())
+}